@@ -13,6 +13,11 @@ use serde::{Deserialize, Serialize, ser::SerializeStruct};
 pub type SyncTime = u64;
 pub const NEVER: SyncTime = SyncTime::MAX;
 
+/// Slowest tempo accepted by the clock, in BPM.
+pub const MIN_TEMPO: f64 = 20.0;
+/// Fastest tempo accepted by the clock, in BPM (the Ableton Link limit).
+pub const MAX_TEMPO: f64 = 999.0;
+
 /// Represents a duration that can be measured in microseconds, beats, or frames.
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -314,14 +319,14 @@ impl Clock {
 
     /// Sets a new tempo for the Ableton Link session.
     ///
-    /// The tempo is clamped to a minimum of 20.0 BPM. The change is associated
-    /// with the current Link time and committed immediately.
+    /// The tempo is clamped between [`MIN_TEMPO`] and [`MAX_TEMPO`]. The change is
+    /// associated with the current Link time and committed immediately.
     ///
     /// # Arguments
     ///
     /// * `tempo` - The desired tempo in beats per minute (BPM).
     pub fn set_tempo(&mut self, tempo: f64) {
-        let tempo = if tempo < 20.0 { 20.0 } else { tempo };
+        let tempo = tempo.clamp(MIN_TEMPO, MAX_TEMPO);
         let timestamp = self.server.link.clock_micros();
         self.session_state.set_tempo(tempo, timestamp);
         self.commit_app_state();
@@ -19,6 +19,9 @@ pub use compilation_error::CompilationError;
 mod compilation_state;
 pub use compilation_state::CompilationState;
 
+mod compilation_warning;
+pub use compilation_warning::CompilationWarning;
+
 /// A trait for types that can compile source code text into a [`Program`].
 ///
 /// Implementors define how source code for a specific language or system
@@ -42,6 +45,18 @@ pub trait Compiler: Send + Sync + std::fmt::Debug {
     /// * `Ok(Program)` if compilation is successful.
     /// * `Err(CompilationError)` if any error occurs during compilation.
     fn compile(&self, text: &str, args: &BTreeMap<String, String>) -> Result<Program, CompilationError>;
+
+    /// Compiles the given source code text, also returning the warnings found on the way.
+    ///
+    /// Warnings never block execution: the program is returned alongside them.
+    /// The default implementation reports no warnings.
+    fn compile_with_warnings(
+        &self,
+        text: &str,
+        args: &BTreeMap<String, String>,
+    ) -> Result<(Program, Vec<CompilationWarning>), CompilationError> {
+        self.compile(text, args).map(|prog| (prog, Vec::new()))
+    }
 }
 
 /// A [`Compiler`] implementation that delegates compilation to an external executable.
@@ -0,0 +1,36 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// A non-fatal issue found during compilation.
+///
+/// Unlike a [`CompilationError`](super::CompilationError), a warning never
+/// prevents the compiled program from running.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompilationWarning {
+    /// The name of the language that emitted the warning.
+    pub lang: String,
+    /// A message describing the warning.
+    pub info: String,
+    /// The starting position in the source code related to the warning, if applicable.
+    pub from: usize,
+    /// The ending position in the source code related to the warning, if applicable.
+    pub to: usize,
+}
+
+impl CompilationWarning {
+    pub fn new(lang: impl Into<String>, info: impl Into<String>) -> Self {
+        Self {
+            lang: lang.into(),
+            info: info.into(),
+            from: 0,
+            to: 0,
+        }
+    }
+}
+
+impl fmt::Display for CompilationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} warning: {}", self.lang, self.info)
+    }
+}
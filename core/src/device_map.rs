@@ -14,24 +14,33 @@
 //!   (like `MIDIMessage`, `OSCMessage`, `LogMessage`)
 //!   based on the target device (specified by name or slot ID).
 //! - Providing a list of available and connected devices (`DeviceInfo`).
+//! - Tracking output devices whose sends fail, and periodically reconnecting them.
+//! - Spreading the MPE notes sent to each MIDI output across the channels of its MPE zone.
 
 use std::{
     collections::{BTreeMap, BTreeSet},
     net::{IpAddr, SocketAddr},
     str::FromStr,
     sync::{Arc, Mutex},
+    time::{Duration, Instant},
 };
 
 use crate::{
     clock::{Clock, SyncTime},
+    instruments::InstrumentMap,
     log_eprintln, log_println,
     protocol::{
-        DeviceDirection, DeviceInfo, DeviceKind, ProtocolDevice, ProtocolMessage, TimedMessage,
+        DeviceCapabilities, DeviceDirection, DeviceInfo, DeviceKind, ProtocolDevice,
+        ProtocolError, ProtocolMessage, Reachability, TimedMessage,
         audio_engine_proxy::AudioEngineProxy,
         log::{LOG_NAME, LogMessage, Severity},
-        midi::{MIDIMessage, MIDIMessageType, MidiIn, MidiInterface, MidiOut},
+        midi::{
+            MAX_MPE_MEMBER_CHANNELS, MIDIMessage, MIDIMessageType, MidiIn, MidiInterface, MidiOut,
+            MpeZone,
+        },
         osc::OSCOut,
     },
+    schedule::SCHEDULED_DRIFT,
     vm::event::ConcreteEvent,
 };
 
@@ -40,6 +49,31 @@ use midir::{Ignore, MidiInput, MidiOutput};
 /// Maximum number of user-assignable device slots (1-based).
 const MAX_DEVICE_SLOTS: usize = 16;
 const DEFAULT_LATENCY: f64 = 0.02;
+/// Most negative latency (in seconds) a device can have: events cannot be
+/// sent earlier than the scheduler lookahead allows.
+const MIN_LATENCY: f64 = -(SCHEDULED_DRIFT as f64) / 1_000_000.0;
+/// Delay between two reconnection attempts of a disconnected device.
+pub const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// State of an output device whose last send failed.
+#[derive(Debug, Clone)]
+struct Disconnection {
+    /// Whether the disconnection has already been reported by `check_connections`.
+    reported: bool,
+    /// Number of failed reconnection attempts.
+    attempts: u32,
+    /// Date of the failure or of the last reconnection attempt.
+    last_attempt: Instant,
+}
+
+/// A change in the connection state of an output device, see [`DeviceMap::check_connections`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionChange {
+    /// Sending to the device failed, its events are dropped until it reconnects.
+    Lost(String),
+    /// The device has been reconnected.
+    Restored(String),
+}
 
 /// Manages device connections, slot assignments, and event-to-protocol mapping.
 ///
@@ -64,7 +98,13 @@ pub struct DeviceMap {
     /// Names of devices from snapshot that couldn't be restored (unplugged physical devices).
     /// These are reconstructed as DeviceInfo in device_list() with is_missing: true.
     missing_devices: Mutex<BTreeSet<String>>,
-    latencies: Mutex<BTreeMap<String, f64>>
+    latencies: Mutex<BTreeMap<String, f64>>,
+    /// Output devices whose last send failed, keyed by name.
+    disconnected: Mutex<BTreeMap<String, Disconnection>>,
+    /// Sample folders and synth presets played by the instruments named in scripts.
+    instruments: Mutex<InstrumentMap>,
+    /// Channels of the MPE notes playing on each output, created with its first MPE note.
+    mpe_zones: Mutex<BTreeMap<String, MpeZone>>,
 }
 
 impl DeviceMap {
@@ -103,6 +143,9 @@ impl DeviceMap {
             midi_out,
             missing_devices: Default::default(),
             latencies: Default::default(),
+            disconnected: Default::default(),
+            instruments: Default::default(),
+            mpe_zones: Default::default(),
         }
     }
 
@@ -123,7 +166,10 @@ impl DeviceMap {
     /// `output_connections` map, keyed by the device's address.
     /// Note: This only registers the connection; slot assignment is separate.
     pub fn register_output_connection(&self, name: String, device: ProtocolDevice) {
-        self.set_latency(name.clone(), DEFAULT_LATENCY);
+        self.latencies
+            .lock()
+            .unwrap()
+            .insert(name.clone(), DEFAULT_LATENCY);
         self.output_connections
             .lock()
             .unwrap()
@@ -267,11 +313,83 @@ impl DeviceMap {
             .unwrap_or_default()
     }
 
-    pub fn set_latency(&self, name: String, value: f64) {
+    /// Sets the latency (in seconds) of an output device. Negative values send its
+    /// events early, up to the scheduler lookahead.
+    pub fn set_latency(&self, name: String, value: f64) -> Result<(), String> {
+        if !self.output_connections.lock().unwrap().contains_key(&name) {
+            return Err(format!("Device '{}' not found.", name));
+        }
         self.latencies
             .lock()
             .unwrap()
-            .insert(name, value);
+            .insert(name, value.max(MIN_LATENCY));
+        Ok(())
+    }
+
+    pub fn instruments(&self) -> InstrumentMap {
+        self.instruments.lock().unwrap().clone()
+    }
+
+    pub fn set_instruments(&self, instruments: InstrumentMap) {
+        *self.instruments.lock().unwrap() = instruments;
+    }
+
+    /// Maps an instrument to a sample folder or synth preset, `None` removes it.
+    pub fn set_instrument(&self, name: String, sound: Option<String>) {
+        self.instruments.lock().unwrap().set(name, sound);
+    }
+
+    /// Spreads the MPE notes of an output across `member_channels` channels after the
+    /// master channel, all 15 of them by default.
+    pub fn set_mpe_zone(&self, name: String, member_channels: usize) -> Result<(), String> {
+        if !self.output_connections.lock().unwrap().contains_key(&name) {
+            return Err(format!("Device '{}' not found.", name));
+        }
+        if !(1..=MAX_MPE_MEMBER_CHANNELS).contains(&member_channels) {
+            return Err(format!(
+                "An MPE zone has 1 to {} member channels, not {}.",
+                MAX_MPE_MEMBER_CHANNELS, member_channels
+            ));
+        }
+        self.mpe_zones
+            .lock()
+            .unwrap()
+            .insert(name, MpeZone::new(member_channels));
+        Ok(())
+    }
+
+    /// Gives an MPE note the channel of the zone of the device on its slot, unless it already
+    /// has one. Lines call it before keeping track of their notes, to release them later.
+    pub fn allocate_mpe_channel(&self, event: &mut ConcreteEvent, date: SyncTime) {
+        let Some(device_id) = event.device_id() else {
+            return;
+        };
+        if let Some(device_name) = self.get_name_for_slot(device_id) {
+            self.allocate_mpe_channel_on(&device_name, event, date);
+        }
+    }
+
+    /// Gives an MPE note without a channel one from the zone of `device_name`.
+    fn allocate_mpe_channel_on(
+        &self,
+        device_name: &str,
+        event: &mut ConcreteEvent,
+        date: SyncTime,
+    ) {
+        if let ConcreteEvent::MidiMpeNote(_, _, channel @ 0, duration, _, _) = event {
+            let mut zones = self.mpe_zones.lock().unwrap();
+            let zone = zones.entry(device_name.to_owned()).or_default();
+            *channel = zone.allocate(date, *duration);
+        }
+    }
+
+    fn shift_by_latency(date: SyncTime, latency: f64) -> SyncTime {
+        let offset = (latency.abs() * 1_000_000.0) as SyncTime;
+        if latency < 0.0 {
+            date.saturating_sub(offset)
+        } else {
+            date + offset
+        }
     }
 
     fn map_event_to_device(
@@ -308,6 +426,7 @@ impl DeviceMap {
     /// - If `target_device_name` is `"log"` (case-sensitive), it generates a `LogMessage`.
     /// - Otherwise, it looks up the device in `output_connections`.
     /// - If the device is not found, it generates an error `LogMessage`.
+    /// - If the device is disconnected, the event is dropped.
     /// - If the device is found, it dispatches based on the `ProtocolDevice` type:
     ///   - `OSCOutDevice`: Maps the event to an `OSCMessage`. Handles `ConcreteEvent::Osc` directly
     ///     and maps `ConcreteEvent::Dirt` to a SuperDirt `/dirt/play` message, calculating
@@ -323,7 +442,7 @@ impl DeviceMap {
     pub fn map_event_for_device_name(
         &self,
         target_device_name: &str,
-        event: ConcreteEvent,
+        mut event: ConcreteEvent,
         date: SyncTime,
         clock: &Clock, // Required for time context, e.g., Dirt messages
     ) -> Vec<TimedMessage> {
@@ -333,9 +452,7 @@ impl DeviceMap {
             return Self::map_event_to_device(&self.log_device, event, date, clock);
         }
 
-        let latency = self.get_latency(target_device_name);
-        let latency_micros = (latency * 1_000_000.0) as SyncTime;
-        let date = date + latency_micros;
+        let date = Self::shift_by_latency(date, self.get_latency(target_device_name));
 
         // Look up the device in connected outputs
         let device_opt = self
@@ -360,6 +477,12 @@ impl DeviceMap {
             ];
         };
 
+        // The disconnection has been logged once, events are dropped until it reconnects
+        if self.is_disconnected(target_device_name) {
+            return Vec::new();
+        }
+
+        self.allocate_mpe_channel_on(target_device_name, &mut event, date);
         Self::map_event_to_device(&device, event, date, clock)
     }
 
@@ -428,9 +551,138 @@ impl DeviceMap {
         let Some(device_id) = event.device_id() else {
             return Vec::new();
         };
+        let event = self.instruments.lock().unwrap().apply(event);
         self.map_event_for_slot_id(device_id, event, date, clock)
     }
 
+    /// Marks a MIDI output as disconnected after a failed send, its port being gone.
+    ///
+    /// Its events are dropped until [`check_connections`](Self::check_connections)
+    /// manages to reconnect it. Other outputs fail for passing reasons, like a full
+    /// network buffer, and keep sending. Failures of unregistered devices are ignored.
+    pub fn report_send_failure(&self, device: &Arc<ProtocolDevice>, error: &ProtocolError) {
+        let name = self
+            .output_connections
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(_, registered)| Arc::ptr_eq(registered, device))
+            .map(|(name, _)| name.clone());
+        let Some(name) = name else {
+            return;
+        };
+        if device.kind() != DeviceKind::Midi {
+            log_eprintln!("[!] Device '{}' failed to send ({}).", name, error);
+            return;
+        }
+        let mut disconnected = self.disconnected.lock().unwrap();
+        if disconnected.contains_key(&name) {
+            return;
+        }
+        log_eprintln!(
+            "[!] Device '{}' failed to send ({}), dropping its events until it reconnects.",
+            name,
+            error
+        );
+        disconnected.insert(
+            name,
+            Disconnection {
+                reported: false,
+                attempts: 0,
+                last_attempt: Instant::now(),
+            },
+        );
+    }
+
+    /// Whether the last send to the named device failed and it has not reconnected since.
+    pub fn is_disconnected(&self, name: &str) -> bool {
+        self.disconnected.lock().unwrap().contains_key(name)
+    }
+
+    /// Whether some device is waiting to be reconnected.
+    pub fn has_disconnected(&self) -> bool {
+        !self.disconnected.lock().unwrap().is_empty()
+    }
+
+    /// Number of failed reconnection attempts of a disconnected device.
+    pub fn reconnection_attempts(&self, name: &str) -> Option<u32> {
+        self.disconnected
+            .lock()
+            .unwrap()
+            .get(name)
+            .map(|disconnection| disconnection.attempts)
+    }
+
+    /// Reports the devices lost since the last call, and tries to reconnect the
+    /// disconnected devices whose last attempt is older than `RECONNECT_INTERVAL`.
+    pub fn check_connections(&self, now: Instant) -> Vec<ConnectionChange> {
+        let mut changes = Vec::new();
+        let mut due = Vec::new();
+        for (name, disconnection) in self.disconnected.lock().unwrap().iter_mut() {
+            if !disconnection.reported {
+                disconnection.reported = true;
+                changes.push(ConnectionChange::Lost(name.clone()));
+            }
+            if now.saturating_duration_since(disconnection.last_attempt) >= RECONNECT_INTERVAL {
+                due.push(name.clone());
+            }
+        }
+
+        // Reconnecting locks the connections, so the attempts are made without holding `disconnected`
+        for name in due {
+            let result = self.reconnect(&name);
+            let mut disconnected = self.disconnected.lock().unwrap();
+            match result {
+                Ok(()) => {
+                    log_println!("[✅] Reconnected device '{}'", name);
+                    disconnected.remove(&name);
+                    changes.push(ConnectionChange::Restored(name));
+                }
+                Err(e) => {
+                    if let Some(disconnection) = disconnected.get_mut(&name) {
+                        disconnection.attempts += 1;
+                        disconnection.last_attempt = now;
+                    }
+                    log_eprintln!("Failed to reconnect device '{}': {}", name, e);
+                }
+            }
+        }
+        changes
+    }
+
+    /// Reopens the port of a disconnected MIDI device by name, keeping its slot and latency.
+    /// Other devices have no connection to reopen, so sending to them is simply retried.
+    fn reconnect(&self, name: &str) -> Result<(), String> {
+        let kind = self
+            .output_connections
+            .lock()
+            .unwrap()
+            .get(name)
+            .map(|device| device.kind());
+        match kind {
+            None => Err(format!("Device '{}' is no longer registered.", name)),
+            Some(DeviceKind::Midi) => {
+                let mut midi_out = MidiOut::new(name.to_string()).map_err(|e| e.to_string())?;
+                midi_out.connect().map_err(|e| e.to_string())?;
+                self.output_connections.lock().unwrap().insert(
+                    name.to_string(),
+                    Arc::new(ProtocolDevice::MIDIOutDevice(midi_out)),
+                );
+                // The input may come back later than the output, it is not required to play
+                let midi_in = MidiIn::new(name.to_string())
+                    .and_then(|mut midi_in| midi_in.connect().map(|_| midi_in));
+                if let Ok(midi_in) = midi_in {
+                    self.register_input_connection(
+                        name.to_string(),
+                        ProtocolDevice::MIDIInDevice(midi_in),
+                    );
+                }
+                Ok(())
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
     /// Generates a list of discoverable and currently connected devices.
     ///
     /// This function aggregates information from:
@@ -448,6 +700,8 @@ impl DeviceMap {
     /// The internal Log device is excluded from this list.
     pub fn device_list(&self) -> Vec<DeviceInfo> {
         let mut discovered_devices_map: BTreeMap<String, DeviceInfo> = BTreeMap::new();
+        let disconnected: BTreeSet<String> =
+            self.disconnected.lock().unwrap().keys().cloned().collect();
         let connected_map = self.output_connections.lock().unwrap(); // Lock output connections once
 
         // Helper to create DeviceInfo, checking slot assignment and connection status
@@ -460,12 +714,25 @@ impl DeviceMap {
 
             // Determine connection status based on presence in connected_map for outputs
             // For system ports discovered but not explicitly connected via Sova, this might show false.
-            let is_connected = connected_map.contains_key(&name);
+            let is_connected = connected_map.contains_key(&name) && !disconnected.contains(&name);
 
             // Extract address specifically for OSC devices using the provided reference
             let address = device_ref_opt.map(ProtocolDevice::address);
             let latency = self.get_latency(&name);
 
+            // Discovered ports are present on the system, OSC gives no feedback
+            let mut capabilities = device_ref_opt.map_or_else(
+                || DeviceCapabilities::from(kind.clone()),
+                ProtocolDevice::capabilities,
+            );
+            capabilities.reachability = if disconnected.contains(&name) {
+                Reachability::Unreachable
+            } else if kind == DeviceKind::Osc {
+                Reachability::Unknown
+            } else {
+                Reachability::Reachable
+            };
+
             DeviceInfo {
                 slot_id: assigned_slot_id,
                 name,
@@ -473,7 +740,8 @@ impl DeviceMap {
                 direction,
                 is_connected,
                 address,
-                latency
+                latency,
+                capabilities: Some(capabilities),
             }
         };
 
@@ -554,7 +822,11 @@ impl DeviceMap {
                         direction: DeviceDirection::Output,
                         is_connected: false,
                         address: None,
-                        latency: 0.0
+                        latency: 0.0,
+                        capabilities: Some(DeviceCapabilities {
+                            reachability: Reachability::Unreachable,
+                            ..DeviceKind::Midi.into()
+                        }),
                 },
                 );
             }
@@ -669,6 +941,7 @@ impl DeviceMap {
             "Attempting to disconnect MIDI device (In/Out): {}",
             device_name
         );
+        self.disconnected.lock().unwrap().remove(device_name);
 
         let (input, output) = (
             self.output_connections
@@ -907,6 +1180,7 @@ impl DeviceMap {
     /// - `Err(String)` if no OSC Output device with the given name is found.
     pub fn remove_input_device(&self, name: &str) -> Result<(), String> {
         log_println!("[🗑️] Removing OSC Output device: '{}'", name);
+        self.disconnected.lock().unwrap().remove(name);
         let mut input_connections = self.input_connections.lock().unwrap();
 
         if input_connections.remove(name).is_some() {
@@ -940,6 +1214,7 @@ impl DeviceMap {
     /// - `Err(String)` if no OSC Output device with the given name is found.
     pub fn remove_output_device(&self, name: &str) -> Result<(), String> {
         log_println!("[🗑️] Removing OSC Output device: '{}'", name);
+        self.disconnected.lock().unwrap().remove(name);
         let mut output_connections = self.output_connections.lock().unwrap();
 
         if output_connections.remove(name).is_some() {
@@ -988,6 +1263,7 @@ impl DeviceMap {
                     is_connected: true,
                     address: Some(device_arc.address()),
                     latency: self.get_latency(name),
+                    capabilities: None,
             })
             })
             .collect()
@@ -1115,8 +1391,13 @@ impl DeviceMap {
                 }
             }
 
-            // Restore latency
-            self.set_latency(device.name, device.latency);
+            // Restore latency, missing devices get the default one once they reconnect
+            if missing.contains(&device.name) {
+                continue;
+            }
+            if let Err(e) = self.set_latency(device.name, device.latency) {
+                log_eprintln!("Failed to restore latency: {}", e);
+            }
         }
 
         missing
@@ -1177,3 +1458,182 @@ impl Default for DeviceMap {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{ProtocolPayload, midi::MpeExpression};
+
+    #[test]
+    fn latency_delays_or_advances_events() {
+        let date = 1_000_000;
+        assert_eq!(DeviceMap::shift_by_latency(date, 0.0), date);
+        assert_eq!(DeviceMap::shift_by_latency(date, 0.02), date + 20_000);
+        assert_eq!(DeviceMap::shift_by_latency(date, -0.01), date - 10_000);
+    }
+
+    #[test]
+    fn negative_latency_is_bounded_by_lookahead() {
+        let devices = DeviceMap::new();
+        // A log output stands in for a synth, without opening a port
+        devices.register_output_connection("early".to_owned(), ProtocolDevice::Log);
+        devices.set_latency("early".to_owned(), -1.0).unwrap();
+        assert_eq!(devices.get_latency("early"), MIN_LATENCY);
+        // Typos do not create latencies for devices that do not exist
+        assert!(devices.set_latency("erly".to_owned(), -1.0).is_err());
+        assert_eq!(devices.get_latency("erly"), 0.0);
+        assert_eq!(
+            DeviceMap::shift_by_latency(SCHEDULED_DRIFT, devices.get_latency("early")),
+            0
+        );
+    }
+
+    #[test]
+    fn failed_sends_disconnect_the_device_until_it_reconnects() {
+        let devices = DeviceMap::new();
+        // Never connected to a port, like an interface that has been unplugged
+        let unplugged = MidiOut::new("Unplugged Synth".to_owned()).unwrap();
+        devices.register_output_connection(
+            "Unplugged Synth".to_owned(),
+            ProtocolDevice::MIDIOutDevice(unplugged),
+        );
+        devices.assign_slot(1, "Unplugged Synth").unwrap();
+        let clock: Clock = Arc::new(crate::clock::ClockServer::new(120.0, 4.0)).into();
+        let note = ConcreteEvent::MidiNote(60, 100, 0, 100_000, 1, None);
+
+        let messages = devices.map_event(note.clone(), 0, &clock);
+        let message = messages[0].message.clone();
+        let error = message.clone().send().unwrap_err();
+        devices.report_send_failure(&message.device, &error);
+
+        assert!(devices.is_disconnected("Unplugged Synth"));
+        assert!(devices.map_event(note, 0, &clock).is_empty());
+        let listed = devices.device_list();
+        let info = listed.iter().find(|d| d.name == "Unplugged Synth").unwrap();
+        assert!(!info.is_connected);
+
+        let now = Instant::now();
+        assert_eq!(
+            devices.check_connections(now),
+            vec![ConnectionChange::Lost("Unplugged Synth".to_owned())]
+        );
+        assert_eq!(devices.reconnection_attempts("Unplugged Synth"), Some(0));
+
+        // No port with that name exists, so the attempt fails and is retried later
+        assert!(
+            devices
+                .check_connections(now + RECONNECT_INTERVAL)
+                .is_empty()
+        );
+        assert_eq!(devices.reconnection_attempts("Unplugged Synth"), Some(1));
+        assert!(
+            devices
+                .check_connections(now + RECONNECT_INTERVAL)
+                .is_empty()
+        );
+        assert_eq!(devices.reconnection_attempts("Unplugged Synth"), Some(1));
+        devices.check_connections(now + RECONNECT_INTERVAL * 2);
+        assert_eq!(devices.reconnection_attempts("Unplugged Synth"), Some(2));
+        assert!(devices.is_disconnected("Unplugged Synth"));
+    }
+
+    #[test]
+    fn failed_sends_keep_network_outputs_connected() {
+        let devices = DeviceMap::new();
+        devices
+            .create_osc_output_device("Visuals", "127.0.0.1", 57120)
+            .unwrap();
+        let visuals = devices.output_connections.lock().unwrap()["Visuals"].clone();
+
+        let error = ProtocolError("No buffer space available".to_owned());
+        devices.report_send_failure(&visuals, &error);
+        devices.report_send_failure(&visuals, &error);
+
+        assert!(!devices.is_disconnected("Visuals"));
+        assert!(devices.check_connections(Instant::now()).is_empty());
+    }
+
+    #[test]
+    fn simultaneous_mpe_notes_play_on_their_own_channel() {
+        let devices = DeviceMap::new();
+        let synth = MidiOut::new("MPE Synth".to_owned()).unwrap();
+        devices.register_output_connection(
+            "MPE Synth".to_owned(),
+            ProtocolDevice::MIDIOutDevice(synth),
+        );
+        devices.assign_slot(1, "MPE Synth").unwrap();
+        let clock: Clock = Arc::new(crate::clock::ClockServer::new(120.0, 4.0)).into();
+        let play = |note: u64, bend: f64, pressure: u64, timbre: u64| -> Vec<MIDIMessage> {
+            let expression = MpeExpression {
+                bend: Some(bend),
+                pressure: Some(pressure),
+                timbre: Some(timbre),
+            };
+            let event = ConcreteEvent::MidiMpeNote(note, 100, 0, 500_000, expression, 1);
+            devices
+                .map_event(event, 0, &clock)
+                .into_iter()
+                .filter_map(|timed| match timed.message.payload {
+                    ProtocolPayload::MIDI(midi) => Some(midi),
+                    _ => None,
+                })
+                .collect()
+        };
+        let expressed = |bend: u16, pressure: u8, timbre: u8, note: u8| {
+            vec![
+                MIDIMessageType::PitchBend { value: bend },
+                MIDIMessageType::ControlChange {
+                    control: 74,
+                    value: timbre,
+                },
+                MIDIMessageType::ChannelPressure { value: pressure },
+                MIDIMessageType::NoteOn {
+                    note,
+                    velocity: 100,
+                },
+                MIDIMessageType::NoteOff { note, velocity: 0 },
+            ]
+        };
+
+        let first = play(60, 12.0, 80, 20);
+        let second = play(64, -2.0, 30, 100);
+
+        // Member channels start after the master channel, 0-based in MIDI messages
+        assert!(first.iter().all(|midi| midi.channel == 1));
+        assert!(second.iter().all(|midi| midi.channel == 2));
+        let payloads = |messages: Vec<MIDIMessage>| -> Vec<MIDIMessageType> {
+            messages.into_iter().map(|midi| midi.payload).collect()
+        };
+        assert_eq!(payloads(first), expressed(10240, 80, 20, 60));
+        assert_eq!(payloads(second), expressed(7851, 30, 100, 64));
+    }
+
+    #[test]
+    fn mpe_zones_need_a_known_output_and_a_valid_size() {
+        let devices = DeviceMap::new();
+        devices.register_output_connection("MPE Synth".to_owned(), ProtocolDevice::Log);
+
+        assert!(devices.set_mpe_zone("MPE Snth".to_owned(), 4).is_err());
+        assert!(devices.set_mpe_zone("MPE Synth".to_owned(), 0).is_err());
+        assert!(devices.set_mpe_zone("MPE Synth".to_owned(), 16).is_err());
+        assert!(devices.mpe_zones.lock().unwrap().is_empty());
+        assert!(devices.set_mpe_zone("MPE Synth".to_owned(), 15).is_ok());
+        assert_eq!(devices.mpe_zones.lock().unwrap()["MPE Synth"].member_channels(), 15);
+    }
+
+    #[test]
+    fn osc_devices_report_their_target() {
+        let devices = DeviceMap::new();
+        devices
+            .create_osc_output_device("Visuals", "127.0.0.1", 57120)
+            .unwrap();
+        let listed = devices.device_list();
+        let info = listed.iter().find(|d| d.name == "Visuals").unwrap();
+        let capabilities = info.capabilities.as_ref().unwrap();
+        assert_eq!(capabilities.kind, DeviceKind::Osc);
+        assert_eq!(capabilities.ip.as_deref(), Some("127.0.0.1"));
+        assert_eq!(capabilities.port, Some(57120));
+        assert_eq!(capabilities.channels, None);
+        assert_eq!(capabilities.reachability, Reachability::Unknown);
+    }
+}
@@ -16,13 +16,17 @@ pub fn start_scheduler_and_world(
     Sender<SchedulerMessage>,
     Receiver<SovaNotification>,
 ) {
-    let (world_handle, world_iface) = World::create(clock_server.clone());
+    // The world reports failed sends to the scheduler, which owns the reconnections
+    let (sched_iface, sched_source) = crossbeam_channel::unbounded();
+    let (world_handle, world_iface) = World::create(clock_server.clone(), sched_iface.clone());
 
-    let (sched_handle, sched_iface, sched_update) = Scheduler::create(
+    let (sched_handle, sched_update) = Scheduler::create(
         clock_server,
         devices,
         languages,
-        world_iface
+        world_iface,
+        sched_iface.clone(),
+        sched_source
     );
 
     (world_handle, sched_handle, sched_iface, sched_update)
@@ -0,0 +1,102 @@
+//! Names of instruments shared by every language, such as `kick` or `pad`.
+//!
+//! Scripts play an instrument by name, as the sound (`s`) of their audio events,
+//! and the map resolves it to the sample folder or synth preset currently behind it.
+//! Swapping a sample folder then only takes a change to the map, not to the scripts.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::vm::{event::ConcreteEvent, variable::VariableValue};
+
+/// Argument of audio events naming the sound to play.
+const SOUND_ARG: &str = "s";
+
+/// Instrument names, mapped to the sample folder or synth preset they play.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InstrumentMap {
+    sounds: BTreeMap<String, String>,
+}
+
+impl InstrumentMap {
+    pub fn new(sounds: BTreeMap<String, String>) -> Self {
+        InstrumentMap { sounds }
+    }
+
+    /// Maps an instrument to a sample folder or synth preset, `None` removes it.
+    pub fn set(&mut self, name: String, sound: Option<String>) {
+        match sound {
+            Some(sound) => self.sounds.insert(name, sound),
+            None => self.sounds.remove(&name),
+        };
+    }
+
+    /// Sample folder or synth preset of an instrument, `None` when it is not mapped.
+    pub fn resolve(&self, name: &str) -> Option<&str> {
+        self.sounds.get(name).map(String::as_str)
+    }
+
+    pub fn sounds(&self) -> &BTreeMap<String, String> {
+        &self.sounds
+    }
+
+    /// Replaces the instrument played by an audio event with the sound it is mapped to.
+    /// Unmapped names are left as they are, for the audio engine to find a sample folder
+    /// or synth of that name.
+    pub fn apply(&self, mut event: ConcreteEvent) -> ConcreteEvent {
+        if let ConcreteEvent::Dirt { args, .. } = &mut event {
+            let sound = match args.get(SOUND_ARG) {
+                Some(VariableValue::Str(name)) => self.resolve(name),
+                _ => None,
+            };
+            if let Some(sound) = sound {
+                args.insert(SOUND_ARG.to_owned(), VariableValue::Str(sound.to_owned()));
+            }
+        }
+        event
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn play(sound: &str) -> ConcreteEvent {
+        let mut args = HashMap::new();
+        args.insert(SOUND_ARG.to_owned(), VariableValue::Str(sound.to_owned()));
+        ConcreteEvent::Dirt { args, device_id: 1 }
+    }
+
+    fn sound_of(event: &ConcreteEvent) -> &VariableValue {
+        match event {
+            ConcreteEvent::Dirt { args, .. } => &args[SOUND_ARG],
+            other => panic!("expected an audio event, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn instruments_resolve_to_their_folder() {
+        let mut instruments = InstrumentMap::default();
+        instruments.set("kick".to_owned(), Some("bd_909".to_owned()));
+
+        assert_eq!(instruments.resolve("kick"), Some("bd_909"));
+        let event = instruments.apply(play("kick"));
+        assert_eq!(sound_of(&event), &VariableValue::Str("bd_909".to_owned()));
+
+        instruments.set("kick".to_owned(), None);
+        assert_eq!(instruments.resolve("kick"), None);
+    }
+
+    #[test]
+    fn unmapped_instruments_keep_their_name() {
+        let instruments =
+            InstrumentMap::new(BTreeMap::from([("kick".to_owned(), "bd_909".to_owned())]));
+
+        assert_eq!(instruments.resolve("snare"), None);
+        let event = instruments.apply(play("snare"));
+        assert_eq!(sound_of(&event), &VariableValue::Str("snare".to_owned()));
+    }
+}
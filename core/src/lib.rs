@@ -2,6 +2,7 @@ pub mod clock;
 pub mod compiler;
 pub mod device_map;
 pub mod init;
+pub mod instruments;
 pub mod logger;
 pub mod protocol;
 pub mod scene;
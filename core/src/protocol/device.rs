@@ -20,7 +20,51 @@ pub struct DeviceInfo {
     pub direction: DeviceDirection,
     pub is_connected: bool,
     pub address: Option<String>,
-    pub latency: f64
+    pub latency: f64,
+    /// What the device supports, absent from device snapshots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<DeviceCapabilities>,
+}
+
+/// Whether events sent to a device can reach it.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub enum Reachability {
+    Reachable,
+    Unreachable,
+    /// The device gives no feedback, as OSC over UDP.
+    #[default]
+    Unknown,
+}
+
+/// A typed description of what a device supports.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
+pub struct DeviceCapabilities {
+    pub kind: DeviceKind,
+    /// Number of channels events can be sent on, for MIDI devices.
+    #[serde(default)]
+    pub channels: Option<u8>,
+    /// Target IP address of OSC devices.
+    #[serde(default)]
+    pub ip: Option<String>,
+    /// Target port of OSC devices.
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub reachability: Reachability,
+}
+
+impl From<DeviceKind> for DeviceCapabilities {
+    fn from(kind: DeviceKind) -> Self {
+        let channels = match kind {
+            DeviceKind::Midi | DeviceKind::VirtualMidi => Some(16),
+            _ => None,
+        };
+        DeviceCapabilities {
+            kind,
+            channels,
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
@@ -281,6 +325,17 @@ impl ProtocolDevice {
         }
     }
 
+    /// What the device supports. Its reachability is left unknown,
+    /// connections are tracked by the `DeviceMap`.
+    pub fn capabilities(&self) -> DeviceCapabilities {
+        let mut capabilities = DeviceCapabilities::from(self.kind());
+        if let ProtocolDevice::OSCOutDevice(osc_out) = self {
+            capabilities.ip = Some(osc_out.address.ip().to_string());
+            capabilities.port = Some(osc_out.address.port());
+        }
+        capabilities
+    }
+
     pub fn translate_event(&self, event: ConcreteEvent, date: SyncTime, clock: &Clock) 
         -> Vec<(ProtocolPayload, SyncTime)> 
     {
@@ -5,7 +5,7 @@ use std::fmt::{self, Display};
 /// This struct wraps a descriptive error message as a `String`.
 /// It serves as a unified error type, often created by converting
 /// more specific errors (like IO, MIDI, or OSC errors) using the `From` trait.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ProtocolError(pub String);
 
 impl Display for ProtocolError {
@@ -8,6 +8,8 @@ use std::sync::{Arc, Mutex};
 mod control_memory;
 mod message;
 pub use message::*;
+mod mpe;
+pub use mpe::*;
 
 use crate::clock::SyncTime;
 use crate::protocol::error::ProtocolError;
@@ -5,6 +5,7 @@ use crate::clock::SyncTime;
 use crate::vm::event::ConcreteEvent;
 use crate::protocol::error::ProtocolError;
 use crate::protocol::midi::midi_constants::*;
+use crate::protocol::midi::{MPE_DEFAULT_TIMBRE, MPE_TIMBRE_CC};
 use crate::protocol::payload::ProtocolPayload;
 use crate::vm::variable::VariableValue;
 
@@ -121,7 +122,7 @@ impl MIDIMessage {
         epsilon: SyncTime
     ) -> Vec<(ProtocolPayload, SyncTime)> {
         match event {
-            ConcreteEvent::MidiNote(note, vel, chan, dur, _device_id) => {
+            ConcreteEvent::MidiNote(note, vel, chan, dur, _device_id, release) => {
                 let midi_chan = (chan.saturating_sub(1) % 16) as u8; // Convert to 0-based MIDI channel
                 vec![(
                         MIDIMessage {
@@ -142,18 +143,62 @@ impl MIDIMessage {
                             channel: midi_chan,
                         }.into(), date + epsilon
                     ),
-                    // NoteOff
+                    // NoteOff, with the release velocity if any
                     (
                         MIDIMessage {
                             payload: MIDIMessageType::NoteOff {
                                 note: note as u8,
-                                velocity: 0,
+                                velocity: release.map_or(0, |rel| rel.min(127) as u8),
                             },
                             channel: midi_chan,
                         }.into(), date + dur - epsilon,
                     ),
                 ]
             }
+            ConcreteEvent::MidiNoteOff(note, chan, _device_id) => {
+                let midi_chan = (chan.saturating_sub(1) % 16) as u8;
+                vec![
+                    (
+                        MIDIMessage {
+                            payload: MIDIMessageType::NoteOff {
+                                note: note as u8,
+                                velocity: 0,
+                            },
+                            channel: midi_chan,
+                        }.into(), date
+                    ),
+                ]
+            }
+            ConcreteEvent::MidiMpeNote(note, vel, chan, dur, expression, _device_id) => {
+                let midi_chan = (chan.saturating_sub(1) % 16) as u8;
+                let message = |payload: MIDIMessageType| -> ProtocolPayload {
+                    MIDIMessage {
+                        payload,
+                        channel: midi_chan,
+                    }.into()
+                };
+                // The channel may still carry the expression of its previous note
+                let timbre = expression.timbre.unwrap_or(MPE_DEFAULT_TIMBRE).min(127) as u8;
+                let pressure = expression.pressure.unwrap_or(0).min(127) as u8;
+                let bend = expression.bend_value();
+                let (note, velocity) = (note as u8, vel as u8);
+                vec![
+                    (message(MIDIMessageType::PitchBend { value: bend }), date),
+                    (
+                        message(MIDIMessageType::ControlChange {
+                            control: MPE_TIMBRE_CC,
+                            value: timbre,
+                        }),
+                        date,
+                    ),
+                    (message(MIDIMessageType::ChannelPressure { value: pressure }), date),
+                    (message(MIDIMessageType::NoteOn { note, velocity }), date + epsilon),
+                    (
+                        message(MIDIMessageType::NoteOff { note, velocity: 0 }),
+                        date + dur - epsilon,
+                    ),
+                ]
+            }
             ConcreteEvent::MidiControl(control, value, chan, _device_id) => {
                 let midi_chan = (chan.saturating_sub(1) % 16) as u8;
                 vec![
@@ -168,18 +213,35 @@ impl MIDIMessage {
                     ),
                 ]
             }
-            ConcreteEvent::MidiProgram(program, chan, _device_id) => {
+            ConcreteEvent::MidiProgram(program, chan, _device_id, bank) => {
                 let midi_chan = (chan.saturating_sub(1) % 16) as u8;
-                vec![
+                let control_change = |control: u8, value: u64| -> (ProtocolPayload, SyncTime) {
                     (
                         MIDIMessage {
-                            payload: MIDIMessageType::ProgramChange {
-                                program: program as u8,
+                            payload: MIDIMessageType::ControlChange {
+                                control,
+                                value: (value & 0x7F) as u8,
                             },
                             channel: midi_chan,
                         }.into(), date
-                    ),
-                ]
+                    )
+                };
+                let mut messages = Vec::new();
+                // Bank select, MSB (CC 0) then LSB (CC 32), both before the program change
+                if let Some(bank) = bank {
+                    let bank = bank.min(MAX_MIDI_BANK);
+                    messages.push(control_change(0, bank >> 7));
+                    messages.push(control_change(32, bank));
+                }
+                messages.push((
+                    MIDIMessage {
+                        payload: MIDIMessageType::ProgramChange {
+                            program: program.min(127) as u8,
+                        },
+                        channel: midi_chan,
+                    }.into(), date
+                ));
+                messages
             }
             ConcreteEvent::MidiAftertouch(note, pressure, chan, _device_id) => {
                 let midi_chan = (chan.saturating_sub(1) % 16) as u8;
@@ -274,7 +336,7 @@ impl MIDIMessage {
                 match args {
                     VariableValue::Integer(i) => {
                         Self::generate_messages(
-                            ConcreteEvent::MidiNote(i as u64, 90, midi_chan, duration, _device_id), 
+                            ConcreteEvent::MidiNote(i as u64, 90, midi_chan, duration, _device_id, None), 
                             date, epsilon
                         )
                     }
@@ -288,7 +350,7 @@ impl MIDIMessage {
                             _ => 90
                         };
                         Self::generate_messages(
-                            ConcreteEvent::MidiNote(note, velocity, midi_chan, duration, _device_id),
+                            ConcreteEvent::MidiNote(note, velocity, midi_chan, duration, _device_id, None),
                             date, epsilon
                         )
                     },
@@ -406,3 +468,55 @@ impl Display for MIDIMessageType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note_off_velocities(release: Option<u64>) -> Vec<u8> {
+        let note = ConcreteEvent::MidiNote(60, 100, 1, 100_000, 1, release);
+        MIDIMessage::generate_messages(note, 0, 10)
+            .into_iter()
+            .filter_map(|(payload, _)| match payload {
+                ProtocolPayload::MIDI(MIDIMessage {
+                    payload: MIDIMessageType::NoteOff { velocity, .. },
+                    ..
+                }) => Some(velocity),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn program_change_bytes(program: u64, bank: Option<u64>) -> Vec<Vec<u8>> {
+        let event = ConcreteEvent::MidiProgram(program, 3, 1, bank);
+        MIDIMessage::generate_messages(event, 0, 10)
+            .into_iter()
+            .map(|(payload, _)| match payload {
+                ProtocolPayload::MIDI(message) => message.to_bytes().unwrap(),
+                other => panic!("expected a MIDI message, got {other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn program_changes_select_the_bank_first() {
+        assert_eq!(program_change_bytes(5, None), vec![vec![0xC2, 5]]);
+        // Bank 300 is MSB 2, LSB 44
+        assert_eq!(
+            program_change_bytes(5, Some(300)),
+            vec![vec![0xB2, 0, 2], vec![0xB2, 32, 44], vec![0xC2, 5]]
+        );
+        assert_eq!(
+            program_change_bytes(200, Some(20_000)),
+            vec![vec![0xB2, 0, 127], vec![0xB2, 32, 127], vec![0xC2, 127]]
+        );
+    }
+
+    #[test]
+    fn note_off_carries_the_release_velocity() {
+        // The first note off clears a previous instance of the note, the last one ends it
+        assert_eq!(note_off_velocities(Some(42)), vec![0, 42]);
+        assert_eq!(note_off_velocities(Some(300)), vec![0, 127]);
+        assert_eq!(note_off_velocities(None), vec![0, 0]);
+    }
+}
@@ -12,3 +12,4 @@ pub const START_MSG: u8 = 0xFA;
 pub const STOP_MSG: u8 = 0xFC;
 pub const SYSTEM_EXCLUSIVE_MSG: u8 = 0xF0;
 pub const SYSTEM_EXCLUSIVE_END_MSG: u8 = 0xF7;
+pub const MAX_MIDI_BANK: u64 = 0x3FFF;
@@ -0,0 +1,81 @@
+//! MIDI Polyphonic Expression (MPE): each note plays on a channel of its own, so that its
+//! pitch bend, pressure and timbre can change without affecting the other notes.
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::SyncTime;
+
+/// Pitch bend range of the member channels in semitones, the default of the MPE specification.
+pub const MPE_BEND_RANGE: f64 = 48.0;
+/// Control change carrying the timbre of MPE notes.
+pub const MPE_TIMBRE_CC: u8 = 74;
+/// Timbre of MPE notes that do not set one.
+pub const MPE_DEFAULT_TIMBRE: u64 = 64;
+/// Member channels of a zone spanning every channel after the master one.
+pub const MAX_MPE_MEMBER_CHANNELS: usize = 15;
+
+/// Expression of a single MPE note, sent on its channel before the note starts.
+/// Dimensions left to `None` are reset to their neutral value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MpeExpression {
+    /// Pitch bend in semitones, within `MPE_BEND_RANGE` either way.
+    pub bend: Option<f64>,
+    /// Pressure from 0 to 127.
+    pub pressure: Option<u64>,
+    /// Timbre from 0 to 127.
+    pub timbre: Option<u64>,
+}
+
+impl MpeExpression {
+    /// 14 bits pitch bend value, 8192 being no bend.
+    pub fn bend_value(&self) -> u16 {
+        let bend = self
+            .bend
+            .unwrap_or(0.0)
+            .clamp(-MPE_BEND_RANGE, MPE_BEND_RANGE);
+        (8192.0 + bend / MPE_BEND_RANGE * 8192.0)
+            .round()
+            .clamp(0.0, 16383.0) as u16
+    }
+}
+
+/// Lower MPE zone of a MIDI output: channel 1 is the master channel, and notes are spread
+/// across the member channels that follow it.
+#[derive(Debug, Clone)]
+pub struct MpeZone {
+    /// Date until which each member channel plays a note.
+    busy_until: Vec<SyncTime>,
+}
+
+impl MpeZone {
+    /// Zone of `member_channels` channels after the master one, at most 15.
+    pub fn new(member_channels: usize) -> Self {
+        MpeZone {
+            busy_until: vec![0; member_channels.clamp(1, MAX_MPE_MEMBER_CHANNELS)],
+        }
+    }
+
+    pub fn member_channels(&self) -> usize {
+        self.busy_until.len()
+    }
+
+    /// Picks the channel of a note played at `date` for `duration`, from 2 to 16.
+    /// Free channels are reused in the order they were released. When all of them
+    /// are playing, the note steals the channel of the note ending first.
+    pub fn allocate(&mut self, date: SyncTime, duration: SyncTime) -> u64 {
+        let (index, _) = self
+            .busy_until
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, until)| **until)
+            .unwrap_or((0, &0));
+        self.busy_until[index] = date + duration;
+        index as u64 + 2
+    }
+}
+
+impl Default for MpeZone {
+    fn default() -> Self {
+        MpeZone::new(MAX_MPE_MEMBER_CHANNELS)
+    }
+}
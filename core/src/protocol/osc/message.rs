@@ -121,7 +121,7 @@ impl OSCMessage {
                 vec![(dirt_msg.into(), date)]
             }
             // Legacy MIDI-to-OSC mappings (consider removal/refinement)
-            ConcreteEvent::MidiNote(note, vel, chan, _dur, _device_id) => {
+            ConcreteEvent::MidiNote(note, vel, chan, _dur, _device_id, _) => {
                 vec![(OSCMessage {
                     addr: "/midi/noteon".to_string(),
                     args: vec![
@@ -143,13 +143,15 @@ impl OSCMessage {
                     timetag: timetag
                 }.into(), date)]
             }
-            ConcreteEvent::MidiProgram(program, chan, _device_id) => {
+            ConcreteEvent::MidiProgram(program, chan, _device_id, bank) => {
+                let mut args = vec![
+                    VariableValue::Integer(program as i64),
+                    VariableValue::Integer(chan as i64),
+                ];
+                args.extend(bank.map(|bank| VariableValue::Integer(bank as i64)));
                 vec![(OSCMessage {
                     addr: "/midi/program".to_string(),
-                    args: vec![
-                        VariableValue::Integer(program as i64),
-                        VariableValue::Integer(chan as i64),
-                    ],
+                    args,
                     timetag: timetag
                 }.into(), date)]
             }
@@ -8,13 +8,26 @@ use core::f64;
 use std::usize;
 mod frame;
 mod line;
+pub mod interned;
 pub mod script;
 
 mod execution_mode;
 pub use execution_mode::*;
 
+mod metadata;
+pub use metadata::{KeySignature, SceneMetadata, TimeSignature};
+
+mod playback_mode;
+pub use playback_mode::LinePlaybackMode;
+
+mod fill;
+pub use fill::LineFill;
+
+mod text_format;
+pub use text_format::{SceneTextError, parse_scene_text};
+
 pub use frame::Frame;
-pub use line::Line;
+pub use line::{Line, MAX_GATE, MAX_NUDGE, MIN_GATE, ScriptPanic};
 
 fn default_date() -> SyncTime {
     NEVER
@@ -38,6 +51,8 @@ pub struct Scene {
     pub vars: VariableStore,
     #[serde(default)]
     pub mode: ExecutionMode,
+    #[serde(default, skip_serializing_if = "SceneMetadata::is_default")]
+    pub metadata: SceneMetadata,
     #[serde(skip, default = "default_date")]
     last_date: SyncTime,
     #[serde(skip, default = "default_offset")]
@@ -54,6 +69,7 @@ impl Scene {
             lines,
             vars: VariableStore::new(),
             mode: ExecutionMode::default(),
+            metadata: SceneMetadata::default(),
             last_date: default_date(),
             beat_offset: default_offset(),
         }
@@ -160,6 +176,24 @@ impl Scene {
         self.lines.remove(index);
     }
 
+    /// Moves the line at index `from` to index `to`, shifting the lines in between.
+    ///
+    /// The line keeps its frames and playback state. Prints a warning and does nothing
+    /// if either index is out of bounds. Returns whether the line was moved.
+    pub fn move_line(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.n_lines() || to >= self.n_lines() {
+            log_eprintln!(
+                "Warning: Attempted to move line {} to invalid position {}. Ignoring.",
+                from,
+                to
+            );
+            return false;
+        }
+        let line = self.lines.remove(from);
+        self.lines.insert(to, line);
+        true
+    }
+
     /// Returns an immutable reference to the line at the specified `index`,
     /// or None if it doesn't exist.
     pub fn line(&self, index: usize) -> Option<&Line> {
@@ -201,10 +235,48 @@ impl Scene {
         self.lines.iter().map(Line::position)
     }
 
+    /// Takes the enabled frames without a script reached by the lines since the last call,
+    /// as `(line, frame, date)`.
+    pub fn take_empty_frames_reached(&mut self) -> Vec<(usize, usize, SyncTime)> {
+        let mut reached = Vec::new();
+        for (line_id, line) in self.lines.iter_mut().enumerate() {
+            for (frame_id, date) in line.take_empty_frames_reached() {
+                reached.push((line_id, frame_id, date));
+            }
+        }
+        reached
+    }
+
+    /// Takes the one-shot frames which have fired since the last call, as `(line, frame)`.
+    pub fn take_fired_one_shots(&mut self) -> Vec<(usize, usize)> {
+        let mut fired = Vec::new();
+        for (line_id, line) in self.lines.iter_mut().enumerate() {
+            for frame_id in line.take_fired_one_shots() {
+                fired.push((line_id, frame_id));
+            }
+        }
+        fired
+    }
+
+    /// Takes the scripts which panicked since the last call, along with their line.
+    pub fn take_script_panics(&mut self) -> Vec<(usize, ScriptPanic)> {
+        let mut panics = Vec::new();
+        for (line_id, line) in self.lines.iter_mut().enumerate() {
+            for panic in line.take_script_panics() {
+                panics.push((line_id, panic));
+            }
+        }
+        panics
+    }
+
     pub fn kill_executions(&mut self) {
         self.lines.iter_mut().for_each(Line::kill_executions);
     }
 
+    pub fn reset_rngs(&mut self) {
+        self.lines.iter_mut().for_each(Line::reset_rng);
+    }
+
     pub fn update_executions<'a>(
         &'a mut self,
         mut partial: PartialContext<'a>,
@@ -222,6 +294,19 @@ impl Scene {
         (events, next_wait)
     }
 
+    /// Rescales the lines driven by a duration generator as they start a new cycle.
+    /// Returns the time to wait until the next trigger of the rescaled lines.
+    pub fn update_duration_scales<'a>(&'a mut self, mut partial: PartialContext<'a>) -> SyncTime {
+        let mut next_wait = NEVER;
+        partial.global_vars = Some(&mut self.vars);
+        for (index, line) in self.lines.iter_mut().enumerate() {
+            let mut partial_child = partial.child();
+            partial_child.line_index = Some(index);
+            next_wait = std::cmp::min(next_wait, line.update_duration_scale(&mut partial_child));
+        }
+        next_wait
+    }
+
     pub fn go_to_date(&mut self, clock: &Clock, date: SyncTime) {
         for line in self.lines.iter_mut() {
             line.go_to_date(clock, date);
@@ -243,12 +328,12 @@ impl Scene {
         date: &mut SyncTime,
     ) -> SyncTime {
         let len = line.length();
-        let rem = ActionTiming::AtNextModulo(len).remaining(last_date.saturating_sub(date_offset), clock);
+        let rem = ActionTiming::AtNextModulo(len).remaining(last_date.saturating_sub(date_offset), clock, len);
         if date.saturating_sub(last_date) >= rem {
             line.start();
             *date = last_date.saturating_add(rem);
         }
-        ActionTiming::AtNextModulo(len).remaining(uncorrected.saturating_sub(date_offset), clock)
+        ActionTiming::AtNextModulo(len).remaining(uncorrected.saturating_sub(date_offset), clock, len)
     }
 
     pub fn step(
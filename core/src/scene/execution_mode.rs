@@ -16,7 +16,7 @@ impl ExecutionMode {
     pub fn remaining(&self, scene: &Scene, date: SyncTime, clock: &Clock) -> SyncTime {
         match self {
             ExecutionMode::AtQuantum => {
-                ActionTiming::AtNextPhase.remaining(date, clock)
+                ActionTiming::AtNextPhase.remaining(date, clock, clock.quantum())
             }
             ExecutionMode::Free => {
                 NEVER
@@ -25,7 +25,8 @@ impl ExecutionMode {
                 let Some(line) = scene.longest_line() else {
                     return NEVER;
                 };
-                ActionTiming::AtNextModulo(line.length()).remaining(date, clock)
+                let len = line.length();
+                ActionTiming::AtNextModulo(len).remaining(date, clock, len)
             }
         }
     }
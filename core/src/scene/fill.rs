@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::scene::Frame;
+
+/// Alternate frames a line plays in place of its own every few cycles,
+/// such as a drum fill closing every fourth bar.
+///
+/// Fill frames stand in for the frame at the same position, which keeps its duration
+/// and repetitions, so that the fill lasts exactly one cycle of the line.
+/// Positions beyond the end of the fill play the frames of the line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineFill {
+    pub frames: Vec<Frame>,
+    /// The fill plays on every cycle whose number is a multiple of this one.
+    pub every: usize,
+}
+
+impl LineFill {
+    /// Whether the fill plays during the given cycle, counted from `1`.
+    pub fn plays_on(&self, cycle: usize) -> bool {
+        self.every > 0 && cycle > 0 && cycle.is_multiple_of(self.every)
+    }
+}
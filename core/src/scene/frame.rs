@@ -30,6 +30,17 @@ pub struct Frame {
         skip_serializing_if = "is_default_enabledness"
     )]
     pub enabled: bool,
+    /// Whether the frame disables itself once it has been triggered, to play a single time.
+    /// Enabling it again arms it for another play.
+    #[serde(default, skip_serializing_if = "is_default_one_shot")]
+    pub one_shot: bool,
+    /// Relative chance of this frame being drawn when its line plays in `WeightedRandom` mode.
+    #[serde(default = "default_weight", skip_serializing_if = "is_default_weight")]
+    pub weight: f64,
+    /// Offset in beats applied to the trigger of the frame, negative values trigger it earlier.
+    /// Bounded by [`Line::make_consistent`](crate::scene::Line::make_consistent) so that frames stay in order.
+    #[serde(default, skip_serializing_if = "is_default_nudge")]
+    pub nudge: f64,
     /// Scripts associated with the frame. Executed when the frame becomes active.
     script: Script,
     /// Optional user-defined names for each frame. Useful for identification in UIs or debugging.
@@ -42,6 +53,9 @@ pub struct Frame {
     script_has_changed: bool,
     #[serde(skip)]
     pub executions: Vec<ScriptExecution>,
+    /// Messages of the executions which panicked since they were last taken.
+    #[serde(skip)]
+    panics: Vec<String>,
 }
 
 impl Frame {
@@ -154,11 +168,19 @@ impl Frame {
                 _ => events.push(event),
             }
         }
+        for exec in self.executions.iter_mut() {
+            self.panics.extend(exec.take_panic());
+        }
         self.executions.retain(|exec| !exec.has_terminated());
         self.executions.append(&mut new_executions);
         (events, next_wait)
     }
 
+    /// Takes the messages of the executions which panicked since the last call.
+    pub fn take_panics(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.panics)
+    }
+
     pub fn before_next_update(&self, date: SyncTime) -> SyncTime {
         self.executions
             .iter()
@@ -180,6 +202,22 @@ fn is_default_repetitions(value: &usize) -> bool {
     *value == default_repetitions()
 }
 
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn is_default_weight(value: &f64) -> bool {
+    *value == default_weight()
+}
+
+fn is_default_nudge(value: &f64) -> bool {
+    *value == 0.0
+}
+
+fn is_default_one_shot(value: &bool) -> bool {
+    !*value
+}
+
 fn default_enabledness() -> bool {
     true
 }
@@ -212,11 +250,15 @@ impl Default for Frame {
             duration: 1.0,
             repetitions: default_repetitions(),
             enabled: default_enabledness(),
+            one_shot: false,
+            weight: default_weight(),
+            nudge: 0.0,
             script: Default::default(),
             name: None,
             vars: Default::default(),
             script_has_changed: false,
             executions: Default::default(),
+            panics: Vec::new(),
         }
     }
 }
@@ -227,11 +269,15 @@ impl Clone for Frame {
             duration: self.duration.clone(),
             repetitions: self.repetitions.clone(),
             enabled: self.enabled.clone(),
+            one_shot: self.one_shot,
+            weight: self.weight,
+            nudge: self.nudge,
             script: self.script.clone(),
             name: self.name.clone(),
             vars: Default::default(),
             script_has_changed: false,
             executions: Default::default(),
+            panics: Vec::new(),
         }
     }
 }
@@ -242,6 +288,9 @@ impl fmt::Debug for Frame {
             .field("duration", &self.duration)
             .field("repetitions", &self.repetitions)
             .field("enabled", &self.enabled)
+            .field("one_shot", &self.one_shot)
+            .field("weight", &self.weight)
+            .field("nudge", &self.nudge)
             .field("script", &self.script)
             .field("name", &self.name)
             .field("vars", &self.vars)
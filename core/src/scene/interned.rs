@@ -0,0 +1,145 @@
+//! Serialization of a [`Scene`] where identical script contents are stored once.
+//!
+//! Meant to be used with `#[serde(with = "sova_core::scene::interned")]` on stored scenes.
+//! Scenes written without interning can still be read.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error};
+
+use crate::Scene;
+
+/// A scene whose script contents are replaced by indices in a table.
+#[derive(Serialize, Deserialize)]
+struct InternedScene {
+    /// Distinct script contents.
+    scripts: Vec<String>,
+    /// Index in `scripts` of the content of each frame, line by line.
+    contents: Vec<Vec<usize>>,
+    /// The scene, with empty script contents.
+    scene: Scene,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredScene {
+    Interned(InternedScene),
+    Plain(Scene),
+}
+
+pub fn serialize<S: Serializer>(scene: &Scene, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut scene = scene.clone();
+    let mut scripts: Vec<String> = Vec::new();
+    let mut indices: HashMap<String, usize> = HashMap::new();
+    let mut contents = Vec::with_capacity(scene.n_lines());
+    for line in scene.lines.iter_mut() {
+        let mut line_contents = Vec::with_capacity(line.n_frames());
+        for frame in line.frames.iter_mut() {
+            let mut script = frame.script().clone();
+            let content = script.content().to_owned();
+            let index = *indices.entry(content).or_insert_with_key(|content| {
+                scripts.push(content.clone());
+                scripts.len() - 1
+            });
+            line_contents.push(index);
+            script.set_content(String::new());
+            frame.set_script(script);
+        }
+        contents.push(line_contents);
+    }
+    InternedScene {
+        scripts,
+        contents,
+        scene,
+    }
+    .serialize(serializer)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Scene, D::Error> {
+    let InternedScene {
+        scripts,
+        contents,
+        mut scene,
+    } = match StoredScene::deserialize(deserializer)? {
+        StoredScene::Interned(interned) => interned,
+        StoredScene::Plain(scene) => return Ok(scene),
+    };
+    if contents.len() != scene.n_lines() {
+        return Err(D::Error::custom(
+            "interned scene has script contents for a different number of lines",
+        ));
+    }
+    for (line, indices) in scene.lines.iter_mut().zip(contents) {
+        if indices.len() != line.n_frames() {
+            return Err(D::Error::custom(
+                "interned scene has script contents for a different number of frames",
+            ));
+        }
+        for (frame, index) in line.frames.iter_mut().zip(indices) {
+            let Some(content) = scripts.get(index) else {
+                return Err(D::Error::custom(format!(
+                    "interned scene refers to unknown script content {index}"
+                )));
+            };
+            let mut script = frame.script().clone();
+            script.set_content(content.clone());
+            frame.set_script(script);
+        }
+    }
+    Ok(scene)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scene::{Line, script::Script};
+
+    #[derive(Serialize, Deserialize)]
+    struct Stored {
+        #[serde(with = "super")]
+        scene: Scene,
+    }
+
+    #[test]
+    fn duplicate_scripts_are_stored_once() {
+        let kick = ">> [note: 36 vel: 100 dur: 0.25 chan: 10] ".repeat(8);
+        let hats = ">> [note: 42 vel: 60 dur: 0.125 chan: 10] ".repeat(8);
+        let lines = (0..8)
+            .map(|l| {
+                let mut line = Line::new(vec![1.0; 16]);
+                for (f, frame) in line.frames.iter_mut().enumerate() {
+                    let content = if (l + f) % 2 == 0 { &kick } else { &hats };
+                    frame.set_script(Script::new(content.clone(), "bob".to_owned()));
+                }
+                line
+            })
+            .collect();
+        let mut scene = Scene::new(lines);
+        scene
+            .line_mut(3)
+            .frame_mut(5)
+            .set_script(Script::new("(note 60)".to_owned(), "bali".to_owned()));
+
+        let plain = serde_json::to_string(&scene).unwrap();
+        let interned = serde_json::to_string(&Stored {
+            scene: scene.clone(),
+        })
+        .unwrap();
+        assert!(interned.len() * 3 < plain.len());
+
+        let restored = serde_json::from_str::<Stored>(&interned).unwrap().scene;
+        assert_eq!(restored.n_lines(), scene.n_lines());
+        for (line, original) in restored.lines.iter().zip(scene.lines.iter()) {
+            assert_eq!(line.n_frames(), original.n_frames());
+            for (frame, expected) in line.frames.iter().zip(original.frames.iter()) {
+                assert!(frame.script().is_like(expected.script()));
+            }
+        }
+        assert_eq!(restored.get_frame(3, 5).unwrap().script().lang(), "bali");
+
+        let from_plain = serde_json::from_str::<Stored>(&format!("{{\"scene\":{plain}}}"))
+            .unwrap()
+            .scene;
+        assert_eq!(from_plain.get_frame(0, 0).unwrap().script().content(), kick);
+    }
+}
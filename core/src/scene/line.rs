@@ -1,10 +1,13 @@
-use std::cmp;
+use std::{cmp, collections::VecDeque};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
 
 use crate::{
     clock::NEVER,
-    scene::{Frame, script::Script},
-    util::decimal_operations::precise_division,
-    vm::{PartialContext, event::ConcreteEvent, interpreter::InterpreterDirectory},
+    scene::{Frame, LineFill, LinePlaybackMode, script::Script},
+    util::{decimal_operations::precise_division, random},
+    vm::{PartialContext, ValueGenerator, event::ConcreteEvent, interpreter::InterpreterDirectory},
 };
 
 use serde::{Deserialize, Serialize};
@@ -21,12 +24,61 @@ pub fn default_speed_factor() -> f64 {
     1.0f64
 }
 
+/// Shortest note length multiplier accepted by [`Line::set_gate`].
+pub const MIN_GATE: f64 = 0.01;
+/// Longest note length multiplier accepted by [`Line::set_gate`].
+pub const MAX_GATE: f64 = 4.0;
+
+/// Default gate for lines if not specified.
+/// Returns `1.0`, which leaves note durations unchanged. Used for serde default.
+pub fn default_gate() -> f64 {
+    1.0f64
+}
+
+fn is_default_gate(gate: &f64) -> bool {
+    *gate == default_gate()
+}
+
+/// Largest shift, in semitones, accepted by [`Line::set_transpose`].
+pub const MAX_TRANSPOSE: i64 = 127;
+
+fn is_default_transpose(transpose: &i64) -> bool {
+    *transpose == 0
+}
+
+/// Largest part of a frame duration a nudge can move a trigger by.
+/// Frames can move later by this part of their own duration, and earlier by this part
+/// of the shortest of their duration and the duration of the frame before them,
+/// so that nudged frames never cross their neighbors.
+pub const MAX_NUDGE: f64 = 0.5;
+
+/// A script of a line which panicked while running, ending its execution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptPanic {
+    /// Index of the frame running the script.
+    pub frame: usize,
+    /// Whether the frame belongs to the fill of the line rather than to the line itself.
+    pub in_fill: bool,
+    pub message: String,
+}
+
+/// A MIDI note played by a line, sounding until its note-off is due.
+#[derive(Debug, Clone)]
+struct SoundingNote {
+    note: u64,
+    channel: u64,
+    device_id: usize,
+    until: SyncTime,
+}
+
 #[derive(Debug, Clone)]
 pub struct LineState {
     pub current_frame: usize,
     /// The current repetition count for the currently active frame (0-based). Resets when moving to a new frame.
     pub current_repetition: usize,
     pub last_trigger: SyncTime,
+    /// Number of frames drawn so far, in random playback modes.
+    pub frames_drawn: usize,
 }
 
 /// Represents a sequence of timed frames within a scene, each with associated scripts and properties.
@@ -55,6 +107,39 @@ pub struct Line {
     pub looping: bool,
     #[serde(default)]
     pub trailing: bool,
+    /// Order in which frames are played. Defaults to `Sequential`.
+    #[serde(default, skip_serializing_if = "LinePlaybackMode::is_sequential")]
+    pub playback_mode: LinePlaybackMode,
+    /// Seed of the random playback modes, for reproducible draws. Unseeded lines draw differently each run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// A multiplier applied to the duration of the MIDI notes of this line. `< 1.0` is more staccato, `> 1.0` more legato.
+    #[serde(default = "default_gate", skip_serializing_if = "is_default_gate")]
+    pub gate: f64,
+    /// If set, the MIDI events of this line are all sent on this channel (1 to 16), whatever their script specifies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub midi_channel: Option<u64>,
+    /// Semitones added to the MIDI notes of this line, on top of the notes of its scripts.
+    #[serde(default, skip_serializing_if = "is_default_transpose")]
+    pub transpose: i64,
+    /// If set, the playhead goes back to the start frame when the line is enabled again.
+    #[serde(default)]
+    pub retrigger_on_enable: bool,
+    /// If set, notes still sounding when the line is disabled ring until their end
+    /// instead of being released.
+    #[serde(default)]
+    pub sustain_on_disable: bool,
+    /// If set, scales the frame durations at the start of each cycle of the line.
+    /// Generated values that are not strictly positive leave the durations unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_generator: Option<ValueGenerator>,
+    /// If set, alternate frames played instead of the frames of the line every few cycles.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fill: Option<LineFill>,
+    /// If set, grid in beats on which the quantized edits of this line apply,
+    /// instead of the quantization grid of the scheduler.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quantization: Option<f64>,
 
     // --- Runtime State (Not Serialized) ---
     /// The current loop iteration number for the line.
@@ -66,8 +151,27 @@ pub struct Line {
     /// Total number *unique* frames that have been *executed* (started) during playback.
     #[serde(skip)]
     pub frames_passed: usize,
+    /// Number of cycles started since the line was reset, counting restarts and loops alike.
+    #[serde(skip)]
+    pub cycles_started: usize,
     #[serde(skip)]
     states: Vec<LineState>,
+    #[serde(skip)]
+    rng: Option<ChaCha20Rng>,
+    #[serde(skip)]
+    sounding: Vec<SoundingNote>,
+    /// Cycle the duration generator was last evaluated for, and the scale it gave.
+    #[serde(skip)]
+    generated_scale: Option<(usize, f64)>,
+    /// Enabled frames without a script reached since they were last taken, with their dates.
+    #[serde(skip)]
+    empty_frames_reached: Vec<(usize, SyncTime)>,
+    /// One-shot frames which have played and disabled themselves since they were last taken.
+    #[serde(skip)]
+    fired_one_shots: Vec<usize>,
+    /// Scripts which panicked since they were last taken.
+    #[serde(skip)]
+    script_panics: Vec<ScriptPanic>,
 }
 
 impl Line {
@@ -102,6 +206,21 @@ impl Line {
         for frame in self.frames.iter_mut() {
             frame.make_consistent();
         }
+        if let Some(fill) = self.fill.as_mut() {
+            fill.frames.iter_mut().for_each(Frame::make_consistent);
+        }
+
+        for index in 0..n_frames {
+            let before = self.frames[(index + n_frames - 1) % n_frames].duration;
+            let frame = &mut self.frames[index];
+            let latest = frame.duration.max(0.0) * MAX_NUDGE;
+            let earliest = -(before.min(frame.duration).max(0.0) * MAX_NUDGE);
+            frame.nudge = if frame.nudge.is_finite() {
+                frame.nudge.clamp(earliest, latest)
+            } else {
+                0.0
+            };
+        }
 
         if let Some(start) = self.start_frame {
             if start >= n_frames {
@@ -129,8 +248,10 @@ impl Line {
         self.current_iteration = 0;
         self.frames_passed = 0;
         self.frames_executed = 0;
+        self.cycles_started = 0;
         self.vars.clear();
         self.states.clear();
+        self.rng = None;
     }
 
     pub fn configure(&mut self, other: &Line) {
@@ -139,6 +260,81 @@ impl Line {
         self.end_frame = other.end_frame;
         self.looping = other.looping;
         self.trailing = other.trailing;
+        self.playback_mode = other.playback_mode;
+        if self.seed != other.seed {
+            self.seed = other.seed;
+            self.rng = None;
+        }
+        self.set_gate(other.gate);
+        self.set_midi_channel(other.midi_channel);
+        self.set_transpose(other.transpose);
+        self.retrigger_on_enable = other.retrigger_on_enable;
+        self.sustain_on_disable = other.sustain_on_disable;
+        if self.duration_generator != other.duration_generator {
+            self.set_duration_generator(other.duration_generator.clone());
+        }
+        self.set_quantization(other.quantization);
+    }
+
+    /// Sets the note length multiplier, clamped between [`MIN_GATE`] and [`MAX_GATE`].
+    /// Invalid values reset the gate to `1.0`.
+    pub fn set_gate(&mut self, gate: f64) {
+        self.gate = if gate.is_finite() {
+            gate.clamp(MIN_GATE, MAX_GATE)
+        } else {
+            default_gate()
+        };
+    }
+
+    /// Sets the MIDI channel of the line, clamped to 16.
+    /// `None` or channel `0` keeps the channels of the scripts.
+    pub fn set_midi_channel(&mut self, channel: Option<u64>) {
+        self.midi_channel = channel.filter(|c| *c > 0).map(|c| c.min(16));
+    }
+
+    /// Sets the transposition of the line in semitones, clamped to [`MAX_TRANSPOSE`] either way.
+    pub fn set_transpose(&mut self, semitones: i64) {
+        self.transpose = semitones.clamp(-MAX_TRANSPOSE, MAX_TRANSPOSE);
+    }
+
+    /// Sets the grid of the quantized edits of the line, in beats.
+    /// `None`, or a grid that is not a positive number of beats, follows the grid of the scheduler.
+    pub fn set_quantization(&mut self, grid: Option<f64>) {
+        self.quantization = grid.filter(|grid| grid.is_finite() && *grid > 0.0);
+    }
+
+    /// Drives the frame durations with a generator, or restores the static durations with `None`.
+    pub fn set_duration_generator(&mut self, generator: Option<ValueGenerator>) {
+        self.duration_generator = generator;
+        self.generated_scale = None;
+    }
+
+    /// Sets the fill of the line, or removes it with `None`.
+    /// A fill that never plays, every `0` cycles, removes the fill too.
+    pub fn set_fill(&mut self, fill: Option<LineFill>) {
+        if let Some(old) = self.fill.as_mut() {
+            old.frames.iter_mut().for_each(Frame::kill_executions);
+        }
+        self.fill = fill.filter(|fill| fill.every > 0);
+        self.make_consistent();
+    }
+
+    /// Scale currently applied to the frame durations by the duration generator.
+    pub fn duration_scale(&self) -> f64 {
+        match (&self.duration_generator, self.generated_scale) {
+            (Some(_), Some((_, scale))) => scale,
+            _ => 1.0,
+        }
+    }
+
+    /// Speed at which the frames play, combining the speed factor and the duration scale.
+    fn playback_speed(&self) -> f64 {
+        self.speed_factor / self.duration_scale()
+    }
+
+    /// Number of cycles the line started, counting both restarts and loops.
+    fn cycle(&self) -> usize {
+        self.current_iteration + self.frames_passed / self.get_effective_num_frames().max(1)
     }
 
     /// Returns light version without frames
@@ -159,6 +355,11 @@ impl Line {
             .sum()
     }
 
+    /// A line is enabled as long as one of its frames is.
+    pub fn is_enabled(&self) -> bool {
+        self.frames.iter().any(|frame| frame.enabled)
+    }
+
     /// Returns the total number of frames in this line.
     #[inline]
     pub fn n_frames(&self) -> usize {
@@ -210,6 +411,15 @@ impl Line {
         self.make_consistent();
     }
 
+    /// Sets the trigger offset of a frame, in beats, bounded to [`MAX_NUDGE`] of its neighbors.
+    pub fn set_frame_nudge(&mut self, index: usize, nudge: f64) {
+        let Some(frame) = self.frames.get_mut(index) else {
+            return;
+        };
+        frame.nudge = nudge;
+        self.make_consistent();
+    }
+
     #[inline]
     pub fn structure(&self) -> Vec<f64> {
         self.frames.iter().map(|f| f.duration).collect()
@@ -336,28 +546,139 @@ impl Line {
 
     pub fn kill_executions(&mut self) {
         self.frames.iter_mut().for_each(Frame::kill_executions);
+        if let Some(fill) = self.fill.as_mut() {
+            fill.frames.iter_mut().for_each(Frame::kill_executions);
+        }
     }
 
     pub fn update_executions<'a>(
         &'a mut self,
         mut partial: PartialContext<'a>,
     ) -> (Vec<ConcreteEvent>, SyncTime) {
+        let date = partial.logic_date;
+        let devices = partial.device_map;
         partial.line_vars = Some(&mut self.vars);
         let mut events = Vec::new();
         let mut next_wait = NEVER;
-        for (index, frame) in self.frames.iter_mut().enumerate() {
+        let line_frames = self.frames.iter_mut().map(|frame| (frame, false));
+        let fill_frames = self
+            .fill
+            .iter_mut()
+            .flat_map(|fill| fill.frames.iter_mut().map(|frame| (frame, true)));
+        for (index, (frame, in_fill)) in line_frames.enumerate().chain(fill_frames.enumerate()) {
             let mut partial_child = partial.child();
             partial_child.frame_index = Some(index);
             let (mut new_events, wait) = frame.update_executions(partial_child);
             events.append(&mut new_events);
             next_wait = std::cmp::min(next_wait, wait);
+            for message in frame.take_panics() {
+                self.script_panics.push(ScriptPanic {
+                    frame: index,
+                    in_fill,
+                    message,
+                });
+            }
+        }
+        if !is_default_gate(&self.gate) {
+            for event in events.iter_mut() {
+                if let ConcreteEvent::MidiNote(_, _, _, duration, _, _)
+                | ConcreteEvent::MidiMpeNote(_, _, _, duration, _, _) = event
+                {
+                    *duration = (*duration as f64 * self.gate).round() as SyncTime;
+                }
+            }
+        }
+        if let Some(channel) = self.midi_channel {
+            for event in events.iter_mut() {
+                if let Some(event_channel) = event.midi_channel_mut() {
+                    *event_channel = channel;
+                }
+            }
+        }
+        if !is_default_transpose(&self.transpose) {
+            for event in events.iter_mut() {
+                if let Some(note) = event.midi_note_mut() {
+                    *note = (*note as i64 + self.transpose).clamp(0, 127) as u64;
+                }
+            }
+        }
+        // MPE notes need their channel to be released later on
+        if let Some(devices) = devices {
+            for event in events.iter_mut() {
+                devices.allocate_mpe_channel(event, date);
+            }
+        }
+        self.sounding.retain(|sounding| sounding.until > date);
+        for event in events.iter() {
+            if let ConcreteEvent::MidiNote(note, _, channel, duration, device_id, _)
+            | ConcreteEvent::MidiMpeNote(note, _, channel, duration, _, device_id) = event
+            {
+                self.sounding.push(SoundingNote {
+                    note: *note,
+                    channel: *channel,
+                    device_id: *device_id,
+                    until: date + duration,
+                });
+            }
         }
         (events, next_wait)
     }
 
+    /// Evaluates the duration generator when the line starts a new cycle. A new generator
+    /// starts with the first cycle it scales.
+    /// Returns the time to wait until the next trigger of the line, which moves with the scale.
+    pub fn update_duration_scale(&mut self, partial: &mut PartialContext) -> SyncTime {
+        let cycle = self.cycle();
+        let frame_len = self.length();
+        let (Some(generator), Some(clock)) = (&mut self.duration_generator, partial.clock) else {
+            return NEVER;
+        };
+        if self.states.is_empty() || self.generated_scale.is_some_and(|(c, _)| c == cycle) {
+            return NEVER;
+        }
+        let date = partial.logic_date;
+        let mut frame_vars = VariableStore::new();
+        let mut instance_vars = VariableStore::new();
+        let mut stack = VecDeque::new();
+        let mut partial = partial.child();
+        partial.line_vars = Some(&mut self.vars);
+        partial.frame_vars = Some(&mut frame_vars);
+        partial.instance_vars = Some(&mut instance_vars);
+        partial.stack = Some(&mut stack);
+        partial.frame_index = Some(0);
+        partial.frame_len = Some(frame_len);
+        let Some(ctx) = partial.to_context() else {
+            return NEVER;
+        };
+        if self.generated_scale.is_none() {
+            generator.start(&ctx, date);
+        }
+        let scale = generator.get_current(&ctx).as_float(&ctx);
+        let scale = if scale.is_finite() && scale > 0.0 {
+            scale
+        } else {
+            self.duration_scale()
+        };
+        self.generated_scale = Some((cycle, scale));
+        self.before_next_trigger(clock, date)
+    }
+
+    /// Note-offs for the MIDI notes of this line still sounding at `date`.
+    pub fn release_notes(&mut self, date: SyncTime) -> Vec<ConcreteEvent> {
+        self.sounding
+            .drain(..)
+            .filter(|sounding| sounding.until > date)
+            .map(|sounding| {
+                ConcreteEvent::MidiNoteOff(sounding.note, sounding.channel, sounding.device_id)
+            })
+            .collect()
+    }
+
     pub fn before_next_update(&self, date: SyncTime) -> SyncTime {
+        let fill_frames = self.fill.iter().flat_map(|fill| fill.frames.iter());
         self.frames
             .iter()
+            .chain(fill_frames)
             .map(|frame| frame.before_next_update(date))
             .min()
             .unwrap_or(NEVER)
@@ -378,16 +699,38 @@ impl Line {
         frame_len.saturating_sub(relative_date)
     }
 
+    /// How long before the end of its current frame a state must step, so that the frame
+    /// coming next can trigger earlier when nudged. Only known for sequential playback.
+    fn state_lead(&self, state: &LineState, clock: &Clock) -> SyncTime {
+        let Some(frame) = self.get_current_frame(state) else {
+            return 0;
+        };
+        let next = if state.current_repetition + 1 < frame.repetitions {
+            frame
+        } else if !self.playback_mode.is_sequential() {
+            return 0;
+        } else if state.current_frame < self.get_effective_end_frame() {
+            &self.frames[state.current_frame + 1]
+        } else if self.looping {
+            &self.frames[self.get_effective_start_frame()]
+        } else {
+            return 0;
+        };
+        if next.nudge >= 0.0 {
+            return 0;
+        }
+        clock.beats_to_micros(precise_division(-next.nudge, self.playback_speed()))
+    }
+
     pub fn before_next_trigger(&self, clock: &Clock, date: SyncTime) -> SyncTime {
         let mut next = NEVER;
         for state in self.states.iter() {
             let Some(frame) = self.get_current_frame(state) else {
                 continue;
             };
-            next = cmp::min(
-                next,
-                Self::before_next_state_trigger(frame, state, clock, date, self.speed_factor),
-            );
+            let before =
+                Self::before_next_state_trigger(frame, state, clock, date, self.playback_speed());
+            next = cmp::min(next, before.saturating_sub(self.state_lead(state, clock)));
         }
         next
     }
@@ -396,12 +739,45 @@ impl Line {
         if !self.trailing {
             self.states.clear();
         }
+        let current_frame = if self.playback_mode.is_sequential() {
+            self.get_effective_start_frame()
+        } else {
+            self.draw_frame()
+        };
         self.states.push(LineState { 
-            current_frame: self.get_effective_start_frame(), 
+            current_frame, 
             current_repetition: 0, 
-            last_trigger: NEVER 
+            last_trigger: NEVER,
+            frames_drawn: 0,
         });
         self.current_iteration += 1;
+        self.cycles_started += 1;
+    }
+
+    fn new_rng(seed: Option<u64>) -> ChaCha20Rng {
+        match seed {
+            Some(seed) => ChaCha20Rng::seed_from_u64(seed),
+            None => random::derive_rng(),
+        }
+    }
+
+    /// Drops the generator of the random playback modes, so that the next draw
+    /// starts over from the line seed or the global random source.
+    pub fn reset_rng(&mut self) {
+        self.rng = None;
+    }
+
+    /// Draws a frame index within the effective range, following the playback mode.
+    fn draw_frame(&mut self) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let start = self.get_effective_start_frame();
+        let end = self.get_effective_end_frame();
+        let mode = self.playback_mode;
+        let seed = self.seed;
+        let rng = self.rng.get_or_insert_with(|| Self::new_rng(seed));
+        start + mode.draw(&self.frames[start..=end], rng)
     }
 
     pub fn start_at(&mut self, frame_id: usize) {
@@ -416,25 +792,51 @@ impl Line {
         interpreters: &InterpreterDirectory,
     ) -> bool {
         let mut stepped = false;
+        let now = date;
+        let leads: Vec<SyncTime> = self
+            .states
+            .iter()
+            .map(|state| self.state_lead(state, clock))
+            .collect();
         let start_frame = self.get_effective_start_frame();
         let end_frame = self.get_effective_end_frame();
+        let speed = self.playback_speed();
         let frames = &mut self.frames;
+        let fill = &mut self.fill;
         let n_states = self.states.len();
-        for state in self.states.iter_mut() {
+        let mode = self.playback_mode;
+        let seed = self.seed;
+        let rng = &mut self.rng;
+        for (state, lead) in self.states.iter_mut().zip(leads) {
             let Some(frame) = frames.get(state.current_frame) else {
                 continue;
             };
-            if Self::before_next_state_trigger(frame, state, clock, date, self.speed_factor) > 0 {
+            if Self::before_next_state_trigger(frame, state, clock, date, speed) > lead {
                 continue;
             }
             stepped = true;
             if state.last_trigger != NEVER {
                 // Precise date correction if the exact time has been stepped over
-                let frame_len = clock.beats_to_micros(frame.duration / self.speed_factor);
+                let frame_len = clock.beats_to_micros(frame.duration / speed);
                 date = state.last_trigger + frame_len;
 
                 if state.current_repetition < (frame.repetitions - 1) {
                     state.current_repetition += 1;
+                } else if !mode.is_sequential() {
+                    state.current_repetition = 0;
+                    state.frames_drawn += 1;
+                    self.frames_passed += 1;
+                    let n_drawable = end_frame - start_frame + 1;
+                    if state.frames_drawn >= n_drawable && !(self.looping && n_states == 1) {
+                        state.current_frame = usize::MAX;
+                        continue;
+                    }
+                    if state.frames_drawn % n_drawable == 0 {
+                        self.cycles_started += 1;
+                    }
+                    let rng = rng.get_or_insert_with(|| Self::new_rng(seed));
+                    state.current_frame =
+                        start_frame + mode.draw(&frames[start_frame..=end_frame], rng);
                 } else {
                     state.current_frame += 1;
                     state.current_repetition = 0;
@@ -442,6 +844,7 @@ impl Line {
                     if state.current_frame > end_frame {
                         if self.looping && n_states == 1 {
                             state.current_frame = start_frame;
+                            self.cycles_started += 1;
                         } else {
                             state.current_frame = usize::MAX;
                             continue;
@@ -449,8 +852,29 @@ impl Line {
                     }
                 }
             }
-            let frame = frames.get_mut(state.current_frame).unwrap();
-            frame.trigger(date, interpreters);
+            let fill_frame = fill
+                .as_mut()
+                .filter(|fill| fill.plays_on(self.cycles_started))
+                .and_then(|fill| fill.frames.get_mut(state.current_frame));
+            let (frame, in_fill) = match fill_frame {
+                Some(fill_frame) => (fill_frame, true),
+                None => (frames.get_mut(state.current_frame).unwrap(), false),
+            };
+            let nudge = clock.beats_to_micros(precise_division(frame.nudge.abs(), speed));
+            let trigger_date = if frame.nudge < 0.0 {
+                cmp::max(date.saturating_sub(nudge), now)
+            } else {
+                date + nudge
+            };
+            if !in_fill && frame.enabled && frame.script().is_empty() {
+                self.empty_frames_reached
+                    .push((state.current_frame, trigger_date));
+            }
+            frame.trigger(trigger_date, interpreters);
+            if !in_fill && frame.one_shot && frame.enabled {
+                frame.enabled = false;
+                self.fired_one_shots.push(state.current_frame);
+            }
             self.frames_executed += 1;
             state.last_trigger = date;
         }
@@ -459,11 +883,37 @@ impl Line {
         stepped
     }
 
+    /// Takes the enabled frames without a script reached since the last call,
+    /// along with the dates they were triggered at.
+    pub fn take_empty_frames_reached(&mut self) -> Vec<(usize, SyncTime)> {
+        std::mem::take(&mut self.empty_frames_reached)
+    }
+
+    /// Takes the one-shot frames which have fired since the last call.
+    pub fn take_fired_one_shots(&mut self) -> Vec<usize> {
+        std::mem::take(&mut self.fired_one_shots)
+    }
+
+    /// Takes the scripts which panicked since the last call.
+    pub fn take_script_panics(&mut self) -> Vec<ScriptPanic> {
+        std::mem::take(&mut self.script_panics)
+    }
+
+    /// Moves the playhead of a playing line back to its start frame.
+    pub fn retrigger(&mut self) {
+        if self.states.is_empty() {
+            return;
+        }
+        self.states.clear();
+        self.go_to_frame(self.get_effective_start_frame(), 0);
+    }
+
     pub fn go_to_frame(&mut self, frame: usize, repetition: usize) {
         self.states.push(LineState {
             current_frame: frame,
             current_repetition: repetition,
             last_trigger: NEVER,
+            frames_drawn: 0,
         });
     }
 
@@ -518,7 +968,80 @@ impl Default for Line {
             frames_executed: Default::default(),
             frames_passed: Default::default(),
             looping: false,
-            trailing: false
+            trailing: false,
+            playback_mode: Default::default(),
+            seed: None,
+            gate: default_gate(),
+            midi_channel: None,
+            transpose: 0,
+            retrigger_on_enable: false,
+            sustain_on_disable: false,
+            duration_generator: None,
+            fill: None,
+            quantization: None,
+            cycles_started: 0,
+            rng: None,
+            sounding: Vec::new(),
+            generated_scale: None,
+            empty_frames_reached: Vec::new(),
+            fired_one_shots: Vec::new(),
+            script_panics: Vec::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ClockServer;
+    use std::sync::Arc;
+
+    fn visited_frames(line: &mut Line, steps: u64) -> Vec<usize> {
+        let clock = Clock::from(Arc::new(ClockServer::new(120.0, 4.0)));
+        let interpreters = InterpreterDirectory::new();
+        let beat = clock.beats_to_micros(1.0);
+        line.start();
+        (0..steps)
+            .map(|i| {
+                line.step(&clock, i * beat, &interpreters);
+                line.position()[0].0
+            })
+            .collect()
+    }
+
+    fn weighted_line(seed: u64) -> Line {
+        let mut line = Line::new(vec![1.0, 1.0, 1.0]);
+        line.looping = true;
+        line.playback_mode = LinePlaybackMode::WeightedRandom;
+        line.seed = Some(seed);
+        for (frame, weight) in line.frames.iter_mut().zip([1.0, 0.0, 3.0]) {
+            frame.weight = weight;
+        }
+        line
+    }
+
+    #[test]
+    fn sequential_playback_is_unchanged() {
+        let mut line = Line::new(vec![1.0, 1.0, 1.0]);
+        line.looping = true;
+        assert_eq!(visited_frames(&mut line, 7), vec![0, 1, 2, 0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn weighted_playback_follows_seeded_draws() {
+        let mut line = weighted_line(7);
+        let visited = visited_frames(&mut line, 64);
+
+        let mut rng = ChaCha20Rng::seed_from_u64(7);
+        let expected: Vec<usize> = (0..64)
+            .map(|_| LinePlaybackMode::WeightedRandom.draw(&line.frames, &mut rng))
+            .collect();
+        assert_eq!(visited, expected);
+        assert!(!visited.contains(&1));
+        let heavy = visited.iter().filter(|&&f| f == 2).count();
+        assert!(heavy > visited.len() / 2);
+
+        let mut replay = weighted_line(7);
+        assert_eq!(visited_frames(&mut replay, 64), visited);
+    }
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+/// Key signature, stored the way Standard MIDI Files encode it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeySignature {
+    /// Number of sharps (positive) or flats (negative), from -7 to 7.
+    pub sharps: i8,
+    pub minor: bool,
+}
+
+/// Time signature. The denominator must be a power of two.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeSignature {
+    pub numerator: u8,
+    pub denominator: u8,
+}
+
+impl Default for TimeSignature {
+    fn default() -> Self {
+        TimeSignature {
+            numerator: 4,
+            denominator: 4,
+        }
+    }
+}
+
+/// Musical information about a scene that does not affect playback,
+/// but is carried along for exports (C major, 4/4 by default).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SceneMetadata {
+    #[serde(default)]
+    pub key_signature: KeySignature,
+    #[serde(default)]
+    pub time_signature: TimeSignature,
+}
+
+impl SceneMetadata {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Checks that the signatures can be written in a Standard MIDI File.
+    pub fn validate(&self) -> Result<(), String> {
+        let key = &self.key_signature;
+        if !(-7..=7).contains(&key.sharps) {
+            return Err(format!(
+                "Invalid key signature: {} sharps, it must be from -7 to 7.",
+                key.sharps
+            ));
+        }
+        let time = &self.time_signature;
+        if time.numerator == 0 || !time.denominator.is_power_of_two() {
+            return Err(format!(
+                "Invalid time signature: {}/{}, the denominator must be a power of two.",
+                time.numerator, time.denominator
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Scene;
+
+    #[test]
+    fn metadata_round_trips_through_save_and_load() {
+        let scene = Scene {
+            metadata: SceneMetadata {
+                key_signature: KeySignature {
+                    sharps: -3,
+                    minor: true,
+                },
+                time_signature: TimeSignature {
+                    numerator: 7,
+                    denominator: 8,
+                },
+            },
+            ..Default::default()
+        };
+        let saved = serde_json::to_string(&scene).unwrap();
+        let loaded: Scene = serde_json::from_str(&saved).unwrap();
+        assert_eq!(loaded.metadata, scene.metadata);
+    }
+
+    #[test]
+    fn only_smf_signatures_are_valid() {
+        let metadata = |sharps, numerator, denominator| SceneMetadata {
+            key_signature: KeySignature {
+                sharps,
+                minor: false,
+            },
+            time_signature: TimeSignature {
+                numerator,
+                denominator,
+            },
+        };
+        assert!(SceneMetadata::default().validate().is_ok());
+        assert!(metadata(-7, 7, 8).validate().is_ok());
+        assert!(metadata(8, 4, 4).validate().is_err());
+        assert!(metadata(0, 0, 0).validate().is_err());
+        assert!(metadata(0, 7, 3).validate().is_err());
+        assert!(metadata(0, 0, 4).validate().is_err());
+    }
+
+    #[test]
+    fn older_scenes_default_to_c_major_in_four_four() {
+        let loaded: Scene = serde_json::from_str(r#"{"lines":[]}"#).unwrap();
+        assert_eq!(loaded.metadata.key_signature, KeySignature::default());
+        assert_eq!(loaded.metadata.time_signature, TimeSignature::default());
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt::Display;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::scene::Frame;
+
+/// Order in which a line plays its frames.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LinePlaybackMode {
+    /// Frames play one after the other.
+    #[default]
+    Sequential,
+    /// Each next frame is drawn uniformly among the frames of the line.
+    Random,
+    /// Each next frame is drawn according to the frame weights.
+    WeightedRandom,
+}
+
+impl LinePlaybackMode {
+    pub fn is_sequential(&self) -> bool {
+        matches!(self, LinePlaybackMode::Sequential)
+    }
+
+    /// Draws the index of the next frame among `frames`.
+    /// Weights that are not strictly positive never get drawn,
+    /// and a line without any positive weight falls back to a uniform draw.
+    pub fn draw<R: Rng>(&self, frames: &[Frame], rng: &mut R) -> usize {
+        if frames.len() <= 1 {
+            return 0;
+        }
+        let weight = |frame: &Frame| match self {
+            LinePlaybackMode::WeightedRandom if frame.weight > 0.0 => frame.weight,
+            LinePlaybackMode::WeightedRandom => 0.0,
+            _ => 1.0,
+        };
+        let total: f64 = frames.iter().map(weight).sum();
+        if !total.is_finite() || total <= 0.0 {
+            return rng.random_range(0..frames.len());
+        }
+        let mut target = rng.random::<f64>() * total;
+        for (index, frame) in frames.iter().enumerate() {
+            let w = weight(frame);
+            if target < w {
+                return index;
+            }
+            target -= w;
+        }
+        frames.iter().rposition(|f| weight(f) > 0.0).unwrap_or(0)
+    }
+}
+
+impl Display for LinePlaybackMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Sequential => "Sequential",
+            Self::Random => "Random",
+            Self::WeightedRandom => "WeightedRandom",
+        };
+        write!(f, "{name}")
+    }
+}
@@ -1,6 +1,9 @@
 use std::{
+    any::Any,
     collections::{BTreeMap, VecDeque},
-    hash::{self, DefaultHasher, Hash, Hasher}, thread::{self, ThreadId},
+    hash::{self, DefaultHasher, Hash, Hasher},
+    panic::{self, AssertUnwindSafe},
+    thread::{self, ThreadId},
 };
 
 use serde::{Deserialize, Serialize};
@@ -149,7 +152,20 @@ pub struct ScriptExecution {
     pub stack: VecDeque<VariableValue>,
     pub scheduled_time: SyncTime,
     interpreter: Option<Box<dyn Interpreter>>,
-    thread_id: ThreadId
+    thread_id: ThreadId,
+    /// Message of the panic which ended the execution, until it is taken.
+    panic: Option<String>,
+}
+
+/// Message carried by the payload of a panic, when it is a string.
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
 }
 
 impl ScriptExecution {
@@ -164,7 +180,8 @@ impl ScriptExecution {
             instance_vars,
             stack: VecDeque::new(),
             interpreter: Some(interpreter),
-            thread_id: thread::current().id()
+            thread_id: thread::current().id(),
+            panic: None,
         }
     }
 
@@ -210,7 +227,17 @@ impl ScriptExecution {
         let Some(mut ctx) = partial.to_context() else {
             return (None, NEVER);
         };
-        let (opt_ev, wait) = interpreter.execute_next(&mut ctx);
+        // A panicking interpreter must not take the scheduler down with it
+        let step = panic::catch_unwind(AssertUnwindSafe(|| interpreter.execute_next(&mut ctx)));
+        let (opt_ev, wait) = match step {
+            Ok(step) => step,
+            Err(payload) => {
+                // Its state can no longer be trusted, so the execution ends there
+                self.interpreter = None;
+                self.panic = Some(panic_message(payload));
+                return (None, NEVER);
+            }
+        };
         self.scheduled_time = self.scheduled_time.saturating_add(wait);
         let rem = self.scheduled_time.saturating_sub(prev_date);
         (opt_ev, rem)
@@ -224,6 +251,11 @@ impl ScriptExecution {
         interpreter.stop();
     }
 
+    /// Takes the message of the panic which ended the execution, if it panicked.
+    pub fn take_panic(&mut self) -> Option<String> {
+        self.panic.take()
+    }
+
     #[inline]
     pub fn has_terminated(&self) -> bool {
         let Some(interpreter) = self.interpreter() else {
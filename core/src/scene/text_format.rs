@@ -0,0 +1,174 @@
+//! A terse text format to write scenes by hand.
+//!
+//! Each text line describes one scene line, as frames separated by whitespace.
+//! A frame is its duration in beats, optionally followed by a language and an
+//! inline script between braces. Braces inside a script must be balanced.
+//! Empty text lines and lines starting with `#` are ignored.
+//!
+//! ```text
+//! # kick on the beat, hats on the offbeat
+//! 1:bob{>> [note: 36]} 1 1:bob{>> [note: 36]} 1
+//! 0.5 0.5:bob{>> [note: 42 vel: 60]}
+//! ```
+
+use std::{error, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Scene,
+    scene::{Frame, Line, script::Script},
+};
+
+/// An error found while reading a scene from text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneTextError {
+    /// The text line where the error occurred, starting at 1.
+    pub line: usize,
+    /// The column where the error occurred, in characters, starting at 1.
+    pub column: usize,
+    /// A message describing the error.
+    pub message: String,
+}
+
+impl fmt::Display for SceneTextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl error::Error for SceneTextError {}
+
+/// Reads a [`Scene`] from its text description.
+pub fn parse_scene_text(text: &str) -> Result<Scene, SceneTextError> {
+    let mut lines = Vec::new();
+    for (index, source) in text.lines().enumerate() {
+        let trimmed = source.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        lines.push(parse_line(source, index + 1)?);
+    }
+    let mut scene = Scene::new(lines);
+    scene.make_consistent();
+    Ok(scene)
+}
+
+fn parse_line(source: &str, line: usize) -> Result<Line, SceneTextError> {
+    let chars: Vec<char> = source.chars().collect();
+    let error = |pos: usize, message: String| SceneTextError {
+        line,
+        column: pos + 1,
+        message,
+    };
+    let skip_while = |mut pos: usize, pred: fn(char) -> bool| {
+        while pos < chars.len() && pred(chars[pos]) {
+            pos += 1;
+        }
+        pos
+    };
+
+    let mut frames = Vec::new();
+    let mut pos = skip_while(0, char::is_whitespace);
+    while pos < chars.len() {
+        let start = pos;
+        pos = skip_while(pos, |c| !c.is_whitespace() && c != ':');
+        let word: String = chars[start..pos].iter().collect();
+        let mut frame = match word.parse::<f64>() {
+            Ok(duration) if duration.is_finite() && duration > 0.0 => Frame::from(duration),
+            _ => {
+                return Err(error(
+                    start,
+                    format!("expected a positive frame duration, found '{word}'"),
+                ));
+            }
+        };
+
+        if chars.get(pos) == Some(&':') {
+            pos += 1;
+            let lang_start = pos;
+            pos = skip_while(pos, |c| c.is_alphanumeric() || c == '_' || c == '-');
+            if pos == lang_start {
+                return Err(error(pos, "expected a language name after ':'".to_owned()));
+            }
+            let lang: String = chars[lang_start..pos].iter().collect();
+            if chars.get(pos) != Some(&'{') {
+                return Err(error(pos, format!("expected '{{' to open the {lang} script")));
+            }
+            let open = pos;
+            let mut depth = 0usize;
+            let mut close = None;
+            for (i, c) in chars.iter().enumerate().skip(open) {
+                match c {
+                    '{' => depth += 1,
+                    '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            close = Some(i);
+                            break;
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            let Some(close) = close else {
+                return Err(error(open, "script is never closed, missing '}'".to_owned()));
+            };
+            let content: String = chars[open + 1..close].iter().collect();
+            frame.set_script(Script::new(content.trim().to_owned(), lang));
+            pos = close + 1;
+            if pos < chars.len() && !chars[pos].is_whitespace() {
+                return Err(error(pos, "expected whitespace after the script".to_owned()));
+            }
+        }
+
+        frames.push(frame);
+        pos = skip_while(pos, char::is_whitespace);
+    }
+
+    let mut line = Line::default();
+    line.frames = frames;
+    line.make_consistent();
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_lines_frames_and_scripts() {
+        let text = "\
+# drums
+1:bob{>> [note: 36]} 0.5 0.5:bob{>> [note: 42 vel: {60}]}
+
+2.5
+";
+        let scene = parse_scene_text(text).unwrap();
+        assert_eq!(scene.n_lines(), 2);
+
+        let drums = scene.line(0).unwrap();
+        let durations: Vec<f64> = drums.frames.iter().map(|f| f.duration).collect();
+        assert_eq!(durations, vec![1.0, 0.5, 0.5]);
+        assert_eq!(drums.frames[0].script().lang(), "bob");
+        assert_eq!(drums.frames[0].script().content(), ">> [note: 36]");
+        assert!(drums.frames[1].script().content().is_empty());
+        assert_eq!(drums.frames[2].script().content(), ">> [note: 42 vel: {60}]");
+
+        let pad = scene.line(1).unwrap();
+        assert_eq!(pad.n_frames(), 1);
+        assert_eq!(pad.frames[0].duration, 2.5);
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_line() {
+        let err = parse_scene_text("1 1\n\n1 fast 1").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 3));
+
+        let err = parse_scene_text("1:bob{>> [note: 36]").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 6));
+
+        let err = parse_scene_text("1:{x}").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 3));
+    }
+}
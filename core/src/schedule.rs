@@ -1,28 +1,52 @@
 use crate::{
     clock::{Clock, ClockServer, NEVER, SyncTime},
-    device_map::DeviceMap,
+    LogMessage,
+    device_map::{ConnectionChange, DeviceMap, RECONNECT_INTERVAL},
     log_println,
     protocol::TimedMessage,
-    scene::Scene,
-    schedule::{playback::PlaybackManager, scheduler_actions::ActionProcessor},
-    vm::{LanguageCenter, PartialContext, variable::VariableStore},
+    scene::{Frame, Line, Scene},
+    schedule::{
+        audition::Audition,
+        metronome::MidiMetronome,
+        morph::{SceneMorph, fade},
+        osc_clock::OscClock,
+        playback::PlaybackManager,
+        scheduler_actions::ActionProcessor,
+    },
+    util::random,
+    vm::{LanguageCenter, PartialContext, event::ConcreteEvent, variable::VariableStore},
     world::ACTIVE_WAITING_SWITCH_MICROS,
 };
 
 use crossbeam_channel::{self, Receiver, RecvTimeoutError, Sender, TryRecvError};
-use std::{cmp::min, sync::Arc, thread::JoinHandle, time::Duration, usize};
+use std::{
+    cmp::min,
+    sync::Arc,
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
 use thread_priority::{ThreadBuilder, ThreadPriority};
 
 pub mod playback;
 
 mod action_timing;
+mod audition;
+mod empty_frames;
 mod message;
+mod metronome;
+mod morph;
 mod notification;
+mod osc_clock;
 mod scheduler_actions;
 
-pub use action_timing::ActionTiming;
+#[cfg(test)]
+mod tests;
+
+pub use action_timing::{ActionTiming, DEFAULT_QUANTIZATION_GRID};
+pub use empty_frames::EmptyFrameBehavior;
 pub use message::SchedulerMessage;
 pub use notification::SovaNotification;
+pub use osc_clock::OscClockConfig;
 
 pub const SCHEDULED_DRIFT: SyncTime = 30_000;
 pub const SCHEDULER_ACTIVE_WAITING_SWITCH: SyncTime = 100;
@@ -40,8 +64,17 @@ pub struct Scheduler {
 
     next_wait: Option<SyncTime>,
     deferred_actions: Vec<SchedulerMessage>,
+    /// Grid of `ActionTiming::Quantized` actions, in beats
+    quantization_grid: f64,
     playback_manager: PlaybackManager,
     shutdown_requested: bool,
+    audition: Audition,
+    metronome: MidiMetronome,
+    osc_clock: OscClock,
+    empty_frames: EmptyFrameBehavior,
+    /// Whether frames whose script panicked are disabled
+    disable_panicking_frames: bool,
+    morph: Option<SceneMorph>,
 
     scene_structure: Vec<Vec<f64>>,
 }
@@ -52,16 +85,12 @@ impl Scheduler {
         devices: Arc<DeviceMap>,
         languages: Arc<LanguageCenter>,
         world_iface: Sender<TimedMessage>,
-    ) -> (
-        JoinHandle<()>,
-        Sender<SchedulerMessage>,
-        Receiver<SovaNotification>,
-    ) {
-        let (tx, rx) = crossbeam_channel::unbounded();
+        feedback: Sender<SchedulerMessage>,
+        rx: Receiver<SchedulerMessage>,
+    ) -> (JoinHandle<()>, Receiver<SovaNotification>) {
         let (p_tx, p_rx) = crossbeam_channel::unbounded();
 
         let clock = Clock::from(clock_server).with_drift(SCHEDULED_DRIFT);
-        let feedback = tx.clone();
 
         let handle = ThreadBuilder::default()
             .name("Sova-scheduler")
@@ -76,7 +105,7 @@ impl Scheduler {
                 sched.do_your_thing();
             })
             .expect("Unable to start Scheduler");
-        (handle, tx, p_rx)
+        (handle, p_rx)
     }
 
     pub fn new(
@@ -99,8 +128,15 @@ impl Scheduler {
             update_notifier,
             next_wait: None,
             deferred_actions: Vec::new(),
+            quantization_grid: DEFAULT_QUANTIZATION_GRID,
             playback_manager: PlaybackManager::default(),
             shutdown_requested: false,
+            audition: Audition::default(),
+            metronome: MidiMetronome::default(),
+            osc_clock: OscClock::default(),
+            empty_frames: EmptyFrameBehavior::default(),
+            disable_panicking_frames: true,
+            morph: None,
             scene_structure: Vec::new(),
         }
     }
@@ -120,6 +156,29 @@ impl Scheduler {
             .send(SovaNotification::UpdatedScene(self.scene.clone()));
     }
 
+    /// Crossfades from the current scene to a new one over `duration` beats.
+    /// Without a positive duration, the new scene replaces the current one at once.
+    pub fn morph_to_scene(&mut self, scene: Scene, duration: f64) {
+        if !duration.is_finite() || duration <= 0.0 {
+            self.change_scene(scene);
+            return;
+        }
+        // A morph interrupted by another one cuts its own source short
+        if let Some(mut previous) = self.morph.take() {
+            previous.source.kill_executions();
+        }
+        let source = std::mem::take(&mut self.scene);
+        let start_beat = self.clock.beat();
+        self.change_scene(scene);
+        self.morph = Some(SceneMorph::new(source, start_beat, duration));
+    }
+
+    /// Gains of the previous and current scenes at the given date, while morphing.
+    pub fn morph_gains(&self, date: SyncTime) -> Option<(f64, f64)> {
+        let beat = self.clock.beat_at_date(date);
+        self.morph.as_ref().map(|morph| morph.gains(beat))
+    }
+
     fn apply_action(&mut self, action: SchedulerMessage) {
         match action {
             SchedulerMessage::TransportStart(_) => {
@@ -132,7 +191,13 @@ impl Scheduler {
                 self.clock.set_tempo(tempo);
                 let _ = self
                     .update_notifier
-                    .send(SovaNotification::TempoChanged(tempo));
+                    .send(SovaNotification::TempoChanged(self.clock.tempo()));
+            }
+            SchedulerMessage::NudgeTempo(delta, _) => {
+                self.clock.set_tempo(self.clock.tempo() + delta);
+                let _ = self
+                    .update_notifier
+                    .send(SovaNotification::TempoChanged(self.clock.tempo()));
             }
             SchedulerMessage::SetQuantum(quantum, _) => {
                 self.clock.set_quantum(quantum);
@@ -140,12 +205,31 @@ impl Scheduler {
                     .update_notifier
                     .send(SovaNotification::QuantumChanged(quantum));
             }
+            SchedulerMessage::SetQuantizationGrid(grid) => {
+                if !grid.is_finite() || grid <= 0.0 {
+                    return;
+                }
+                self.quantization_grid = grid;
+                let _ = self
+                    .update_notifier
+                    .send(SovaNotification::QuantizationGridChanged(grid));
+            }
+            SchedulerMessage::SetRandomSeed(seed) => {
+                random::set_seed(seed);
+                self.scene.reset_rngs();
+                if let Some(morph) = self.morph.as_mut() {
+                    morph.source.reset_rngs();
+                }
+            }
             SchedulerMessage::SetScene(scene, _) => {
                 self.change_scene(scene.clone());
                 let _ = self
                     .update_notifier
                     .send(SovaNotification::UpdatedScene(scene.clone()));
             }
+            SchedulerMessage::MorphToScene(scene, duration) => {
+                self.morph_to_scene(scene, duration);
+            }
             SchedulerMessage::DeviceMessage(id, msg, _) => {
                 let device = self.devices.get_out_device_at_slot(id);
                 if let Some(device) = device {
@@ -154,11 +238,39 @@ impl Scheduler {
                         .send(msg.with_device(device).timed(self.clock.micros()));
                 }
             }
+            SchedulerMessage::AuditionScript(script) => {
+                self.audition
+                    .start(script, self.clock.micros(), &self.languages.interpreters);
+            }
+            SchedulerMessage::SetMidiMetronome(slot, on) => {
+                self.metronome.set_output(slot, on);
+            }
+            SchedulerMessage::SetOscClock(config) => {
+                self.osc_clock.configure(config);
+            }
+            SchedulerMessage::SetEmptyFrameBehavior(behavior) => {
+                self.empty_frames = behavior;
+            }
+            SchedulerMessage::SetDisablePanickingFrames(disable) => {
+                self.disable_panicking_frames = disable;
+            }
+            SchedulerMessage::SetLanguages(languages) => {
+                self.languages = languages;
+                // Scripts in a language that was just registered can now compile
+                self.languages
+                    .process_scene(&self.scene, self.feedback.clone());
+            }
+            SchedulerMessage::DeviceSendFailed(device, error) => {
+                self.devices.report_send_failure(&device, &error);
+            }
             SchedulerMessage::Shutdown => {
                 log_println!("[-] Scheduler received shutdown signal");
                 self.shutdown_requested = true;
             }
             _ => {
+                let mut was_enabled: Vec<bool> =
+                    self.scene.lines.iter().map(Line::is_enabled).collect();
+                Self::follow_line_changes(&mut was_enabled, &action);
                 ActionProcessor::process_scene_modifications(
                     action,
                     &mut self.scene,
@@ -167,8 +279,44 @@ impl Scheduler {
                     &self.feedback,
                 );
                 self.scene_structure = self.scene.structure();
+                self.release_disabled_lines(&was_enabled);
+            }
+        }
+    }
+
+    /// Moves the enabled states of the lines along with the lines added, removed or
+    /// moved by `action`, so that they keep matching the lines once it is applied.
+    fn follow_line_changes(was_enabled: &mut Vec<bool>, action: &SchedulerMessage) {
+        let n_lines = was_enabled.len();
+        match *action {
+            SchedulerMessage::AddLine(at, _, _) => {
+                if n_lines < at {
+                    was_enabled.resize(at, false);
+                }
+                was_enabled.insert(at, false);
+            }
+            SchedulerMessage::RemoveLine(index, _) if index < n_lines => {
+                was_enabled.remove(index);
+            }
+            SchedulerMessage::MoveLine(from, to, _) if from < n_lines && to < n_lines => {
+                let moved = was_enabled.remove(from);
+                was_enabled.insert(to, moved);
+            }
+            _ => (),
+        }
+    }
+
+    /// Releases the notes still sounding on the lines that were just disabled.
+    /// Lines appended since `was_enabled` was taken were not enabled before.
+    fn release_disabled_lines(&mut self, was_enabled: &[bool]) {
+        let date = self.clock.micros();
+        let mut releases = Vec::new();
+        for (line, was_enabled) in self.scene.lines.iter_mut().zip(was_enabled) {
+            if *was_enabled && !line.is_enabled() && !line.sustain_on_disable {
+                releases.extend(line.release_notes(date));
             }
         }
+        self.send_events(releases, date);
     }
 
     pub fn process_message(&mut self, msg: SchedulerMessage) {
@@ -182,7 +330,11 @@ impl Scheduler {
     }
 
     fn wait_for_message(&mut self) -> bool {
-        if let Some(timeout) = self.next_wait {
+        if let Some(mut timeout) = self.next_wait {
+            // Wake up in time to retry connecting the disconnected devices
+            if self.devices.has_disconnected() {
+                timeout = min(timeout, RECONNECT_INTERVAL.as_micros() as SyncTime);
+            }
             let wait = timeout.saturating_sub(ACTIVE_WAITING_SWITCH_MICROS);
             let duration = Duration::from_micros(wait);
             match self.message_source.recv_timeout(duration) {
@@ -208,12 +360,13 @@ impl Scheduler {
     pub fn process_deferred(&mut self, previous_date: SyncTime, date: SyncTime) -> SyncTime {
         let previous_beat = self.clock.beat_at_date(previous_date);
         let beat = self.clock.beat_at_date(date);
+        let grid = self.quantization_grid;
+        let scene = &self.scene;
         let to_apply: Vec<SchedulerMessage> = self
             .deferred_actions
             .extract_if(.., |action| {
-                action
-                    .timing()
-                    .should_apply(&self.clock, previous_beat, beat)
+                let grid = Self::grid_of(action, scene, grid);
+                action.timing().should_apply(&self.clock, grid, previous_beat, beat)
             })
             .collect();
         for action in to_apply {
@@ -221,24 +374,215 @@ impl Scheduler {
         }
         self.deferred_actions
             .iter()
-            .map(|a| a.timing().remaining(date, &self.clock))
+            .map(|a| {
+                let grid = Self::grid_of(a, &self.scene, grid);
+                a.timing().remaining(date, &self.clock, grid)
+            })
             .min()
             .unwrap_or(NEVER)
     }
 
+    /// Grid of a quantized action: the quantization of the line it edits if that line
+    /// has one, the grid of the scheduler otherwise. Other timings ignore it.
+    fn grid_of(action: &SchedulerMessage, scene: &Scene, grid: f64) -> f64 {
+        action
+            .target_line()
+            .and_then(|i| scene.lines.get(i))
+            .and_then(|line| line.quantization)
+            .unwrap_or(grid)
+    }
+
+    /// Rescales the lines driven by a duration generator as they start a new cycle.
+    pub fn process_duration_generators(&mut self, date: SyncTime) -> SyncTime {
+        let partial = PartialContext {
+            logic_date: date,
+            clock: Some(&self.clock),
+            device_map: Some(&self.devices),
+            structure: Some(&self.scene_structure),
+            ..Default::default()
+        };
+        self.scene.update_duration_scales(partial)
+    }
+
     pub fn process_executions(&mut self, date: SyncTime) -> SyncTime {
         let mut partial = PartialContext::default();
         partial.logic_date = date;
         partial.clock = Some(&self.clock);
         partial.device_map = Some(&self.devices);
         partial.structure = Some(&self.scene_structure);
-        let (events, wait) = self.scene.update_executions(partial);
+        let (mut events, wait) = self.scene.update_executions(partial);
+        if let Some((_, gain)) = self.morph_gains(date) {
+            events.retain_mut(|event| fade(event, gain));
+        }
+        self.send_events(events, date);
+        min(wait, self.process_auditions(date))
+    }
+
+    /// Plays the scene fading out during a morph, and ends the morph once it is over.
+    pub fn process_morph(&mut self, date: SyncTime) -> SyncTime {
+        let Some(morph) = self.morph.as_mut() else {
+            return NEVER;
+        };
+        let beat = self.clock.beat_at_date(date);
+        if morph.is_over(beat) {
+            morph.source.kill_executions();
+            self.morph = None;
+            return NEVER;
+        }
+        let (gain, _) = morph.gains(beat);
+        let (step_wait, _) = morph
+            .source
+            .step(&self.clock, date, &self.languages.interpreters);
+        let partial = PartialContext {
+            logic_date: date,
+            clock: Some(&self.clock),
+            device_map: Some(&self.devices),
+            structure: Some(&morph.source_structure),
+            ..Default::default()
+        };
+        let (mut events, exec_wait) = morph.source.update_executions(partial);
+        events.retain_mut(|event| fade(event, gain));
+        self.send_events(events, date);
+        min(step_wait, exec_wait)
+    }
+
+    /// Runs auditioned scripts, which play whether or not the transport is running.
+    pub fn process_auditions(&mut self, date: SyncTime) -> SyncTime {
+        if self.audition.is_empty() {
+            return NEVER;
+        }
+        let partial = PartialContext {
+            logic_date: date,
+            clock: Some(&self.clock),
+            device_map: Some(&self.devices),
+            structure: Some(&self.scene_structure),
+            ..Default::default()
+        };
+        let (events, wait) = self.audition.update_executions(partial);
+        self.send_events(events, date);
+        wait
+    }
+
+    /// Tells the clients about the one-shot frames which have just disabled themselves.
+    pub fn process_fired_one_shots(&mut self) {
+        let updated: Vec<(usize, usize, Frame)> = self
+            .scene
+            .take_fired_one_shots()
+            .into_iter()
+            .filter_map(|(line_id, frame_id)| {
+                let frame = self.scene.get_frame(line_id, frame_id)?.clone();
+                Some((line_id, frame_id, frame))
+            })
+            .collect();
+        if !updated.is_empty() {
+            let _ = self
+                .update_notifier
+                .send(SovaNotification::UpdatedFrames(updated));
+        }
+    }
+
+    /// Warns about or fills in the frames without a script reached by the lines.
+    pub fn process_empty_frames(&mut self) {
+        let reached = self.scene.take_empty_frames_reached();
+        match &self.empty_frames {
+            EmptyFrameBehavior::Ignore => (),
+            EmptyFrameBehavior::Warn => {
+                for (line_id, frame_id, _) in reached {
+                    let log = LogMessage::warn(format!(
+                        "Frame {frame_id} of line {line_id} has no script, nothing is played."
+                    ));
+                    let _ = self.update_notifier.send(SovaNotification::Log(log));
+                }
+            }
+            EmptyFrameBehavior::Play(script) => {
+                for (_, _, date) in reached {
+                    self.audition
+                        .start(script.clone(), date, &self.languages.interpreters);
+                }
+            }
+        }
+    }
+
+    /// Reports the scripts which panicked, and disables their frame unless told otherwise.
+    /// The other scripts keep playing.
+    pub fn process_script_panics(&mut self) {
+        let mut disabled = Vec::new();
+        for (line_id, panic) in self.scene.take_script_panics() {
+            let frame_id = panic.frame;
+            let frame = if panic.in_fill {
+                format!("Fill frame {frame_id} of line {line_id}")
+            } else {
+                format!("Frame {frame_id} of line {line_id}")
+            };
+            let log = LogMessage::error(format!("{frame} panicked: {}", panic.message));
+            let _ = self.update_notifier.send(SovaNotification::Log(log));
+            if !self.disable_panicking_frames || panic.in_fill {
+                continue;
+            }
+            if self.scene.has_frame(line_id, frame_id) {
+                let frame = self.scene.get_frame_mut(line_id, frame_id);
+                frame.enabled = false;
+                disabled.push((line_id, frame_id, frame.clone()));
+            }
+        }
+        if !disabled.is_empty() {
+            let _ = self
+                .update_notifier
+                .send(SovaNotification::UpdatedFrames(disabled));
+        }
+    }
+
+    /// Sends the clicks of the MIDI metronome that are due by `date`.
+    pub fn process_metronome(&mut self, date: SyncTime) -> SyncTime {
+        let (clicks, wait) = self.metronome.update(&self.clock, date);
+        for (click_date, event) in clicks {
+            for msg in self.devices.map_event(event, click_date, &self.clock) {
+                let _ = self.world_iface.send(msg);
+            }
+        }
+        wait
+    }
+
+    /// Sends the clock state messages of the OSC clock mirror that are due by `date`.
+    pub fn process_osc_clock(&mut self, date: SyncTime) -> SyncTime {
+        let playing = self.playback_manager.state().is_playing();
+        let (ticks, wait) = self.osc_clock.update(&self.clock, date, playing);
+        for (tick_date, event) in ticks {
+            for msg in self.devices.map_event(event, tick_date, &self.clock) {
+                let _ = self.world_iface.send(msg);
+            }
+        }
+        wait
+    }
+
+    fn send_events(&self, events: Vec<ConcreteEvent>, date: SyncTime) {
         for event in events {
             for msg in self.devices.map_event(event, date, &self.clock) {
                 let _ = self.world_iface.send(msg);
             }
         }
-        wait
+    }
+
+    /// Warns clients about devices lost or reconnected since the last check.
+    fn check_device_connections(&self) {
+        let changes = self.devices.check_connections(Instant::now());
+        if changes.is_empty() {
+            return;
+        }
+        for change in changes {
+            let log = match change {
+                ConnectionChange::Lost(name) => LogMessage::warn(format!(
+                    "Device '{name}' is disconnected, its events are dropped until it reconnects."
+                )),
+                ConnectionChange::Restored(name) => {
+                    LogMessage::info(format!("Device '{name}' is reconnected."))
+                }
+            };
+            let _ = self.update_notifier.send(SovaNotification::Log(log));
+        }
+        let _ = self
+            .update_notifier
+            .send(SovaNotification::DeviceListChanged(self.devices.device_list()));
     }
 
     pub fn active_wait(&self, date: &mut SyncTime, target: SyncTime) {
@@ -262,6 +606,8 @@ impl Scheduler {
                 break;
             }
 
+            self.check_device_connections();
+
             let mut date = self.clock.micros();
 
             if let Some(wait) = self.next_wait {
@@ -287,13 +633,21 @@ impl Scheduler {
                     ));
             }
 
+            // The clock state is mirrored whether the transport plays or not
+            let osc_clock_delay = self.process_osc_clock(date);
+
             if !self.playback_manager.state().is_playing() {
+                let audition_delay = min(self.process_auditions(date), osc_clock_delay);
+                self.next_wait = Some(min(audition_delay, self.next_wait.unwrap_or(NEVER)));
                 continue;
             }
 
             let (next_frame_delay, positions_changed) =
                 self.scene
                     .step(&self.clock, date, &self.languages.interpreters);
+            let next_frame_delay = min(next_frame_delay, self.process_duration_generators(date));
+            self.process_empty_frames();
+            self.process_fired_one_shots();
 
             if positions_changed {
                 let frame_updates: Vec<Vec<(usize, usize)>> = self.scene.positions().collect();
@@ -305,7 +659,10 @@ impl Scheduler {
             // Clone global vars to detect changes
             let one_letters_before: VariableStore = self.scene.vars.one_letter_vars().collect();
 
-            let next_exec_delay = self.process_executions(date);
+            let next_exec_delay = min(self.process_executions(date), self.process_metronome(date));
+            let next_exec_delay = min(next_exec_delay, self.process_morph(date));
+            let next_exec_delay = min(next_exec_delay, osc_clock_delay);
+            self.process_script_panics();
 
             // Check if global variables changed and send notification
             let one_letter_vars: VariableStore = self.scene.vars.one_letter_vars().collect();
@@ -356,5 +713,8 @@ impl Scheduler {
         self.clock.commit_app_state();
 
         self.scene.kill_executions();
+        if let Some(mut morph) = self.morph.take() {
+            morph.source.kill_executions();
+        }
     }
 }
@@ -2,7 +2,13 @@ use serde::{Deserialize, Serialize};
 
 use crate::{clock::{Clock, SyncTime}};
 
+/// Quantization grid of a new scheduler, in beats.
+pub const DEFAULT_QUANTIZATION_GRID: f64 = 1.0;
+
 /// Specifies when a scheduler action should be applied.
+///
+/// Only `Quantized` actions follow a quantization grid, the one of the line they edit if it
+/// has one. The other timings name their own boundary, which no grid changes.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
 pub enum ActionTiming {
     /// Apply the action immediately upon processing.
@@ -14,11 +20,15 @@ pub enum ActionTiming {
     AtNextPhase,
     /// Apply the action when reaching the next multiple of this value.
     AtNextModulo(f64),
+    /// Apply the action on the next line of the quantization grid of the scheduler.
+    Quantized,
 }
 
 impl ActionTiming {
 
-    pub fn remaining(&self, date: SyncTime, clock: &Clock) -> SyncTime {
+    /// Time to wait from `date` before applying the action, `Quantized` actions
+    /// waiting for the next multiple of `grid`, in beats.
+    pub fn remaining(&self, date: SyncTime, clock: &Clock, grid: f64) -> SyncTime {
         let beat = clock.beat_at_date(date);
         match self {
             ActionTiming::Immediate => 0,
@@ -44,10 +54,20 @@ impl ActionTiming {
                 let rem = m - ((beat % m) + m) % m;
                 clock.beats_to_micros(rem) 
             }
+            ActionTiming::Quantized => {
+                ActionTiming::AtNextModulo(grid).remaining(date, clock, grid)
+            }
         }
     }
 
-    pub fn should_apply(&self, clock: &Clock, previous_beat: f64, current_beat: f64) -> bool {
+    /// Whether the action applies between two beats, `Quantized` actions on `grid`.
+    pub fn should_apply(
+        &self,
+        clock: &Clock,
+        grid: f64,
+        previous_beat: f64,
+        current_beat: f64,
+    ) -> bool {
         match self {
             ActionTiming::Immediate => false,
             ActionTiming::AtBeat(target) => current_beat >= *target as f64,
@@ -61,6 +81,12 @@ impl ActionTiming {
             ActionTiming::AtNextModulo(m) => {
                 (previous_beat.div_euclid(*m)) != (current_beat.div_euclid(*m))
             }
+            ActionTiming::Quantized => ActionTiming::AtNextModulo(grid).should_apply(
+                clock,
+                grid,
+                previous_beat,
+                current_beat,
+            ),
         }
     }
 
@@ -0,0 +1,58 @@
+use crate::{
+    clock::{NEVER, SyncTime},
+    scene::{Frame, script::Script},
+    vm::{
+        PartialContext, event::ConcreteEvent, interpreter::InterpreterDirectory,
+        variable::VariableStore,
+    },
+};
+
+/// Scripts played a single time, outside of the scene.
+///
+/// Auditioned scripts get their own variables, so running them
+/// leaves the scene untouched.
+#[derive(Default)]
+pub struct Audition {
+    global_vars: VariableStore,
+    line_vars: VariableStore,
+    frames: Vec<Frame>,
+}
+
+impl Audition {
+    /// Starts a compiled script at the given date.
+    pub fn start(&mut self, script: Script, date: SyncTime, interpreters: &InterpreterDirectory) {
+        if self.frames.is_empty() {
+            self.global_vars.clear();
+            self.line_vars.clear();
+        }
+        let mut frame = Frame::from(script);
+        frame.trigger(date, interpreters);
+        if frame.has_executions() {
+            self.frames.push(frame);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn update_executions<'a>(
+        &'a mut self,
+        mut partial: PartialContext<'a>,
+    ) -> (Vec<ConcreteEvent>, SyncTime) {
+        let mut events = Vec::new();
+        let mut next_wait = NEVER;
+        partial.global_vars = Some(&mut self.global_vars);
+        partial.line_vars = Some(&mut self.line_vars);
+        partial.line_index = Some(0);
+        for frame in self.frames.iter_mut() {
+            let mut partial_child = partial.child();
+            partial_child.frame_index = Some(0);
+            let (mut new_events, wait) = frame.update_executions(partial_child);
+            events.append(&mut new_events);
+            next_wait = std::cmp::min(next_wait, wait);
+        }
+        self.frames.retain(|frame| frame.has_executions());
+        (events, next_wait)
+    }
+}
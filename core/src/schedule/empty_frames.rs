@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+use crate::scene::script::Script;
+
+/// What the scheduler does when a line reaches an enabled frame without a script.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum EmptyFrameBehavior {
+    /// The frame is a silent gap.
+    #[default]
+    Ignore,
+    /// A warning naming the line and the frame is sent to the clients.
+    Warn,
+    /// A compiled script, such as a click, is played in place of the frame.
+    Play(Script),
+}
@@ -1,21 +1,49 @@
-use crate::compiler::CompilationState;
-use crate::protocol::ProtocolPayload;
-use crate::scene::{ExecutionMode, Frame};
+use crate::compiler::{CompilationState, CompilationWarning};
+use crate::protocol::{ProtocolDevice, ProtocolError, ProtocolPayload};
+use crate::scene::{ExecutionMode, Frame, LineFill, LinePlaybackMode, SceneMetadata};
 use crate::scene::script::Script;
 use crate::scene::{Scene, Line};
 use crate::schedule::action_timing::ActionTiming;
+use crate::schedule::empty_frames::EmptyFrameBehavior;
+use crate::schedule::osc_clock::OscClockConfig;
+use crate::vm::{LanguageCenter, ValueGenerator};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SchedulerMessage {
     /// Set the entire scene.
     SetScene(Scene, ActionTiming),
+    /// Crossfade from the current scene to a new one over a number of beats.
+    MorphToScene(Scene, f64),
     SetSceneMode(ExecutionMode, ActionTiming),
+    /// Set the key and time signature of the scene.
+    SetSceneMetadata(SceneMetadata, ActionTiming),
     /// Set a line at a specific index.
     SetLines(Vec<(usize, Line)>, ActionTiming),
     ConfigureLines(Vec<(usize, Line)>, ActionTiming),
+    /// Set the order in which a line plays its frames.
+    SetLinePlaybackMode(usize, LinePlaybackMode, ActionTiming),
+    /// Set the note length multiplier of a line.
+    SetLineGate(usize, f64, ActionTiming),
+    /// Set the MIDI channel all the MIDI events of a line are sent on.
+    /// `None` or channel `0` keeps the channels of the scripts.
+    SetLineMidiChannel(usize, Option<u64>, ActionTiming),
+    /// Set the number of semitones added to the MIDI notes of a line.
+    SetLineTranspose(usize, i64, ActionTiming),
+    /// Set whether a line restarts from its start frame when it is enabled again.
+    SetLineRetriggerOnEnable(usize, bool, ActionTiming),
+    /// Drive the frame durations of a line with a generator, `None` restores its static durations.
+    SetLineDurationGenerator(usize, Option<ValueGenerator>, ActionTiming),
+    /// Set the frames a line plays instead of its own every few cycles, `None` removes them.
+    SetLineFill(usize, Option<LineFill>, ActionTiming),
+    /// Set the grid, in beats, on which the `ActionTiming::Quantized` edits of a line apply.
+    /// `None` follows the quantization grid of the scheduler.
+    SetLineQuantization(usize, Option<f64>, ActionTiming),
     AddLine(usize, Line, ActionTiming),
     RemoveLine(usize, ActionTiming),
+    /// Move a line from an index to another, shifting the lines in between.
+    MoveLine(usize, usize, ActionTiming),
 
     /// Set the current frame in specified line
     GoToFrame(usize, usize, ActionTiming),
@@ -26,14 +54,30 @@ pub enum SchedulerMessage {
     AddFrame(usize, usize, Frame, ActionTiming),
     /// Remove the frame at a specific position in a line.
     RemoveFrame(usize, usize, ActionTiming),
+    /// Set the trigger offset of a frame, in beats.
+    SetFrameNudge(usize, usize, f64, ActionTiming),
+    /// Set whether a frame disables itself after playing once.
+    SetFrameOneShot(usize, usize, bool, ActionTiming),
+    /// Enable a set of frames, given as (line, frame) pairs, as a single edit.
+    EnableFramesBatch(Vec<(usize, usize)>, ActionTiming),
+    /// Disable a set of frames, given as (line, frame) pairs, as a single edit.
+    DisableFramesBatch(Vec<(usize, usize)>, ActionTiming),
+    /// Remove a set of frames, given as (line, frame) pairs, as a single edit.
+    RemoveFramesBatch(Vec<(usize, usize)>, ActionTiming),
 
     /// Set the script content and lang for specified frame
     SetScript(usize, usize, Script, ActionTiming),
     
     /// Set the master tempo.
     SetTempo(f64, ActionTiming),
+    /// Adjust the master tempo by a delta in BPM.
+    NudgeTempo(f64, ActionTiming),
     /// Set the clock quantum.
     SetQuantum(f64, ActionTiming),
+    /// Set the grid, in beats, on which `ActionTiming::Quantized` actions apply.
+    SetQuantizationGrid(f64),
+    /// Seed the random source of scripts and random playback, `None` seeds it from entropy.
+    SetRandomSeed(Option<u64>),
     /// Request the transport to start playback at the specified timing.
     TransportStart(ActionTiming),
     /// Request the transport to stop playback at the specified timing.
@@ -50,6 +94,32 @@ pub enum SchedulerMessage {
     /// Updates the compilation status of a frame
     CompilationUpdate(usize, usize, u64, CompilationState),
 
+    /// Updates the compilation status of a frame of the fill of a line
+    FillCompilationUpdate(usize, usize, u64, CompilationState),
+
+    /// Reports the warnings of the latest successful compilation of a frame
+    CompilationWarnings(usize, usize, u64, Vec<CompilationWarning>),
+
+    /// Plays a compiled script once, outside of the scene
+    AuditionScript(Script),
+
+    /// Turns the MIDI metronome on or off, clicking on the given output slot
+    SetMidiMetronome(usize, bool),
+    /// Mirrors the clock and transport state to an OSC output, `None` stops it
+    SetOscClock(Option<OscClockConfig>),
+    /// Sets what happens when a line reaches a frame without a script
+    SetEmptyFrameBehavior(EmptyFrameBehavior),
+    /// Sets whether frames whose script panicked are disabled, so that they do not
+    /// panic again on their next trigger
+    SetDisablePanickingFrames(bool),
+    /// Replaces the languages scripts are compiled and run with, after languages were
+    /// registered or removed at runtime. Only sent within the process.
+    #[serde(skip)]
+    SetLanguages(Arc<LanguageCenter>),
+    /// Reports a failed send to an output device, from the world. Only sent within the process.
+    #[serde(skip)]
+    DeviceSendFailed(Arc<ProtocolDevice>, ProtocolError),
+
     /// Request the scheduler to shutdown cleanly.
     Shutdown,
 }
@@ -60,14 +130,30 @@ impl SchedulerMessage {
         match self {
             SchedulerMessage::SetScene(_, t)
             | SchedulerMessage::SetSceneMode(_, t)
+            | SchedulerMessage::SetSceneMetadata(_, t)
             | SchedulerMessage::SetLines(_, t)
             | SchedulerMessage::ConfigureLines(_, t)
+            | SchedulerMessage::SetLinePlaybackMode(_, _, t)
+            | SchedulerMessage::SetLineGate(_, _, t)
+            | SchedulerMessage::SetLineMidiChannel(_, _, t)
+            | SchedulerMessage::SetLineTranspose(_, _, t)
+            | SchedulerMessage::SetLineRetriggerOnEnable(_, _, t)
+            | SchedulerMessage::SetLineDurationGenerator(_, _, t)
+            | SchedulerMessage::SetLineFill(_, _, t)
+            | SchedulerMessage::SetLineQuantization(_, _, t)
             | SchedulerMessage::AddLine(_, _, t)
             | SchedulerMessage::RemoveLine(_, t)
+            | SchedulerMessage::MoveLine(_, _, t)
             | SchedulerMessage::SetFrames(_, t)
             | SchedulerMessage::AddFrame(_, _, _, t)
             | SchedulerMessage::RemoveFrame(_, _, t)
+            | SchedulerMessage::SetFrameNudge(_, _, _, t)
+            | SchedulerMessage::SetFrameOneShot(_, _, _, t)
+            | SchedulerMessage::EnableFramesBatch(_, t)
+            | SchedulerMessage::DisableFramesBatch(_, t)
+            | SchedulerMessage::RemoveFramesBatch(_, t)
             | SchedulerMessage::SetTempo(_, t)
+            | SchedulerMessage::NudgeTempo(_, t)
             | SchedulerMessage::SetQuantum(_, t)
             | SchedulerMessage::TransportStart(t) 
             | SchedulerMessage::TransportStop(t)
@@ -78,8 +164,44 @@ impl SchedulerMessage {
             | SchedulerMessage::StartLineAt(_, _, t)
                 => *t,
             SchedulerMessage::CompilationUpdate(_, _, _, _)
+            | SchedulerMessage::FillCompilationUpdate(_, _, _, _)
+            | SchedulerMessage::CompilationWarnings(_, _, _, _)
+            | SchedulerMessage::AuditionScript(_)
+            | SchedulerMessage::SetMidiMetronome(_, _)
+            | SchedulerMessage::SetOscClock(_)
+            | SchedulerMessage::SetEmptyFrameBehavior(_)
+            | SchedulerMessage::SetDisablePanickingFrames(_)
+            | SchedulerMessage::SetLanguages(_)
+            | SchedulerMessage::DeviceSendFailed(_, _)
+            | SchedulerMessage::SetQuantizationGrid(_)
+            | SchedulerMessage::SetRandomSeed(_)
+            | SchedulerMessage::MorphToScene(_, _)
             | SchedulerMessage::Shutdown => ActionTiming::Immediate,
         }
     }
 
+    /// The line edited by the action, if it only edits one.
+    pub fn target_line(&self) -> Option<usize> {
+        match self {
+            SchedulerMessage::SetLinePlaybackMode(i, _, _)
+            | SchedulerMessage::SetLineGate(i, _, _)
+            | SchedulerMessage::SetLineMidiChannel(i, _, _)
+            | SchedulerMessage::SetLineTranspose(i, _, _)
+            | SchedulerMessage::SetLineRetriggerOnEnable(i, _, _)
+            | SchedulerMessage::SetLineDurationGenerator(i, _, _)
+            | SchedulerMessage::SetLineFill(i, _, _)
+            | SchedulerMessage::SetLineQuantization(i, _, _)
+            | SchedulerMessage::RemoveLine(i, _)
+            | SchedulerMessage::GoToFrame(i, _, _)
+            | SchedulerMessage::AddFrame(i, _, _, _)
+            | SchedulerMessage::RemoveFrame(i, _, _)
+            | SchedulerMessage::SetFrameNudge(i, _, _, _)
+            | SchedulerMessage::SetFrameOneShot(i, _, _, _)
+            | SchedulerMessage::SetScript(i, _, _, _)
+            | SchedulerMessage::StartLine(i, _)
+            | SchedulerMessage::StartLineAt(i, _, _) => Some(*i),
+            _ => None,
+        }
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,78 @@
+use crate::{
+    clock::{Clock, NEVER, SyncTime},
+    vm::event::ConcreteEvent,
+};
+
+/// Clicks still sent when they are late by less than this, in microseconds.
+const MAX_LATENESS: SyncTime = 20_000;
+
+/// A click played as MIDI notes on every beat of the clock,
+/// with an accented note on the first beat of each bar.
+pub struct MidiMetronome {
+    /// Output slot receiving the clicks, `None` when the metronome is off.
+    slot: Option<usize>,
+    /// MIDI channel of the clicks, from 1 to 16.
+    pub channel: u64,
+    /// Note and velocity played on the first beat of each bar.
+    pub accent: (u64, u64),
+    /// Note and velocity played on the other beats.
+    pub click: (u64, u64),
+    /// Length of each click, in beats.
+    pub length: f64,
+    next_beat: Option<f64>,
+}
+
+impl Default for MidiMetronome {
+    fn default() -> Self {
+        // High and low wood blocks on the General MIDI drum channel
+        MidiMetronome {
+            slot: None,
+            channel: 10,
+            accent: (76, 127),
+            click: (77, 90),
+            length: 0.1,
+            next_beat: None,
+        }
+    }
+}
+
+impl MidiMetronome {
+    /// Sends the clicks to the given output slot, or stops them.
+    pub fn set_output(&mut self, slot: usize, on: bool) {
+        self.slot = on.then_some(slot);
+        self.next_beat = None;
+    }
+
+    /// Returns the clicks due by `date` along with their dates,
+    /// and the time to wait until the next one.
+    pub fn update(
+        &mut self,
+        clock: &Clock,
+        date: SyncTime,
+    ) -> (Vec<(SyncTime, ConcreteEvent)>, SyncTime) {
+        let Some(slot) = self.slot else {
+            return (Vec::new(), NEVER);
+        };
+        let beat = clock.beat_at_date(date);
+        let late = clock.micros_to_beats(MAX_LATENESS);
+        // Start over from the current beat after a pause or a jump of the timeline
+        let mut next = self
+            .next_beat
+            .filter(|next| *next + late >= beat && *next <= beat + 1.0)
+            .unwrap_or_else(|| (beat - late).ceil());
+
+        let mut clicks = Vec::new();
+        let quantum = clock.quantum();
+        let duration = clock.beats_to_micros(self.length);
+        while next <= beat {
+            let phase = next.rem_euclid(quantum);
+            let downbeat = phase < 1e-6 || quantum - phase < 1e-6;
+            let (note, velocity) = if downbeat { self.accent } else { self.click };
+            let event = ConcreteEvent::MidiNote(note, velocity, self.channel, duration, slot, None);
+            clicks.push((clock.date_at_beat(next), event));
+            next += 1.0;
+        }
+        self.next_beat = Some(next);
+        (clicks, clock.date_at_beat(next).saturating_sub(date))
+    }
+}
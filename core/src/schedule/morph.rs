@@ -0,0 +1,67 @@
+use crate::{
+    scene::Scene,
+    vm::{event::ConcreteEvent, variable::VariableValue},
+};
+
+/// A crossfade from the scene played before a morph to the current scene.
+///
+/// Both scenes play during the morph, the previous one fading out
+/// while the current one fades in.
+pub struct SceneMorph {
+    /// Scene fading out, played along the current one until the end of the morph.
+    pub source: Scene,
+    pub source_structure: Vec<Vec<f64>>,
+    start_beat: f64,
+    duration: f64,
+}
+
+impl SceneMorph {
+    pub fn new(source: Scene, start_beat: f64, duration: f64) -> Self {
+        SceneMorph {
+            source_structure: source.structure(),
+            source,
+            start_beat,
+            duration,
+        }
+    }
+
+    /// Progress of the morph at the given beat, from `0.0` to `1.0`.
+    pub fn progress(&self, beat: f64) -> f64 {
+        ((beat - self.start_beat) / self.duration).clamp(0.0, 1.0)
+    }
+
+    /// Gains of the source and target scenes at the given beat.
+    pub fn gains(&self, beat: f64) -> (f64, f64) {
+        let progress = self.progress(beat);
+        (1.0 - progress, progress)
+    }
+
+    pub fn is_over(&self, beat: f64) -> bool {
+        self.progress(beat) >= 1.0
+    }
+}
+
+/// Scales the loudness of an event: the velocity of MIDI notes and the gain
+/// of audio engine events. Returns `false` when the event becomes silent.
+pub fn fade(event: &mut ConcreteEvent, gain: f64) -> bool {
+    if gain >= 1.0 {
+        return true;
+    }
+    match event {
+        ConcreteEvent::MidiNote(_, velocity, _, _, _, _)
+        | ConcreteEvent::MidiMpeNote(_, velocity, _, _, _, _) => {
+            *velocity = (*velocity as f64 * gain).round() as u64;
+            *velocity > 0
+        }
+        ConcreteEvent::Dirt { args, .. } => {
+            let faded = match args.get("gain") {
+                Some(VariableValue::Float(g)) => g * gain,
+                Some(VariableValue::Integer(g)) => *g as f64 * gain,
+                _ => gain,
+            };
+            args.insert("gain".to_owned(), VariableValue::Float(faded));
+            gain > 0.0
+        }
+        _ => true,
+    }
+}
@@ -1,11 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use serde::{Deserialize, Serialize};
 
-use crate::compiler::CompilationState;
+use crate::compiler::{CompilationState, CompilationWarning};
 use crate::vm::variable::VariableValue;
-use crate::scene::{ExecutionMode, Frame, Line, Scene};
+use crate::scene::{ExecutionMode, Frame, Line, Scene, SceneMetadata};
 use crate::protocol::DeviceInfo;
+use crate::instruments::InstrumentMap;
 use crate::LogMessage;
 use crate::schedule::playback::PlaybackState;
 
@@ -18,6 +19,8 @@ pub enum SovaNotification {
     UpdatedScene(Scene),
     /// New global execution mode
     UpdatedSceneMode(ExecutionMode),
+    /// New scene key and time signature
+    UpdatedSceneMetadata(SceneMetadata),
     /// New lines values
     UpdatedLines(Vec<(usize, Line)>),
     /// New lines configurations (without frames)
@@ -26,6 +29,8 @@ pub enum SovaNotification {
     AddedLine(usize, Line),
     /// Removed a line
     RemovedLine(usize),
+    /// Moved a line from an index to another
+    MovedLine(usize, usize),
     /// New frames values
     UpdatedFrames(Vec<(usize, usize, Frame)>),
     /// Added a frame
@@ -34,9 +39,13 @@ pub enum SovaNotification {
     RemovedFrame(usize, usize),
 
     CompilationUpdated(usize, usize, u64, CompilationState),
+    /// Warnings of the latest compilation of a frame, empty when there are none
+    CompilationWarnings(usize, usize, u64, Vec<CompilationWarning>),
 
     TempoChanged(f64),
     QuantumChanged(f64),
+    /// New quantization grid, in beats
+    QuantizationGridChanged(f64),
     Log(LogMessage),
     PlaybackStateChanged(PlaybackState),
     /// Current frame position for each playing line (line_idx, frame_idx, repetition_idx)
@@ -49,10 +58,17 @@ pub enum SovaNotification {
     PeerStartedEditingFrame(String, usize, usize),
     /// A peer stopped editing a specific frame.
     PeerStoppedEditingFrame(String, usize, usize),
+    /// The scene was locked by a client (`Some(holder)`) or released (`None`).
+    SceneLockChanged(Option<String>),
     /// The list of available/connected devices changed.
     DeviceListChanged(Vec<DeviceInfo>),
+    /// An instrument was mapped to a sound or unmapped.
+    InstrumentsChanged(InstrumentMap),
     /// Global variables have been updated
     GlobalVariablesChanged(HashMap<String, VariableValue>),
     /// Oscilloscope waveform data as min/max peak pairs.
     ScopeData(Vec<(f32, f32)>),
+    /// Languages were registered or removed at runtime: the available languages,
+    /// and the syntax definitions of those registered with one.
+    LanguagesChanged(Vec<String>, BTreeMap<String, String>),
 }
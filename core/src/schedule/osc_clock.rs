@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clock::{Clock, NEVER, SyncTime},
+    protocol::osc::OSCMessage,
+    vm::{event::ConcreteEvent, variable::VariableValue},
+};
+
+/// Ticks still sent when they are late by less than this, in microseconds.
+const MAX_LATENESS: SyncTime = 20_000;
+
+/// Where and how often the clock state is sent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OscClockConfig {
+    /// Output slot of the OSC device receiving the messages.
+    pub slot: usize,
+    /// OSC address of the messages.
+    pub address: String,
+    /// Messages sent per beat.
+    pub rate: f64,
+}
+
+impl Default for OscClockConfig {
+    fn default() -> Self {
+        OscClockConfig {
+            slot: 1,
+            address: "/sova/clock".to_owned(),
+            rate: 4.0,
+        }
+    }
+}
+
+/// Mirrors the clock and transport state to an OSC output, at a fixed rate
+/// per beat, so that visuals and lighting rigs can follow along.
+///
+/// Each message carries the tempo, the beat, the bar and whether the transport
+/// is playing (`1`) or stopped (`0`).
+#[derive(Default)]
+pub struct OscClock {
+    /// Destination of the messages, `None` when the mirror is off.
+    config: Option<OscClockConfig>,
+    next_tick: Option<f64>,
+}
+
+impl OscClock {
+    pub fn configure(&mut self, config: Option<OscClockConfig>) {
+        self.config = config.filter(|config| config.rate.is_finite() && config.rate > 0.0);
+        self.next_tick = None;
+    }
+
+    /// Returns the messages due by `date` along with their dates,
+    /// and the time to wait until the next one.
+    pub fn update(
+        &mut self,
+        clock: &Clock,
+        date: SyncTime,
+        playing: bool,
+    ) -> (Vec<(SyncTime, ConcreteEvent)>, SyncTime) {
+        let Some(config) = &self.config else {
+            return (Vec::new(), NEVER);
+        };
+        let step = 1.0 / config.rate;
+        let beat = clock.beat_at_date(date);
+        let late = clock.micros_to_beats(MAX_LATENESS);
+        // Start over from the current tick after a pause or a jump of the timeline
+        let mut next = self
+            .next_tick
+            .filter(|next| *next + late >= beat && *next <= beat + step)
+            .unwrap_or_else(|| ((beat - late) / step).ceil() * step);
+
+        let mut ticks = Vec::new();
+        let tempo = clock.tempo();
+        let quantum = clock.quantum();
+        while next <= beat {
+            let message = OSCMessage {
+                addr: config.address.clone(),
+                args: vec![
+                    VariableValue::Float(tempo),
+                    VariableValue::Float(next),
+                    VariableValue::Integer((next / quantum).floor() as i64),
+                    VariableValue::Integer(playing as i64),
+                ],
+                timetag: None,
+            };
+            let event = ConcreteEvent::Osc {
+                message,
+                device_id: config.slot,
+            };
+            ticks.push((clock.date_at_beat(next), event));
+            next += step;
+        }
+        self.next_tick = Some(next);
+        (ticks, clock.date_at_beat(next).saturating_sub(date))
+    }
+}
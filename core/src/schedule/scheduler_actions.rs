@@ -1,4 +1,6 @@
 use crate::{
+    LogMessage,
+    compiler::CompilationState,
     scene::{Frame, Scene}, schedule::{message::SchedulerMessage, notification::SovaNotification}, vm::LanguageCenter
 };
 use crossbeam_channel::Sender;
@@ -36,6 +38,13 @@ impl ActionProcessor {
                 scene.mode = mode;
                 let _ = update_notifier.send(SovaNotification::UpdatedSceneMode(mode));
             }
+            SchedulerMessage::SetSceneMetadata(metadata, _) => {
+                if metadata.validate().is_err() {
+                    return;
+                }
+                scene.metadata = metadata;
+                let _ = update_notifier.send(SovaNotification::UpdatedSceneMetadata(metadata));
+            }
             SchedulerMessage::ConfigureLines(mut lines, _) => {
                 let mut upd_index = BTreeSet::new();
                 let previous_len = scene.n_lines();
@@ -51,6 +60,85 @@ impl ActionProcessor {
                 }
                 let _ = update_notifier.send(SovaNotification::UpdatedLineConfigurations(lines));
             }
+            SchedulerMessage::SetLinePlaybackMode(i, mode, _) => {
+                let Some(line) = scene.lines.get_mut(i) else {
+                    return;
+                };
+                line.playback_mode = mode;
+                let _ = update_notifier.send(SovaNotification::UpdatedLineConfigurations(vec![(
+                    i,
+                    line.configuration(),
+                )]));
+            }
+            SchedulerMessage::SetLineGate(i, gate, _) => {
+                let Some(line) = scene.lines.get_mut(i) else {
+                    return;
+                };
+                line.set_gate(gate);
+                let _ = update_notifier.send(SovaNotification::UpdatedLineConfigurations(vec![(
+                    i,
+                    line.configuration(),
+                )]));
+            }
+            SchedulerMessage::SetLineMidiChannel(i, channel, _) => {
+                let Some(line) = scene.lines.get_mut(i) else {
+                    return;
+                };
+                line.set_midi_channel(channel);
+                let _ = update_notifier.send(SovaNotification::UpdatedLineConfigurations(vec![(
+                    i,
+                    line.configuration(),
+                )]));
+            }
+            SchedulerMessage::SetLineTranspose(i, semitones, _) => {
+                let Some(line) = scene.lines.get_mut(i) else {
+                    return;
+                };
+                line.set_transpose(semitones);
+                let _ = update_notifier.send(SovaNotification::UpdatedLineConfigurations(vec![(
+                    i,
+                    line.configuration(),
+                )]));
+            }
+            SchedulerMessage::SetLineRetriggerOnEnable(i, retrigger, _) => {
+                let Some(line) = scene.lines.get_mut(i) else {
+                    return;
+                };
+                line.retrigger_on_enable = retrigger;
+                let _ = update_notifier.send(SovaNotification::UpdatedLineConfigurations(vec![(
+                    i,
+                    line.configuration(),
+                )]));
+            }
+            SchedulerMessage::SetLineDurationGenerator(i, generator, _) => {
+                let Some(line) = scene.lines.get_mut(i) else {
+                    return;
+                };
+                line.set_duration_generator(generator);
+                let _ = update_notifier.send(SovaNotification::UpdatedLineConfigurations(vec![(
+                    i,
+                    line.configuration(),
+                )]));
+            }
+            SchedulerMessage::SetLineFill(i, fill, _) => {
+                let Some(line) = scene.lines.get_mut(i) else {
+                    return;
+                };
+                line.set_fill(fill);
+                languages.process_fill(i, line, feedback.clone());
+                let _ =
+                    update_notifier.send(SovaNotification::UpdatedLines(vec![(i, line.clone())]));
+            }
+            SchedulerMessage::SetLineQuantization(i, grid, _) => {
+                let Some(line) = scene.lines.get_mut(i) else {
+                    return;
+                };
+                line.set_quantization(grid);
+                let _ = update_notifier.send(SovaNotification::UpdatedLineConfigurations(vec![(
+                    i,
+                    line.configuration(),
+                )]));
+            }
             SchedulerMessage::AddLine(i, line, _) => {
                 scene.insert_line(i, line.clone());
                 languages.process_line(i, scene.line(i).unwrap(), feedback.clone());
@@ -60,6 +148,11 @@ impl ActionProcessor {
                 scene.remove_line(index);
                 let _ = update_notifier.send(SovaNotification::RemovedLine(index));
             }
+            SchedulerMessage::MoveLine(from, to, _) => {
+                if scene.move_line(from, to) {
+                    let _ = update_notifier.send(SovaNotification::MovedLine(from, to));
+                }
+            }
             SchedulerMessage::GoToFrame(line_id, frame_id, _) => {
                 let line = scene.line_mut(line_id);
                 line.go_to_frame(frame_id, 0);
@@ -100,6 +193,37 @@ impl ActionProcessor {
                     ));
                 }
             }
+            SchedulerMessage::SetFrameNudge(line_id, frame_id, nudge, _) => {
+                if !scene.has_frame(line_id, frame_id) {
+                    return;
+                }
+                let line = scene.line_mut(line_id);
+                line.set_frame_nudge(frame_id, nudge);
+                let frame = line.frame(frame_id).unwrap().clone();
+                let _ = update_notifier.send(SovaNotification::UpdatedFrames(vec![(
+                    line_id, frame_id, frame,
+                )]));
+            }
+            SchedulerMessage::SetFrameOneShot(line_id, frame_id, one_shot, _) => {
+                if !scene.has_frame(line_id, frame_id) {
+                    return;
+                }
+                let frame = scene.get_frame_mut(line_id, frame_id);
+                frame.one_shot = one_shot;
+                let frame = frame.clone();
+                let _ = update_notifier.send(SovaNotification::UpdatedFrames(vec![(
+                    line_id, frame_id, frame,
+                )]));
+            }
+            SchedulerMessage::EnableFramesBatch(frames, _) => {
+                Self::set_frames_enabled(scene, frames, true, update_notifier);
+            }
+            SchedulerMessage::DisableFramesBatch(frames, _) => {
+                Self::set_frames_enabled(scene, frames, false, update_notifier);
+            }
+            SchedulerMessage::RemoveFramesBatch(frames, _) => {
+                Self::remove_frames(scene, frames, update_notifier);
+            }
             SchedulerMessage::SetScript(line_id, frame_id, script, _) => {
                 let frame = scene.get_frame_mut(line_id, frame_id);
                 frame.set_script(script);
@@ -127,6 +251,40 @@ impl ActionProcessor {
                     let _ = update_notifier.send(notif);
                 }
             }
+            SchedulerMessage::FillCompilationUpdate(line_id, frame_id, id, state) => {
+                let frame = scene
+                    .lines
+                    .get_mut(line_id)
+                    .and_then(|line| line.fill.as_mut())
+                    .and_then(|fill| fill.frames.get_mut(frame_id));
+                let Some(frame) = frame else {
+                    return;
+                };
+                // Fill frames are not shown by clients, so only their errors are reported
+                let error = match &state {
+                    CompilationState::Error(err) => Some(err.info.clone()),
+                    _ => None,
+                };
+                if !frame.update_compilation_state(id, state) {
+                    return;
+                }
+                if let Some(error) = error {
+                    let log = LogMessage::warn(format!(
+                        "Fill frame {frame_id} of line {line_id} does not compile: {error}"
+                    ));
+                    let _ = update_notifier.send(SovaNotification::Log(log));
+                }
+            }
+            SchedulerMessage::CompilationWarnings(line_id, frame_id, id, warnings) => {
+                let is_current = scene
+                    .get_frame(line_id, frame_id)
+                    .is_some_and(|frame| frame.script().id() == id);
+                if is_current {
+                    let _ = update_notifier.send(SovaNotification::CompilationWarnings(
+                        line_id, frame_id, id, warnings,
+                    ));
+                }
+            }
             SchedulerMessage::StartLine(line_id, _) => {
                 scene.line_mut(line_id).start();
             }
@@ -137,9 +295,20 @@ impl ActionProcessor {
             SchedulerMessage::TransportStart(_)
             | SchedulerMessage::TransportStop(_)
             | SchedulerMessage::SetTempo(_, _)
+            | SchedulerMessage::NudgeTempo(_, _)
             | SchedulerMessage::SetQuantum(_, _)
+            | SchedulerMessage::SetQuantizationGrid(_)
+            | SchedulerMessage::SetRandomSeed(_)
             | SchedulerMessage::SetScene(_, _)
+            | SchedulerMessage::MorphToScene(_, _)
             | SchedulerMessage::DeviceMessage(_, _, _)
+            | SchedulerMessage::AuditionScript(_)
+            | SchedulerMessage::SetMidiMetronome(_, _)
+            | SchedulerMessage::SetOscClock(_)
+            | SchedulerMessage::SetEmptyFrameBehavior(_)
+            | SchedulerMessage::SetDisablePanickingFrames(_)
+            | SchedulerMessage::SetLanguages(_)
+            | SchedulerMessage::DeviceSendFailed(_, _)
             | SchedulerMessage::Shutdown => (),
         }
     }
@@ -154,6 +323,7 @@ impl ActionProcessor {
         let mut updated = frames.clone();
         let mut upd_index = BTreeSet::new();
         let previous_lens: Vec<usize> = scene.lines.iter().map(|l| l.n_frames()).collect();
+        let enabled = Self::enabled_lines(scene, frames.iter().map(|(line_id, _, _)| *line_id));
         for (line_id, frame_id, frame) in frames {
             upd_index.insert((line_id, frame_id));
             let line = scene.line_mut(line_id);
@@ -176,5 +346,94 @@ impl ActionProcessor {
             }
         }
         let _ = update_notifier.send(SovaNotification::UpdatedFrames(updated));
+        Self::retrigger_enabled_lines(scene, enabled, update_notifier);
+    }
+
+    /// Sets the enabledness of every existing frame of the batch,
+    /// then reports all of them in a single notification.
+    fn set_frames_enabled(
+        scene: &mut Scene,
+        frames: Vec<(usize, usize)>,
+        enabled: bool,
+        update_notifier: &Sender<SovaNotification>,
+    ) {
+        let targets: BTreeSet<(usize, usize)> = frames
+            .into_iter()
+            .filter(|(line_id, frame_id)| scene.has_frame(*line_id, *frame_id))
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+        let was_enabled = Self::enabled_lines(scene, targets.iter().map(|(line_id, _)| *line_id));
+        let mut updated = Vec::with_capacity(targets.len());
+        for (line_id, frame_id) in targets {
+            let frame = scene.get_frame_mut(line_id, frame_id);
+            frame.enabled = enabled;
+            updated.push((line_id, frame_id, frame.clone()));
+        }
+        let _ = update_notifier.send(SovaNotification::UpdatedFrames(updated));
+        Self::retrigger_enabled_lines(scene, was_enabled, update_notifier);
+    }
+
+    /// Whether each of the given existing lines is currently enabled.
+    fn enabled_lines(scene: &Scene, lines: impl Iterator<Item = usize>) -> Vec<(usize, bool)> {
+        let lines: BTreeSet<usize> = lines.collect();
+        lines
+            .into_iter()
+            .filter_map(|line_id| Some((line_id, scene.lines.get(line_id)?.is_enabled())))
+            .collect()
+    }
+
+    /// Restarts the lines which were disabled and have just been enabled,
+    /// when they ask to be retriggered.
+    fn retrigger_enabled_lines(
+        scene: &mut Scene,
+        was_enabled: Vec<(usize, bool)>,
+        update_notifier: &Sender<SovaNotification>,
+    ) {
+        let mut moved = false;
+        for (line_id, enabled) in was_enabled {
+            let line = scene.line_mut(line_id);
+            if !enabled && line.retrigger_on_enable && line.is_enabled() {
+                line.retrigger();
+                moved = true;
+            }
+        }
+        if moved {
+            let _ = update_notifier.send(SovaNotification::FramePositionChanged(
+                scene.positions().collect(),
+            ));
+        }
+    }
+
+    /// Removes every existing frame of the batch, then sends the resulting lines
+    /// in a single notification. Frames are removed from the end of each line,
+    /// so the indices of the batch all refer to the scene before the edit.
+    fn remove_frames(
+        scene: &mut Scene,
+        frames: Vec<(usize, usize)>,
+        update_notifier: &Sender<SovaNotification>,
+    ) {
+        let targets: BTreeSet<(usize, usize)> = frames
+            .into_iter()
+            .filter(|(line_id, frame_id)| scene.has_frame(*line_id, *frame_id))
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+        let positions: Vec<_> = scene.positions().collect();
+        for &(line_id, frame_id) in targets.iter().rev() {
+            scene.line_mut(line_id).remove_frame(frame_id);
+        }
+        let lines: BTreeSet<usize> = targets.iter().map(|(line_id, _)| *line_id).collect();
+        let updated = lines
+            .into_iter()
+            .map(|line_id| (line_id, scene.line(line_id).unwrap().clone()))
+            .collect();
+        let _ = update_notifier.send(SovaNotification::UpdatedLines(updated));
+        let new_positions: Vec<_> = scene.positions().collect();
+        if positions != new_positions {
+            let _ = update_notifier.send(SovaNotification::FramePositionChanged(new_positions));
+        }
     }
 }
@@ -0,0 +1,321 @@
+use super::Fixture;
+use crate::{
+    clock::{NEVER, SyncTime},
+    compiler::CompilationState,
+    protocol::log::Severity,
+    scene::{Frame, Line, Scene, script::Script},
+    schedule::{ActionTiming, EmptyFrameBehavior, SchedulerMessage, SovaNotification},
+    vm::{
+        EvaluationContext,
+        event::ConcreteEvent,
+        interpreter::{Interpreter, InterpreterDirectory, InterpreterFactory},
+    },
+};
+use std::time::Duration;
+
+#[test]
+fn audition_plays_once_without_touching_the_scene() {
+    let mut fixture = Fixture::new();
+    let script = fixture.script("60");
+    fixture
+        .scheduler
+        .process_message(SchedulerMessage::AuditionScript(script));
+    for _ in 0..16 {
+        if fixture.scheduler.process_auditions(fixture.clock.micros()) == NEVER {
+            break;
+        }
+    }
+
+    assert_eq!(fixture.played_notes(), vec![60]);
+    assert_eq!(fixture.scheduler.scene.n_lines(), 0);
+    assert!(fixture.notifications.try_recv().is_err());
+}
+
+#[test]
+fn empty_frames_warn_when_reached() {
+    let mut fixture = Fixture::new();
+    let mut line = Line::new(vec![1.0, 1.0]);
+    line.frame_mut(0).set_script(fixture.script("60"));
+    fixture.scheduler.change_scene(Scene::new(vec![line]));
+    fixture
+        .scheduler
+        .process_message(SchedulerMessage::SetEmptyFrameBehavior(
+            EmptyFrameBehavior::Warn,
+        ));
+    let warnings = |fixture: &Fixture| -> Vec<String> {
+        fixture
+            .notifications
+            .try_iter()
+            .filter_map(|notification| match notification {
+                SovaNotification::Log(log) if log.level == Severity::Warn => Some(log.msg),
+                _ => None,
+            })
+            .collect()
+    };
+
+    let date = fixture.clock.micros();
+    let line = fixture.scheduler.scene.line_mut(0);
+    line.start();
+    line.step(&fixture.clock, date, &fixture.languages.interpreters);
+    fixture.scheduler.process_empty_frames();
+    assert!(warnings(&fixture).is_empty());
+
+    let next_beat = date + fixture.clock.beats_to_micros(1.0);
+    let line = fixture.scheduler.scene.line_mut(0);
+    line.step(&fixture.clock, next_beat, &fixture.languages.interpreters);
+    fixture.scheduler.process_empty_frames();
+    let warnings = warnings(&fixture);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("Frame 1 of line 0"));
+}
+
+#[test]
+fn one_shot_frames_play_once_then_disable() {
+    let mut fixture = Fixture::new();
+    let mut line = Line::new(vec![1.0, 1.0]);
+    line.looping = true;
+    line.frame_mut(0).set_script(fixture.script("60"));
+    fixture.scheduler.change_scene(Scene::new(vec![line]));
+    fixture
+        .scheduler
+        .process_message(SchedulerMessage::SetFrameOneShot(
+            0,
+            0,
+            true,
+            ActionTiming::Immediate,
+        ));
+    fixture.clear_notifications();
+
+    let start = fixture.clock.micros();
+    fixture.scheduler.scene.line_mut(0).start();
+    for beat in 0..4 {
+        let date = start + fixture.clock.beats_to_micros(beat as f64);
+        fixture.scheduler.scene.line_mut(0).step(
+            &fixture.clock,
+            date,
+            &fixture.languages.interpreters,
+        );
+        fixture.scheduler.process_fired_one_shots();
+        fixture.scheduler.process_executions(date);
+    }
+
+    assert_eq!(fixture.played_notes().len(), 1);
+    let frame = fixture.scheduler.scene.get_frame(0, 0).unwrap();
+    assert!(frame.one_shot);
+    assert!(!frame.enabled);
+    let disabled: Vec<(usize, usize, Frame)> = fixture
+        .notifications
+        .try_iter()
+        .filter_map(|notification| match notification {
+            SovaNotification::UpdatedFrames(frames) => Some(frames),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    assert_eq!(disabled.len(), 1);
+    assert_eq!((disabled[0].0, disabled[0].1), (0, 0));
+    assert!(!disabled[0].2.enabled);
+}
+
+/// Interpreter of a buggy language, panicking as soon as it runs.
+struct PanickingInterpreter;
+
+impl Interpreter for PanickingInterpreter {
+    fn execute_next(&mut self, _ctx: &mut EvaluationContext) -> (Option<ConcreteEvent>, SyncTime) {
+        panic!("index out of bounds");
+    }
+
+    fn has_terminated(&self) -> bool {
+        false
+    }
+
+    fn stop(&mut self) {}
+}
+
+struct PanickingFactory;
+
+impl InterpreterFactory for PanickingFactory {
+    fn name(&self) -> &str {
+        "crash"
+    }
+
+    fn make_instance(&self, _script: &Script) -> Result<Box<dyn Interpreter>, String> {
+        Ok(Box::new(PanickingInterpreter))
+    }
+
+    fn check(&self, _script: &Script) -> CompilationState {
+        CompilationState::Parsed(None)
+    }
+}
+
+#[test]
+fn panicking_scripts_are_disabled_while_other_lines_play() {
+    let mut interpreters = InterpreterDirectory::new();
+    interpreters.add_factory(PanickingFactory);
+    let mut fixture = Fixture::with_interpreters(interpreters);
+    let mut crashing = Script::new("boom".to_string(), "crash".to_string());
+    fixture.languages.blocking_process(&mut crashing);
+    let mut lines = vec![Line::new(vec![1.0]), Line::new(vec![1.0])];
+    for (line, script) in lines.iter_mut().zip([crashing, fixture.script("60")]) {
+        line.looping = true;
+        line.frame_mut(0).set_script(script);
+    }
+    fixture.scheduler.change_scene(Scene::new(lines));
+    fixture.clear_notifications();
+
+    let start = fixture.clock.micros();
+    fixture.scheduler.scene.line_mut(0).start();
+    fixture.scheduler.scene.line_mut(1).start();
+    for beat in 0..3 {
+        let date = start + fixture.clock.beats_to_micros(beat as f64);
+        fixture
+            .scheduler
+            .scene
+            .step(&fixture.clock, date, &fixture.languages.interpreters);
+        fixture.scheduler.process_executions(date);
+        fixture.scheduler.process_script_panics();
+    }
+
+    assert_eq!(fixture.played_notes().len(), 3);
+    assert!(!fixture.scheduler.scene.get_frame(0, 0).unwrap().enabled);
+    let notifications: Vec<SovaNotification> = fixture.notifications.try_iter().collect();
+    let errors: Vec<&String> = notifications
+        .iter()
+        .filter_map(|notification| match notification {
+            SovaNotification::Log(log) if log.level == Severity::Error => Some(&log.msg),
+            _ => None,
+        })
+        .collect();
+    // Disabled, the frame does not panic again on the next cycles
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("Frame 0 of line 0"));
+    assert!(errors[0].contains("index out of bounds"));
+    assert!(notifications.iter().any(|notification| matches!(
+        notification,
+        SovaNotification::UpdatedFrames(frames) if frames[0].0 == 0 && !frames[0].2.enabled
+    )));
+}
+
+/// Trigger dates of the frames of a three beats line, relative to its start.
+fn frame_trigger_dates(nudge: Option<(usize, f64)>) -> Vec<SyncTime> {
+    let mut fixture = Fixture::new();
+    let script = fixture.script("60");
+    let mut line = Line::new(vec![1.0, 1.0, 1.0]);
+    for frame in line.frames.iter_mut() {
+        frame.set_script(script.clone());
+    }
+    fixture.scheduler.change_scene(Scene::new(vec![line]));
+    if let Some((frame_id, offset)) = nudge {
+        fixture
+            .scheduler
+            .process_message(SchedulerMessage::SetFrameNudge(
+                0,
+                frame_id,
+                offset,
+                ActionTiming::Immediate,
+            ));
+    }
+
+    let start = fixture.clock.micros();
+    let line = fixture.scheduler.scene.line_mut(0);
+    line.start();
+    let mut date = start;
+    for _ in 0..16 {
+        line.step(&fixture.clock, date, &fixture.languages.interpreters);
+        if line.frames.iter().all(Frame::has_executions) {
+            break;
+        }
+        date += line.before_next_trigger(&fixture.clock, date);
+    }
+    line.frames
+        .iter()
+        .map(|frame| frame.executions[0].scheduled_time - start)
+        .collect()
+}
+
+#[test]
+fn nudging_a_frame_only_shifts_its_trigger() {
+    let beat = Fixture::new().clock.beats_to_micros(1.0);
+    assert_eq!(frame_trigger_dates(None), vec![0, beat, 2 * beat]);
+    assert_eq!(
+        frame_trigger_dates(Some((1, -0.25))),
+        vec![0, beat - beat / 4, 2 * beat]
+    );
+    // Nudges are bounded to half a frame, so frames never cross their neighbors
+    assert_eq!(
+        frame_trigger_dates(Some((1, 3.0))),
+        vec![0, beat + beat / 2, 2 * beat]
+    );
+}
+
+#[test]
+fn batch_disable_sends_a_single_notification() {
+    let mut fixture = Fixture::new();
+    fixture.scheduler.change_scene(Scene::new(vec![
+        Line::new(vec![1.0, 1.0, 1.0]),
+        Line::new(vec![1.0, 1.0]),
+    ]));
+    fixture.clear_notifications();
+
+    let selection = vec![(0, 0), (0, 2), (1, 1), (0, 2), (4, 0)];
+    fixture
+        .scheduler
+        .process_message(SchedulerMessage::DisableFramesBatch(
+            selection,
+            ActionTiming::Immediate,
+        ));
+
+    let notifications: Vec<_> = fixture.notifications.try_iter().collect();
+    assert_eq!(notifications.len(), 1);
+    let SovaNotification::UpdatedFrames(frames) = &notifications[0] else {
+        panic!("expected an UpdatedFrames notification");
+    };
+    let ids: Vec<(usize, usize)> = frames.iter().map(|(l, f, _)| (*l, *f)).collect();
+    assert_eq!(ids, vec![(0, 0), (0, 2), (1, 1)]);
+    assert!(frames.iter().all(|(_, _, frame)| !frame.enabled));
+    assert!(fixture.scheduler.scene.get_frame(0, 1).unwrap().enabled);
+    assert!(!fixture.scheduler.scene.get_frame(1, 1).unwrap().enabled);
+}
+
+#[test]
+fn compilation_warnings_reach_clients_without_blocking() {
+    let mut fixture = Fixture::new();
+    let mut line = Line::new(vec![1.0]);
+    line.frame_mut(0)
+        .set_script(Script::new("200".to_string(), "note".to_string()));
+    fixture.scheduler.change_scene(Scene::new(vec![line]));
+
+    // Compilation runs on its own thread and reports back through the feedback channel.
+    loop {
+        let msg = fixture
+            .feedback
+            .recv_timeout(Duration::from_secs(5))
+            .expect("compilation never finished");
+        let done = matches!(msg, SchedulerMessage::CompilationWarnings(..));
+        fixture.scheduler.process_message(msg);
+        if done {
+            break;
+        }
+    }
+
+    let warnings = fixture
+        .notifications
+        .try_iter()
+        .find_map(|notif| match notif {
+            SovaNotification::CompilationWarnings(0, 0, _, warnings) => Some(warnings),
+            _ => None,
+        })
+        .expect("no warnings were sent");
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].info.contains("clamped to 127"));
+    assert!(
+        fixture
+            .scheduler
+            .scene
+            .get_frame(0, 0)
+            .unwrap()
+            .script()
+            .compilation_state()
+            .is_compiled()
+    );
+}
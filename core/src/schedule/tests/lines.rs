@@ -0,0 +1,408 @@
+use super::Fixture;
+use crate::{
+    clock::{SyncTime, TimeSpan},
+    protocol::{
+        ProtocolDevice, ProtocolPayload,
+        midi::{MIDIMessage, MIDIMessageType, MidiInterface, MidiOut},
+    },
+    scene::{Frame, Line, LineFill, MAX_GATE, Scene, script::Script},
+    schedule::{ActionTiming, Scheduler, SchedulerMessage, SovaNotification},
+    vm::{
+        GeneratorModifier, GeneratorShape, ValueGenerator, event::ConcreteEvent,
+        interpreter::InterpreterDirectory, variable::VariableValue,
+    },
+};
+use std::time::Duration;
+
+/// The first note played by a one frame line, after the given message.
+fn first_played_note(message: SchedulerMessage) -> ConcreteEvent {
+    let mut fixture = Fixture::new();
+    let mut line = Line::new(vec![4.0]);
+    line.frame_mut(0).set_script(fixture.script("60"));
+    fixture.scheduler.change_scene(Scene::new(vec![line]));
+    fixture.scheduler.process_message(message);
+
+    let date = fixture.clock.micros();
+    let line = fixture.scheduler.scene.line_mut(0);
+    line.start();
+    line.step(&fixture.clock, date, &fixture.languages.interpreters);
+    fixture.scheduler.process_executions(date);
+
+    fixture
+        .played_events()
+        .into_iter()
+        .find(|event| matches!(event, ConcreteEvent::MidiNote(..)))
+        .expect("no note was played")
+}
+
+fn gated_note_duration(gate: f64) -> SyncTime {
+    let message = SchedulerMessage::SetLineGate(0, gate, ActionTiming::Immediate);
+    match first_played_note(message) {
+        ConcreteEvent::MidiNote(_, _, _, duration, _, _) => duration,
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn gate_scales_note_durations() {
+    let full = gated_note_duration(1.0);
+    let half = gated_note_duration(0.5);
+    assert!(full > 0);
+    assert!(half.abs_diff(full / 2) <= 1);
+    assert_eq!(gated_note_duration(100.0), gated_note_duration(MAX_GATE));
+}
+
+#[test]
+fn line_midi_channel_overrides_the_script() {
+    let channel_after = |channel| {
+        let message = SchedulerMessage::SetLineMidiChannel(0, channel, ActionTiming::Immediate);
+        match first_played_note(message) {
+            ConcreteEvent::MidiNote(_, _, channel, _, _, _) => channel,
+            _ => unreachable!(),
+        }
+    };
+    let script_channel = channel_after(None);
+    assert_ne!(script_channel, 5);
+    assert_eq!(channel_after(Some(5)), 5);
+    assert_eq!(channel_after(Some(0)), script_channel);
+}
+
+#[test]
+fn line_transpose_shifts_the_notes() {
+    let note_after = |semitones| {
+        let message = SchedulerMessage::SetLineTranspose(0, semitones, ActionTiming::Immediate);
+        match first_played_note(message) {
+            ConcreteEvent::MidiNote(note, _, _, _, _, _) => note,
+            _ => unreachable!(),
+        }
+    };
+    let script_note = note_after(0);
+    assert_eq!(note_after(12), script_note + 12);
+    assert_eq!(note_after(-12), script_note - 12);
+    assert_eq!(note_after(1000), 127);
+}
+
+#[test]
+fn line_fill_replaces_every_fourth_cycle() {
+    let mut fixture = Fixture::new();
+    let mut line = Line::new(vec![1.0]);
+    line.looping = true;
+    line.frame_mut(0).set_script(fixture.script("60"));
+    fixture.scheduler.change_scene(Scene::new(vec![line]));
+    // Left uncompiled, the scheduler compiles fill frames itself
+    let mut fill_frame = Frame::from(1.0);
+    fill_frame.set_script(Script::new("72".to_string(), "note".to_string()));
+    let fill = LineFill {
+        frames: vec![fill_frame],
+        every: 4,
+    };
+    fixture
+        .scheduler
+        .process_message(SchedulerMessage::SetLineFill(
+            0,
+            Some(fill),
+            ActionTiming::Immediate,
+        ));
+    // Fill frames compile in the background, like the frames of the line
+    let compiled = loop {
+        match fixture
+            .feedback
+            .recv_timeout(Duration::from_secs(5))
+            .unwrap()
+        {
+            update @ SchedulerMessage::FillCompilationUpdate(..) => break update,
+            _ => continue,
+        }
+    };
+    fixture.scheduler.process_message(compiled);
+
+    let start = fixture.clock.micros();
+    fixture.scheduler.scene.line_mut(0).start();
+    let mut played = Vec::new();
+    for beat in 0..8 {
+        let date = start + fixture.clock.beats_to_micros(beat as f64);
+        fixture.scheduler.scene.line_mut(0).step(
+            &fixture.clock,
+            date,
+            &fixture.languages.interpreters,
+        );
+        fixture.scheduler.process_executions(date);
+        played.extend(fixture.played_notes());
+    }
+
+    assert_eq!(played, vec![60, 60, 60, 72, 60, 60, 60, 72]);
+}
+
+#[test]
+fn duration_generators_rescale_each_cycle() {
+    let Fixture {
+        mut scheduler,
+        clock,
+        ..
+    } = Fixture::new();
+    let mut line = Line::new(vec![1.0]);
+    line.looping = true;
+    scheduler.change_scene(Scene::new(vec![line]));
+
+    // Full durations for the first half of every 4 beats, halved for the second half
+    let table = vec![VariableValue::Float(1.0), VariableValue::Float(0.5)];
+    let mut generator = ValueGenerator::of_shape(GeneratorShape::Table(table));
+    generator.modifiers = vec![GeneratorModifier::Loop];
+    generator.span = TimeSpan::Beats(4.0);
+    let message =
+        SchedulerMessage::SetLineDurationGenerator(0, Some(generator), ActionTiming::Immediate);
+    scheduler.process_message(message);
+
+    // Off the grid of the span, as the generator counts from its first cycle
+    let start = (clock.beat() / 4.0).ceil() * 4.0 + 2.5;
+    let mut date = clock.date_at_beat(start);
+    scheduler.scene.line_mut(0).start();
+    let mut cycles = Vec::new();
+    for _ in 0..4 {
+        let line = scheduler.scene.line_mut(0);
+        line.step(&clock, date, &InterpreterDirectory::new());
+        let wait = scheduler.process_duration_generators(date);
+        let line = scheduler.scene.line(0).unwrap();
+        assert_eq!(wait, line.before_next_trigger(&clock, date));
+        cycles.push((line.duration_scale(), clock.micros_to_beats(wait)));
+        date += wait;
+    }
+
+    let scales: Vec<f64> = cycles.iter().map(|(scale, _)| *scale).collect();
+    assert_eq!(scales, vec![1.0, 1.0, 0.5, 0.5]);
+    assert!((cycles[1].1 - 1.0).abs() < 1e-3);
+    assert!((cycles[2].1 - 0.5).abs() < 1e-3);
+
+    // Without a generator, the static durations are back
+    let message = SchedulerMessage::SetLineDurationGenerator(0, None, ActionTiming::Immediate);
+    scheduler.process_message(message);
+    assert_eq!(scheduler.scene.line(0).unwrap().duration_scale(), 1.0);
+}
+
+/// Note-offs sent when the line of a long note is disabled while it sounds.
+fn released_notes(sustain_on_disable: bool) -> Vec<ConcreteEvent> {
+    let mut fixture = Fixture::new();
+    let mut line = Line::new(vec![4.0]);
+    line.sustain_on_disable = sustain_on_disable;
+    line.frame_mut(0).set_script(fixture.script("60 1"));
+    fixture.scheduler.change_scene(Scene::new(vec![line]));
+
+    let date = fixture.clock.micros();
+    let line = fixture.scheduler.scene.line_mut(0);
+    line.start();
+    line.step(&fixture.clock, date, &fixture.languages.interpreters);
+    fixture.scheduler.process_executions(date);
+    let _ = fixture.played_events();
+
+    let mute = SchedulerMessage::DisableFramesBatch(vec![(0, 0)], ActionTiming::Immediate);
+    fixture.scheduler.process_message(mute);
+    fixture.played_events()
+}
+
+#[test]
+fn disabling_a_line_releases_its_notes() {
+    assert!(matches!(
+        released_notes(false).as_slice(),
+        [ConcreteEvent::MidiNoteOff(60, _, _)]
+    ));
+    assert!(released_notes(true).is_empty());
+}
+
+#[test]
+fn enabled_states_follow_the_lines_they_belong_to() {
+    let timing = ActionTiming::Immediate;
+    let follow = |message: SchedulerMessage| {
+        let mut was_enabled = vec![true, false, true];
+        Scheduler::follow_line_changes(&mut was_enabled, &message);
+        was_enabled
+    };
+
+    let added = follow(SchedulerMessage::AddLine(1, Line::default(), timing));
+    assert_eq!(added, vec![true, false, false, true]);
+    let appended = follow(SchedulerMessage::AddLine(4, Line::default(), timing));
+    assert_eq!(appended, vec![true, false, true, false, false]);
+    let removed = follow(SchedulerMessage::RemoveLine(0, timing));
+    assert_eq!(removed, vec![false, true]);
+    let moved = follow(SchedulerMessage::MoveLine(2, 0, timing));
+    assert_eq!(moved, vec![true, true, false]);
+    let out_of_bounds = follow(SchedulerMessage::MoveLine(0, 3, timing));
+    assert_eq!(out_of_bounds, vec![true, false, true]);
+}
+
+#[test]
+fn mpe_notes_are_released_on_their_own_channel() {
+    let mut fixture = Fixture::new();
+    let synth = MidiOut::new("MPE Synth".to_owned()).unwrap();
+    fixture
+        .devices
+        .register_output_connection("MPE Synth".to_owned(), ProtocolDevice::MIDIOutDevice(synth));
+    fixture.devices.assign_slot(1, "MPE Synth").unwrap();
+    let mut lines = vec![Line::new(vec![4.0]), Line::new(vec![4.0])];
+    for (line, note) in lines.iter_mut().zip(["60", "64"]) {
+        let script = fixture.script(&format!("mpe {note} 1"));
+        line.frame_mut(0).set_script(script);
+    }
+    fixture.scheduler.change_scene(Scene::new(lines));
+    let sent = |fixture: &Fixture| -> Vec<MIDIMessage> {
+        fixture
+            .world
+            .try_iter()
+            .filter_map(|msg| match msg.message.payload {
+                ProtocolPayload::MIDI(midi) => Some(midi),
+                _ => None,
+            })
+            .collect()
+    };
+
+    let date = fixture.clock.micros();
+    for line in fixture.scheduler.scene.lines.iter_mut() {
+        line.start();
+    }
+    fixture
+        .scheduler
+        .scene
+        .step(&fixture.clock, date, &fixture.languages.interpreters);
+    fixture.scheduler.process_executions(date);
+    let channel = sent(&fixture)
+        .into_iter()
+        .find_map(|midi| match midi.payload {
+            MIDIMessageType::NoteOn { note: 64, .. } => Some(midi.channel),
+            _ => None,
+        })
+        .expect("the second note was not played");
+    // The first member channel went to the first note
+    assert_eq!(channel, 2);
+
+    let mute = SchedulerMessage::DisableFramesBatch(vec![(1, 0)], ActionTiming::Immediate);
+    fixture.scheduler.process_message(mute);
+    let released = sent(&fixture);
+    assert_eq!(released.len(), 1);
+    assert_eq!(released[0].channel, channel);
+    assert!(matches!(
+        released[0].payload,
+        MIDIMessageType::NoteOff { note: 64, .. }
+    ));
+}
+
+#[test]
+fn moving_a_line_keeps_its_frames_and_playhead() {
+    let Fixture {
+        mut scheduler,
+        notifications,
+        ..
+    } = Fixture::new();
+    let lines = vec![
+        Line::new(vec![1.0]),
+        Line::new(vec![2.0, 2.0]),
+        Line::new(vec![3.0, 3.0, 3.0]),
+    ];
+    scheduler.change_scene(Scene::new(lines));
+    scheduler.scene.line_mut(2).start_at(1);
+    notifications.try_iter().for_each(drop);
+
+    scheduler.process_message(SchedulerMessage::MoveLine(2, 0, ActionTiming::Immediate));
+    assert_eq!(
+        scheduler.scene.structure(),
+        vec![vec![3.0, 3.0, 3.0], vec![1.0], vec![2.0, 2.0]]
+    );
+    assert_eq!(scheduler.scene.line(0).unwrap().position(), vec![(1, 0)]);
+    assert!(
+        notifications
+            .try_iter()
+            .any(|n| matches!(n, SovaNotification::MovedLine(2, 0)))
+    );
+
+    // Out of range moves leave the scene as it is
+    scheduler.process_message(SchedulerMessage::MoveLine(0, 3, ActionTiming::Immediate));
+    assert_eq!(scheduler.scene.line(0).unwrap().n_frames(), 3);
+    assert!(notifications.try_iter().next().is_none());
+}
+
+#[test]
+fn enabling_a_line_can_retrigger_it() {
+    let Fixture { mut scheduler, .. } = Fixture::new();
+    // Two muted lines playing their third frame, only the first one retriggers
+    let mut lines = vec![Line::new(vec![1.0; 3]), Line::new(vec![1.0; 3])];
+    lines[0].retrigger_on_enable = true;
+    lines[0].start_frame = Some(1);
+    for frame in lines.iter_mut().flat_map(|line| line.frames.iter_mut()) {
+        frame.enabled = false;
+    }
+    scheduler.change_scene(Scene::new(lines));
+    for line in scheduler.scene.lines.iter_mut() {
+        line.start_at(2);
+    }
+
+    let unmute = |frames| SchedulerMessage::EnableFramesBatch(frames, ActionTiming::Immediate);
+    scheduler.process_message(unmute(vec![(0, 2), (1, 2)]));
+    assert_eq!(scheduler.scene.line(0).unwrap().position(), vec![(1, 0)]);
+    assert_eq!(scheduler.scene.line(1).unwrap().position(), vec![(2, 0)]);
+
+    // Lines which were already enabled keep playing where they are
+    scheduler.scene.line_mut(0).start_at(2);
+    scheduler.process_message(unmute(vec![(0, 0)]));
+    assert_eq!(scheduler.scene.line(0).unwrap().position(), vec![(2, 0)]);
+}
+
+#[test]
+fn reseeding_replays_the_same_random_notes() {
+    let mut fixture = Fixture::new();
+    let script = fixture.script("rand");
+    let mut line = Line::new(vec![1.0; 4]);
+    for frame in line.frames.iter_mut() {
+        frame.set_script(script.clone());
+    }
+    fixture.scheduler.change_scene(Scene::new(vec![line]));
+
+    let mut play = |seed: u64| -> Vec<u64> {
+        let scheduler = &mut fixture.scheduler;
+        scheduler.process_message(SchedulerMessage::SetRandomSeed(Some(seed)));
+        let start = fixture.clock.micros();
+        scheduler.scene.line_mut(0).start();
+        for frame in 0..4 {
+            let date = start + fixture.clock.beats_to_micros(frame as f64);
+            let line = scheduler.scene.line_mut(0);
+            line.step(&fixture.clock, date, &fixture.languages.interpreters);
+            scheduler.process_executions(date);
+        }
+        scheduler.scene.kill_executions();
+        fixture.played_notes()
+    };
+
+    let notes = play(42);
+    assert_eq!(notes.len(), 4);
+    assert_eq!(play(42), notes);
+}
+
+#[test]
+fn morphing_crossfades_the_scenes() {
+    let Fixture {
+        mut scheduler,
+        clock,
+        ..
+    } = Fixture::new();
+    scheduler.change_scene(Scene::new(vec![Line::new(vec![1.0])]));
+
+    let start = clock.beat();
+    let target = Scene::new(vec![Line::new(vec![1.0]), Line::new(vec![2.0])]);
+    scheduler.process_message(SchedulerMessage::MorphToScene(target, 4.0));
+    assert_eq!(scheduler.scene.n_lines(), 2);
+
+    let gains_at = |scheduler: &Scheduler, beats: f64| {
+        let date = clock.date_at_beat(start + beats);
+        scheduler.morph_gains(date).unwrap()
+    };
+    let mut previous = gains_at(&scheduler, 0.0);
+    assert!(previous.0 > 0.99 && previous.1 < 0.01);
+    for beats in [1.0, 2.0, 3.0] {
+        let gains = gains_at(&scheduler, beats);
+        assert!(gains.0 < previous.0 && gains.1 > previous.1);
+        assert!((gains.0 + gains.1 - 1.0).abs() < 1e-9);
+        previous = gains;
+    }
+    let (source, target) = gains_at(&scheduler, 2.0);
+    assert!((source - 0.5).abs() < 0.01 && (target - 0.5).abs() < 0.01);
+
+    scheduler.process_morph(clock.date_at_beat(start + 4.5));
+    assert_eq!(scheduler.morph_gains(clock.micros()), None);
+}
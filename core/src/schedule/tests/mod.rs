@@ -0,0 +1,212 @@
+use crate::{
+    clock::{Clock, ClockServer},
+    compiler::{CompilationError, CompilationWarning, Compiler},
+    device_map::DeviceMap,
+    protocol::{ProtocolPayload, TimedMessage},
+    scene::script::Script,
+    schedule::{Scheduler, SchedulerMessage, SovaNotification},
+    vm::{
+        EnvironmentFunc, Instruction, LanguageCenter, Program, Transcoder,
+        control_asm::ControlASM,
+        event::{ConcreteEvent, Event},
+        interpreter::InterpreterDirectory,
+        variable::{Variable, VariableValue},
+    },
+};
+use crossbeam_channel::Receiver;
+use std::{collections::BTreeMap, sync::Arc};
+
+mod frames;
+mod lines;
+mod timing;
+
+/// A scheduler on a clock at 120 BPM, with the other ends of its channels.
+pub struct Fixture {
+    pub scheduler: Scheduler,
+    pub clock: Clock,
+    pub devices: Arc<DeviceMap>,
+    pub languages: Arc<LanguageCenter>,
+    pub world: Receiver<TimedMessage>,
+    pub notifications: Receiver<SovaNotification>,
+    /// Receives what the background compilations send back to the scheduler.
+    pub feedback: Receiver<SchedulerMessage>,
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        Self::with_interpreters(InterpreterDirectory::new())
+    }
+
+    /// The scripts of the test language compile, the others are run by `interpreters`.
+    pub fn with_interpreters(interpreters: InterpreterDirectory) -> Self {
+        let mut transcoder = Transcoder::default();
+        transcoder.add_compiler(NoteCompiler);
+        let languages = Arc::new(LanguageCenter {
+            transcoder,
+            interpreters,
+        });
+        let clock_server = Arc::new(ClockServer::new(120.0, 4.0));
+        let (world_tx, world) = crossbeam_channel::unbounded();
+        let (sched_tx, sched_rx) = crossbeam_channel::unbounded();
+        let (notif_tx, notifications) = crossbeam_channel::unbounded();
+        let feedback = sched_rx.clone();
+        let devices = Arc::new(DeviceMap::new());
+        let scheduler = Scheduler::new(
+            Clock::from(clock_server.clone()),
+            devices.clone(),
+            languages.clone(),
+            world_tx,
+            sched_tx,
+            sched_rx,
+            notif_tx,
+        );
+        Self {
+            scheduler,
+            clock: Clock::from(clock_server),
+            devices,
+            languages,
+            world,
+            notifications,
+            feedback,
+        }
+    }
+
+    /// A compiled script of the test language.
+    pub fn script(&self, content: &str) -> Script {
+        let mut script = Script::new(content.to_string(), "note".to_string());
+        self.languages.blocking_process(&mut script);
+        assert!(script.is_compiled(), "'{content}' does not compile");
+        script
+    }
+
+    /// The events logged so far. Unless a test assigns slot 1, the events sent there reach the
+    /// log along with their event.
+    pub fn played_events(&self) -> Vec<ConcreteEvent> {
+        self.world
+            .try_iter()
+            .filter_map(|msg| match msg.message.payload {
+                ProtocolPayload::LOG(log) => log.event,
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The notes sent so far.
+    pub fn played_notes(&self) -> Vec<u64> {
+        self.played_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                ConcreteEvent::MidiNote(note, ..) => Some(note),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Drops the notifications sent so far.
+    pub fn clear_notifications(&self) {
+        self.notifications.try_iter().for_each(drop);
+    }
+}
+
+/// Test language playing a single MIDI note, written `[mpe] <note> [duration] [device]`.
+/// The note can be `rand`, durations are in frames, like in bob, and notes play on device 1
+/// unless told otherwise.
+#[derive(Debug)]
+struct NoteCompiler;
+
+impl NoteCompiler {
+    fn error(info: String) -> CompilationError {
+        CompilationError {
+            lang: "note".to_string(),
+            info,
+            from: 0,
+            to: 0,
+        }
+    }
+}
+
+impl Compiler for NoteCompiler {
+    fn name(&self) -> &str {
+        "note"
+    }
+
+    fn compile(
+        &self,
+        text: &str,
+        args: &BTreeMap<String, String>,
+    ) -> Result<Program, CompilationError> {
+        self.compile_with_warnings(text, args).map(|(prog, _)| prog)
+    }
+
+    fn compile_with_warnings(
+        &self,
+        text: &str,
+        _args: &BTreeMap<String, String>,
+    ) -> Result<(Program, Vec<CompilationWarning>), CompilationError> {
+        let mut warnings = Vec::new();
+        let mut words = text.split_whitespace().peekable();
+        let mpe = words.next_if_eq(&"mpe").is_some();
+        let note = match words.next() {
+            Some("rand") => Variable::Environment(EnvironmentFunc::RandomUInt(128)),
+            Some(word) => {
+                let note: i64 = word
+                    .parse()
+                    .map_err(|_| Self::error(format!("'{word}' is not a note")))?;
+                if note > 127 {
+                    let info = format!("note {note} out of MIDI range, clamped to 127");
+                    warnings.push(CompilationWarning::new("note", info));
+                }
+                Variable::Constant(VariableValue::Integer(note.clamp(0, 127)))
+            }
+            None => return Err(Self::error("missing note".to_string())),
+        };
+        let dur = match words.next() {
+            Some(word) => word
+                .parse()
+                .map_err(|_| Self::error(format!("'{word}' is not a duration")))?,
+            None => 0.5,
+        };
+        let dev = match words.next() {
+            Some(word) => word
+                .parse()
+                .map_err(|_| Self::error(format!("'{word}' is not a device")))?,
+            None => 1,
+        };
+
+        let dur_frames = Variable::Instance("_note_dur".to_string());
+        let time = Variable::Instance("_note_time".to_string());
+        let constant = |value: i64| Variable::Constant(VariableValue::Integer(value));
+        let event = if mpe {
+            Event::MidiMpeNote(
+                note,
+                constant(90),
+                dur_frames.clone(),
+                constant(dev),
+                None,
+                None,
+                None,
+            )
+        } else {
+            Event::MidiNote(
+                note,
+                constant(90),
+                constant(1),
+                dur_frames.clone(),
+                constant(dev),
+                None,
+            )
+        };
+        let prog = vec![
+            Instruction::Control(ControlASM::FloatAsFrames(
+                Variable::Constant(VariableValue::Float(dur)),
+                dur_frames,
+            )),
+            Instruction::Control(ControlASM::FloatAsFrames(
+                Variable::Constant(VariableValue::Float(0.0)),
+                time.clone(),
+            )),
+            Instruction::Effect(event, time),
+        ];
+        Ok((prog, warnings))
+    }
+}
@@ -0,0 +1,240 @@
+use super::Fixture;
+use crate::{
+    clock::{MIN_TEMPO, NEVER, SyncTime},
+    protocol::ProtocolDevice,
+    scene::{Line, Scene},
+    schedule::{
+        ActionTiming, OscClockConfig, SCHEDULED_DRIFT, Scheduler, SchedulerMessage,
+        SovaNotification,
+    },
+    vm::{event::ConcreteEvent, variable::VariableValue},
+};
+use std::{collections::BTreeMap, sync::Arc};
+
+#[test]
+fn tempo_nudges_accumulate_and_stay_in_bounds() {
+    let Fixture {
+        mut scheduler,
+        mut clock,
+        notifications,
+        ..
+    } = Fixture::new();
+
+    scheduler.process_message(SchedulerMessage::SetTempo(120.0, ActionTiming::Immediate));
+    for _ in 0..4 {
+        scheduler.process_message(SchedulerMessage::NudgeTempo(0.5, ActionTiming::Immediate));
+    }
+    clock.capture_app_state();
+    assert!((clock.tempo() - 122.0).abs() < 1e-6);
+    let last = notifications
+        .try_iter()
+        .filter_map(|notif| match notif {
+            SovaNotification::TempoChanged(tempo) => Some(tempo),
+            _ => None,
+        })
+        .last();
+    assert!(last.is_some_and(|tempo| (tempo - 122.0).abs() < 1e-6));
+
+    scheduler.process_message(SchedulerMessage::NudgeTempo(
+        -1000.0,
+        ActionTiming::Immediate,
+    ));
+    clock.capture_app_state();
+    assert!((clock.tempo() - MIN_TEMPO).abs() < 1e-6);
+}
+
+#[test]
+fn osc_clock_mirrors_the_beat() {
+    let mut fixture = Fixture::new();
+    let clock = &fixture.clock;
+
+    let bar = (clock.beat() / 4.0).ceil() * 4.0 + 4.0;
+    let start = clock.date_at_beat(bar);
+    assert_eq!(fixture.scheduler.process_osc_clock(start), NEVER);
+
+    // Two messages per beat over two beats, polled every eighth of a beat
+    let config = OscClockConfig {
+        rate: 2.0,
+        ..Default::default()
+    };
+    fixture
+        .scheduler
+        .process_message(SchedulerMessage::SetOscClock(Some(config)));
+    for step in 0..=16 {
+        fixture.scheduler.process_osc_clock(start + step * 62_500);
+    }
+
+    let ticks: Vec<Vec<VariableValue>> = fixture
+        .played_events()
+        .into_iter()
+        .filter_map(|event| match event {
+            ConcreteEvent::Osc { message, .. } => {
+                assert_eq!(message.addr, "/sova/clock");
+                Some(message.args)
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(ticks.len(), 5);
+    for (i, args) in ticks.iter().enumerate() {
+        let beat = bar + i as f64 * 0.5;
+        assert_eq!(args[0], VariableValue::Float(120.0));
+        assert!(matches!(args[1], VariableValue::Float(b) if (b - beat).abs() < 1e-6));
+        assert_eq!(args[2], VariableValue::Integer((beat / 4.0).floor() as i64));
+        assert_eq!(args[3], VariableValue::Integer(0));
+    }
+
+    fixture
+        .scheduler
+        .process_message(SchedulerMessage::SetOscClock(None));
+    assert_eq!(
+        fixture.scheduler.process_osc_clock(start + 3_000_000),
+        NEVER
+    );
+    assert!(fixture.played_events().is_empty());
+}
+
+#[test]
+fn midi_metronome_accents_the_downbeat() {
+    let mut fixture = Fixture::new();
+    let clock = &fixture.clock;
+
+    // Nothing clicks until the metronome is turned on
+    let bar = (clock.beat() / 4.0).ceil() * 4.0 + 4.0;
+    let start = clock.date_at_beat(bar);
+    assert_eq!(fixture.scheduler.process_metronome(start), NEVER);
+
+    // From one downbeat to the next, in steps of an eighth of a beat
+    fixture
+        .scheduler
+        .process_message(SchedulerMessage::SetMidiMetronome(1, true));
+    for step in 0..=33 {
+        fixture.scheduler.process_metronome(start + step * 62_500);
+    }
+    assert_eq!(fixture.played_notes(), vec![76, 77, 77, 77, 76]);
+
+    fixture
+        .scheduler
+        .process_message(SchedulerMessage::SetMidiMetronome(1, false));
+    assert_eq!(
+        fixture.scheduler.process_metronome(start + 3_000_000),
+        NEVER
+    );
+    assert!(fixture.played_events().is_empty());
+}
+
+#[test]
+fn quantized_edits_wait_for_the_grid() {
+    let Fixture {
+        mut scheduler,
+        clock,
+        ..
+    } = Fixture::new();
+    scheduler.change_scene(Scene::new(vec![Line::new(vec![1.0])]));
+    scheduler.process_message(SchedulerMessage::SetQuantizationGrid(4.0));
+    let edit = SchedulerMessage::SetLineGate(0, 0.5, ActionTiming::Quantized);
+    scheduler.process_message(edit);
+
+    let bar = (clock.beat() / 4.0).ceil() * 4.0 + 4.0;
+    let at = |beat: f64| clock.date_at_beat(bar + beat);
+    // Crossing a beat inside the bar is not enough with a grid of one bar
+    scheduler.process_deferred(at(1.5), at(2.5));
+    assert_eq!(scheduler.scene.line(0).unwrap().gate, 1.0);
+    scheduler.process_deferred(at(3.5), at(4.5));
+    assert_eq!(scheduler.scene.line(0).unwrap().gate, 0.5);
+}
+
+#[test]
+fn lines_apply_quantized_edits_on_their_own_grid() {
+    let Fixture {
+        mut scheduler,
+        clock,
+        ..
+    } = Fixture::new();
+    let lines = vec![Line::new(vec![1.0]), Line::new(vec![1.0])];
+    scheduler.change_scene(Scene::new(lines));
+    // The pad quantizes to bars and the drums to beats, over a global grid of two bars
+    scheduler.process_message(SchedulerMessage::SetQuantizationGrid(8.0));
+    for (line, grid) in [(0, 4.0), (1, 1.0)] {
+        let message =
+            SchedulerMessage::SetLineQuantization(line, Some(grid), ActionTiming::Immediate);
+        scheduler.process_message(message);
+    }
+    for line in 0..2 {
+        let edit = SchedulerMessage::SetLineGate(line, 0.5, ActionTiming::Quantized);
+        scheduler.process_message(edit);
+    }
+    let gates = |scheduler: &Scheduler| {
+        let line = |i: usize| scheduler.scene.line(i).unwrap().gate;
+        (line(0), line(1))
+    };
+
+    let bar = (clock.beat() / 8.0).ceil() * 8.0 + 8.0;
+    let at = |beat: f64| clock.date_at_beat(bar + beat);
+    scheduler.process_deferred(at(0.5), at(0.9));
+    assert_eq!(gates(&scheduler), (1.0, 1.0));
+    scheduler.process_deferred(at(0.9), at(1.1));
+    assert_eq!(gates(&scheduler), (1.0, 0.5));
+    scheduler.process_deferred(at(3.9), at(4.1));
+    assert_eq!(gates(&scheduler), (0.5, 0.5));
+
+    // Without its own quantization, a line follows the global grid again
+    scheduler.process_message(SchedulerMessage::SetLineQuantization(
+        1,
+        None,
+        ActionTiming::Immediate,
+    ));
+    let edit = SchedulerMessage::SetLineGate(1, 1.0, ActionTiming::Quantized);
+    scheduler.process_message(edit);
+    scheduler.process_deferred(at(4.9), at(5.1));
+    assert_eq!(gates(&scheduler), (0.5, 0.5));
+    scheduler.process_deferred(at(7.9), at(8.1));
+    assert_eq!(gates(&scheduler), (0.5, 1.0));
+}
+
+#[test]
+fn device_latency_shifts_dispatch_within_the_lookahead() {
+    let mut fixture = Fixture::new();
+    let devices = fixture.devices.clone();
+    let outputs = [("Plain", 0.0), ("Late", 0.02), ("Early", -1.0)];
+    let mut lines = Vec::new();
+    for (slot, (name, latency)) in (1..).zip(outputs) {
+        // Log outputs stand in for synths, without opening a port
+        devices.register_output_connection(name.to_owned(), ProtocolDevice::Log);
+        devices.assign_slot(slot, name).unwrap();
+        devices.set_latency(name.to_owned(), latency).unwrap();
+        let mut line = Line::new(vec![1.0]);
+        line.frame_mut(0)
+            .set_script(fixture.script(&format!("60 0.5 {slot}")));
+        lines.push(line);
+    }
+    assert!(devices.set_latency("Missing".to_owned(), 0.02).is_err());
+    fixture.scheduler.change_scene(Scene::new(lines));
+
+    let date = fixture.clock.micros();
+    for line in fixture.scheduler.scene.lines.iter_mut() {
+        line.start();
+    }
+    fixture
+        .scheduler
+        .scene
+        .step(&fixture.clock, date, &fixture.languages.interpreters);
+    fixture.scheduler.process_executions(date);
+    let registered = devices.output_connections.lock().unwrap().clone();
+    let dispatched: BTreeMap<String, SyncTime> = fixture
+        .world
+        .try_iter()
+        .filter_map(|msg| {
+            let (name, _) = registered
+                .iter()
+                .find(|(_, device)| Arc::ptr_eq(device, &msg.message.device))?;
+            Some((name.clone(), msg.time))
+        })
+        .collect();
+
+    assert_eq!(dispatched.len(), 3);
+    let plain = dispatched["Plain"];
+    assert_eq!(dispatched["Late"], plain + 20_000);
+    // Events cannot be sent earlier than the scheduler looks ahead
+    assert_eq!(dispatched["Early"], plain - SCHEDULED_DRIFT);
+}
@@ -1,3 +1,4 @@
 // Defines a few useful operations
 
 pub mod decimal_operations;
+pub mod random;
@@ -0,0 +1,73 @@
+//! The source of randomness of scripts and random playback.
+//!
+//! Random values are drawn from a generator local to each thread. Scripts all run
+//! on the scheduler thread, so seeding it there makes a whole set reproducible.
+
+use std::cell::RefCell;
+
+use rand::{
+    Rng, SeedableRng,
+    distr::{
+        Distribution, StandardUniform,
+        uniform::{SampleRange, SampleUniform},
+    },
+};
+use rand_chacha::ChaCha20Rng;
+
+thread_local! {
+    static SOURCE: RefCell<ChaCha20Rng> = RefCell::new(ChaCha20Rng::from_rng(&mut rand::rng()));
+}
+
+/// Seeds the generator of the current thread.
+/// `None` seeds it from the system entropy, for draws that differ on each run.
+pub fn set_seed(seed: Option<u64>) {
+    let rng = match seed {
+        Some(seed) => ChaCha20Rng::seed_from_u64(seed),
+        None => ChaCha20Rng::from_rng(&mut rand::rng()),
+    };
+    SOURCE.with_borrow_mut(|source| *source = rng);
+}
+
+/// Runs `f` with the generator of the current thread.
+pub fn with_rng<T>(f: impl FnOnce(&mut ChaCha20Rng) -> T) -> T {
+    SOURCE.with_borrow_mut(f)
+}
+
+/// A random value, as [`rand::random`].
+pub fn random<T>() -> T
+where
+    StandardUniform: Distribution<T>,
+{
+    with_rng(|rng| rng.random())
+}
+
+/// A random value in the given range, as [`rand::random_range`].
+pub fn random_range<T, R>(range: R) -> T
+where
+    T: SampleUniform,
+    R: SampleRange<T>,
+{
+    with_rng(|rng| rng.random_range(range))
+}
+
+/// A new generator seeded from the one of the current thread,
+/// for consumers drawing from their own.
+pub fn derive_rng() -> ChaCha20Rng {
+    with_rng(ChaCha20Rng::from_rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeding_replays_the_same_draws() {
+        let draws = || -> Vec<u64> { (0..8).map(|_| random_range(0..1000)).collect() };
+        set_seed(Some(42));
+        let first = draws();
+        let derived = derive_rng().random::<u64>();
+        set_seed(Some(42));
+        assert_eq!(draws(), first);
+        assert_eq!(derive_rng().random::<u64>(), derived);
+    }
+}
@@ -25,6 +25,9 @@ pub use environment_func::*;
 mod transcoder;
 pub use transcoder::*;
 
+mod compile_cache;
+pub use compile_cache::{CompileCache, DEFAULT_COMPILE_CACHE_CAPACITY};
+
 mod language_center;
 pub use language_center::LanguageCenter;
 
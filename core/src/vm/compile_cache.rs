@@ -0,0 +1,275 @@
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use crate::compiler::{CompilationError, CompilationWarning, Compiler};
+
+use super::Program;
+
+/// Number of compiled scripts kept by default.
+pub const DEFAULT_COMPILE_CACHE_CAPACITY: usize = 512;
+
+/// Hash of the language, compiler generation, content and arguments of a script.
+type CacheKey = u64;
+type CachedProgram = (Program, Vec<CompilationWarning>);
+
+#[derive(Debug, Default)]
+struct CacheEntries {
+    /// Programs, along with the language they were compiled from.
+    programs: HashMap<CacheKey, (String, CachedProgram)>,
+    /// Keys from the oldest to the newest, evicted first in, first out.
+    order: VecDeque<CacheKey>,
+    /// Generation of the current compiler of each language, 0 until one is registered.
+    generations: HashMap<String, u64>,
+    /// Last generation handed out.
+    last_generation: u64,
+}
+
+/// Programs of the scripts compiled so far, keyed by a hash of their language and
+/// content, so that identical scripts are only compiled once.
+///
+/// Only successful compilations are kept. The cache holds at most `capacity`
+/// programs, a capacity of zero disables it. Once full, it drops the program
+/// compiled first, however often it was reused since.
+///
+/// Each compiler registered for a language starts a new generation of its programs.
+/// Compilations still running with a replaced compiler are not kept.
+#[derive(Debug)]
+pub struct CompileCache {
+    capacity: AtomicUsize,
+    entries: Mutex<CacheEntries>,
+    hits: AtomicUsize,
+}
+
+impl Default for CompileCache {
+    fn default() -> Self {
+        CompileCache::new(DEFAULT_COMPILE_CACHE_CAPACITY)
+    }
+}
+
+impl CompileCache {
+    pub fn new(capacity: usize) -> Self {
+        CompileCache {
+            capacity: AtomicUsize::new(capacity),
+            entries: Default::default(),
+            hits: AtomicUsize::new(0),
+        }
+    }
+
+    fn key(
+        lang: &str,
+        generation: u64,
+        content: &str,
+        args: &BTreeMap<String, String>,
+    ) -> CacheKey {
+        let mut hasher = DefaultHasher::new();
+        (lang, generation, content, args).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    pub fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+        let mut entries = self.entries.lock().unwrap();
+        while entries.order.len() > capacity {
+            let Some(oldest) = entries.order.pop_front() else {
+                break;
+            };
+            entries.programs.remove(&oldest);
+        }
+    }
+
+    /// Number of compilations answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().programs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Compiles a script with the given compiler, of the given generation, unless the
+    /// same content was already compiled by that compiler.
+    pub fn compile(
+        &self,
+        compiler: &dyn Compiler,
+        generation: u64,
+        content: &str,
+        args: &BTreeMap<String, String>,
+    ) -> Result<CachedProgram, CompilationError> {
+        let key = Self::key(compiler.name(), generation, content, args);
+        if let Some((_, cached)) = self.entries.lock().unwrap().programs.get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(cached.clone());
+        }
+        let compiled = compiler.compile_with_warnings(content, args)?;
+        self.insert(compiler.name(), generation, key, compiled.clone());
+        Ok(compiled)
+    }
+
+    fn insert(&self, lang: &str, generation: u64, key: CacheKey, compiled: CachedProgram) {
+        let capacity = self.capacity();
+        if capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        // The compiler was replaced while compiling
+        if entries.generations.get(lang).copied().unwrap_or(0) != generation {
+            return;
+        }
+        if entries
+            .programs
+            .insert(key, (lang.to_owned(), compiled))
+            .is_some()
+        {
+            return;
+        }
+        entries.order.push_back(key);
+        while entries.order.len() > capacity {
+            let Some(oldest) = entries.order.pop_front() else {
+                break;
+            };
+            entries.programs.remove(&oldest);
+        }
+    }
+
+    /// Forgets the programs of a language, when its compiler changes. Returns the
+    /// generation of the programs of the new compiler.
+    pub fn invalidate(&self, lang: &str) -> u64 {
+        let mut entries = self.entries.lock().unwrap();
+        entries.last_generation += 1;
+        let generation = entries.last_generation;
+        entries.generations.insert(lang.to_owned(), generation);
+        let CacheEntries {
+            programs, order, ..
+        } = &mut *entries;
+        programs.retain(|_, (l, _)| l != lang);
+        order.retain(|key| programs.contains_key(key));
+        generation
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.programs.clear();
+        entries.order.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compiler::CompilationState,
+        vm::{Instruction, Transcoder, control_asm::ControlASM},
+    };
+
+    /// Compiles every script to one `Nop` per character, counting its compilations.
+    #[derive(Debug, Default)]
+    struct CountingCompiler {
+        compilations: AtomicUsize,
+    }
+
+    impl Compiler for CountingCompiler {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn compile(
+            &self,
+            text: &str,
+            _args: &BTreeMap<String, String>,
+        ) -> Result<Program, CompilationError> {
+            self.compilations.fetch_add(1, Ordering::Relaxed);
+            Ok(text
+                .chars()
+                .map(|_| Instruction::Control(ControlASM::Nop))
+                .collect())
+        }
+    }
+
+    fn program(state: CompilationState) -> Program {
+        match state {
+            CompilationState::Compiled(prog) => prog,
+            other => panic!("script did not compile: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn identical_scripts_are_compiled_once() {
+        let mut transcoder = Transcoder::default();
+        transcoder.add_compiler(CountingCompiler::default());
+        let args = BTreeMap::new();
+
+        let first = program(transcoder.compile("abc", "counting", &args));
+        let second = program(transcoder.compile("abc", "counting", &args));
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 3);
+        assert_eq!(transcoder.cache.hits(), 1);
+
+        program(transcoder.compile("abcd", "counting", &args));
+        assert_eq!(transcoder.cache.hits(), 1);
+        assert_eq!(transcoder.cache.len(), 2);
+
+        // Reloading the language drops its programs
+        transcoder.add_compiler(CountingCompiler::default());
+        assert!(transcoder.cache.is_empty());
+    }
+
+    #[test]
+    fn the_cache_keeps_the_newest_programs() {
+        let cache = CompileCache::new(2);
+        let compiler = CountingCompiler::default();
+        let args = BTreeMap::new();
+        for content in ["a", "b", "c"] {
+            cache.compile(&compiler, 0, content, &args).unwrap();
+        }
+        assert_eq!(cache.len(), 2);
+
+        cache.compile(&compiler, 0, "c", &args).unwrap();
+        cache.compile(&compiler, 0, "a", &args).unwrap();
+        assert_eq!(cache.hits(), 1);
+        assert_eq!(compiler.compilations.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn scripts_differing_by_their_arguments_are_compiled_apart() {
+        let cache = CompileCache::default();
+        let compiler = CountingCompiler::default();
+        let mut args = BTreeMap::new();
+        cache.compile(&compiler, 0, "abc", &args).unwrap();
+        args.insert("seed".to_string(), "1".to_string());
+        cache.compile(&compiler, 0, "abc", &args).unwrap();
+        assert_eq!(cache.hits(), 0);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn programs_of_replaced_compilers_are_not_kept() {
+        let cache = CompileCache::default();
+        let compiler = CountingCompiler::default();
+        let args = BTreeMap::new();
+        let old = cache.invalidate("counting");
+
+        // A compilation started before the swap finishes after it
+        let new = cache.invalidate("counting");
+        cache.compile(&compiler, old, "abc", &args).unwrap();
+        assert!(cache.is_empty());
+
+        cache.compile(&compiler, new, "abc", &args).unwrap();
+        cache.compile(&compiler, new, "abc", &args).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.hits(), 1);
+    }
+}
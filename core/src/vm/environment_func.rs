@@ -9,6 +9,8 @@ pub enum EnvironmentFunc {
     FrameLen(Box<Variable>, Box<Variable>),
 }
 
+use crate::util::random;
+
 use super::{
     EvaluationContext,
     variable::{Variable, VariableValue},
@@ -18,16 +20,16 @@ impl EnvironmentFunc {
     pub fn execute(&self, ctx: &mut EvaluationContext) -> VariableValue {
         match self {
             EnvironmentFunc::GetTempo => ctx.clock.session_state.tempo().into(),
-            EnvironmentFunc::RandomUInt(n) => ((rand::random::<u64>() % n) as i64).into(),
-            EnvironmentFunc::RandomInt => rand::random::<i64>().into(),
-            EnvironmentFunc::RandomFloat => rand::random::<f64>().into(),
+            EnvironmentFunc::RandomUInt(n) => ((random::random::<u64>() % n) as i64).into(),
+            EnvironmentFunc::RandomInt => random::random::<i64>().into(),
+            EnvironmentFunc::RandomFloat => random::random::<f64>().into(),
             EnvironmentFunc::RandomDecInBounds(min, max) => {
                 let min = ctx.evaluate(min).as_float(ctx) as f32;
                 let max = ctx.evaluate(max).as_float(ctx) as f32;
                 let mut val : VariableValue = if min >= max {
                     (max as f64).into()
                 } else {
-                    let rand_val: f32 = rand::random_range(min..max);
+                    let rand_val: f32 = random::random_range(min..max);
                     (rand_val as f64).into()
                 };
                 val.cast_as_decimal(ctx);
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::clock::SyncTime;
+use crate::protocol::midi::MpeExpression;
 use crate::protocol::osc::OSCMessage;
 use crate::vm::Program;
 
@@ -13,10 +14,18 @@ use super::{EvaluationContext, variable::Variable};
 pub enum ConcreteEvent {
     Nop,
     Print(String),
-    MidiNote(u64, u64, u64, SyncTime, usize),
+    /// MidiNote(note, velocity, channel, duration, device_id, release_velocity)
+    MidiNote(u64, u64, u64, SyncTime, usize, Option<u64>),
+    /// MidiNoteOff(note, channel, device_id), releases a note before its end
+    MidiNoteOff(u64, u64, usize),
+    /// MidiMpeNote(note, velocity, channel, duration, expression, device_id), the channel
+    /// being a member channel of the MPE zone of the device, picked when the note is mapped
+    MidiMpeNote(u64, u64, u64, SyncTime, MpeExpression, usize),
     // TODO: MIDI Pitchbend
     MidiControl(u64, u64, u64, usize),
-    MidiProgram(u64, u64, usize),
+    /// MidiProgram(program, channel, device_id, bank), with a bank select before the
+    /// program change when a bank is given
+    MidiProgram(u64, u64, usize, Option<u64>),
     MidiAftertouch(u64, u64, u64, usize),
     MidiChannelPressure(u64, u64, usize),
     MidiSystemExclusive(Vec<u64>, usize),
@@ -40,9 +49,11 @@ pub enum ConcreteEvent {
 impl ConcreteEvent {
     pub fn device_id(&self) -> Option<usize> {
         match self {
-            ConcreteEvent::MidiNote(_, _, _, _, device_id)
+            ConcreteEvent::MidiNote(_, _, _, _, device_id, _)
+            | ConcreteEvent::MidiNoteOff(_, _, device_id)
+            | ConcreteEvent::MidiMpeNote(_, _, _, _, _, device_id)
             | ConcreteEvent::MidiControl(_, _, _, device_id)
-            | ConcreteEvent::MidiProgram(_, _, device_id)
+            | ConcreteEvent::MidiProgram(_, _, device_id, _)
             | ConcreteEvent::MidiAftertouch(_, _, _, device_id)
             | ConcreteEvent::MidiChannelPressure(_, _, device_id)
             | ConcreteEvent::MidiSystemExclusive(_, device_id)
@@ -61,6 +72,30 @@ impl ConcreteEvent {
             ConcreteEvent::Nop | ConcreteEvent::StartProgram(_) => None,
         }
     }
+
+    /// The note of MIDI events that play or touch one.
+    pub fn midi_note_mut(&mut self) -> Option<&mut u64> {
+        match self {
+            ConcreteEvent::MidiNote(note, _, _, _, _, _)
+            | ConcreteEvent::MidiNoteOff(note, _, _)
+            | ConcreteEvent::MidiMpeNote(note, _, _, _, _, _)
+            | ConcreteEvent::MidiAftertouch(note, _, _, _) => Some(note),
+            _ => None,
+        }
+    }
+
+    /// The channel of MIDI events that are sent on one, from 1 to 16.
+    pub fn midi_channel_mut(&mut self) -> Option<&mut u64> {
+        match self {
+            ConcreteEvent::MidiNote(_, _, channel, _, _, _)
+            | ConcreteEvent::MidiNoteOff(_, channel, _)
+            | ConcreteEvent::MidiControl(_, _, channel, _)
+            | ConcreteEvent::MidiProgram(_, channel, _, _)
+            | ConcreteEvent::MidiAftertouch(_, _, channel, _)
+            | ConcreteEvent::MidiChannelPressure(_, channel, _) => Some(channel),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -68,11 +103,29 @@ impl ConcreteEvent {
 pub enum Event {
     Nop,
     Print(Variable),
-    /// MidiNote(note, velocity, channel, duration, device_id)
-    MidiNote(Variable, Variable, Variable, Variable, Variable),
+    /// MidiNote(note, velocity, channel, duration, device_id, release_velocity)
+    MidiNote(
+        Variable,
+        Variable,
+        Variable,
+        Variable,
+        Variable,
+        Option<Variable>,
+    ),
+    /// MidiMpeNote(note, velocity, duration, device_id, bend, pressure, timbre)
+    MidiMpeNote(
+        Variable,
+        Variable,
+        Variable,
+        Variable,
+        Option<Variable>,
+        Option<Variable>,
+        Option<Variable>,
+    ),
     // TODO: MIDI Pitchbend
     MidiControl(Variable, Variable, Variable, Variable),
-    MidiProgram(Variable, Variable, Variable),
+    /// MidiProgram(program, channel, device_id, bank)
+    MidiProgram(Variable, Variable, Variable, Option<Variable>),
     MidiAftertouch(Variable, Variable, Variable, Variable),
     MidiChannelPressure(Variable, Variable, Variable),
     MidiSystemExclusive(Vec<Variable>, Variable),
@@ -104,7 +157,7 @@ impl Event {
         match &self {
             Event::Nop => ConcreteEvent::Nop,
             Event::Print(var) => ConcreteEvent::Print(ctx.evaluate(var).as_str(ctx)),
-            Event::MidiNote(note, vel, chan, time, dev) => {
+            Event::MidiNote(note, vel, chan, time, dev, release) => {
                 let note = ctx.evaluate(note).as_integer(ctx) as u64;
                 let time = ctx
                     .evaluate(time)
@@ -113,7 +166,30 @@ impl Event {
                 let chan = ctx.evaluate(chan).as_integer(ctx) as u64;
                 let vel = ctx.evaluate(vel).as_integer(ctx) as u64;
                 let dev_id = ctx.evaluate(dev).as_integer(ctx) as usize;
-                ConcreteEvent::MidiNote(note, vel, chan, time, dev_id)
+                let release = release
+                    .as_ref()
+                    .map(|release| ctx.evaluate(release).as_integer(ctx) as u64);
+                ConcreteEvent::MidiNote(note, vel, chan, time, dev_id, release)
+            }
+            Event::MidiMpeNote(note, vel, time, dev, bend, pressure, timbre) => {
+                let note = ctx.evaluate(note).as_integer(ctx) as u64;
+                let vel = ctx.evaluate(vel).as_integer(ctx) as u64;
+                let time = ctx
+                    .evaluate(time)
+                    .as_dur(ctx)
+                    .as_micros(ctx.clock, ctx.frame_len);
+                let dev_id = ctx.evaluate(dev).as_integer(ctx) as usize;
+                let expression = MpeExpression {
+                    bend: bend.as_ref().map(|bend| ctx.evaluate(bend).as_float(ctx)),
+                    pressure: pressure
+                        .as_ref()
+                        .map(|pressure| ctx.evaluate(pressure).as_integer(ctx).max(0) as u64),
+                    timbre: timbre
+                        .as_ref()
+                        .map(|timbre| ctx.evaluate(timbre).as_integer(ctx).max(0) as u64),
+                };
+                // The channel is picked by the device map, within the MPE zone of the device
+                ConcreteEvent::MidiMpeNote(note, vel, 0, time, expression, dev_id)
             }
             Event::MidiControl(control, value, channel, dev) => {
                 let control = ctx.evaluate(control).as_integer(ctx) as u64;
@@ -122,11 +198,14 @@ impl Event {
                 let dev_id = ctx.evaluate(dev).as_integer(ctx) as usize;
                 ConcreteEvent::MidiControl(control, value, channel, dev_id)
             }
-            Event::MidiProgram(program, channel, dev) => {
+            Event::MidiProgram(program, channel, dev, bank) => {
                 let program = ctx.evaluate(program).as_integer(ctx) as u64;
                 let channel = ctx.evaluate(channel).as_integer(ctx) as u64;
                 let dev_id = ctx.evaluate(dev).as_integer(ctx) as usize;
-                ConcreteEvent::MidiProgram(program, channel, dev_id)
+                let bank = bank
+                    .as_ref()
+                    .map(|bank| ctx.evaluate(bank).as_integer(ctx) as u64);
+                ConcreteEvent::MidiProgram(program, channel, dev_id, bank)
             }
             Event::MidiAftertouch(note, pressure, channel, dev) => {
                 let note = ctx.evaluate(note).as_integer(ctx) as u64;
@@ -1,3 +1,4 @@
+use rand_chacha::ChaCha20Rng;
 use serde::{Deserialize, Serialize};
 
 mod shape;
@@ -11,15 +12,50 @@ pub use state::*;
 
 use crate::{
     clock::{SyncTime, TimeSpan},
+    util::random,
     vm::{EvaluationContext, variable::VariableValue},
 };
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValueGenerator {
     pub shape: GeneratorShape,
     pub modifiers: Vec<GeneratorModifier>,
     pub span: TimeSpan,
     pub state_id: usize,
+    /// Date the generator started at, its phase counting from there.
+    #[serde(default)]
+    pub start_date: SyncTime,
+    /// Source of the random shapes and modifiers, drawn once from the thread source.
+    /// Boxed to keep values holding a generator small.
+    #[serde(skip, default = "derive_rng")]
+    rng: Box<ChaCha20Rng>,
+}
+
+fn derive_rng() -> Box<ChaCha20Rng> {
+    Box::new(random::derive_rng())
+}
+
+impl Default for ValueGenerator {
+    fn default() -> Self {
+        ValueGenerator {
+            shape: GeneratorShape::default(),
+            modifiers: Vec::new(),
+            span: TimeSpan::default(),
+            state_id: 0,
+            start_date: 0,
+            rng: derive_rng(),
+        }
+    }
+}
+
+/// Generators are equal when they generate the same values, wherever they started.
+impl PartialEq for ValueGenerator {
+    fn eq(&self, other: &Self) -> bool {
+        self.shape == other.shape
+            && self.modifiers == other.modifiers
+            && self.span == other.span
+            && self.state_id == other.state_id
+    }
 }
 
 impl ValueGenerator {
@@ -30,8 +66,8 @@ impl ValueGenerator {
         }
     }
 
-    pub fn start(&mut self, _ctx: &EvaluationContext, _date: SyncTime) {
-        //
+    pub fn start(&mut self, _ctx: &EvaluationContext, date: SyncTime) {
+        self.start_date = date;
     }
 
     pub fn seed(&mut self, _ctx: &EvaluationContext, _seed: VariableValue) {
@@ -43,25 +79,26 @@ impl ValueGenerator {
         self.get(ctx, ctx.logic_date)
     }
 
-    pub fn get(&self, ctx: &EvaluationContext, _date: SyncTime) -> VariableValue {
+    /// Value of the generator at `date`, its phase being the beats elapsed since
+    /// its start divided by its span.
+    pub fn get(&self, ctx: &EvaluationContext, date: SyncTime) -> VariableValue {
         let span = self.span.as_beats(ctx.clock, ctx.frame_len);
         if span == 0.0 {
             return VariableValue::default();
         }
-        todo!()
-        // if self.rng.is_none() {
-        //     self.rng = Some(ChaCha20Rng::from_rng(&mut rand::rng()));
-        // }
-        // let rng = self.rng.as_mut().unwrap();
-        // let phase = date.saturating_sub(self.start_date);
-        // let mut phase = ctx.clock.micros_to_beats(phase) / span;
-        // for (modif, m_state) in self.modifiers.iter_mut().rev() {
-        //     phase = modif.get_phase(ctx, m_state, rng, phase, span);
-        // }
-        // if phase < 0.0 || phase > 1.0 {
-        //     return Default::default();
-        // }
-        // self.shape.get_value(ctx, &mut self.shape_state, rng, phase)
+        let elapsed = date.saturating_sub(self.start_date);
+        let mut phase = ctx.clock.micros_to_beats(elapsed) / span;
+        let mut state = VariableValue::default();
+        // Random draws only depend on the date, so that evaluating twice agrees
+        let mut rng = ChaCha20Rng::clone(&self.rng);
+        rng.set_stream(date);
+        for modif in self.modifiers.iter().rev() {
+            phase = modif.get_phase(ctx, &mut state, &mut rng, phase, span);
+        }
+        if !(0.0..=1.0).contains(&phase) {
+            return VariableValue::default();
+        }
+        self.shape.get_value(ctx, &mut state, &mut rng, phase)
     }
 
     pub fn save_state(&self) -> VariableValue {
@@ -103,7 +103,7 @@ impl Interpreter for ASMInterpreter {
 
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct ASMInterpreterFactory;
 
 /// Does not behave the same as the factory trait, as it needs to pass forward the language name to the transcoder
@@ -2,7 +2,7 @@ use std::{collections::HashMap, fmt, sync::Arc};
 
 use crate::{vm::interpreter::{Interpreter, InterpreterFactory, asm_interpreter::ASMInterpreterFactory}, log_error, scene::script::Script};
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct InterpreterDirectory {
     pub factories: HashMap<String, Arc<dyn InterpreterFactory>>,
     asm_factory: ASMInterpreterFactory,
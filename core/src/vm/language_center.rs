@@ -4,7 +4,12 @@ use crossbeam_channel::Sender;
 
 use crate::{Scene, compiler::CompilationState, vm::{Transcoder, interpreter::InterpreterDirectory}, scene::{Line, script::Script}, schedule::SchedulerMessage};
 
-#[derive(Debug, Default)]
+/// Compilers and interpreters of the languages scripts are written in.
+///
+/// A center is shared immutably by the server and the scheduler. Languages are registered
+/// at runtime on a clone, which then replaces the shared one, the clone sharing the
+/// compilation cache of the original.
+#[derive(Debug, Default, Clone)]
 pub struct LanguageCenter {
     pub transcoder: Transcoder,
     pub interpreters: InterpreterDirectory,
@@ -16,6 +21,13 @@ impl LanguageCenter {
         self.transcoder.available_compilers().chain(self.interpreters.available_interpreters())
     }
 
+    /// Removes the compiler or interpreter of a language, returns whether there was one.
+    pub fn remove_language(&mut self, lang: &str) -> bool {
+        let compiler = self.transcoder.remove_compiler(lang);
+        let factory = self.interpreters.remove_factory(lang);
+        compiler.is_some() || factory.is_some()
+    }
+
     pub fn blocking_process(
         &self, 
         script: &mut Script, 
@@ -26,8 +38,9 @@ impl LanguageCenter {
         let lang = script.lang();
         let state = if let Some(compiler) = self.transcoder.get_compiler(lang) {
             let script = script.clone();
-            match compiler.compile(script.content(), &script.args) {
-                Ok(prog) => 
+            let generation = self.transcoder.compiler_generation(lang);
+            match self.transcoder.cache.compile(compiler.as_ref(), generation, script.content(), &script.args) {
+                Ok((prog, _)) => 
                     CompilationState::Compiled(prog),
                 Err(err) => 
                     CompilationState::Error(err),
@@ -58,14 +71,17 @@ impl LanguageCenter {
         );
         if let Some(compiler) = self.transcoder.get_compiler(lang) {
             let script = script.clone();
+            let cache = self.transcoder.cache.clone();
+            let generation = self.transcoder.compiler_generation(lang);
             thread::spawn(move || {
-                let state = match compiler.compile(script.content(), &script.args) {
-                    Ok(prog) => 
-                        CompilationState::Compiled(prog),
+                let (state, warnings) = match cache.compile(compiler.as_ref(), generation, script.content(), &script.args) {
+                    Ok((prog, warnings)) => 
+                        (CompilationState::Compiled(prog), warnings),
                     Err(err) => 
-                        CompilationState::Error(err),
+                        (CompilationState::Error(err), Vec::new()),
                 };
                 let _ = notifier.send(SchedulerMessage::CompilationUpdate(line_id, frame_id, id, state));
+                let _ = notifier.send(SchedulerMessage::CompilationWarnings(line_id, frame_id, id, warnings));
             });
         } else if let Some(factory) = self.interpreters.get_factory(lang) {
             let script = script.clone();
@@ -80,10 +96,59 @@ impl LanguageCenter {
         }
     }
 
+    /// Compiles a script of the fill of a line in the background, the result being sent
+    /// back as a `FillCompilationUpdate`.
+    pub fn process_fill_script(
+        &self,
+        line_id: usize,
+        frame_id: usize,
+        script: &Script,
+        notifier: Sender<SchedulerMessage>,
+    ) {
+        if script.is_empty() {
+            return;
+        }
+        let id = script.id();
+        let lang = script.lang();
+        let script = script.clone();
+        if let Some(compiler) = self.transcoder.get_compiler(lang) {
+            let cache = self.transcoder.cache.clone();
+            let generation = self.transcoder.compiler_generation(lang);
+            thread::spawn(move || {
+                let state = match cache.compile(compiler.as_ref(), generation, script.content(), &script.args) {
+                    Ok((prog, _)) => CompilationState::Compiled(prog),
+                    Err(err) => CompilationState::Error(err),
+                };
+                let _ = notifier.send(SchedulerMessage::FillCompilationUpdate(
+                    line_id, frame_id, id, state,
+                ));
+            });
+        } else if let Some(factory) = self.interpreters.get_factory(lang) {
+            thread::spawn(move || {
+                let state = factory.check(&script);
+                let _ = notifier.send(SchedulerMessage::FillCompilationUpdate(
+                    line_id, frame_id, id, state,
+                ));
+            });
+        }
+    }
+
+    /// Compiles the frames of a line and of its fill.
     pub fn process_line(&self, line_id: usize, line : &Line, notifier: Sender<SchedulerMessage>) {
         for (frame_id, frame) in line.frames.iter().enumerate() {
             self.process_script(line_id, frame_id, frame.script(), notifier.clone());
         }
+        self.process_fill(line_id, line, notifier);
+    }
+
+    /// Compiles the frames of the fill of a line, if it has one.
+    pub fn process_fill(&self, line_id: usize, line: &Line, notifier: Sender<SchedulerMessage>) {
+        let Some(fill) = line.fill.as_ref() else {
+            return;
+        };
+        for (frame_id, frame) in fill.frames.iter().enumerate() {
+            self.process_fill_script(line_id, frame_id, frame.script(), notifier.clone());
+        }
     }
 
     pub fn process_scene(&self, scene : &Scene, notifier: Sender<SchedulerMessage>) {
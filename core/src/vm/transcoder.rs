@@ -3,14 +3,19 @@
 use crate::compiler::{CompilationState, Compiler, CompilerCollection};
 use crate::log_eprintln;
 use crate::scene::script::Script;
+use crate::vm::CompileCache;
 use std::collections::BTreeMap;
 use std::sync::Arc;
 
 /// The transcoder is a repository of compilers. It allows to add, remove and
 /// compile programs in different languages.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Transcoder {
     pub compilers: CompilerCollection,
+    /// Programs already compiled, shared with the compilation threads.
+    pub cache: Arc<CompileCache>,
+    /// Generation of the programs of each compiler added to the transcoder.
+    generations: BTreeMap<String, u64>,
 }
 
 impl Transcoder {
@@ -26,7 +31,11 @@ impl Transcoder {
     ///
     /// A new transcoder with the set of compilers.
     pub fn new(compilers: CompilerCollection) -> Self {
-        Self { compilers }
+        Self {
+            compilers,
+            cache: Default::default(),
+            generations: BTreeMap::new(),
+        }
     }
 
     /// Add a compiler to the transcoder.
@@ -40,6 +49,8 @@ impl Transcoder {
     /// The transcoder with the new compiler added.
     pub fn add_compiler(&mut self, compiler: impl Compiler + 'static) {
         let name: String = compiler.name().into();
+        let generation = self.cache.invalidate(&name);
+        self.generations.insert(name.clone(), generation);
         self.compilers.insert(name.clone(), Arc::new(compiler));
     }
 
@@ -53,9 +64,16 @@ impl Transcoder {
     ///
     /// The removed compiler, or None if the compiler was not found.
     pub fn remove_compiler(&mut self, lang: &str) -> Option<Arc<dyn Compiler>> {
+        self.cache.invalidate(lang);
+        self.generations.remove(lang);
         self.compilers.remove(lang)
     }
 
+    /// Generation of the programs of the compiler of a language, to compile through the cache.
+    pub fn compiler_generation(&self, lang: &str) -> u64 {
+        self.generations.get(lang).copied().unwrap_or(0)
+    }
+
     pub fn get_compiler(&self, lang: &str) -> Option<Arc<dyn Compiler>> {
         self.compilers.get(lang).map(Arc::clone)
     }
@@ -79,8 +97,12 @@ impl Transcoder {
         let Some(compiler) = self.compilers.get(lang) else {
             return CompilationState::NotCompiled;
         };
-        match compiler.compile(content, args) {
-            Ok(prog) => CompilationState::Compiled(prog),
+        let generation = self.compiler_generation(lang);
+        match self
+            .cache
+            .compile(compiler.as_ref(), generation, content, args)
+        {
+            Ok((prog, _)) => CompilationState::Compiled(prog),
             Err(err) => CompilationState::Error(err),
         }
     }
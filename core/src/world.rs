@@ -7,6 +7,7 @@ use crate::{
     clock::{Clock, ClockServer, SyncTime},
     log_println,
     protocol::{ProtocolPayload, TimedMessage},
+    schedule::SchedulerMessage,
 };
 use crate::{get_logger, log_eprintln};
 
@@ -24,10 +25,15 @@ pub struct World {
     midi_early_threshold: SyncTime,
     /// Lookahead for non-MIDI messages (OSC, AudioEngine) - send early for internal scheduling
     non_midi_lookahead: SyncTime,
+    /// Scheduler, told about failed sends so that the world never locks the devices
+    scheduler: Sender<SchedulerMessage>,
 }
 
 impl World {
-    pub fn create(clock_server: Arc<ClockServer>) -> (JoinHandle<()>, Sender<TimedMessage>) {
+    pub fn create(
+        clock_server: Arc<ClockServer>,
+        scheduler: Sender<SchedulerMessage>,
+    ) -> (JoinHandle<()>, Sender<TimedMessage>) {
         let (tx, rx) = crossbeam_channel::unbounded();
         let handle = ThreadBuilder::default()
             .name("sova-world")
@@ -44,6 +50,7 @@ impl World {
                     clock: clock_server.into(),
                     midi_early_threshold: MIDI_EARLY_THRESHOLD, // 2ms for MIDI interface compensation
                     non_midi_lookahead: NON_MIDI_LOOKAHEAD, // 20ms lookahead for OSC/AudioEngine
+                    scheduler,
                 };
                 world.live();
             })
@@ -119,7 +126,12 @@ impl World {
             }
             _ => {
                 // Other protocols: Send with precise target timestamp
-                let _ = message.send();
+                let device = Arc::clone(&message.device);
+                if let Err(e) = message.send() {
+                    let _ = self
+                        .scheduler
+                        .send(SchedulerMessage::DeviceSendFailed(device, e));
+                }
             }
         }
     }
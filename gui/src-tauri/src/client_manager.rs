@@ -1,6 +1,9 @@
 use anyhow::Result;
 use serde::Serialize;
-use sova_server::{ClientMessage, SovaClient, ServerMessage};
+use sova_server::{
+    ClientMessage, DEGRADED_ROUND_TRIP, PING_PROTOCOL_VERSION, PROTOCOL_VERSION, ServerMessage,
+    SovaClient,
+};
 use tauri::{AppHandle, Emitter};
 use tokio::sync::mpsc;
 
@@ -53,6 +56,13 @@ impl ClientManager {
             let mut consecutive_emit_failures = 0;
             let mut last_message = std::time::Instant::now();
             const MESSAGE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+            // Lets the server know we are still here, even when the user does nothing
+            const HEARTBEAT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+            let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+            // Measures the round trip time, once the server is known to answer pings
+            const PING_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+            let mut ping = tokio::time::interval(PING_INTERVAL);
+            let mut server_answers_pings = false;
             loop {
                 tokio::select! {
                     Some(message) = message_receiver.recv() => {
@@ -64,6 +74,24 @@ impl ClientManager {
                             return;
                         }
                     }
+                    _ = heartbeat.tick() => {
+                        if let Err(e) = client.send(ClientMessage::GetClock).await {
+                            sova_core::log_error!("Failed to send heartbeat: {}", e);
+                            let _ = app_handle.emit("client-disconnected", ClientDisconnectEvent {
+                                reason: "send_error".to_string(),
+                            });
+                            return;
+                        }
+                    }
+                    _ = ping.tick(), if server_answers_pings => {
+                        if let Err(e) = client.send(ClientMessage::ping()).await {
+                            sova_core::log_error!("Failed to send ping: {}", e);
+                            let _ = app_handle.emit("client-disconnected", ClientDisconnectEvent {
+                                reason: "send_error".to_string(),
+                            });
+                            return;
+                        }
+                    }
                     Some(_) = disconnect_receiver.recv() => {
                         sova_core::log_info!("Disconnect signal received, closing connection");
                         if let Err(e) = client.disconnect().await {
@@ -114,6 +142,10 @@ impl ClientManager {
                             Ok(message) => {
                                 consecutive_failures = 0;
                                 last_message = std::time::Instant::now();
+                                if let ServerMessage::Hello { protocol_version, codec, .. } = &message {
+                                    server_answers_pings = *protocol_version >= PING_PROTOCOL_VERSION;
+                                    client.codec = *codec;
+                                }
 
                                 if let Err(e) = Self::handle_server_message(&app_handle, message) {
                                     sova_core::log_error!("Failed to handle server message: {}", e);
@@ -182,9 +214,18 @@ impl ClientManager {
 
     fn handle_server_message(app_handle: &AppHandle, message: ServerMessage) -> Result<()> {
         use ServerMessage::*;
+        // Measured on arrival, before the message is taken apart
+        let round_trip = message.round_trip_time();
 
         match message {
-            Hello { username, scene, devices, peers, link_state, is_playing, available_languages, audio_engine_state } => {
+            Hello { username, scene, devices, peers, link_state, is_playing, available_languages, audio_engine_state, audio_available, scene_lock, quantization_grid, protocol_version, codec } => {
+                if protocol_version < PROTOCOL_VERSION {
+                    sova_core::log_warn!(
+                        "Server speaks protocol version {}, older than ours ({}). Some features may not work.",
+                        protocol_version,
+                        PROTOCOL_VERSION
+                    );
+                }
                 app_handle.emit("server:hello", serde_json::json!({
                     "username": username,
                     "scene": scene,
@@ -200,6 +241,11 @@ impl ClientManager {
                     "isPlaying": is_playing,
                     "availableLanguages": available_languages,
                     "audioEngineState": audio_engine_state,
+                    "audioAvailable": audio_available,
+                    "sceneLock": scene_lock,
+                    "quantizationGrid": quantization_grid,
+                    "protocolVersion": protocol_version,
+                    "codec": codec,
                 }))?;
             }
 
@@ -209,7 +255,17 @@ impl ClientManager {
 
             PeerStartedEditing(user, line_id, frame_id) => {
                 app_handle.emit("server:peer-started-editing", serde_json::json!({
-                    "user": user,
+                    "user": user.name,
+                    "color": user.color,
+                    "lineId": line_id,
+                    "frameId": frame_id,
+                }))?;
+            }
+
+            FollowedPeerFocus(user, line_id, frame_id) => {
+                app_handle.emit("server:followed-peer-focus", serde_json::json!({
+                    "user": user.name,
+                    "color": user.color,
                     "lineId": line_id,
                     "frameId": frame_id,
                 }))?;
@@ -217,7 +273,8 @@ impl ClientManager {
 
             PeerStoppedEditing(user, line_id, frame_id) => {
                 app_handle.emit("server:peer-stopped-editing", serde_json::json!({
-                    "user": user,
+                    "user": user.name,
+                    "color": user.color,
                     "lineId": line_id,
                     "frameId": frame_id,
                 }))?;
@@ -250,6 +307,94 @@ impl ClientManager {
                 app_handle.emit("server:connection-refused", reason)?;
             }
 
+            Rejected(reason) => {
+                app_handle.emit("server:rejected", reason)?;
+            }
+
+            AuditionFailed(error) => {
+                app_handle.emit("server:audition-failed", error)?;
+            }
+
+            SceneTextInvalid(error) => {
+                app_handle.emit("server:scene-text-invalid", error)?;
+            }
+
+            SceneValidation(report) => {
+                let report: Vec<_> = report
+                    .into_iter()
+                    .map(|(line_id, frame_id, state)| {
+                        serde_json::json!({
+                            "lineId": line_id,
+                            "frameId": frame_id,
+                            "state": state,
+                        })
+                    })
+                    .collect();
+                app_handle.emit("server:scene-validation", report)?;
+            }
+
+            Pong(_) => {
+                let round_trip = round_trip.unwrap_or_default();
+                let degraded = round_trip > DEGRADED_ROUND_TRIP;
+                if degraded {
+                    sova_core::log_warn!("Connection degraded, round trip of {:?}", round_trip);
+                }
+                app_handle.emit("server:latency", serde_json::json!({
+                    "roundTripMs": round_trip.as_secs_f64() * 1000.0,
+                    "degraded": degraded,
+                }))?;
+            }
+
+            SearchResults(results) => {
+                let results: Vec<_> = results
+                    .into_iter()
+                    .map(|(line_id, frame_id, start, end)| {
+                        serde_json::json!({
+                            "lineId": line_id,
+                            "frameId": frame_id,
+                            "start": start,
+                            "end": end,
+                        })
+                    })
+                    .collect();
+                app_handle.emit("server:search-results", results)?;
+            }
+
+            SceneStats(stats) => {
+                app_handle.emit("server:scene-stats", serde_json::json!({
+                    "lines": stats.lines,
+                    "enabledLines": stats.enabled_lines,
+                    "frames": stats.frames,
+                    "enabledFrames": stats.enabled_frames,
+                    "scriptsPerLanguage": stats.scripts_per_language,
+                    "duration": stats.duration,
+                }))?;
+            }
+
+            ScriptsReplaced(changed, failed) => {
+                let failed: Vec<_> = failed
+                    .into_iter()
+                    .map(|(line_id, frame_id, error)| {
+                        serde_json::json!({
+                            "lineId": line_id,
+                            "frameId": frame_id,
+                            "error": error,
+                        })
+                    })
+                    .collect();
+                app_handle.emit(
+                    "server:scripts-replaced",
+                    serde_json::json!({
+                        "changed": changed,
+                        "failed": failed,
+                    }),
+                )?;
+            }
+
+            SceneLockChanged(holder) => {
+                app_handle.emit("server:scene-lock-changed", holder)?;
+            }
+
             Snapshot(snapshot) => {
                 app_handle.emit("server:snapshot", snapshot)?;
             }
@@ -258,6 +403,17 @@ impl ClientManager {
                 app_handle.emit("server:device-list", devices)?;
             }
 
+            Instruments(instruments) => {
+                app_handle.emit("server:instruments", instruments)?;
+            }
+
+            Languages(languages, syntaxes) => {
+                app_handle.emit("server:languages", serde_json::json!({
+                    "languages": languages,
+                    "syntaxes": syntaxes,
+                }))?;
+            }
+
             ClockState(tempo, beat, micros, quantum) => {
                 app_handle.emit("server:clock-state", serde_json::json!({
                     "tempo": tempo,
@@ -267,10 +423,18 @@ impl ClientManager {
                 }))?;
             }
 
+            QuantizationGrid(grid) => {
+                app_handle.emit("server:quantization-grid", grid)?;
+            }
+
             SceneValue(scene) => {
                 app_handle.emit("server:scene", scene)?;
             }
 
+            SceneMetadata(metadata) => {
+                app_handle.emit("server:scene-metadata", metadata)?;
+            }
+
             SceneMode(mode) => {
                 app_handle.emit("server:global-mode", mode)?;
             }
@@ -294,6 +458,13 @@ impl ClientManager {
                 app_handle.emit("server:remove-line", idx)?;
             }
 
+            MoveLine(from, to) => {
+                app_handle.emit("server:move-line", serde_json::json!({
+                    "from": from,
+                    "to": to,
+                }))?;
+            }
+
             FrameValues(frames) => {
                 app_handle.emit("server:frame-values", frames)?;
             }
@@ -331,6 +502,15 @@ impl ClientManager {
                 }))?;
             }
 
+            CompilationWarnings(line_id, frame_id, script_id, warnings) => {
+                app_handle.emit("server:compilation-warnings", serde_json::json!({
+                    "lineId": line_id,
+                    "frameId": frame_id,
+                    "scriptId": script_id.to_string(),
+                    "warnings": warnings,
+                }))?;
+            }
+
             DevicesRestored { missing_devices } => {
                 app_handle.emit("server:devices-restored", serde_json::json!({
                     "missingDevices": missing_devices,
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sova_server::Snapshot;
+use sova_server::{Snapshot, SnapshotFile};
 use std::path::PathBuf;
 use std::{error::Error, fmt, io, path::Path};
 use tokio::{
@@ -106,7 +106,7 @@ impl Error for DiskError {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ProjectFile {
-    pub snapshot: Snapshot,
+    pub snapshot: SnapshotFile,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -157,7 +157,7 @@ pub async fn save_project(snapshot: &Snapshot, name: &str) -> Result<()> {
     };
 
     let file = ProjectFile {
-        snapshot: snapshot.clone(),
+        snapshot: snapshot.clone().into(),
         created_at,
         updated_at: now,
     };
@@ -193,7 +193,7 @@ pub async fn load_project(name: &str) -> Result<Snapshot> {
             source: e,
         })?;
 
-    Ok(file.snapshot)
+    Ok(file.snapshot.into())
 }
 
 pub async fn list_projects() -> Result<Vec<ProjectInfo>> {
@@ -307,5 +307,5 @@ pub async fn load_project_from_path(path: &Path) -> Result<Snapshot> {
             source: e,
         })?;
 
-    Ok(file.snapshot)
+    Ok(file.snapshot.into())
 }
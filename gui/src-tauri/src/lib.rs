@@ -86,11 +86,19 @@ async fn connect_client(
     ip: String,
     port: u16,
     username: String,
+    color: Option<String>,
+    compression: Option<sova_server::CompressionCodec>,
     client_manager: tauri::State<'_, ClientManagerState>,
 ) -> Result<(), String> {
     let mut client = client_manager.lock().await;
     client.connect(ip, port).await.map_err(|e| e.to_string())?;
-    client.send_message(sova_server::ClientMessage::SetName(username))
+    let identity = sova_server::PeerIdentity {
+        color,
+        protocol_version: Some(sova_server::PROTOCOL_VERSION),
+        codecs: compression.unwrap_or_default().offer(),
+        ..sova_server::PeerIdentity::new(username)
+    };
+    client.send_message(sova_server::ClientMessage::SetIdentity(identity))
         .map_err(|e| e.to_string())?;
     Ok(())
 }
@@ -8,6 +8,11 @@ import type {
 	DeviceInfo,
 	ExecutionMode,
 	VariableStore,
+	PeerIdentity,
+	SceneMetadata,
+	LinePlaybackMode,
+	OscClockConfig,
+	ValueGenerator,
 } from '$lib/types/protocol';
 
 export const ActionTiming = {
@@ -16,6 +21,7 @@ export const ActionTiming = {
 	atNextBeat: (): ActionTiming => 'AtNextBeat',
 	atNextPhase: (): ActionTiming => 'AtNextPhase',
 	atNextModulo: (modulo: number): ActionTiming => ({ AtNextModulo: modulo }),
+	quantized: (): ActionTiming => 'Quantized',
 	never: (): ActionTiming => 'Never',
 };
 
@@ -49,6 +55,21 @@ export async function setTempo(
 	await sendMessage({ SetTempo: [tempo, timing] });
 }
 
+// Adjusts the current tempo by a small delta, in BPM
+export async function nudgeTempo(delta: number): Promise<void> {
+	await sendMessage({ NudgeTempo: delta });
+}
+
+// Grid, in beats, on which quantized edits such as adding or removing lines apply
+export async function setQuantizationGrid(grid: number): Promise<void> {
+	await sendMessage({ SetQuantizationGrid: grid });
+}
+
+// Seeds the random source of scripts, null draws differently on each run
+export async function setRandomSeed(seed: number | null): Promise<void> {
+	await sendMessage({ SetRandomSeed: seed });
+}
+
 // Execution mode
 export async function setSceneMode(
 	mode: ExecutionMode,
@@ -57,6 +78,54 @@ export async function setSceneMode(
 	await sendMessage({ SetSceneMode: [mode, timing] });
 }
 
+// Key and time signature
+export async function setSceneMetadata(
+	metadata: SceneMetadata,
+	timing: ActionTiming = ActionTiming.immediate()
+): Promise<void> {
+	await sendMessage({ SetSceneMetadata: [metadata, timing] });
+}
+
+// Plays a script once without adding it to the scene
+export async function auditionScript(content: string, lang: string): Promise<void> {
+	await sendMessage({ AuditionScript: [content, lang] });
+}
+
+// Compiles every script of the scene and reports the ones that fail
+export async function validateScene(): Promise<void> {
+	await sendMessage('ValidateScene');
+}
+
+// Restores the scene replaced by the latest scene load
+export async function undoSceneLoad(): Promise<void> {
+	await sendMessage('UndoSceneLoad');
+}
+
+// Counts the lines, frames and scripts of the scene
+export async function getSceneStats(): Promise<void> {
+	await sendMessage('GetSceneStats');
+}
+
+// Finds the matches of a regular expression in every script of the scene
+export async function searchScripts(pattern: string): Promise<void> {
+	await sendMessage({ SearchScripts: pattern });
+}
+
+// Replaces the matches of a regular expression in every script, $1 refers to a captured group
+export async function replaceInScripts(pattern: string, replacement: string): Promise<void> {
+	await sendMessage({ ReplaceInScripts: [pattern, replacement] });
+}
+
+// Only receive updates for the given lines, null follows the whole scene again
+export async function subscribeLines(lines: number[] | null): Promise<void> {
+	await sendMessage({ SubscribeLines: lines });
+}
+
+// Mirrors the editor focus of another peer, null goes back to independent navigation
+export async function followPeer(name: string | null): Promise<void> {
+	await sendMessage({ FollowPeer: name });
+}
+
 // Scene operations
 export async function setScene(
 	scene: Scene,
@@ -65,6 +134,16 @@ export async function setScene(
 	await sendMessage({ SetScene: [scene, timing] });
 }
 
+// Crossfades from the current scene to this one over a number of beats
+export async function morphToScene(scene: Scene, durationBeats: number): Promise<void> {
+	await sendMessage({ MorphToScene: [scene, durationBeats] });
+}
+
+// Replaces the scene with one written in the scene text format
+export async function importSceneText(text: string): Promise<void> {
+	await sendMessage({ ImportSceneText: text });
+}
+
 // Line operations
 export async function setLines(
 	lines: [number, Line][],
@@ -83,18 +162,27 @@ export async function configureLines(
 export async function addLine(
 	index: number,
 	line: Line,
-	timing: ActionTiming = ActionTiming.atNextBeat()
+	timing: ActionTiming = ActionTiming.quantized()
 ): Promise<void> {
 	await sendMessage({ AddLine: [index, line, timing] });
 }
 
 export async function removeLine(
 	index: number,
-	timing: ActionTiming = ActionTiming.atNextBeat()
+	timing: ActionTiming = ActionTiming.quantized()
 ): Promise<void> {
 	await sendMessage({ RemoveLine: [index, timing] });
 }
 
+// Moves a line to another index, its frames and playhead move along
+export async function moveLine(
+	from: number,
+	to: number,
+	timing: ActionTiming = ActionTiming.immediate()
+): Promise<void> {
+	await sendMessage({ MoveLine: [from, to, timing] });
+}
+
 // Line property controls
 export async function setLineSpeedFactor(
 	lineIdx: number,
@@ -116,6 +204,77 @@ export async function setLineFrameRange(
 	);
 }
 
+export async function setLinePlaybackMode(
+	lineIdx: number,
+	mode: LinePlaybackMode,
+	timing: ActionTiming = ActionTiming.immediate()
+): Promise<void> {
+	await sendMessage({ SetLinePlaybackMode: [lineIdx, mode, timing] });
+}
+
+// Note length multiplier, 1.0 leaves note durations unchanged
+export async function setLineGate(
+	lineIdx: number,
+	gate: number,
+	timing: ActionTiming = ActionTiming.immediate()
+): Promise<void> {
+	await sendMessage({ SetLineGate: [lineIdx, gate, timing] });
+}
+
+// Sends every MIDI event of the line on one channel, null keeps the script channels
+export async function setLineMidiChannel(
+	lineIdx: number,
+	channel: number | null,
+	timing: ActionTiming = ActionTiming.immediate()
+): Promise<void> {
+	await sendMessage({ SetLineMidiChannel: [lineIdx, channel, timing] });
+}
+
+// Shifts every MIDI note of the line by a number of semitones
+export async function setLineTranspose(
+	lineIdx: number,
+	semitones: number,
+	timing: ActionTiming = ActionTiming.immediate()
+): Promise<void> {
+	await sendMessage({ SetLineTranspose: [lineIdx, semitones, timing] });
+}
+
+// Restarts the line from its start frame whenever it is enabled again
+export async function setLineRetriggerOnEnable(
+	lineIdx: number,
+	retrigger: boolean,
+	timing: ActionTiming = ActionTiming.immediate()
+): Promise<void> {
+	await sendMessage({ SetLineRetriggerOnEnable: [lineIdx, retrigger, timing] });
+}
+
+// Scales the frame durations of the line at each cycle, null restores the static durations
+export async function setLineDurationGenerator(
+	lineIdx: number,
+	generator: ValueGenerator | null,
+	timing: ActionTiming = ActionTiming.immediate()
+): Promise<void> {
+	await sendMessage({ SetLineDurationGenerator: [lineIdx, generator, timing] });
+}
+
+// Plays the fill frames instead of the line's own every N cycles, no frames removes the fill
+export async function setLineFill(
+	lineIdx: number,
+	frames: Frame[],
+	everyN: number
+): Promise<void> {
+	await sendMessage({ SetLineFill: [lineIdx, frames.map(stripCompiledFromFrame), everyN] });
+}
+
+// Quantizes the edits of the line to its own grid in beats, null follows the global grid
+export async function setLineQuantization(
+	lineIdx: number,
+	grid: number | null,
+	timing: ActionTiming = ActionTiming.immediate()
+): Promise<void> {
+	await sendMessage({ SetLineQuantization: [lineIdx, grid, timing] });
+}
+
 export async function setLineVariables(
 	lineIdx: number,
 	vars: VariableStore,
@@ -149,7 +308,7 @@ export async function addFrame(
 	lineId: number,
 	frameId: number,
 	frame: Frame,
-	timing: ActionTiming = ActionTiming.atNextBeat()
+	timing: ActionTiming = ActionTiming.quantized()
 ): Promise<void> {
 	await sendMessage({
 		AddFrame: [lineId, frameId, stripCompiledFromFrame(frame), timing],
@@ -159,12 +318,53 @@ export async function addFrame(
 export async function removeFrame(
 	lineId: number,
 	frameId: number,
-	timing: ActionTiming = ActionTiming.atNextBeat()
+	timing: ActionTiming = ActionTiming.quantized()
 ): Promise<void> {
 	await sendMessage({ RemoveFrame: [lineId, frameId, timing] });
 }
 
+// Batched edits over a selection of [lineId, frameId] pairs, applied as one unit
+export async function enableFramesBatch(
+	frames: [number, number][],
+	timing: ActionTiming = ActionTiming.immediate()
+): Promise<void> {
+	await sendMessage({ EnableFramesBatch: [frames, timing] });
+}
+
+export async function disableFramesBatch(
+	frames: [number, number][],
+	timing: ActionTiming = ActionTiming.immediate()
+): Promise<void> {
+	await sendMessage({ DisableFramesBatch: [frames, timing] });
+}
+
+export async function removeFramesBatch(
+	frames: [number, number][],
+	timing: ActionTiming = ActionTiming.quantized()
+): Promise<void> {
+	await sendMessage({ RemoveFramesBatch: [frames, timing] });
+}
+
 // Frame property controls
+// Moves the trigger of a frame by an offset in beats, negative values play it earlier
+export async function setFrameNudge(
+	lineIdx: number,
+	frameIdx: number,
+	nudge: number,
+	timing: ActionTiming = ActionTiming.immediate()
+): Promise<void> {
+	await sendMessage({ SetFrameNudge: [lineIdx, frameIdx, nudge, timing] });
+}
+
+// Makes a frame disable itself after playing once, enabling it again arms it for another play
+export async function setFrameOneShot(
+	lineIdx: number,
+	frameIdx: number,
+	oneShot: boolean
+): Promise<void> {
+	await sendMessage({ SetFrameOneShot: [lineIdx, frameIdx, oneShot] });
+}
+
 export async function setFrameVariables(
 	lineIdx: number,
 	frameIdx: number,
@@ -193,6 +393,18 @@ export async function setName(name: string): Promise<void> {
 	await sendMessage({ SetName: name });
 }
 
+export async function setIdentity(identity: PeerIdentity): Promise<void> {
+	await sendMessage({ SetIdentity: identity });
+}
+
+export async function lockScene(): Promise<void> {
+	await sendMessage('LockScene');
+}
+
+export async function unlockScene(): Promise<void> {
+	await sendMessage('UnlockScene');
+}
+
 export async function sendChat(message: string): Promise<void> {
 	await sendMessage({ Chat: message });
 }
@@ -251,6 +463,40 @@ export async function removeOscDevice(name: string): Promise<void> {
 	await sendMessage({ RemoveOscDevice: name });
 }
 
+// Latency in seconds; negative values send the device's events earlier
+export async function setDeviceLatency(name: string, latency: number): Promise<void> {
+	await sendMessage({ SetDeviceLatency: [name, latency] });
+}
+
+// Spreads the MPE notes of a MIDI output across its first `members` channels after the master
+export async function setMpeZone(name: string, members: number): Promise<void> {
+	await sendMessage({ SetMpeZone: [name, members] });
+}
+
+// Instruments played by name in scripts, e.g. "kick" playing the samples of "bd_909"
+export async function getInstruments(): Promise<void> {
+	await sendMessage('GetInstruments');
+}
+
+// Maps an instrument to a sample folder or synth preset, null removes it
+export async function setInstrument(name: string, sound: string | null): Promise<void> {
+	await sendMessage({ SetInstrument: [name, sound] });
+}
+
+export async function getLanguages(): Promise<void> {
+	await sendMessage('GetLanguages');
+}
+
+// Clicks on every beat of the given MIDI output slot, accenting the first beat of each bar
+export async function setMidiMetronome(slot: number, on: boolean): Promise<void> {
+	await sendMessage({ SetMidiMetronome: [slot, on] });
+}
+
+// Mirrors tempo, beat, bar and transport state to an OSC output slot, null stops it
+export async function setOscClock(config: OscClockConfig | null): Promise<void> {
+	await sendMessage({ SetOscClock: config });
+}
+
 // Queries
 export async function getSnapshot(): Promise<void> {
 	await sendMessage('GetSnapshot');
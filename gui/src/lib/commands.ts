@@ -7,7 +7,10 @@ import {
   stopTransport,
   setTempo,
   setName,
+  lockScene,
+  unlockScene,
 } from "$lib/api/client";
+import { sceneLock } from "$lib/stores/collaboration";
 import { nickname as nicknameStore } from "$lib/stores/nickname";
 import { isPlaying, isStarting } from "$lib/stores/transport";
 import { isConnected } from "$lib/stores/connectionState";
@@ -56,6 +59,25 @@ registerCommand({
   },
 });
 
+registerCommand({
+  id: "lock",
+  name: "Lock",
+  description: "Lock the scene so that other peers cannot edit it",
+  keywords: ["freeze", "readonly"],
+  isAvailable: () => get(isConnected) && get(sceneLock) === null,
+  execute: () => lockScene(),
+});
+
+registerCommand({
+  id: "unlock",
+  name: "Unlock",
+  description: "Release the scene lock",
+  keywords: ["unfreeze"],
+  isAvailable: () =>
+    get(isConnected) && get(sceneLock) === get(nicknameStore),
+  execute: () => unlockScene(),
+});
+
 registerCommand({
   id: "scene",
   name: "Scene",
@@ -8,13 +8,21 @@ import {
   collapseToFocus,
   getSelectedClipIds,
   fromClipId,
+  toClipId,
 } from "$lib/stores/selection";
 import {
   copySelection,
   getClipboard,
   type ClipboardData,
 } from "$lib/stores/clipboard";
-import { setFrames, addFrame, removeFrame, ActionTiming } from "$lib/api/client";
+import {
+  setFrames,
+  addFrame,
+  removeFramesBatch,
+  enableFramesBatch,
+  disableFramesBatch,
+  ActionTiming,
+} from "$lib/api/client";
 import type { Frame, Line } from "$lib/types/protocol";
 import { type TimelineContext, getDuration } from "./context.svelte";
 import { get } from "svelte/store";
@@ -131,16 +139,11 @@ export function useTimelineKeyboard(config: KeyboardConfig) {
     const clipIds = getSelectedClipIds(currentSelection, currentScene);
     if (clipIds.length === 0) return;
 
-    const framesToRemove = clipIds
-      .map(id => fromClipId(id))
-      .sort((a, b) => {
-        if (a.lineIdx !== b.lineIdx) return b.lineIdx - a.lineIdx;
-        return b.frameIdx - a.frameIdx;
-      });
+    const framesToRemove = clipIds.map(id => fromClipId(id));
 
-    for (const { lineIdx, frameIdx } of framesToRemove) {
-      await removeFrame(lineIdx, frameIdx);
-    }
+    await removeFramesBatch(
+      framesToRemove.map(({ lineIdx, frameIdx }): [number, number] => [lineIdx, frameIdx])
+    );
 
     const updatedScene = get(scene);
     if (!updatedScene || updatedScene.lines.length === 0) {
@@ -181,11 +184,24 @@ export function useTimelineKeyboard(config: KeyboardConfig) {
     const frame = currentScene.lines[lineIdx]?.frames[frameIdx];
     if (!frame) return;
 
-    const newEnabled = frame.enabled === false ? true : false;
-    const updatedFrame = { ...frame, enabled: newEnabled };
+    // Toggling a frame of the selection toggles the whole selection at once
+    const currentSelection = get(selection);
+    const clipIds = currentSelection
+      ? getSelectedClipIds(currentSelection, currentScene)
+      : [];
+    const targets: [number, number][] = clipIds.includes(toClipId(lineIdx, frameIdx))
+      ? clipIds.map((id): [number, number] => {
+          const { lineIdx, frameIdx } = fromClipId(id);
+          return [lineIdx, frameIdx];
+        })
+      : [[lineIdx, frameIdx]];
 
     try {
-      await setFrames([[lineIdx, frameIdx, updatedFrame]], ActionTiming.immediate());
+      if (frame.enabled === false) {
+        await enableFramesBatch(targets);
+      } else {
+        await disableFramesBatch(targets);
+      }
     } catch (error) {
       console.error("Failed to toggle enabled:", error);
     }
@@ -31,9 +31,13 @@ import {
   closeBrackets,
   closeBracketsKeymap,
 } from "@codemirror/autocomplete";
-import { highlightSelectionMatches, searchKeymap } from "@codemirror/search";
+import {
+  highlightSelectionMatches,
+  searchKeymap,
+  selectNextOccurrence,
+} from "@codemirror/search";
 import { lintKeymap } from "@codemirror/lint";
-import { vim } from "@replit/codemirror-vim";
+import { vim, Vim } from "@replit/codemirror-vim";
 import { emacs } from "@replit/codemirror-emacs";
 import { get } from "svelte/store";
 import {
@@ -45,6 +49,24 @@ import { createHighlightStyle } from "$lib/themes";
 import type { Theme } from "$lib/themes";
 import type { EditorConfig } from "$lib/stores/editorConfig";
 
+// Mod-d (from searchKeymap) adds a cursor at the next occurrence of the selection
+const MAX_CURSORS = 32;
+
+// Vim keeps Ctrl-d to scroll, so Ctrl-n adds the next occurrence instead
+Vim.defineAction("selectNextOccurrence", (cm) => {
+  selectNextOccurrence(cm.cm6);
+});
+Vim.mapCommand("<C-n>", "action", "selectNextOccurrence", {}, {
+  context: "normal",
+});
+
+// Drops selection changes that would go past MAX_CURSORS
+const limitCursors = EditorState.transactionFilter.of((tr) =>
+  !tr.docChanged && tr.selection && tr.selection.ranges.length > MAX_CURSORS
+    ? []
+    : tr,
+);
+
 const keymapCompartment = new Compartment();
 const themeCompartment = new Compartment();
 const highlightCompartment = new Compartment();
@@ -150,6 +172,8 @@ function buildExtensions(
       ...lintKeymap,
     ]),
     history(),
+    EditorState.allowMultipleSelections.of(true),
+    limitCursors,
     drawSelection(),
     dropCursor(),
     indentOnInput(),
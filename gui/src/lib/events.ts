@@ -5,10 +5,18 @@ export const SERVER_EVENTS = {
 	// Connection
 	HELLO: 'server:hello',
 	CONNECTION_REFUSED: 'server:connection-refused',
+	LATENCY: 'server:latency',
 
 	// Status
 	SUCCESS: 'server:success',
 	ERROR: 'server:error',
+	REJECTED: 'server:rejected',
+	AUDITION_FAILED: 'server:audition-failed',
+	SCENE_TEXT_INVALID: 'server:scene-text-invalid',
+	SCENE_VALIDATION: 'server:scene-validation',
+	SEARCH_RESULTS: 'server:search-results',
+	SCENE_STATS: 'server:scene-stats',
+	SCRIPTS_REPLACED: 'server:scripts-replaced',
 	LOG: 'server:log',
 	LOG_BATCH: 'server:log-batch',
 	SERVER_LOG: 'server:server-log',
@@ -17,12 +25,14 @@ export const SERVER_EVENTS = {
 	SCENE: 'server:scene',
 	SNAPSHOT: 'server:snapshot',
 	GLOBAL_MODE: 'server:global-mode',
+	SCENE_METADATA: 'server:scene-metadata',
 
 	// Lines
 	LINE_VALUES: 'server:line-values',
 	LINE_CONFIGURATIONS: 'server:line-configurations',
 	ADD_LINE: 'server:add-line',
 	REMOVE_LINE: 'server:remove-line',
+	MOVE_LINE: 'server:move-line',
 
 	// Frames
 	FRAME_VALUES: 'server:frame-values',
@@ -33,20 +43,26 @@ export const SERVER_EVENTS = {
 	// Transport
 	PLAYBACK_STATE_CHANGED: 'server:playback-state-changed',
 	CLOCK_STATE: 'server:clock-state',
+	QUANTIZATION_GRID: 'server:quantization-grid',
 
 	// Devices
 	DEVICE_LIST: 'server:device-list',
 	DEVICES_RESTORED: 'server:devices-restored',
+	INSTRUMENTS: 'server:instruments',
+	LANGUAGES: 'server:languages',
 
 	// Collaboration
 	PEERS_UPDATED: 'server:peers-updated',
 	CHAT: 'server:chat',
 	PEER_STARTED_EDITING: 'server:peer-started-editing',
 	PEER_STOPPED_EDITING: 'server:peer-stopped-editing',
+	FOLLOWED_PEER_FOCUS: 'server:followed-peer-focus',
+	SCENE_LOCK_CHANGED: 'server:scene-lock-changed',
 
 	// Compilation & Variables
 	GLOBAL_VARIABLES: 'server:global-variables',
 	COMPILATION_UPDATE: 'server:compilation-update',
+	COMPILATION_WARNINGS: 'server:compilation-warnings',
 
 	// Audio Engine
 	AUDIO_ENGINE_STATE: 'server:audio-engine-state',
@@ -200,6 +200,10 @@ export const helpContent: Record<string, HelpEntry> = {
 		title: 'Nickname',
 		description: 'Your display name shown to other collaborators.',
 	},
+	'login-color': {
+		title: 'Color',
+		description: 'Color other collaborators see for you. Derived from your nickname if left unset.',
+	},
 	'login-connect': {
 		title: 'Connect',
 		description: 'Connect to the server with the provided credentials.',
@@ -38,6 +38,9 @@ const defaultState: AudioEngineState = {
 
 export const audioEngineState = writable<AudioEngineState>(defaultState);
 
+// False when the server was built without the audio engine, audio controls are then hidden
+export const audioAvailable = writable<boolean>(true);
+
 let unlisten: UnlistenFn | null = null;
 let pollInterval: ReturnType<typeof setInterval> | null = null;
 
@@ -54,7 +57,7 @@ export async function initializeAudioEngineStore(): Promise<void> {
 	);
 
 	pollInterval = setInterval(async () => {
-		if (!get(isConnected)) return;
+		if (!get(isConnected) || !get(audioAvailable)) return;
 		try {
 			await invoke('send_client_message', { message: 'GetAudioEngineState' });
 		} catch {
@@ -73,4 +76,5 @@ export function cleanupAudioEngineStore(): void {
 		unlisten = null;
 	}
 	audioEngineState.set(defaultState);
+	audioAvailable.set(true);
 }
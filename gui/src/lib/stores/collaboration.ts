@@ -1,8 +1,12 @@
 import { writable, derived, type Writable, type Readable } from "svelte/store";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
-import type { ChatPayload } from "$lib/types/protocol";
+import type { ChatPayload, PeerEditingPayload, PeerIdentity } from "$lib/types/protocol";
+import { followPeer } from "$lib/api/client";
+import { SERVER_EVENTS } from "$lib/events";
+import { openEditor } from "./editingFrame";
+import { selectFrame } from "./selection";
 
-export const peers: Writable<string[]> = writable([]);
+export const peers: Writable<PeerIdentity[]> = writable([]);
 
 export interface ChatMessage {
   user: string;
@@ -12,8 +16,19 @@ export interface ChatMessage {
 
 export const chatMessages: Writable<ChatMessage[]> = writable([]);
 
+// Name of the peer holding the scene lock, null when the scene is editable.
+export const sceneLock: Writable<string | null> = writable(null);
+
 export const peerCount: Readable<number> = derived(peers, ($p) => $p.length);
 
+// Name of the peer whose editor focus this client mirrors, null when navigating freely.
+export const following: Writable<string | null> = writable(null);
+
+export async function follow(name: string | null): Promise<void> {
+  await followPeer(name);
+  following.set(name);
+}
+
 let unlistenFns: UnlistenFn[] = [];
 let initialized = false;
 
@@ -22,7 +37,20 @@ export async function initializeCollaborationStore(): Promise<void> {
   initialized = true;
 
   unlistenFns.push(
-    await listen<string[]>("server:peers-updated", (e) => peers.set(e.payload)),
+    await listen<PeerIdentity[]>("server:peers-updated", (e) => peers.set(e.payload)),
+  );
+
+  unlistenFns.push(
+    await listen<string | null>("server:scene-lock-changed", (e) =>
+      sceneLock.set(e.payload),
+    ),
+  );
+
+  unlistenFns.push(
+    await listen<PeerEditingPayload>(SERVER_EVENTS.FOLLOWED_PEER_FOCUS, (e) => {
+      selectFrame(e.payload.lineId, e.payload.frameId);
+      openEditor(e.payload.lineId, e.payload.frameId);
+    }),
   );
 
   unlistenFns.push(
@@ -40,5 +68,7 @@ export function cleanupCollaborationStore(): void {
   unlistenFns = [];
   initialized = false;
   peers.set([]);
+  sceneLock.set(null);
+  following.set(null);
   chatMessages.set([]);
 }
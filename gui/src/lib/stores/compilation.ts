@@ -4,6 +4,8 @@ import { SERVER_EVENTS } from "$lib/events";
 import type {
   CompilationState,
   CompilationUpdatePayload,
+  CompilationWarning,
+  CompilationWarningsPayload,
   RemoveFramePayload,
 } from "$lib/types/protocol";
 import { ListenerGroup } from "./helpers";
@@ -12,6 +14,7 @@ import { ListenerGroup } from "./helpers";
 interface FrameCompilation {
   scriptId: string; // String to avoid JS precision loss for u64
   state: CompilationState;
+  warnings: CompilationWarning[];
 }
 
 // Compilation state per frame: Map<"lineId:frameId", FrameCompilation>
@@ -34,7 +37,25 @@ export async function initializeCompilationStore(): Promise<void> {
         compilationStates.update(($states) => {
           const key = makeKey(lineId, frameId);
           const newStates = new Map($states);
-          newStates.set(key, { scriptId, state });
+          newStates.set(key, { scriptId, state, warnings: [] });
+          return newStates;
+        });
+      },
+    ),
+  );
+
+  // Warnings follow the compilation update of the same script
+  await listeners.add(() =>
+    listen<CompilationWarningsPayload>(
+      SERVER_EVENTS.COMPILATION_WARNINGS,
+      (event) => {
+        const { lineId, frameId, scriptId, warnings } = event.payload;
+        compilationStates.update(($states) => {
+          const key = makeKey(lineId, frameId);
+          const current = $states.get(key);
+          if (!current || current.scriptId !== scriptId) return $states;
+          const newStates = new Map($states);
+          newStates.set(key, { ...current, warnings });
           return newStates;
         });
       },
@@ -9,6 +9,7 @@ export interface ServerConfig {
   auto_start: boolean;
   port: number;
   ip: string;
+  compression: "Zstd" | "Lz4";
 }
 
 export interface AudioConfig {
@@ -60,6 +61,7 @@ const DEFAULT_CONFIG: Config = {
     auto_start: false,
     port: 8080,
     ip: "127.0.0.1",
+    compression: "Zstd",
   },
   audio: {
     enabled: true,
@@ -1,10 +1,19 @@
 import { writable } from "svelte/store";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import { SERVER_EVENTS } from "$lib/events";
+
+export interface Latency {
+  roundTripMs: number;
+  degraded: boolean;
+}
 
 export const isConnected = writable<boolean>(false);
 export const connectionError = writable<string | null>(null);
+// Latest round trip to the server, null until a ping was answered
+export const latency = writable<Latency | null>(null);
 
 let unlisten: UnlistenFn | null = null;
+let unlistenLatency: UnlistenFn | null = null;
 
 export async function initializeConnectionListener(): Promise<void> {
   unlisten = await listen<{ reason: string }>(
@@ -12,8 +21,12 @@ export async function initializeConnectionListener(): Promise<void> {
     (event) => {
       isConnected.set(false);
       connectionError.set(`Disconnected: ${event.payload.reason}`);
+      latency.set(null);
     },
   );
+  unlistenLatency = await listen<Latency>(SERVER_EVENTS.LATENCY, (event) => {
+    latency.set(event.payload);
+  });
 }
 
 export function cleanupConnectionListener(): void {
@@ -21,4 +34,8 @@ export function cleanupConnectionListener(): void {
     unlisten();
     unlisten = null;
   }
+  if (unlistenLatency) {
+    unlistenLatency();
+    unlistenLatency = null;
+  }
 }
@@ -100,6 +100,19 @@ export function removeLineFromScene<S extends { lines: any[] }>(
   return newScene;
 }
 
+export function moveLineInScene<S extends { lines: any[] }>(
+  scene: S | null,
+  from: number,
+  to: number,
+): S | null {
+  if (!scene) return scene;
+  if (from >= scene.lines.length || to >= scene.lines.length) return scene;
+  const newScene = { ...scene, lines: [...scene.lines] };
+  const [line] = newScene.lines.splice(from, 1);
+  newScene.lines.splice(to, 0, line);
+  return newScene;
+}
+
 export function updateFramesInScene<
   S extends { lines: L[] },
   L extends { frames: F[] },
@@ -32,6 +32,7 @@ import {
 	cleanupTransportStore,
 	playbackState,
 	linkState,
+	quantizationGrid,
 } from './transport';
 
 import {
@@ -44,6 +45,7 @@ import {
 	initializeCollaborationStore,
 	cleanupCollaborationStore,
 	peers,
+	sceneLock,
 } from './collaboration';
 
 import {
@@ -61,7 +63,11 @@ import {
 	cleanupNotificationsStore,
 } from './notifications';
 
-import { setAvailableLanguages, cleanupLanguagesStore } from './languages';
+import {
+	setAvailableLanguages,
+	initializeLanguagesStore,
+	cleanupLanguagesStore,
+} from './languages';
 
 import {
 	initializeLocalEditsStore,
@@ -86,6 +92,7 @@ import {
 
 import {
 	audioEngineState,
+	audioAvailable,
 	initializeAudioEngineStore,
 	cleanupAudioEngineStore,
 } from './audioEngineState';
@@ -121,17 +128,20 @@ export async function initializeSovaStores(): Promise<void> {
 		// Initialize transport
 		playbackState.set(data.isPlaying ? 'Playing' : 'Stopped');
 		linkState.set(data.linkState);
+		quantizationGrid.set(data.quantizationGrid ?? 1);
 
 		// Initialize devices
 		devices.set(data.devices);
 
 		// Initialize collaboration
 		peers.set(data.peers);
+		sceneLock.set(data.sceneLock ?? null);
 
 		// Initialize available languages
 		setAvailableLanguages(data.availableLanguages);
 
 		// Initialize audio engine state
+		audioAvailable.set(data.audioAvailable ?? true);
 		if (data.audioEngineState) {
 			audioEngineState.set(data.audioEngineState);
 		}
@@ -144,6 +154,7 @@ export async function initializeSovaStores(): Promise<void> {
 		initializeDevicesStore(),
 		initializeCollaborationStore(),
 		initializeGlobalVariablesStore(),
+		initializeLanguagesStore(),
 		initializeCompilationStore(),
 		initializeNotificationsStore(),
 		initializeLocalEditsStore(),
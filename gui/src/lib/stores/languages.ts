@@ -1,11 +1,31 @@
 import { writable, type Writable } from "svelte/store";
+import { listen } from "@tauri-apps/api/event";
+import { SERVER_EVENTS } from "$lib/events";
+import type { LanguagesPayload } from "$lib/types/protocol";
+import { ListenerGroup } from "./helpers";
 
 export const availableLanguages: Writable<string[]> = writable([]);
 
+// Syntax definitions of the languages registered while the server runs
+export const languageSyntaxes: Writable<Record<string, string>> = writable({});
+
+const listeners = new ListenerGroup();
+
 export function setAvailableLanguages(languages: string[]): void {
   availableLanguages.set(languages);
 }
 
+export async function initializeLanguagesStore(): Promise<void> {
+  await listeners.add(() =>
+    listen<LanguagesPayload>(SERVER_EVENTS.LANGUAGES, (event) => {
+      availableLanguages.set(event.payload.languages);
+      languageSyntaxes.set(event.payload.syntaxes);
+    }),
+  );
+}
+
 export function cleanupLanguagesStore(): void {
+  listeners.cleanup();
   availableLanguages.set([]);
+  languageSyntaxes.set({});
 }
@@ -2,6 +2,11 @@ import { writable, type Writable } from "svelte/store";
 import { listen } from "@tauri-apps/api/event";
 import { SERVER_EVENTS } from "$lib/events";
 import { ListenerGroup } from "./helpers";
+import type {
+  CompilationError,
+  SceneTextError,
+  SceneValidationEntry,
+} from "$lib/types/protocol";
 
 export type NotificationType = "success" | "error" | "info";
 
@@ -72,6 +77,48 @@ export async function initializeNotificationsStore(): Promise<void> {
     }),
   );
 
+  // Listen for requests refused while the scene is locked
+  await listeners.add(() =>
+    listen<string>(SERVER_EVENTS.REJECTED, (event) => {
+      notify("error", `Rejected: ${event.payload}`, 5000);
+    }),
+  );
+
+  // Listen for auditioned scripts that did not compile
+  await listeners.add(() =>
+    listen<CompilationError>(SERVER_EVENTS.AUDITION_FAILED, (event) => {
+      notify("error", `Audition failed: ${event.payload.info}`, 5000);
+    }),
+  );
+
+  // Listen for scene text that could not be imported
+  await listeners.add(() =>
+    listen<SceneTextError>(SERVER_EVENTS.SCENE_TEXT_INVALID, (event) => {
+      const { line, column, message } = event.payload;
+      notify("error", `Scene import failed at line ${line}, column ${column}: ${message}`, 8000);
+    }),
+  );
+
+  // Listen for scene validation reports
+  await listeners.add(() =>
+    listen<SceneValidationEntry[]>(SERVER_EVENTS.SCENE_VALIDATION, (event) => {
+      const failures = event.payload.filter(
+        (entry) => typeof entry.state === "object" && "Error" in entry.state,
+      );
+      if (failures.length === 0) {
+        notify("success", `All ${event.payload.length} scripts compile`, 3000);
+        return;
+      }
+      const details = failures
+        .map((entry) => {
+          const state = entry.state as { Error: CompilationError };
+          return `line ${entry.lineId}, frame ${entry.frameId}: ${state.Error.info}`;
+        })
+        .join("; ");
+      notify("error", `${failures.length} scripts fail to compile: ${details}`, 10000);
+    }),
+  );
+
   // Listen for connection refused
   await listeners.add(() =>
     listen<string>(SERVER_EVENTS.CONNECTION_REFUSED, (event) => {
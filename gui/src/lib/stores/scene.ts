@@ -4,8 +4,10 @@ import type {
   Scene,
   Frame,
   AddLinePayload,
+  MoveLinePayload,
   AddFramePayload,
   RemoveFramePayload,
+  SceneMetadata,
 } from "$lib/types/protocol";
 import {
   ListenerGroup,
@@ -14,6 +16,7 @@ import {
   updateLinesInScene,
   addLineToScene,
   removeLineFromScene,
+  moveLineInScene,
   updateFramesInScene,
   addFrameToScene,
   removeFrameFromScene,
@@ -34,6 +37,15 @@ export async function initializeSceneStore(): Promise<void> {
   // Full scene updates
   await listeners.add(createSetListener(SERVER_EVENTS.SCENE, scene));
 
+  await listeners.add(
+    createUpdateListener(
+      SERVER_EVENTS.SCENE_METADATA,
+      scene,
+      (scene, metadata: SceneMetadata) =>
+        scene ? { ...scene, metadata } : scene,
+    ),
+  );
+
   // Line updates with pure functions
   await listeners.add(
     createUpdateListener(SERVER_EVENTS.LINE_VALUES, scene, updateLinesInScene),
@@ -82,6 +94,15 @@ export async function initializeSceneStore(): Promise<void> {
     ),
   );
 
+  await listeners.add(
+    createUpdateListener(
+      SERVER_EVENTS.MOVE_LINE,
+      scene,
+      (currentScene, payload: MoveLinePayload) =>
+        moveLineInScene(currentScene, payload.from, payload.to),
+    ),
+  );
+
   // Frame updates
   await listeners.add(
     createUpdateListener(
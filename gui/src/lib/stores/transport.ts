@@ -22,6 +22,9 @@ export const isStarting: Readable<boolean> = derived(
 // Clock state
 export const clockState: Writable<ClockState | null> = writable(null);
 
+// Grid of quantized edits, in beats
+export const quantizationGrid: Writable<number> = writable(1);
+
 // Link state (Ableton Link)
 export const linkState: Writable<LinkState | null> = writable(null);
 
@@ -45,6 +48,13 @@ export async function initializeTransportStore(): Promise<void> {
     }),
   );
 
+  // Listen for quantization grid changes
+  await listeners.add(() =>
+    listen<number>(SERVER_EVENTS.QUANTIZATION_GRID, (event) => {
+      quantizationGrid.set(event.payload);
+    }),
+  );
+
   // Listen for frame position updates
   await listeners.add(() =>
     listen<FramePosition[]>(SERVER_EVENTS.FRAME_POSITION, (event) => {
@@ -57,6 +67,7 @@ export function cleanupTransportStore(): void {
   listeners.cleanup();
   playbackState.set("Stopped");
   clockState.set(null);
+  quantizationGrid.set(1);
   linkState.set(null);
   framePositions.set([]);
 }
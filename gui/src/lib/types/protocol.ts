@@ -19,6 +19,7 @@ export type ActionTiming =
 	| 'AtNextBeat'
 	| 'AtNextPhase'
 	| { AtNextModulo: number }
+	| 'Quantized'
 	| 'Never';
 
 // ExecutionMode controls scene-level scheduling behavior
@@ -51,6 +52,21 @@ export interface CompilationError {
 	to: number;
 }
 
+// Error found while reading a scene from text, line and column start at 1
+export interface SceneTextError {
+	line: number;
+	column: number;
+	message: string;
+}
+
+// Non-fatal compilation issue, the script still runs
+export interface CompilationWarning {
+	lang: string;
+	info: string;
+	from: number;
+	to: number;
+}
+
 // Compilation state
 // Note: Compiled is a string because Program has #[serde(skip)]
 export type CompilationState =
@@ -73,6 +89,9 @@ export interface Frame {
 	duration: number; // In beats
 	repetitions: number;
 	enabled: boolean;
+	one_shot?: boolean; // Disables itself after playing once
+	weight?: number; // Relative chance in WeightedRandom playback, 1 by default
+	nudge?: number; // Trigger offset in beats, 0 by default
 	script: Script;
 	name: string | null;
 	vars: VariableStore;
@@ -87,6 +106,51 @@ export interface Line {
 	end_frame: number | null;
 	looping: boolean;
 	trailing: boolean;
+	playback_mode?: LinePlaybackMode;
+	seed?: number | null;
+	gate?: number;
+	midi_channel?: number | null;
+	transpose?: number;
+	retrigger_on_enable?: boolean;
+	sustain_on_disable?: boolean;
+	duration_generator?: ValueGenerator | null;
+	fill?: LineFill | null;
+	quantization?: number | null;
+}
+
+// Frames played instead of those of the line on every cycle multiple of `every`
+export interface LineFill {
+	frames: Frame[];
+	every: number;
+}
+
+// Generator of values over time, e.g. { shape: { Table: [1, 0.5] }, modifiers: ['Loop'], span: { Beats: 8 }, state_id: 0 }
+export interface ValueGenerator {
+	shape: string | Record<string, unknown>;
+	modifiers: (string | Record<string, unknown>)[];
+	span: Record<string, number>;
+	state_id: number;
+	// Date the generator started at, in microseconds, set by the server
+	start_date?: number;
+}
+
+export type LinePlaybackMode = 'Sequential' | 'Random' | 'WeightedRandom';
+
+// Key signature: sharps (positive) or flats (negative), from -7 to 7
+export interface KeySignature {
+	sharps: number;
+	minor: boolean;
+}
+
+export interface TimeSignature {
+	numerator: number;
+	denominator: number;
+}
+
+// Scene metadata, omitted when C major / 4/4
+export interface SceneMetadata {
+	key_signature: KeySignature;
+	time_signature: TimeSignature;
 }
 
 // Scene
@@ -94,6 +158,7 @@ export interface Scene {
 	lines: Line[];
 	vars?: VariableStore;
 	mode: ExecutionMode;
+	metadata?: SceneMetadata;
 }
 
 // Device types
@@ -115,6 +180,19 @@ export interface DeviceInfo {
 	is_connected: boolean;
 	address: string | null;
 	is_missing: boolean;
+	latency: number; // In seconds, negative values send events earlier
+	capabilities?: DeviceCapabilities | null;
+}
+
+export type Reachability = 'Reachable' | 'Unreachable' | 'Unknown';
+
+// What a device supports, ip and port are only set for OSC devices
+export interface DeviceCapabilities {
+	kind: DeviceKind;
+	channels: number | null;
+	ip: string | null;
+	port: number | null;
+	reachability: Reachability;
 }
 
 // Link state
@@ -167,16 +245,29 @@ export interface AudioEngineState {
 // Scope data - min/max peak pairs for oscilloscope
 export type ScopePeaks = [number, number][];
 
+// Peer identity (matches Rust PeerIdentity struct)
+export interface PeerIdentity {
+	name: string;
+	color?: string | null;
+	emoji?: string | null;
+}
+
 // Server event payloads
 export interface HelloPayload {
 	username: string;
 	scene: Scene;
 	devices: DeviceInfo[];
-	peers: string[];
+	peers: PeerIdentity[];
 	linkState: LinkState;
 	isPlaying: boolean;
 	availableLanguages: string[];
 	audioEngineState: AudioEngineState;
+	// False when the server was built without the audio engine
+	audioAvailable?: boolean;
+	sceneLock: string | null;
+	quantizationGrid?: number;
+	// Protocol version agreed on with the server
+	protocolVersion?: number;
 }
 
 export interface ChatPayload {
@@ -184,11 +275,31 @@ export interface ChatPayload {
 	message: string;
 }
 
+// Frame opened by a peer, as sent with peer-started-editing and followed-peer-focus
+export interface PeerEditingPayload {
+	user: string;
+	color: string | null;
+	lineId: number;
+	frameId: number;
+}
+
 export interface AddLinePayload {
 	index: number;
 	line: Line;
 }
 
+// Destination of the OSC mirror of the clock, rate in messages per beat
+export interface OscClockConfig {
+	slot: number;
+	address: string;
+	rate: number;
+}
+
+export interface MoveLinePayload {
+	from: number;
+	to: number;
+}
+
 export interface AddFramePayload {
 	lineId: number;
 	frameId: number;
@@ -207,24 +318,87 @@ export interface CompilationUpdatePayload {
 	state: CompilationState;
 }
 
+export interface SceneValidationEntry {
+	lineId: number;
+	frameId: number;
+	state: CompilationState;
+}
+
+export interface ScriptSearchMatch {
+	lineId: number;
+	frameId: number;
+	start: number; // Byte offsets in the script content
+	end: number;
+}
+
+// Instrument names mapped to the sample folder or synth preset they play
+export type InstrumentMap = Record<string, string>;
+
+export interface LanguagesPayload {
+	languages: string[];
+	syntaxes: Record<string, string>; // Syntax definitions of the languages registered at runtime
+}
+
+export interface SceneStats {
+	lines: number;
+	enabledLines: number;
+	frames: number;
+	enabledFrames: number;
+	scriptsPerLanguage: Record<string, number>; // Non-empty scripts only
+	duration: number; // Length in beats of the longest line
+}
+
+export interface ScriptsReplacedPayload {
+	changed: number; // Number of frames changed
+	failed: { lineId: number; frameId: number; error: CompilationError }[]; // Left untouched
+}
+
+export interface CompilationWarningsPayload {
+	lineId: number;
+	frameId: number;
+	scriptId: string; // String to avoid JS precision loss for u64
+	warnings: CompilationWarning[];
+}
+
 // Client message types for sending to server
 export type ClientMessage =
 	| { TransportStart: ActionTiming }
 	| { TransportStop: ActionTiming }
 	| { SetTempo: [number, ActionTiming] }
+	| { NudgeTempo: number }
+	| { SetQuantizationGrid: number }
+	| { SetRandomSeed: number | null }
 	| { SetSceneMode: [ExecutionMode, ActionTiming] }
+	| { SetSceneMetadata: [SceneMetadata, ActionTiming] }
 	| 'GetScene'
 	| { SetScene: [Scene, ActionTiming] }
+	| { MorphToScene: [Scene, number] }
+	| { ImportSceneText: string }
 	| { GetLine: number }
 	| { SetLines: [[number, Line][], ActionTiming] }
 	| { ConfigureLines: [[number, Line][], ActionTiming] }
+	| { SetLinePlaybackMode: [number, LinePlaybackMode, ActionTiming] }
+	| { SetLineGate: [number, number, ActionTiming] }
+	| { SetLineMidiChannel: [number, number | null, ActionTiming] }
+	| { SetLineTranspose: [number, number, ActionTiming] }
+	| { SetLineRetriggerOnEnable: [number, boolean, ActionTiming] }
+	| { SetLineDurationGenerator: [number, ValueGenerator | null, ActionTiming] }
+	| { SetLineFill: [number, Frame[], number] }
+	| { SetLineQuantization: [number, number | null, ActionTiming] }
 	| { AddLine: [number, Line, ActionTiming] }
 	| { RemoveLine: [number, ActionTiming] }
+	| { MoveLine: [number, number, ActionTiming] }
 	| { GetFrame: [number, number] }
 	| { SetFrames: [[number, number, Frame][], ActionTiming] }
 	| { AddFrame: [number, number, Frame, ActionTiming] }
 	| { RemoveFrame: [number, number, ActionTiming] }
+	| { SetFrameNudge: [number, number, number, ActionTiming] }
+	| { SetFrameOneShot: [number, number, boolean] }
+	| { EnableFramesBatch: [[number, number][], ActionTiming] }
+	| { DisableFramesBatch: [[number, number][], ActionTiming] }
+	| { RemoveFramesBatch: [[number, number][], ActionTiming] }
 	| { SetName: string }
+	| { SetIdentity: PeerIdentity }
 	| 'GetPeers'
 	| { Chat: string }
 	| { StartedEditingFrame: [number, number] }
@@ -237,7 +411,24 @@ export type ClientMessage =
 	| { UnassignDeviceFromSlot: number }
 	| { CreateOscDevice: [string, string, number] }
 	| { RemoveOscDevice: string }
+	| { SetDeviceLatency: [string, number] }
+	| { SetMpeZone: [string, number] }
+	| 'GetInstruments'
+	| { SetInstrument: [string, string | null] }
+	| 'GetLanguages'
+	| { SetMidiMetronome: [number, boolean] }
+	| { SetOscClock: OscClockConfig | null }
 	| 'GetClock'
 	| 'GetSnapshot'
 	| { RestoreDevices: DeviceInfo[] }
-	| 'GetAudioEngineState';
+	| 'GetAudioEngineState'
+	| 'LockScene'
+	| 'UnlockScene'
+	| { AuditionScript: [string, string] }
+	| 'ValidateScene'
+	| 'UndoSceneLoad'
+	| { SearchScripts: string }
+	| 'GetSceneStats'
+	| { ReplaceInScripts: [string, string] }
+	| { SubscribeLines: number[] | null }
+	| { FollowPeer: string | null };
@@ -77,3 +77,26 @@ export function transformThemeColors<T>(obj: T, hue: number): T {
   }
   return obj;
 }
+
+/**
+ * Derives a stable color from a name, used when a peer did not choose one.
+ */
+export function hashNameColor(name: string): string {
+  let hash = 0;
+  for (let i = 0; i < name.length; i++) {
+    hash = name.charCodeAt(i) + ((hash << 5) - hash);
+  }
+  const hue = Math.abs(hash) % 360;
+  return `hsl(${hue}, 70%, 65%)`;
+}
+
+/**
+ * Color of a peer: the one it chose if any, otherwise derived from its name.
+ */
+export function peerColor(
+  peers: { name: string; color?: string | null }[],
+  name: string,
+): string {
+  const chosen = peers.find((p) => p.name === name)?.color;
+  return chosen ? chosen : hashNameColor(name);
+}
@@ -9,6 +9,7 @@
 //!   cargo run -p langs --example run_lang -- bob "X 42"
 //!   cargo run -p langs --example run_lang -- bali "(note 60)"
 //!   cargo run -p langs --example run_lang -- boinx "60"
+//!   cargo run -p langs --example run_lang -- mini "bd [hh hh] sn"
 
 use std::collections::BTreeMap;
 use std::env;
@@ -19,6 +20,7 @@ use langs::bali::BaliCompiler;
 use langs::bob::BobCompiler;
 use langs::boinx::{BoinxInterpreter, parse_boinx};
 use langs::forth::ForthInterpreter;
+use langs::mini::{MiniInterpreter, parse_mini};
 use sova_core::compiler::Compiler;
 use sova_core::vm::interpreter::Interpreter;
 use sova_core::vm::runner::{execute_interpreter, execute_program};
@@ -34,6 +36,7 @@ fn get_lang(lang: &str) -> Option<LangType> {
         "bali" => Some(LangType::Compiled(Box::new(BaliCompiler))),
         "boinx" => Some(LangType::Interpreted("boinx")),
         "forth" => Some(LangType::Interpreted("forth")),
+        "mini" => Some(LangType::Interpreted("mini")),
         _ => None,
     }
 }
@@ -43,7 +46,7 @@ fn print_usage() {
     eprintln!("       run_lang <lang> --file <path>");
     eprintln!("       run_lang <lang> --stdin");
     eprintln!();
-    eprintln!("Languages: bob, bali, boinx, forth");
+    eprintln!("Languages: bob, bali, boinx, forth, mini");
 }
 
 fn main() {
@@ -122,6 +125,20 @@ fn main() {
                 let interp: Box<dyn Interpreter> = Box::new(ForthInterpreter::new(&source));
                 run_and_print(execute_interpreter(interp));
             }
+            "mini" => match parse_mini(&source) {
+                Ok(pattern) => {
+                    println!("--- PARSED (mini) ---");
+                    println!("{:?}", pattern);
+                    println!();
+                    let interp: Box<dyn Interpreter> = Box::new(MiniInterpreter::new(pattern));
+                    run_and_print(execute_interpreter(interp));
+                }
+                Err(e) => {
+                    eprintln!("--- PARSE ERROR ---");
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            },
             _ => unreachable!(),
         },
     }
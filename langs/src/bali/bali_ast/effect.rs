@@ -88,6 +88,7 @@ impl Effect {
                         chan_var.clone(),
                         duration_time_var.clone(),
                         target_device_id_var.clone(),
+                        None,
                     ),
                     0.0.into(),
                 ));
@@ -105,6 +106,7 @@ impl Effect {
                         program_var.clone(),
                         chan_var.clone(),
                         target_device_id_var.clone(),
+                        None,
                     ),
                     0.0.into(),
                 ));
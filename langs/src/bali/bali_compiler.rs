@@ -74,3 +74,36 @@ impl Compiler for BaliCompiler {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(script: &str) -> Program {
+        BaliCompiler
+            .compile(script, &BTreeMap::new())
+            .expect("compilation failed")
+    }
+
+    #[test]
+    fn comments_are_ignored() {
+        let plain = compile("(note 60)\n(note 64)");
+        let commented = [
+            "; intro\n(note 60)\n(note 64)",
+            "(note 60) ; root\n(note 64) # third",
+            "(note 60)\n(note 64)\n; end",
+            "/* two notes */ (note /* root */ 60)\n(note\n  # third\n  64)",
+        ];
+        for script in commented {
+            assert_eq!(compile(script), plain, "with comments: {:?}", script);
+        }
+    }
+
+    #[test]
+    fn comments_do_not_shift_error_spans() {
+        let err = BaliCompiler
+            .compile("/* c */ (note 60) )", &BTreeMap::new())
+            .unwrap_err();
+        assert_eq!((err.from, err.to), (18, 19));
+    }
+}
@@ -8,7 +8,7 @@ use crate::bob::bob_grammar;
 use crate::bob::compile_expr::compile_expr;
 use crate::bob::context::CompileContext;
 use lalrpop_util::ParseError;
-use sova_core::compiler::{CompilationError, Compiler};
+use sova_core::compiler::{CompilationError, CompilationWarning, Compiler};
 use sova_core::vm::Program;
 use std::collections::BTreeMap;
 
@@ -27,8 +27,16 @@ impl Compiler for BobCompiler {
     fn compile(
         &self,
         script: &str,
-        _args: &BTreeMap<String, String>,
+        args: &BTreeMap<String, String>,
     ) -> Result<Program, CompilationError> {
+        self.compile_with_warnings(script, args).map(|(prog, _)| prog)
+    }
+
+    fn compile_with_warnings(
+        &self,
+        script: &str,
+        _args: &BTreeMap<String, String>,
+    ) -> Result<(Program, Vec<CompilationWarning>), CompilationError> {
         let preprocessed = super::bob_preprocess::preprocess(script);
         match bob_grammar::ProgramParser::new().parse(&preprocessed) {
             Ok(parsed) => Ok(bob_as_asm(parsed)),
@@ -53,7 +61,7 @@ impl Compiler for BobCompiler {
     }
 }
 
-fn bob_as_asm(program: BobProgram) -> Program {
+fn bob_as_asm(program: BobProgram) -> (Program, Vec<CompilationWarning>) {
     let mut ctx = CompileContext::new();
 
     // First pass: collect function definitions
@@ -61,7 +69,13 @@ fn bob_as_asm(program: BobProgram) -> Program {
 
     // Second pass: compile expression
     let dest = ctx.temp("_bob_result");
-    compile_expr(&program, &dest, &mut ctx)
+    let prog = compile_expr(&program, &dest, &mut ctx);
+    let warnings = ctx
+        .warnings
+        .into_iter()
+        .map(|info| CompilationWarning::new("Bob", info))
+        .collect();
+    (prog, warnings)
 }
 
 fn collect_function_defs(expr: &BobExpr, ctx: &mut CompileContext) {
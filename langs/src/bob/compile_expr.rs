@@ -659,10 +659,12 @@ pub(crate) fn compile_expr(
                 default_dev: ctx.default_dev,
                 temp_counter: ctx.temp_counter,
                 label_counter: ctx.label_counter,
+                warnings: Vec::new(),
             };
             func_code.extend(compile_expr(body, &Variable::StackBack, &mut func_ctx));
             func_code.push(Instruction::Control(ControlASM::Return));
             ctx.temp_counter = func_ctx.temp_counter;
+            ctx.warnings.append(&mut func_ctx.warnings);
             vec![Instruction::Control(ControlASM::Mov(
                 Variable::Constant(VariableValue::Func(func_code)),
                 dest.clone(),
@@ -681,10 +683,12 @@ pub(crate) fn compile_expr(
                 default_dev: ctx.default_dev,
                 temp_counter: ctx.temp_counter,
                 label_counter: ctx.label_counter,
+                warnings: Vec::new(),
             };
             func_code.extend(compile_expr(body, &Variable::StackBack, &mut func_ctx));
             func_code.push(Instruction::Control(ControlASM::Return));
             ctx.temp_counter = func_ctx.temp_counter;
+            ctx.warnings.append(&mut func_ctx.warnings);
             let func_var = Variable::Instance(format!("_func_{name}"));
             let mut instrs = vec![Instruction::Control(ControlASM::Mov(
                 Variable::Constant(VariableValue::Func(func_code)),
@@ -1107,6 +1111,7 @@ fn compile_fork_branch(body: &BobExpr, ctx: &mut CompileContext) -> Vec<Instruct
         default_dev: ctx.default_dev,
         temp_counter: ctx.temp_counter,
         label_counter: ctx.label_counter,
+        warnings: Vec::new(),
     };
 
     let result_var = Variable::Instance("_bob_branch_result".to_string());
@@ -1115,6 +1120,7 @@ fn compile_fork_branch(body: &BobExpr, ctx: &mut CompileContext) -> Vec<Instruct
     // Sync counters back to parent to avoid collisions with future branches
     ctx.temp_counter = branch_ctx.temp_counter;
     ctx.label_counter = branch_ctx.label_counter;
+    ctx.warnings.append(&mut branch_ctx.warnings);
 
     prog
 }
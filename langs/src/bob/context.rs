@@ -85,6 +85,8 @@ pub(crate) struct CompileContext {
     pub default_dev: i64,
     pub temp_counter: usize,
     pub label_counter: usize,
+    /// Non-fatal issues found while compiling.
+    pub warnings: Vec<String>,
 }
 
 impl CompileContext {
@@ -94,6 +96,7 @@ impl CompileContext {
             default_dev: 1,
             temp_counter: 0,
             label_counter: 0,
+            warnings: Vec::new(),
         }
     }
 
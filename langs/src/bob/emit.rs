@@ -278,6 +278,7 @@ pub(crate) fn emit_as_asm(
     }
     // 4. Program Change
     else if keys.contains(&"pc") {
+        clamp_midi_constants(&mut compiled, ctx, &[("pc", 127), ("bank", 16383)]);
         instrs.extend(emit_midi_program(&compiled, &device_id, ctx));
     }
     // 5. Polyphonic Aftertouch (requires both at AND note)
@@ -296,11 +297,24 @@ pub(crate) fn emit_as_asm(
     else if keys.iter().any(|k| *k == "sound" || *k == "s") {
         instrs.extend(emit_dirt(&compiled, &device_id, ctx));
     }
-    // 9. MIDI Note (only if no sound specified)
+    // 9. MPE Note (a note with an expression of its own)
+    else if keys
+        .iter()
+        .any(|k| *k == "bend" || *k == "press" || *k == "timbre")
+    {
+        clamp_midi_constants(
+            &mut compiled,
+            ctx,
+            &[("note", 127), ("vel", 127), ("press", 127), ("timbre", 127)],
+        );
+        instrs.extend(emit_midi_mpe_note(&compiled, &device_id, ctx));
+    }
+    // 10. MIDI Note (only if no sound specified)
     else if keys.iter().any(|k| *k == "note" || *k == "vel") {
+        clamp_midi_constants(&mut compiled, ctx, &[("note", 127), ("vel", 127), ("rel", 127)]);
         instrs.extend(emit_midi_note(&compiled, &device_id, ctx));
     }
-    // 10. Dirt generic
+    // 11. Dirt generic
     else {
         instrs.extend(emit_dirt_generic(&compiled, &device_id, ctx));
     }
@@ -308,6 +322,25 @@ pub(crate) fn emit_as_asm(
     instrs
 }
 
+/// Clamps literal MIDI values to the range from 0 to their maximum,
+/// warning about every value that had to be changed.
+fn clamp_midi_constants(
+    compiled: &mut HashMap<String, Variable>,
+    ctx: &mut CompileContext,
+    ranges: &[(&str, i64)],
+) {
+    for &(key, max) in ranges {
+        if let Some(Variable::Constant(VariableValue::Integer(value))) = compiled.get_mut(key) {
+            let clamped = (*value).clamp(0, max);
+            if clamped != *value {
+                ctx.warnings
+                    .push(format!("{key} {value} out of MIDI range, clamped to {clamped}"));
+                *value = clamped;
+            }
+        }
+    }
+}
+
 pub(crate) fn emit_midi_note_single(
     compiled: &HashMap<String, Variable>,
     device_id: &Variable,
@@ -338,10 +371,20 @@ pub(crate) fn emit_midi_note_single(
         .cloned()
         .unwrap_or(Variable::Constant(VariableValue::Float(defaults::MIDI_DUR)));
 
+    // Without a release velocity, the note ends with a standard note off
+    let rel = compiled.get("rel").cloned();
+
     let dur_frames_var = Variable::Instance("_bob_dur".to_string());
     let time_var = Variable::Instance("_bob_time".to_string());
 
-    let event = Event::MidiNote(note, vel, chan, dur_frames_var.clone(), device_id.clone());
+    let event = Event::MidiNote(
+        note,
+        vel,
+        chan,
+        dur_frames_var.clone(),
+        device_id.clone(),
+        rel,
+    );
 
     vec![
         Instruction::Control(ControlASM::FloatAsFrames(dur, dur_frames_var)),
@@ -360,13 +403,74 @@ fn emit_midi_note(
 ) -> Vec<Instruction> {
     let device_id = device_id.clone();
     emit_with_expansion(
-        &["note", "vel", "chan", "dur"],
+        &["note", "vel", "chan", "dur", "rel"],
         compiled,
         ctx,
         move |params| emit_midi_note_single(params, &device_id),
     )
 }
 
+pub(crate) fn emit_midi_mpe_note_single(
+    compiled: &HashMap<String, Variable>,
+    device_id: &Variable,
+) -> Vec<Instruction> {
+    let note = compiled
+        .get("note")
+        .cloned()
+        .unwrap_or(Variable::Constant(VariableValue::Integer(
+            defaults::MIDI_NOTE,
+        )));
+
+    let vel = compiled
+        .get("vel")
+        .cloned()
+        .unwrap_or(Variable::Constant(VariableValue::Integer(
+            defaults::MIDI_VEL,
+        )));
+
+    let dur = compiled
+        .get("dur")
+        .cloned()
+        .unwrap_or(Variable::Constant(VariableValue::Float(defaults::MIDI_DUR)));
+
+    let dur_frames_var = Variable::Instance("_bob_dur".to_string());
+    let time_var = Variable::Instance("_bob_time".to_string());
+
+    // The channel is left to the MPE zone of the device
+    let event = Event::MidiMpeNote(
+        note,
+        vel,
+        dur_frames_var.clone(),
+        device_id.clone(),
+        compiled.get("bend").cloned(),
+        compiled.get("press").cloned(),
+        compiled.get("timbre").cloned(),
+    );
+
+    vec![
+        Instruction::Control(ControlASM::FloatAsFrames(dur, dur_frames_var)),
+        Instruction::Control(ControlASM::FloatAsFrames(
+            Variable::Constant(VariableValue::Float(0.0)),
+            time_var.clone(),
+        )),
+        Instruction::Effect(event, time_var),
+    ]
+}
+
+fn emit_midi_mpe_note(
+    compiled: &HashMap<String, Variable>,
+    device_id: &Variable,
+    ctx: &mut CompileContext,
+) -> Vec<Instruction> {
+    let device_id = device_id.clone();
+    emit_with_expansion(
+        &["note", "vel", "dur", "bend", "press", "timbre"],
+        compiled,
+        ctx,
+        move |params| emit_midi_mpe_note_single(params, &device_id),
+    )
+}
+
 pub(crate) fn emit_midi_control_single(
     compiled: &HashMap<String, Variable>,
     device_id: &Variable,
@@ -424,7 +528,10 @@ pub(crate) fn emit_midi_program_single(
             defaults::MIDI_CHAN,
         )));
 
-    emit_immediate(Event::MidiProgram(pc, chan, device_id.clone()))
+    // Without a bank, only the program change is sent
+    let bank = compiled.get("bank").cloned();
+
+    emit_immediate(Event::MidiProgram(pc, chan, device_id.clone(), bank))
 }
 
 fn emit_midi_program(
@@ -433,7 +540,7 @@ fn emit_midi_program(
     ctx: &mut CompileContext,
 ) -> Vec<Instruction> {
     let device_id = device_id.clone();
-    emit_with_expansion(&["pc", "chan"], compiled, ctx, move |params| {
+    emit_with_expansion(&["pc", "chan", "bank"], compiled, ctx, move |params| {
         emit_midi_program_single(params, &device_id)
     })
 }
@@ -79,7 +79,7 @@ fn eu_with_else_branch() {
         .events
         .iter()
         .filter_map(|(e, _)| match e {
-            ConcreteEvent::MidiNote(note, vel, _, _, _) => Some((*note, *vel)),
+            ConcreteEvent::MidiNote(note, vel, _, _, _, _) => Some((*note, *vel)),
             _ => None,
         })
         .collect();
@@ -117,7 +117,7 @@ fn eu_index_for_velocity_curve() {
         .events
         .iter()
         .filter_map(|(e, _)| match e {
-            ConcreteEvent::MidiNote(_, vel, _, _, _) => Some(*vel),
+            ConcreteEvent::MidiNote(_, vel, _, _, _, _) => Some(*vel),
             _ => None,
         })
         .collect();
@@ -318,7 +318,7 @@ fn bin_with_else_branch() {
         .events
         .iter()
         .filter_map(|(e, _)| match e {
-            ConcreteEvent::MidiNote(note, vel, _, _, _) => Some((*note, *vel)),
+            ConcreteEvent::MidiNote(note, vel, _, _, _, _) => Some((*note, *vel)),
             _ => None,
         })
         .collect();
@@ -15,7 +15,7 @@ fn note_list_expands_to_chord() {
         .events
         .iter()
         .map(|(e, _)| match e {
-            ConcreteEvent::MidiNote(n, _, _, _, _) => *n,
+            ConcreteEvent::MidiNote(n, _, _, _, _, _) => *n,
             _ => panic!("Expected MidiNote, got {:?}", e),
         })
         .collect();
@@ -24,7 +24,7 @@ fn note_list_expands_to_chord() {
     // Verify all have same velocity
     for (event, _) in &result.events {
         match event {
-            ConcreteEvent::MidiNote(_, vel, _, _, _) => {
+            ConcreteEvent::MidiNote(_, vel, _, _, _, _) => {
                 assert_eq!(*vel, 100, "All notes should have vel=100");
             }
             _ => panic!("Expected MidiNote"),
@@ -38,7 +38,7 @@ fn note_list_with_parallel_vel_list() {
     assert_eq!(result.events.len(), 2);
 
     match (&result.events[0].0, &result.events[1].0) {
-        (ConcreteEvent::MidiNote(n1, v1, _, _, _), ConcreteEvent::MidiNote(n2, v2, _, _, _)) => {
+        (ConcreteEvent::MidiNote(n1, v1, _, _, _, _), ConcreteEvent::MidiNote(n2, v2, _, _, _, _)) => {
             assert_eq!((*n1, *v1), (60, 100), "First: note=60, vel=100");
             assert_eq!((*n2, *v2), (64, 80), "Second: note=64, vel=80");
         }
@@ -56,7 +56,7 @@ fn shorter_list_wraps_around() {
         .events
         .iter()
         .map(|(e, _)| match e {
-            ConcreteEvent::MidiNote(n, v, _, _, _) => (*n, *v),
+            ConcreteEvent::MidiNote(n, v, _, _, _, _) => (*n, *v),
             _ => panic!("Expected MidiNote"),
         })
         .collect();
@@ -72,7 +72,7 @@ fn all_params_can_be_lists() {
     assert_eq!(result.events.len(), 2);
 
     match (&result.events[0].0, &result.events[1].0) {
-        (ConcreteEvent::MidiNote(n1, v1, c1, _, _), ConcreteEvent::MidiNote(n2, v2, c2, _, _)) => {
+        (ConcreteEvent::MidiNote(n1, v1, c1, _, _, _), ConcreteEvent::MidiNote(n2, v2, c2, _, _, _)) => {
             assert_eq!((*n1, *v1, *c1), (60, 100, 0));
             assert_eq!((*n2, *v2, *c2), (64, 80, 1));
         }
@@ -86,7 +86,7 @@ fn single_note_no_expansion() {
     let result = compile_and_run(">> [note: 60 vel: 100]");
     assert_eq!(result.events.len(), 1);
     match &result.events[0].0 {
-        ConcreteEvent::MidiNote(n, v, _, _, _) => {
+        ConcreteEvent::MidiNote(n, v, _, _, _, _) => {
             assert_eq!((*n, *v), (60, 100));
         }
         _ => panic!("Expected MidiNote"),
@@ -98,7 +98,7 @@ fn single_element_list_same_as_scalar() {
     let result = compile_and_run(">> [note: '[60] vel: 100]");
     assert_eq!(result.events.len(), 1);
     match &result.events[0].0 {
-        ConcreteEvent::MidiNote(n, v, _, _, _) => {
+        ConcreteEvent::MidiNote(n, v, _, _, _, _) => {
             assert_eq!((*n, *v), (60, 100));
         }
         _ => panic!("Expected MidiNote"),
@@ -120,7 +120,7 @@ fn complex_wrap_pattern() {
         .events
         .iter()
         .map(|(e, _)| match e {
-            ConcreteEvent::MidiNote(n, v, c, _, _) => (*n, *v, *c),
+            ConcreteEvent::MidiNote(n, v, c, _, _, _) => (*n, *v, *c),
             _ => panic!("Expected MidiNote"),
         })
         .collect();
@@ -196,7 +196,7 @@ fn pc_list_expansion() {
         .events
         .iter()
         .map(|(e, _)| match e {
-            ConcreteEvent::MidiProgram(pc, _, _) => *pc,
+            ConcreteEvent::MidiProgram(pc, _, _, _) => *pc,
             _ => panic!("Expected MidiProgram"),
         })
         .collect();
@@ -212,7 +212,7 @@ fn pc_chan_parallel() {
         .events
         .iter()
         .map(|(e, _)| match e {
-            ConcreteEvent::MidiProgram(pc, c, _) => (*pc, *c),
+            ConcreteEvent::MidiProgram(pc, c, _, _) => (*pc, *c),
             _ => panic!("Expected MidiProgram"),
         })
         .collect();
@@ -368,7 +368,7 @@ fn note_from_variable_list() {
         .events
         .iter()
         .map(|(e, _)| match e {
-            ConcreteEvent::MidiNote(n, _, _, _, _) => *n,
+            ConcreteEvent::MidiNote(n, _, _, _, _, _) => *n,
             _ => panic!("Expected MidiNote"),
         })
         .collect();
@@ -385,7 +385,7 @@ fn mixed_variable_and_literal_lists() {
         .events
         .iter()
         .map(|(e, _)| match e {
-            ConcreteEvent::MidiNote(n, v, _, _, _) => (*n, *v),
+            ConcreteEvent::MidiNote(n, v, _, _, _, _) => (*n, *v),
             _ => panic!("Expected MidiNote"),
         })
         .collect();
@@ -409,7 +409,7 @@ fn computed_list_values() {
         .events
         .iter()
         .map(|(e, _)| match e {
-            ConcreteEvent::MidiNote(n, _, _, _, _) => *n,
+            ConcreteEvent::MidiNote(n, _, _, _, _, _) => *n,
             _ => panic!("Expected MidiNote"),
         })
         .collect();
@@ -442,7 +442,7 @@ fn list_with_symbols() {
         .events
         .iter()
         .map(|(e, _)| match e {
-            ConcreteEvent::MidiNote(n, _, _, _, _) => *n,
+            ConcreteEvent::MidiNote(n, _, _, _, _, _) => *n,
             _ => panic!("Expected MidiNote"),
         })
         .collect();
@@ -509,7 +509,7 @@ fn mmerge_second_list_wins() {
         .events
         .iter()
         .map(|(e, _)| match e {
-            ConcreteEvent::MidiNote(n, _, _, _, _) => *n,
+            ConcreteEvent::MidiNote(n, _, _, _, _, _) => *n,
             _ => panic!("Expected MidiNote"),
         })
         .collect();
@@ -533,7 +533,7 @@ fn bor_first_list_wins() {
         .events
         .iter()
         .map(|(e, _)| match e {
-            ConcreteEvent::MidiNote(n, _, _, _, _) => *n,
+            ConcreteEvent::MidiNote(n, _, _, _, _, _) => *n,
             _ => panic!("Expected MidiNote"),
         })
         .collect();
@@ -546,7 +546,7 @@ fn inline_bor_with_emit() {
     let result = compile_and_run(">> BOR [note: 60] [note: 64 vel: 80]");
     assert_eq!(result.events.len(), 1);
     match &result.events[0].0 {
-        ConcreteEvent::MidiNote(n, v, _, _, _) => {
+        ConcreteEvent::MidiNote(n, v, _, _, _, _) => {
             assert_eq!(*n, 60, "BOR: first map's note wins");
             assert_eq!(*v, 80, "BOR: second map adds vel");
         }
@@ -564,7 +564,7 @@ fn inline_bor_with_list_expansion() {
         .events
         .iter()
         .map(|(e, _)| match e {
-            ConcreteEvent::MidiNote(n, _, _, _, _) => *n,
+            ConcreteEvent::MidiNote(n, _, _, _, _, _) => *n,
             _ => panic!("Expected MidiNote"),
         })
         .collect();
@@ -1,5 +1,9 @@
 use super::compile_and_run;
+use crate::bob::BobCompiler;
+use sova_core::compiler::Compiler;
 use sova_core::vm::event::ConcreteEvent;
+use sova_core::vm::runner::execute_program;
+use std::collections::BTreeMap;
 
 // ============================================================================
 // Helper macros for event type assertions
@@ -17,7 +21,7 @@ macro_rules! assert_midi_note {
     ($result:expr, $note:expr) => {
         assert_eq!($result.events.len(), 1);
         match &$result.events[0].0 {
-            ConcreteEvent::MidiNote(note, _, _, _, _) => {
+            ConcreteEvent::MidiNote(note, _, _, _, _, _) => {
                 assert_eq!(*note, $note, "Expected note {}, got {}", $note, note);
             }
             other => panic!("Expected MidiNote, got {:?}", other),
@@ -26,7 +30,7 @@ macro_rules! assert_midi_note {
     ($result:expr, $note:expr, $vel:expr) => {
         assert_eq!($result.events.len(), 1);
         match &$result.events[0].0 {
-            ConcreteEvent::MidiNote(note, vel, _, _, _) => {
+            ConcreteEvent::MidiNote(note, vel, _, _, _, _) => {
                 assert_eq!(*note, $note, "Expected note {}, got {}", $note, note);
                 assert_eq!(*vel, $vel, "Expected vel {}, got {}", $vel, vel);
             }
@@ -36,7 +40,7 @@ macro_rules! assert_midi_note {
     ($result:expr, $note:expr, $vel:expr, $chan:expr) => {
         assert_eq!($result.events.len(), 1);
         match &$result.events[0].0 {
-            ConcreteEvent::MidiNote(note, vel, chan, _, _) => {
+            ConcreteEvent::MidiNote(note, vel, chan, _, _, _) => {
                 assert_eq!(*note, $note, "Expected note {}, got {}", $note, note);
                 assert_eq!(*vel, $vel, "Expected vel {}, got {}", $vel, vel);
                 assert_eq!(*chan, $chan, "Expected chan {}, got {}", $chan, chan);
@@ -80,7 +84,7 @@ macro_rules! assert_midi_program {
     ($result:expr, $pc:expr, $chan:expr) => {
         assert_eq!($result.events.len(), 1);
         match &$result.events[0].0 {
-            ConcreteEvent::MidiProgram(pc, chan, _) => {
+            ConcreteEvent::MidiProgram(pc, chan, _, _) => {
                 assert_eq!(*pc, $pc, "Expected pc {}, got {}", $pc, pc);
                 assert_eq!(*chan, $chan, "Expected chan {}, got {}", $chan, chan);
             }
@@ -245,6 +249,58 @@ fn midi_vel_only_triggers_note() {
     assert_midi_note!(result, 60, 127, 0);
 }
 
+#[test]
+fn midi_note_release_velocity() {
+    let result = compile_and_run(">> [note: 60 rel: 30]");
+    assert_midi_note!(result, 60);
+    assert!(matches!(
+        result.events[0].0,
+        ConcreteEvent::MidiNote(_, _, _, _, _, Some(30))
+    ));
+
+    let result = compile_and_run(">> [note: 60]");
+    assert!(matches!(
+        result.events[0].0,
+        ConcreteEvent::MidiNote(_, _, _, _, _, None)
+    ));
+}
+
+#[test]
+fn midi_expression_keys_play_an_mpe_note() {
+    let result = compile_and_run(">> [note: 64 bend: 0.5 timbre: 20]");
+    assert_eq!(result.events.len(), 1);
+    match &result.events[0].0 {
+        ConcreteEvent::MidiMpeNote(note, vel, chan, _, expression, _) => {
+            assert_eq!((*note, *vel), (64, 100));
+            // The channel is left to the MPE zone of the device
+            assert_eq!(*chan, 0);
+            assert_eq!(expression.bend, Some(0.5));
+            assert_eq!(expression.pressure, None);
+            assert_eq!(expression.timbre, Some(20));
+        }
+        other => panic!("Expected MidiMpeNote, got {:?}", other),
+    }
+}
+
+#[test]
+fn midi_out_of_range_literals_are_clamped_with_warnings() {
+    let (prog, warnings) = BobCompiler
+        .compile_with_warnings(">> [note: 200 vel: -5]", &BTreeMap::new())
+        .expect("compilation failed");
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings.iter().all(|w| w.lang == "Bob" && w.info.contains("clamped")));
+    let result = execute_program(prog);
+    assert_midi_note!(result, 127, 0);
+}
+
+#[test]
+fn midi_in_range_literals_have_no_warnings() {
+    let (_, warnings) = BobCompiler
+        .compile_with_warnings(">> [note: 60 vel: 127]", &BTreeMap::new())
+        .expect("compilation failed");
+    assert!(warnings.is_empty());
+}
+
 // ============================================================================
 // MIDI CC Tests
 // ============================================================================
@@ -284,6 +340,35 @@ fn midi_program_change_with_channel() {
     assert_midi_program!(result, 10, 2);
 }
 
+#[test]
+fn midi_program_change_with_bank() {
+    let result = compile_and_run(">> [pc: 12 bank: 130]");
+    assert_midi_program!(result, 12, 0);
+    assert!(matches!(
+        result.events[0].0,
+        ConcreteEvent::MidiProgram(_, _, _, Some(130))
+    ));
+
+    let result = compile_and_run(">> [pc: 12]");
+    assert!(matches!(
+        result.events[0].0,
+        ConcreteEvent::MidiProgram(_, _, _, None)
+    ));
+}
+
+#[test]
+fn midi_program_and_bank_out_of_range_are_clamped() {
+    let (prog, warnings) = BobCompiler
+        .compile_with_warnings(">> [pc: 300 bank: 20000]", &BTreeMap::new())
+        .expect("compilation failed");
+    assert_eq!(warnings.len(), 2);
+    let result = execute_program(prog);
+    assert!(matches!(
+        result.events[0].0,
+        ConcreteEvent::MidiProgram(127, _, _, Some(16383))
+    ));
+}
+
 // ============================================================================
 // MIDI Aftertouch Tests
 // ============================================================================
@@ -71,7 +71,7 @@ impl BoinxLine {
         match item {
             BoinxItem::Note(n) => {
                 let channel = channel.yield_integer(ctx) as u64;
-                Some(ConcreteEvent::MidiNote(*n as u64, 90, channel, dur, device))
+                Some(ConcreteEvent::MidiNote(*n as u64, 90, channel, dur, device, None))
             }
             BoinxItem::ArgMap(map) => {
                 let mut map : HashMap<String, VariableValue> = 
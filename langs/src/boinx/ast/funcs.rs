@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use rand::seq::SliceRandom;
 
 use sova_core::{
-    clock::TimeSpan, log_warn, 
+    clock::TimeSpan, log_warn, util::random,
     vm::{EvaluationContext, variable::VariableValue}
 };
 
@@ -47,12 +47,12 @@ pub fn execute_boinx_function(
     match name {
         "choice" => {
             args = unpack_if_one(args);
-            let i = rand::random_range(0..args.len());
+            let i = random::random_range(0..args.len());
             args.remove(i)
         }
         "shuffle" => {
             args = unpack_if_one(args);
-            args.shuffle(&mut rand::rng());
+            random::with_rng(|rng| args.shuffle(rng));
             Sequence(args)
         }
         "rev" => {
@@ -88,7 +88,7 @@ pub fn execute_boinx_function(
                 let a = a.as_float(ctx);
                 (0.0, a)
             };
-            Number(rand::random_range(i1..i2))
+            Number(random::random_range(i1..i2))
         }
         "irandrange" => {
             let (i1, i2) = if args.len() >= 2 {
@@ -103,7 +103,7 @@ pub fn execute_boinx_function(
                 let a = a.as_integer(ctx);
                 (0, a)
             };
-            Note(rand::random_range(i1..i2))
+            Note(random::random_range(i1..i2))
         }
         "after" => {
             if args.len() > 1 {
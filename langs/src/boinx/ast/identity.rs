@@ -4,6 +4,7 @@ use crate::boinx::ast::{BoinxArithmeticOp, BoinxCompo, BoinxItem};
 use sova_core::{
     clock::TimeSpan,
     log_eprintln,
+    util::random,
     vm::{EvaluationContext, variable::Variable},
 };
 
@@ -81,8 +82,8 @@ pub fn env_func(name: &str, ctx: &EvaluationContext) -> BoinxItem {
         "micros" => Duration(TimeSpan::Micros(ctx.logic_date)),
         "tempo" => Number(ctx.clock.tempo()),
         "quantum" => Number(ctx.clock.quantum()),
-        "rand" => Number(rand::random()),
-        "irand" => Note(rand::random()),
+        "rand" => Number(random::random()),
+        "irand" => Note(random::random()),
         _ if name.starts_with("seq") => {
             let value = &name[3..];
             if let Ok(n) = value.parse::<usize>() {
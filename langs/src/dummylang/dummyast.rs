@@ -56,6 +56,7 @@ impl Inst {
                             0.into(),
                             TimeSpan::Micros(each_duration).into(),
                             DEVICE_NAME.to_string().into(),
+                            None,
                         ),
                         TimeSpan::Micros(each_pause).into(),
                     ))
@@ -84,6 +85,7 @@ impl Inst {
                                 0.into(),
                                 TimeSpan::Micros(duration).into(),
                                 DEVICE_NAME.to_string().into(),
+                                None,
                             ),
                             TimeSpan::Micros(pause).into(),
                         )
@@ -101,6 +103,7 @@ impl Inst {
                         0.into(),
                         TimeSpan::Micros(duration).into(),
                         DEVICE_NAME.to_string().into(),
+                        None,
                     ),
                     TimeSpan::Micros(pause).into(),
                 )]
@@ -3,5 +3,6 @@ pub mod bob;
 pub mod boinx;
 pub mod dummylang;
 pub mod forth;
+pub mod mini;
 // pub mod lua;
 pub mod rhai;
@@ -0,0 +1,31 @@
+use sova_core::compiler::{CompilationError, CompilationState};
+use sova_core::scene::script::Script;
+use sova_core::vm::interpreter::{Interpreter, InterpreterFactory};
+
+use super::interpreter::MiniInterpreter;
+use super::pattern::{MiniError, parse_mini};
+
+pub struct MiniInterpreterFactory;
+
+impl InterpreterFactory for MiniInterpreterFactory {
+    fn name(&self) -> &str {
+        "mini"
+    }
+
+    fn make_instance(&self, script: &Script) -> Result<Box<dyn Interpreter>, String> {
+        let pattern = parse_mini(script.content()).map_err(|e| e.to_string())?;
+        Ok(Box::new(MiniInterpreter::new(pattern)))
+    }
+
+    fn check(&self, script: &Script) -> CompilationState {
+        match parse_mini(script.content()) {
+            Ok(_) => CompilationState::Parsed(None),
+            Err(MiniError { info, from, to }) => CompilationState::Error(CompilationError {
+                lang: self.name().to_owned(),
+                info,
+                from,
+                to,
+            }),
+        }
+    }
+}
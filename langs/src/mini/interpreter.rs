@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use sova_core::clock::{NEVER, SyncTime};
+use sova_core::vm::EvaluationContext;
+use sova_core::vm::event::ConcreteEvent;
+use sova_core::vm::interpreter::Interpreter;
+use sova_core::vm::variable::VariableValue;
+
+use super::pattern::{Hit, Step};
+
+/// Frame variable counting the cycles already played by the frame.
+const CYCLE_VAR: &str = "_mini_cycle";
+
+/// Device receiving the sounds, the same default as the other languages.
+const DEFAULT_DEVICE: usize = 1;
+
+pub struct MiniInterpreter {
+    pattern: Step,
+    hits: Vec<Hit>,
+    dates: Vec<SyncTime>,
+    index: usize,
+    started: bool,
+}
+
+impl MiniInterpreter {
+    pub fn new(pattern: Step) -> Self {
+        Self {
+            pattern,
+            hits: Vec::new(),
+            dates: Vec::new(),
+            index: 0,
+            started: false,
+        }
+    }
+
+    fn start(&mut self, ctx: &mut EvaluationContext) {
+        let cycle = match ctx.frame_vars.get(CYCLE_VAR) {
+            Some(VariableValue::Integer(n)) if *n >= 0 => *n as u64,
+            _ => 0,
+        };
+        ctx.frame_vars.insert(
+            CYCLE_VAR.to_owned(),
+            VariableValue::Integer(cycle as i64 + 1),
+        );
+
+        self.hits = self.pattern.hits(cycle);
+        self.dates = self
+            .hits
+            .iter()
+            .map(|hit| ctx.clock.beats_to_micros(hit.start * ctx.frame_len))
+            .collect();
+        self.started = true;
+    }
+
+    fn event(hit: &Hit) -> ConcreteEvent {
+        let mut args = HashMap::new();
+        args.insert("s".to_owned(), VariableValue::Str(hit.sound.clone()));
+        if let Some(n) = hit.n {
+            args.insert("n".to_owned(), VariableValue::Integer(n));
+        }
+        ConcreteEvent::Dirt {
+            args,
+            device_id: DEFAULT_DEVICE,
+        }
+    }
+}
+
+impl Interpreter for MiniInterpreter {
+    fn execute_next(&mut self, ctx: &mut EvaluationContext) -> (Option<ConcreteEvent>, SyncTime) {
+        if !self.started {
+            self.start(ctx);
+            if let Some(first) = self.dates.first().filter(|date| **date > 0) {
+                return (None, *first);
+            }
+        }
+        let Some(hit) = self.hits.get(self.index) else {
+            return (None, NEVER);
+        };
+        let event = Self::event(hit);
+        let wait = match self.dates.get(self.index + 1) {
+            Some(next) => next - self.dates[self.index],
+            None => NEVER,
+        };
+        self.index += 1;
+        (Some(event), wait)
+    }
+
+    fn has_terminated(&self) -> bool {
+        self.started && self.index >= self.hits.len()
+    }
+
+    fn stop(&mut self) {
+        self.started = true;
+        self.index = self.hits.len();
+    }
+}
@@ -0,0 +1,18 @@
+//! A subset of the TidalCycles mini-notation.
+//!
+//! A script is a sequence of steps dividing the frame evenly. Supported steps:
+//! sample names (`bd`, with an optional sample number as in `bd:3`), rests (`~`),
+//! subdivisions (`[hh hh]`), alternations picking one step per cycle (`<bd sn>`),
+//! and the `*n` / `/n` modifiers to play a step faster or slower, with `n` up to 128.
+//! One cycle lasts one frame, and every execution of the frame plays the next cycle.
+
+mod factory;
+mod interpreter;
+mod pattern;
+
+#[cfg(test)]
+mod tests;
+
+pub use factory::MiniInterpreterFactory;
+pub use interpreter::MiniInterpreter;
+pub use pattern::{Hit, MiniError, Step, parse_mini};
@@ -0,0 +1,262 @@
+use std::fmt;
+
+/// Largest factor of `*` and `/`.
+const MAX_FACTOR: u32 = 128;
+/// Most steps a pattern can visit in a cycle, rests included, as nested factors multiply.
+const MAX_STEPS_PER_CYCLE: u64 = 4096;
+
+/// A step of a mini-notation pattern.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// A sample name, with an optional sample number.
+    Sound(String, Option<i64>),
+    /// Silence.
+    Rest,
+    /// Steps sharing the duration of a single step.
+    Sequence(Vec<Step>),
+    /// One of the steps, chosen in turn at each cycle.
+    Alternation(Vec<Step>),
+    /// A step played `n` times in its own duration.
+    Fast(Box<Step>, u32),
+    /// A step stretched over `n` cycles.
+    Slow(Box<Step>, u32),
+}
+
+/// A sound triggered during a cycle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    /// Onset, as a fraction of the cycle.
+    pub start: f64,
+    pub sound: String,
+    pub n: Option<i64>,
+}
+
+/// An error found while parsing a pattern, positions are byte offsets in the source.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MiniError {
+    pub info: String,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl fmt::Display for MiniError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at {})", self.info, self.from)
+    }
+}
+
+/// Parses a whole pattern, returned as the sequence of its top level steps.
+pub fn parse_mini(source: &str) -> Result<Step, MiniError> {
+    let mut parser = Parser {
+        chars: source.char_indices().collect(),
+        len: source.len(),
+        pos: 0,
+    };
+    let pattern = Step::Sequence(parser.sequence(None)?);
+    if pattern.max_steps() > MAX_STEPS_PER_CYCLE {
+        return Err(MiniError {
+            info: format!("pattern has more than {MAX_STEPS_PER_CYCLE} steps per cycle"),
+            from: 0,
+            to: source.len(),
+        });
+    }
+    Ok(pattern)
+}
+
+struct Parser {
+    chars: Vec<(usize, char)>,
+    len: usize,
+    pos: usize,
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).map(|(_, c)| *c)
+    }
+
+    fn offset(&self) -> usize {
+        self.chars
+            .get(self.pos)
+            .map(|(i, _)| *i)
+            .unwrap_or(self.len)
+    }
+
+    fn error(&self, info: String) -> MiniError {
+        let from = self.offset();
+        let to = self
+            .chars
+            .get(self.pos)
+            .map(|(i, c)| i + c.len_utf8())
+            .unwrap_or(self.len);
+        MiniError { info, from, to }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self.peek().filter(|c| is_name_char(*c)) {
+            word.push(c);
+            self.pos += 1;
+        }
+        word
+    }
+
+    fn sequence(&mut self, closing: Option<char>) -> Result<Vec<Step>, MiniError> {
+        let mut steps = Vec::new();
+        loop {
+            self.skip_whitespace();
+            match self.peek() {
+                None => match closing {
+                    Some(c) => return Err(self.error(format!("missing closing '{c}'"))),
+                    None => return Ok(steps),
+                },
+                Some(c) if Some(c) == closing => {
+                    self.pos += 1;
+                    return Ok(steps);
+                }
+                Some(c @ (']' | '>')) => return Err(self.error(format!("unexpected '{c}'"))),
+                Some(_) => steps.push(self.step()?),
+            }
+        }
+    }
+
+    fn step(&mut self) -> Result<Step, MiniError> {
+        let mut step = self.atom()?;
+        while let Some(op @ ('*' | '/')) = self.peek() {
+            self.pos += 1;
+            let start = self.pos;
+            let word = self.word();
+            let factor = match word.parse::<u32>() {
+                Ok(n) if n > 0 && n <= MAX_FACTOR => n,
+                Ok(n) if n > 0 => {
+                    self.pos = start;
+                    return Err(self.error(format!("factor {n} is above {MAX_FACTOR}")));
+                }
+                _ => {
+                    self.pos = start;
+                    return Err(self.error(format!("expected a positive integer after '{op}'")));
+                }
+            };
+            step = if op == '*' {
+                Step::Fast(Box::new(step), factor)
+            } else {
+                Step::Slow(Box::new(step), factor)
+            };
+        }
+        Ok(step)
+    }
+
+    fn atom(&mut self) -> Result<Step, MiniError> {
+        match self.peek() {
+            Some('[') => {
+                self.pos += 1;
+                Ok(Step::Sequence(self.sequence(Some(']'))?))
+            }
+            Some('<') => {
+                let open = self.pos;
+                self.pos += 1;
+                let steps = self.sequence(Some('>'))?;
+                if steps.is_empty() {
+                    self.pos = open;
+                    return Err(self.error("empty alternation".to_owned()));
+                }
+                Ok(Step::Alternation(steps))
+            }
+            Some('~') => {
+                self.pos += 1;
+                Ok(Step::Rest)
+            }
+            Some(c) if is_name_char(c) => {
+                let sound = self.word();
+                if self.peek() != Some(':') {
+                    return Ok(Step::Sound(sound, None));
+                }
+                self.pos += 1;
+                let start = self.pos;
+                match self.word().parse::<i64>() {
+                    Ok(n) => Ok(Step::Sound(sound, Some(n))),
+                    Err(_) => {
+                        self.pos = start;
+                        Err(self.error(format!("expected a sample number after '{sound}:'")))
+                    }
+                }
+            }
+            Some(c) => Err(self.error(format!("unsupported token '{c}'"))),
+            None => Err(self.error("unexpected end of pattern".to_owned())),
+        }
+    }
+}
+
+impl Step {
+    /// Lists the sounds of the given cycle, in order of onset.
+    pub fn hits(&self, cycle: u64) -> Vec<Hit> {
+        let mut hits = Vec::new();
+        self.arrange(0.0, 1.0, cycle, &mut hits);
+        hits.sort_by(|a, b| a.start.total_cmp(&b.start));
+        hits
+    }
+
+    /// Most steps visited while arranging a cycle. Rests are visited like sounds, so they
+    /// count too, and a repeated step counts at least once per repetition.
+    fn max_steps(&self) -> u64 {
+        match self {
+            Step::Sound(..) | Step::Rest => 1,
+            Step::Sequence(steps) => steps
+                .iter()
+                .fold(0, |total, step| total.saturating_add(step.max_steps())),
+            Step::Alternation(steps) => steps.iter().map(Step::max_steps).max().unwrap_or(0),
+            Step::Fast(step, n) => step.max_steps().max(1).saturating_mul(*n as u64),
+            Step::Slow(step, _) => step.max_steps(),
+        }
+    }
+
+    fn arrange(&self, start: f64, dur: f64, cycle: u64, hits: &mut Vec<Hit>) {
+        match self {
+            Step::Sound(sound, n) => hits.push(Hit {
+                start,
+                sound: sound.clone(),
+                n: *n,
+            }),
+            Step::Rest => (),
+            Step::Sequence(steps) => {
+                let step_dur = dur / steps.len() as f64;
+                for (i, step) in steps.iter().enumerate() {
+                    step.arrange(start + i as f64 * step_dur, step_dur, cycle, hits);
+                }
+            }
+            Step::Alternation(steps) => {
+                let len = steps.len() as u64;
+                steps[(cycle % len) as usize].arrange(start, dur, cycle / len, hits);
+            }
+            Step::Fast(step, n) => {
+                let n = *n as u64;
+                let step_dur = dur / n as f64;
+                for k in 0..n {
+                    step.arrange(start + k as f64 * step_dur, step_dur, cycle * n + k, hits);
+                }
+            }
+            Step::Slow(step, n) => {
+                // Only the slice of the stretched step falling in this cycle is heard
+                let n = *n as u64;
+                let offset = (cycle % n) as f64 * dur;
+                let mut stretched = Vec::new();
+                step.arrange(start - offset, dur * n as f64, cycle / n, &mut stretched);
+                let end = start + dur;
+                hits.extend(
+                    stretched
+                        .into_iter()
+                        .filter(|hit| hit.start > start - 1e-9 && hit.start < end - 1e-9),
+                );
+            }
+        }
+    }
+}
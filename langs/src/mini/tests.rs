@@ -0,0 +1,144 @@
+use sova_core::clock::SyncTime;
+use sova_core::vm::event::ConcreteEvent;
+use sova_core::vm::runner::{Runner, execute_interpreter};
+use sova_core::vm::variable::VariableValue;
+
+use super::{MiniInterpreter, parse_mini};
+
+/// One frame of one beat at the default runner tempo of 120 BPM.
+const CYCLE: SyncTime = 500_000;
+
+fn sounds(result: &sova_core::vm::runner::ExecutionResult) -> Vec<(String, SyncTime)> {
+    result
+        .events
+        .iter()
+        .map(|(event, date)| match event {
+            ConcreteEvent::Dirt { args, .. } => match args.get("s") {
+                Some(VariableValue::Str(s)) => (s.clone(), *date),
+                other => panic!("unexpected sound {other:?}"),
+            },
+            other => panic!("unexpected event {other:?}"),
+        })
+        .collect()
+}
+
+fn run_mini(source: &str) -> Vec<(String, SyncTime)> {
+    let interp = MiniInterpreter::new(parse_mini(source).unwrap());
+    sounds(&execute_interpreter(Box::new(interp)))
+}
+
+/// Plays `cycles` successive executions of the same frame, keeping its variables.
+fn run_cycles(source: &str, cycles: usize) -> Vec<Vec<String>> {
+    let pattern = parse_mini(source).unwrap();
+    let mut runner = Runner::new();
+    let mut played = Vec::new();
+    for _ in 0..cycles {
+        let result = runner.run_interpreter(Box::new(MiniInterpreter::new(pattern.clone())));
+        played.push(sounds(&result).into_iter().map(|(s, _)| s).collect());
+        runner = Runner {
+            frame_vars: result.frame_vars,
+            ..Runner::new()
+        };
+    }
+    played
+}
+
+fn owned(expected: &[(&str, SyncTime)]) -> Vec<(String, SyncTime)> {
+    expected.iter().map(|(s, t)| (s.to_string(), *t)).collect()
+}
+
+#[test]
+fn steps_are_evenly_spaced() {
+    assert_eq!(run_mini("bd sn"), owned(&[("bd", 0), ("sn", CYCLE / 2)]));
+    assert_eq!(
+        run_mini("bd sn hh bd"),
+        owned(&[
+            ("bd", 0),
+            ("sn", CYCLE / 4),
+            ("hh", CYCLE / 2),
+            ("bd", 3 * CYCLE / 4)
+        ])
+    );
+}
+
+#[test]
+fn groups_subdivide_their_step() {
+    assert_eq!(
+        run_mini("bd sn [hh hh] bd"),
+        owned(&[
+            ("bd", 0),
+            ("sn", CYCLE / 4),
+            ("hh", CYCLE / 2),
+            ("hh", 5 * CYCLE / 8),
+            ("bd", 3 * CYCLE / 4),
+        ])
+    );
+    assert_eq!(
+        run_mini("bd [~ [sn sn]]"),
+        owned(&[("bd", 0), ("sn", 3 * CYCLE / 4), ("sn", 7 * CYCLE / 8)])
+    );
+}
+
+#[test]
+fn rests_and_repeats() {
+    assert_eq!(run_mini("~ sn"), owned(&[("sn", CYCLE / 2)]));
+    assert_eq!(
+        run_mini("hh*3 ~"),
+        owned(&[("hh", 0), ("hh", CYCLE / 6), ("hh", 166_667)])
+    );
+    assert!(run_mini("~ ~").is_empty());
+}
+
+#[test]
+fn alternations_and_slow_steps_follow_cycles() {
+    assert_eq!(
+        run_cycles("<bd sn> hh", 3),
+        vec![vec!["bd", "hh"], vec!["sn", "hh"], vec!["bd", "hh"]]
+    );
+    assert_eq!(
+        run_cycles("bd/2 hh", 4),
+        vec![vec!["bd", "hh"], vec!["hh"], vec!["bd", "hh"], vec!["hh"]]
+    );
+    assert_eq!(run_cycles("<bd sn>*2", 1), vec![vec!["bd", "sn"]]);
+}
+
+#[test]
+fn sample_numbers_are_sent_as_n() {
+    let interp = MiniInterpreter::new(parse_mini("bd:3").unwrap());
+    let result = execute_interpreter(Box::new(interp));
+    let ConcreteEvent::Dirt { args, .. } = &result.events[0].0 else {
+        panic!("expected a dirt event");
+    };
+    assert_eq!(args.get("n"), Some(&VariableValue::Integer(3)));
+}
+
+#[test]
+fn unsupported_tokens_are_rejected() {
+    let err = parse_mini("bd sn!").unwrap_err();
+    assert_eq!((err.from, err.to), (5, 6));
+    assert!(err.info.contains("'!'"));
+
+    assert!(
+        parse_mini("bd [sn")
+            .unwrap_err()
+            .info
+            .contains("missing closing ']'")
+    );
+    assert!(parse_mini("bd*0").is_err());
+    assert!(parse_mini("bd sn]").is_err());
+    assert!(parse_mini("<>").is_err());
+}
+
+#[test]
+fn factors_are_bounded() {
+    assert!(parse_mini("bd*128 sn/128").is_ok());
+    let err = parse_mini("bd*4000000000").unwrap_err();
+    assert_eq!((err.from, err.to), (3, 4));
+    assert!(err.info.contains("above 128"));
+    // Nested factors multiply
+    assert!(parse_mini("[[bd*128]*128]*128").is_err());
+    // Rests are visited too, even if they play nothing
+    assert!(parse_mini("~*128*128*128").is_err());
+    assert!(parse_mini("[~ ~]*128*128").is_err());
+    assert!(parse_mini("~*64 bd*64").is_ok());
+}
@@ -1,3 +1,121 @@
+use sova_core::LogMessage;
+
+/// Whether this server was built with the audio engine.
+/// Clients hide their audio controls when it was not.
+pub const AUDIO_AVAILABLE: bool = cfg!(feature = "audio");
+
+/// Part of the sample memory budget above which clients are warned.
+pub const SAMPLE_BUDGET_WARNING: f32 = 0.9;
+
+/// Memory budget of the samples loaded by the audio engine, in megabytes.
+///
+/// The engine evicts samples on its own once its pool is full, which degrades playback,
+/// so clients are warned each time the pool comes close to the budget.
+#[derive(Debug, Clone)]
+pub struct SampleBudget {
+    limit_mb: f32,
+    used_mb: f32,
+    warned: bool,
+}
+
+impl SampleBudget {
+    pub fn new(limit_mb: f32) -> Self {
+        SampleBudget {
+            limit_mb: limit_mb.max(0.0),
+            used_mb: 0.0,
+            warned: false,
+        }
+    }
+
+    pub fn limit_mb(&self) -> f32 {
+        self.limit_mb
+    }
+
+    pub fn used_mb(&self) -> f32 {
+        self.used_mb
+    }
+
+    pub fn remaining_mb(&self) -> f32 {
+        (self.limit_mb - self.used_mb).max(0.0)
+    }
+
+    /// Records the size of the sample pool, as reported by the engine.
+    /// Returns a warning when the pool has just come close to the budget.
+    pub fn update(&mut self, used_mb: f32) -> Option<LogMessage> {
+        self.used_mb = used_mb;
+        if used_mb < self.limit_mb * SAMPLE_BUDGET_WARNING {
+            self.warned = false;
+            return None;
+        }
+        if self.warned {
+            return None;
+        }
+        self.warned = true;
+        Some(LogMessage::warn(format!(
+            "Samples use {:.0} MB of their {:.0} MB budget, {:.0} MB left. \
+             Loading more will evict samples and may degrade playback.",
+            self.used_mb,
+            self.limit_mb,
+            self.remaining_mb()
+        )))
+    }
+}
+
+/// Default weight of each new telemetry reading in the smoothed value.
+pub const DEFAULT_TELEMETRY_SMOOTHING: f32 = 0.3;
+
+/// Exponential smoothing of the scope peaks and CPU load sent to clients,
+/// which would otherwise jitter from one reading to the next.
+#[derive(Debug, Clone)]
+pub struct TelemetrySmoothing {
+    /// Weight of each new reading, from `0` for frozen values to `1` for raw ones.
+    factor: f32,
+    cpu_load: Option<f32>,
+    scope: Vec<(f32, f32)>,
+}
+
+impl TelemetrySmoothing {
+    pub fn new(factor: f32) -> Self {
+        TelemetrySmoothing {
+            factor: factor.clamp(0.0, 1.0),
+            cpu_load: None,
+            scope: Vec::new(),
+        }
+    }
+
+    /// Passes readings through untouched.
+    pub fn raw() -> Self {
+        Self::new(1.0)
+    }
+
+    fn ease(&self, previous: f32, reading: f32) -> f32 {
+        previous + self.factor * (reading - previous)
+    }
+
+    pub fn cpu_load(&mut self, load: f32) -> f32 {
+        let smoothed = match self.cpu_load {
+            Some(previous) => self.ease(previous, load),
+            None => load,
+        };
+        self.cpu_load = Some(smoothed);
+        smoothed
+    }
+
+    /// Smooths each (min, max) pair with the pair at the same position in the previous
+    /// peaks. Peaks of another resolution start over from the new readings.
+    pub fn scope(&mut self, peaks: Vec<(f32, f32)>) -> Vec<(f32, f32)> {
+        if self.scope.len() != peaks.len() {
+            self.scope = peaks;
+            return self.scope.clone();
+        }
+        for (i, (min, max)) in peaks.into_iter().enumerate() {
+            let (previous_min, previous_max) = self.scope[i];
+            self.scope[i] = (self.ease(previous_min, min), self.ease(previous_max, max));
+        }
+        self.scope.clone()
+    }
+}
+
 #[cfg(feature = "audio")]
 pub use doux_sova::{AudioEngineState, DouxConfig, DouxManager};
 
@@ -33,7 +151,7 @@ mod stub {
                 buffer_size: None,
                 active_voices: 0,
                 sample_paths: Vec::new(),
-                error: Some("Audio disabled at compile time".to_string()),
+                error: Some("Audio not compiled in this server".to_string()),
                 cpu_load: 0.0,
                 peak_voices: 0,
                 max_voices: 0,
@@ -46,3 +164,49 @@ mod stub {
 
 #[cfg(not(feature = "audio"))]
 pub use stub::AudioEngineState;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sova_core::Severity;
+
+    #[test]
+    fn sample_budget_warns_once_near_the_limit() {
+        let mut budget = SampleBudget::new(100.0);
+        for used_mb in [10.0, 45.0, 80.0] {
+            assert!(budget.update(used_mb).is_none());
+            assert_eq!(budget.used_mb(), used_mb);
+        }
+        assert_eq!(budget.remaining_mb(), 20.0);
+
+        let warning = budget.update(92.0).expect("no warning near the limit");
+        assert_eq!(warning.level, Severity::Warn);
+        assert!(warning.msg.contains("8 MB left"));
+        assert!(budget.update(97.0).is_none());
+
+        // Warned again once samples have been unloaded and the pool fills up anew
+        assert!(budget.update(50.0).is_none());
+        assert!(budget.update(95.0).is_some());
+        assert_eq!(budget.update(120.0), None);
+        assert_eq!(budget.remaining_mb(), 0.0);
+    }
+
+    #[test]
+    fn smoothed_telemetry_approaches_a_step_change() {
+        let mut smoothing = TelemetrySmoothing::new(0.5);
+        assert_eq!(smoothing.cpu_load(0.0), 0.0);
+        let loads: Vec<f32> = (0..4).map(|_| smoothing.cpu_load(1.0)).collect();
+        assert_eq!(loads, vec![0.5, 0.75, 0.875, 0.9375]);
+
+        smoothing.scope(vec![(0.0, 0.0); 2]);
+        assert_eq!(smoothing.scope(vec![(-1.0, 1.0); 2]), vec![(-0.5, 0.5); 2]);
+        assert_eq!(
+            smoothing.scope(vec![(-1.0, 1.0); 2]),
+            vec![(-0.75, 0.75); 2]
+        );
+
+        let mut raw = TelemetrySmoothing::raw();
+        raw.cpu_load(0.0);
+        assert_eq!(raw.cpu_load(1.0), 1.0);
+    }
+}
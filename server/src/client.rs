@@ -1,22 +1,21 @@
+use crate::codec::{CompressionCodec, decode_frame, encode_frame, frame_length};
 use crate::message::ServerMessage;
+use crate::peer::PeerIdentity;
 use serde::{Deserialize, Serialize};
 use sova_core::log_eprintln;
 use sova_core::protocol::DeviceInfo;
-use sova_core::scene::{ExecutionMode, Frame, Line, Scene};
+use sova_core::scene::{ExecutionMode, Frame, Line, LinePlaybackMode, Scene, SceneMetadata};
 use sova_core::schedule::ActionTiming;
+use sova_core::schedule::OscClockConfig;
 use sova_core::schedule::SchedulerMessage;
+use sova_core::vm::ValueGenerator;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::io::AsyncReadExt;
 use tokio::{
     io::{self, AsyncWriteExt},
     net::TcpStream,
 };
 
-const COMPRESSION_MIN_SIZE: usize = 64;
-const COMPRESSION_ADAPTIVE_THRESHOLD: usize = 256;
-const HIGH_COMPRESSION_CUTOFF: usize = 1024;
-const COMPRESSION_FLAG: u32 = 0x80000000;
-const LENGTH_MASK: u32 = 0x7FFFFFFF;
-
 #[derive(Debug, Clone, Copy)]
 pub enum CompressionStrategy {
     Never,
@@ -28,18 +27,65 @@ pub enum CompressionStrategy {
 pub enum ClientMessage {
     SchedulerControl(SchedulerMessage),
     SetTempo(f64, ActionTiming),
+    /// Adjusts the current tempo by a delta in BPM.
+    NudgeTempo(f64),
+    /// Sets the grid, in beats, on which `ActionTiming::Quantized` edits apply.
+    SetQuantizationGrid(f64),
+    /// Seeds the random source of scripts and random playback, `None` seeds it from entropy.
+    SetRandomSeed(Option<u64>),
     SetName(String),
+    SetIdentity(PeerIdentity),
     GetScene,
     SetScene(Scene, ActionTiming),
+    /// Crossfades from the current scene to a new one over a number of beats,
+    /// both scenes playing until the end of the morph.
+    MorphToScene(Scene, f64),
+    /// Replaces the scene with one written in the scene text format.
+    ImportSceneText(String),
+    /// Restores the scene replaced by the latest scene load, once.
+    UndoSceneLoad,
     GetLine(usize),
     SetLines(Vec<(usize, Line)>, ActionTiming),
     ConfigureLines(Vec<(usize, Line)>, ActionTiming),
+    SetLinePlaybackMode(usize, LinePlaybackMode, ActionTiming),
+    /// Sets the note length multiplier of a line (line_id, gate, timing).
+    SetLineGate(usize, f64, ActionTiming),
+    /// Sends all the MIDI events of a line on a channel (line_id, channel, timing).
+    /// `None` or channel `0` keeps the channels of the scripts.
+    SetLineMidiChannel(usize, Option<u64>, ActionTiming),
+    /// Shifts the MIDI notes of a line by a number of semitones (line_id, semitones, timing).
+    SetLineTranspose(usize, i64, ActionTiming),
+    /// Sets whether a line restarts from its start frame when it is enabled again
+    /// (line_id, retrigger, timing).
+    SetLineRetriggerOnEnable(usize, bool, ActionTiming),
+    /// Drives the frame durations of a line with a generator (line_id, generator, timing).
+    /// `None` restores the static durations.
+    SetLineDurationGenerator(usize, Option<ValueGenerator>, ActionTiming),
+    /// Makes a line play other frames instead of its own every few cycles
+    /// (line_id, fill_frames, every_n). No frames or `0` cycles removes the fill.
+    SetLineFill(usize, Vec<Frame>, usize),
+    /// Sets the grid, in beats, on which the quantized edits of a line apply
+    /// (line_id, grid, timing). `None` follows the global quantization grid.
+    /// Edits with another timing than `ActionTiming::Quantized` keep their own boundary.
+    SetLineQuantization(usize, Option<f64>, ActionTiming),
     AddLine(usize, Line, ActionTiming),
     RemoveLine(usize, ActionTiming),
+    /// Moves a line to another index, along with its frames and playback state.
+    MoveLine(usize, usize, ActionTiming),
     GetFrame(usize, usize),
     SetFrames(Vec<(usize, usize, Frame)>, ActionTiming),
     AddFrame(usize, usize, Frame, ActionTiming),
     RemoveFrame(usize, usize, ActionTiming),
+    /// Moves the trigger of a frame by an offset in beats (line_id, frame_id, offset, timing).
+    SetFrameNudge(usize, usize, f64, ActionTiming),
+    /// Makes a frame disable itself after playing once (line_id, frame_id, one_shot).
+    SetFrameOneShot(usize, usize, bool),
+    /// Enables a selection of frames as one edit, given as (line_id, frame_id) pairs.
+    EnableFramesBatch(Vec<(usize, usize)>, ActionTiming),
+    /// Disables a selection of frames as one edit, given as (line_id, frame_id) pairs.
+    DisableFramesBatch(Vec<(usize, usize)>, ActionTiming),
+    /// Removes a selection of frames as one edit, given as (line_id, frame_id) pairs.
+    RemoveFramesBatch(Vec<(usize, usize)>, ActionTiming),
     GetClock,
     GetPeers,
     Chat(String),
@@ -49,6 +95,7 @@ pub enum ClientMessage {
     TransportStart(ActionTiming),
     TransportStop(ActionTiming),
     SetSceneMode(ExecutionMode, ActionTiming),
+    SetSceneMetadata(SceneMetadata, ActionTiming),
     RequestDeviceList,
     ConnectMidiDeviceByName(String),
     DisconnectMidiDeviceByName(String),
@@ -57,6 +104,21 @@ pub enum ClientMessage {
     UnassignDeviceFromSlot(usize),
     CreateOscDevice(String, String, u16),
     RemoveOscDevice(String),
+    /// Latency of a device in seconds, as in `DeviceInfo::latency`.
+    /// Negative values send its events earlier.
+    SetDeviceLatency(String, f64),
+    /// Number of member channels the MPE notes of a MIDI output are spread across (name, members),
+    /// from 1 to 15. Unknown outputs and other sizes are refused.
+    SetMpeZone(String, usize),
+    GetInstruments,
+    /// Maps an instrument name to a sample folder or synth preset (name, sound),
+    /// `None` removes it.
+    SetInstrument(String, Option<String>),
+    GetLanguages,
+    /// Turns the MIDI metronome on or off, clicking on the given output slot.
+    SetMidiMetronome(usize, bool),
+    /// Mirrors the tempo, beat, bar and transport state to an OSC output, `None` stops it.
+    SetOscClock(Option<OscClockConfig>),
     RestoreDevices(Vec<DeviceInfo>),
     GetAudioEngineState,
     RestartAudioEngine {
@@ -66,9 +128,43 @@ pub enum ClientMessage {
         buffer_size: Option<u32>,
         sample_paths: Vec<String>,
     },
+    LockScene,
+    UnlockScene,
+    /// Compiles a script (content, lang) and plays it once,
+    /// without adding it to the scene. Refused while another client holds the scene lock.
+    AuditionScript(String, String),
+    /// Compiles every script of the scene and reports the results,
+    /// without changing anything.
+    ValidateScene,
+    /// Finds the matches of a regular expression in every script of the scene.
+    SearchScripts(String),
+    /// Counts the lines, frames and scripts of the scene.
+    GetSceneStats,
+    /// Replaces the matches of a regular expression in every script of the scene
+    /// (pattern, replacement), `$1` or `${name}` referring to the captured groups.
+    ReplaceInScripts(String, String),
+    /// Restricts the notifications sent to this client to a subset of lines,
+    /// `None` subscribes to the whole scene again.
+    SubscribeLines(Option<Vec<usize>>),
+    /// Mirrors the editor focus of another peer, by name, until `None` is sent.
+    FollowPeer(Option<String>),
+    /// Asks for a `Pong` carrying the same stamp, to measure the round trip time.
+    Ping(u64),
+}
+
+/// Microseconds since the Unix epoch, the stamp of pings.
+pub(crate) fn unix_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_micros() as u64)
 }
 
 impl ClientMessage {
+    /// A ping stamped with the current time.
+    pub fn ping() -> Self {
+        ClientMessage::Ping(unix_micros())
+    }
+
     pub fn compression_strategy(&self) -> CompressionStrategy {
         match self {
             ClientMessage::StartedEditingFrame(_, _)
@@ -79,16 +175,100 @@ impl ClientMessage {
             | ClientMessage::GetSnapshot
             | ClientMessage::RequestDeviceList
             | ClientMessage::GetAudioEngineState
-            | ClientMessage::RestartAudioEngine { .. } => CompressionStrategy::Never,
+            | ClientMessage::RestartAudioEngine { .. }
+            | ClientMessage::LockScene
+            | ClientMessage::UnlockScene
+            | ClientMessage::Ping(_) => CompressionStrategy::Never,
 
-            ClientMessage::SetScene(_, _) | ClientMessage::SetLines(_, _) => {
-                CompressionStrategy::Always
-            }
+            ClientMessage::SetScene(_, _)
+            | ClientMessage::MorphToScene(_, _)
+            | ClientMessage::SetLines(_, _) => CompressionStrategy::Always,
 
             _ => CompressionStrategy::Adaptive,
         }
     }
 
+    /// Whether this message changes the scene, transport or devices, or makes sound.
+    /// Such messages are refused while another client holds the scene lock.
+    pub fn is_mutating(&self) -> bool {
+        match self {
+            ClientMessage::SetName(_)
+            | ClientMessage::SetIdentity(_)
+            | ClientMessage::GetScene
+            | ClientMessage::GetLine(_)
+            | ClientMessage::GetFrame(_, _)
+            | ClientMessage::GetClock
+            | ClientMessage::GetPeers
+            | ClientMessage::Chat(_)
+            | ClientMessage::GetSnapshot
+            | ClientMessage::StartedEditingFrame(_, _)
+            | ClientMessage::StoppedEditingFrame(_, _)
+            | ClientMessage::RequestDeviceList
+            | ClientMessage::GetInstruments
+            | ClientMessage::GetLanguages
+            | ClientMessage::GetAudioEngineState
+            | ClientMessage::LockScene
+            | ClientMessage::UnlockScene
+            | ClientMessage::ValidateScene
+            | ClientMessage::SearchScripts(_)
+            | ClientMessage::GetSceneStats
+            | ClientMessage::SubscribeLines(_)
+            | ClientMessage::FollowPeer(_)
+            | ClientMessage::Ping(_) => false,
+
+            ClientMessage::SchedulerControl(_)
+            | ClientMessage::SetTempo(_, _)
+            | ClientMessage::NudgeTempo(_)
+            | ClientMessage::SetQuantizationGrid(_)
+            | ClientMessage::SetRandomSeed(_)
+            | ClientMessage::SetScene(_, _)
+            | ClientMessage::MorphToScene(_, _)
+            | ClientMessage::ImportSceneText(_)
+            | ClientMessage::UndoSceneLoad
+            | ClientMessage::SetLines(_, _)
+            | ClientMessage::ConfigureLines(_, _)
+            | ClientMessage::SetLinePlaybackMode(_, _, _)
+            | ClientMessage::SetLineGate(_, _, _)
+            | ClientMessage::SetLineMidiChannel(_, _, _)
+            | ClientMessage::SetLineTranspose(_, _, _)
+            | ClientMessage::SetLineRetriggerOnEnable(_, _, _)
+            | ClientMessage::SetLineDurationGenerator(_, _, _)
+            | ClientMessage::SetLineFill(_, _, _)
+            | ClientMessage::SetLineQuantization(_, _, _)
+            | ClientMessage::AddLine(_, _, _)
+            | ClientMessage::RemoveLine(_, _)
+            | ClientMessage::MoveLine(_, _, _)
+            | ClientMessage::SetFrames(_, _)
+            | ClientMessage::AuditionScript(_, _)
+            | ClientMessage::ReplaceInScripts(_, _)
+            | ClientMessage::AddFrame(_, _, _, _)
+            | ClientMessage::RemoveFrame(_, _, _)
+            | ClientMessage::SetFrameNudge(_, _, _, _)
+            | ClientMessage::SetFrameOneShot(_, _, _)
+            | ClientMessage::EnableFramesBatch(_, _)
+            | ClientMessage::DisableFramesBatch(_, _)
+            | ClientMessage::RemoveFramesBatch(_, _)
+            | ClientMessage::TransportStart(_)
+            | ClientMessage::TransportStop(_)
+            | ClientMessage::SetSceneMode(_, _)
+            | ClientMessage::SetSceneMetadata(_, _)
+            | ClientMessage::ConnectMidiDeviceByName(_)
+            | ClientMessage::DisconnectMidiDeviceByName(_)
+            | ClientMessage::CreateVirtualMidiOutput(_)
+            | ClientMessage::AssignDeviceToSlot(_, _)
+            | ClientMessage::UnassignDeviceFromSlot(_)
+            | ClientMessage::CreateOscDevice(_, _, _)
+            | ClientMessage::RemoveOscDevice(_)
+            | ClientMessage::SetDeviceLatency(_, _)
+            | ClientMessage::SetMpeZone(_, _)
+            | ClientMessage::SetInstrument(_, _)
+            | ClientMessage::SetMidiMetronome(_, _)
+            | ClientMessage::SetOscClock(_)
+            | ClientMessage::RestoreDevices(_)
+            | ClientMessage::RestartAudioEngine { .. } => true,
+        }
+    }
+
     pub fn deserialize(final_bytes: &[u8]) -> io::Result<Option<Self>> {
         match rmp_serde::from_slice::<ClientMessage>(final_bytes) {
             Ok(msg) => Ok(Some(msg)),
@@ -105,6 +285,8 @@ pub struct SovaClient {
     pub port: u16,
     pub stream: Option<TcpStream>,
     pub connected: bool,
+    /// Codec compressing the messages sent, agreed on in the handshake.
+    pub codec: CompressionCodec,
 }
 
 impl SovaClient {
@@ -114,6 +296,7 @@ impl SovaClient {
             port,
             stream: None,
             connected: false,
+            codec: CompressionCodec::default(),
         }
     }
 
@@ -134,16 +317,12 @@ impl SovaClient {
             )
         })?;
 
-        let (final_bytes, is_compressed) = Self::compress_intelligently(&message, &msgpack_bytes)?;
-
-        let mut length = final_bytes.len() as u32;
-        if is_compressed {
-            length |= COMPRESSION_FLAG;
-        }
+        let (header, final_bytes) =
+            encode_frame(message.compression_strategy(), self.codec, &msgpack_bytes)?;
 
         let socket = self.mut_socket()?;
 
-        if let Err(e) = socket.write_all(&length.to_be_bytes()).await {
+        if let Err(e) = socket.write_all(&header.to_be_bytes()).await {
             self.connected = false;
             return Err(e);
         }
@@ -156,47 +335,6 @@ impl SovaClient {
         Ok(())
     }
 
-    fn compress_intelligently(
-        message: &ClientMessage,
-        msgpack_bytes: &[u8],
-    ) -> io::Result<(Vec<u8>, bool)> {
-        match message.compression_strategy() {
-            CompressionStrategy::Never => Ok((msgpack_bytes.to_vec(), false)),
-            CompressionStrategy::Always => {
-                if msgpack_bytes.len() > COMPRESSION_MIN_SIZE {
-                    let compression_level = if msgpack_bytes.len() < HIGH_COMPRESSION_CUTOFF {
-                        1
-                    } else {
-                        3
-                    };
-                    let compressed = zstd::encode_all(msgpack_bytes, compression_level)
-                        .map_err(|e| io::Error::other(format!("Compression failed: {}", e)))?;
-                    if compressed.len() < msgpack_bytes.len() {
-                        Ok((compressed, true))
-                    } else {
-                        Ok((msgpack_bytes.to_vec(), false))
-                    }
-                } else {
-                    Ok((msgpack_bytes.to_vec(), false))
-                }
-            }
-            CompressionStrategy::Adaptive => {
-                if msgpack_bytes.len() < COMPRESSION_ADAPTIVE_THRESHOLD {
-                    Ok((msgpack_bytes.to_vec(), false))
-                } else {
-                    let compression_level = if msgpack_bytes.len() < HIGH_COMPRESSION_CUTOFF {
-                        1
-                    } else {
-                        3
-                    };
-                    let compressed = zstd::encode_all(msgpack_bytes, compression_level)
-                        .map_err(|e| io::Error::other(format!("Compression failed: {}", e)))?;
-                    Ok((compressed, true))
-                }
-            }
-        }
-    }
-
     pub fn mut_socket(&mut self) -> io::Result<&mut TcpStream> {
         match &mut self.stream {
             Some(x) => Ok(x),
@@ -258,9 +396,8 @@ impl SovaClient {
             return Err(e);
         }
 
-        let len_with_flag = u32::from_be_bytes(len_buf);
-        let is_compressed = (len_with_flag & COMPRESSION_FLAG) != 0;
-        let length = len_with_flag & LENGTH_MASK;
+        let header = u32::from_be_bytes(len_buf);
+        let length = frame_length(header);
 
         if length == 0 {
             return Err(io::Error::new(
@@ -269,23 +406,15 @@ impl SovaClient {
             ));
         }
 
-        let mut message_buf = vec![0u8; length as usize];
+        let mut message_buf = vec![0u8; length];
         if let Err(e) = socket.read_exact(&mut message_buf).await {
             self.connected = false;
             return Err(e);
         }
 
-        let final_bytes = if is_compressed {
-            zstd::decode_all(message_buf.as_slice()).map_err(|e| {
-                log_eprintln!("Failed to decompress Zstd data from server: {}", e);
-                io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    format!("Zstd decompression failed: {}", e),
-                )
-            })?
-        } else {
-            message_buf
-        };
+        let final_bytes = decode_frame(header, message_buf).inspect_err(|e| {
+            log_eprintln!("Failed to decompress data from server: {}", e);
+        })?;
 
         rmp_serde::from_slice::<ServerMessage>(&final_bytes).map_err(|e| {
             log_eprintln!("Failed to deserialize MessagePack from server: {}", e);
@@ -0,0 +1,224 @@
+//! Framing and compression of the messages exchanged by clients and the server.
+//!
+//! Each message is preceded by a 4 bytes header holding its length, and flags telling
+//! whether it is compressed and with which codec. Both sides agree on the codec during
+//! the handshake, and every peer speaks Zstd, the codec of the older builds.
+
+use serde::{Deserialize, Serialize};
+use std::io;
+
+use crate::client::CompressionStrategy;
+
+const COMPRESSION_MIN_SIZE: usize = 64;
+const COMPRESSION_ADAPTIVE_THRESHOLD: usize = 256;
+const HIGH_COMPRESSION_CUTOFF: usize = 1024;
+const COMPRESSION_FLAG: u32 = 0x80000000;
+/// Set along with `COMPRESSION_FLAG` on messages compressed with LZ4.
+const LZ4_FLAG: u32 = 0x40000000;
+const LENGTH_MASK: u32 = 0x3FFFFFFF;
+
+/// Codec compressing the messages of a connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompressionCodec {
+    /// Smaller messages, for remote setups short on bandwidth.
+    #[default]
+    Zstd,
+    /// Faster to compress and decompress, for low latency setups on a LAN.
+    Lz4,
+    /// A codec offered by a newer build, which this one skips when negotiating.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Codecs this build can compress and decompress.
+pub const SUPPORTED_CODECS: [CompressionCodec; 2] = [CompressionCodec::Zstd, CompressionCodec::Lz4];
+
+/// Picks the first codec offered by the other side that this build supports,
+/// or Zstd, which every peer speaks, when there is none.
+pub fn negotiate_codec(offered: &[CompressionCodec]) -> CompressionCodec {
+    offered
+        .iter()
+        .copied()
+        .find(|codec| SUPPORTED_CODECS.contains(codec))
+        .unwrap_or_default()
+}
+
+impl CompressionCodec {
+    /// Codecs to offer in a handshake, this one first.
+    pub fn offer(self) -> Vec<CompressionCodec> {
+        let mut codecs = vec![self];
+        codecs.extend(SUPPORTED_CODECS.into_iter().filter(|codec| *codec != self));
+        codecs
+    }
+
+    fn header_flags(self) -> u32 {
+        match self {
+            CompressionCodec::Zstd => COMPRESSION_FLAG,
+            CompressionCodec::Lz4 => COMPRESSION_FLAG | LZ4_FLAG,
+            CompressionCodec::Unknown => COMPRESSION_FLAG,
+        }
+    }
+
+    fn compress(self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            CompressionCodec::Zstd => {
+                let compression_level = if bytes.len() < HIGH_COMPRESSION_CUTOFF {
+                    1
+                } else {
+                    3
+                };
+                zstd::encode_all(bytes, compression_level)
+                    .map_err(|e| io::Error::other(format!("Compression failed: {}", e)))
+            }
+            CompressionCodec::Lz4 => Ok(lz4_flex::compress_prepend_size(bytes)),
+            CompressionCodec::Unknown => Err(io::Error::other("Unknown compression codec")),
+        }
+    }
+
+    fn decompress(self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        let decompressed = match self {
+            CompressionCodec::Zstd => zstd::decode_all(bytes).map_err(|e| e.to_string()),
+            CompressionCodec::Lz4 => {
+                lz4_flex::decompress_size_prepended(bytes).map_err(|e| e.to_string())
+            }
+            CompressionCodec::Unknown => Err("unknown codec".to_string()),
+        };
+        decompressed.map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{:?} decompression failed: {}", self, e),
+            )
+        })
+    }
+}
+
+/// Header and body of a message, compressed with `codec` when the strategy calls for it.
+pub(crate) fn encode_frame(
+    strategy: CompressionStrategy,
+    codec: CompressionCodec,
+    msgpack_bytes: &[u8],
+) -> io::Result<(u32, Vec<u8>)> {
+    let compress = match strategy {
+        CompressionStrategy::Never => false,
+        CompressionStrategy::Always => msgpack_bytes.len() > COMPRESSION_MIN_SIZE,
+        CompressionStrategy::Adaptive => msgpack_bytes.len() >= COMPRESSION_ADAPTIVE_THRESHOLD,
+    };
+    if compress {
+        let compressed = codec.compress(msgpack_bytes)?;
+        // Messages compressed whatever their size are only kept compressed when smaller
+        let keep = !matches!(strategy, CompressionStrategy::Always)
+            || compressed.len() < msgpack_bytes.len();
+        if keep {
+            let header = frame_header(compressed.len())? | codec.header_flags();
+            return Ok((header, compressed));
+        }
+    }
+    Ok((frame_header(msgpack_bytes.len())?, msgpack_bytes.to_vec()))
+}
+
+/// Header announcing a body of `len` bytes, which must leave room for the flags.
+fn frame_header(len: usize) -> io::Result<u32> {
+    u32::try_from(len)
+        .ok()
+        .filter(|len| *len <= LENGTH_MASK)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Message of {} bytes is too large to be sent", len),
+            )
+        })
+}
+
+/// Length of the body announced by a header.
+pub(crate) fn frame_length(header: u32) -> usize {
+    (header & LENGTH_MASK) as usize
+}
+
+/// MessagePack bytes of a message body, decompressed with the codec named by its header.
+pub(crate) fn decode_frame(header: u32, body: Vec<u8>) -> io::Result<Vec<u8>> {
+    if header & COMPRESSION_FLAG == 0 {
+        return Ok(body);
+    }
+    let codec = if header & LZ4_FLAG != 0 {
+        CompressionCodec::Lz4
+    } else {
+        CompressionCodec::Zstd
+    };
+    codec.decompress(&body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::ServerMessage;
+    use crate::peer::PeerIdentity;
+    use sova_core::scene::{Line, Scene};
+
+    #[test]
+    fn large_messages_round_trip_under_each_codec() {
+        let lines = (0..64).map(|_| Line::new(vec![1.0; 16])).collect();
+        let msg = ServerMessage::SceneValue(Scene::new(lines));
+        let msgpack_bytes = rmp_serde::to_vec_named(&msg).unwrap();
+        assert!(msgpack_bytes.len() > HIGH_COMPRESSION_CUTOFF);
+
+        for codec in SUPPORTED_CODECS {
+            let (header, body) =
+                encode_frame(msg.compression_strategy(), codec, &msgpack_bytes).unwrap();
+            assert_ne!(header & COMPRESSION_FLAG, 0);
+            assert_eq!(frame_length(header), body.len());
+            assert!(body.len() < msgpack_bytes.len());
+
+            let decoded = decode_frame(header, body).unwrap();
+            assert_eq!(decoded, msgpack_bytes);
+            match rmp_serde::from_slice::<ServerMessage>(&decoded).unwrap() {
+                ServerMessage::SceneValue(scene) => assert_eq!(scene.n_lines(), 64),
+                other => panic!("expected the scene, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn codecs_fall_back_to_zstd() {
+        assert_eq!(negotiate_codec(&[]), CompressionCodec::Zstd);
+        assert_eq!(
+            negotiate_codec(&CompressionCodec::Lz4.offer()),
+            CompressionCodec::Lz4
+        );
+        assert_eq!(
+            negotiate_codec(&CompressionCodec::Zstd.offer()),
+            CompressionCodec::Zstd
+        );
+    }
+
+    #[test]
+    fn codecs_of_newer_builds_are_skipped() {
+        #[derive(Serialize)]
+        struct Offer {
+            name: &'static str,
+            codecs: Vec<&'static str>,
+        }
+        let offer = Offer {
+            name: "remote",
+            codecs: vec!["Brotli", "Lz4"],
+        };
+        let bytes = rmp_serde::to_vec_named(&offer).unwrap();
+        let identity: PeerIdentity = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(
+            identity.codecs,
+            vec![CompressionCodec::Unknown, CompressionCodec::Lz4]
+        );
+        assert_eq!(negotiate_codec(&identity.codecs), CompressionCodec::Lz4);
+        assert_eq!(
+            negotiate_codec(&[CompressionCodec::Unknown]),
+            CompressionCodec::Zstd
+        );
+    }
+
+    #[test]
+    fn bodies_too_large_for_the_header_are_refused() {
+        assert_eq!(frame_header(1024).unwrap(), 1024);
+        assert_eq!(frame_header(LENGTH_MASK as usize).unwrap(), LENGTH_MASK);
+        let err = frame_header(LENGTH_MASK as usize + 1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,240 @@
+//! Text commands to drive a server without any client, e.g. on a remote machine.
+//!
+//! Commands are read one per line, such as `tempo 130`, `load scene.json` or `panic`.
+//! Blank lines and lines starting with `#` are ignored, so command files can be piped in.
+//!
+//! Languages compiled by an external command are registered here rather than by clients,
+//! as only whoever started the server should decide which programs it runs.
+
+use sova_core::{
+    compiler::ExternalCompiler,
+    schedule::{ActionTiming, SchedulerMessage},
+};
+use std::{io::BufRead, path::PathBuf};
+
+use crate::{scene_file::load_scene_file, server::ServerState};
+
+/// Summary of the commands, printed by `help`.
+pub const CONTROL_HELP: &str = "Commands: tempo <bpm>, play, stop, load <path>, panic, \
+register <lang> <command>, unregister <lang>, help";
+
+/// A command of the control interface.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ControlCommand {
+    Tempo(f64),
+    Play,
+    Stop,
+    /// Replaces the scene with a scene, snapshot or project file.
+    Load(PathBuf),
+    /// Stops the transport and sends all notes off to every MIDI output.
+    Panic,
+    /// Compiles the scripts of a language with an external command, which reads a script
+    /// on its standard input.
+    Register {
+        lang: String,
+        command: String,
+    },
+    /// Removes the compiler or interpreter of a language.
+    Unregister(String),
+    Help,
+}
+
+impl ControlCommand {
+    /// Parses a line of input, `None` for blank lines and comments.
+    pub fn parse(line: &str) -> Result<Option<Self>, String> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+        let (name, arg) = match line.split_once(char::is_whitespace) {
+            Some((name, arg)) => (name, arg.trim()),
+            None => (line, ""),
+        };
+        let command = match (name, arg) {
+            ("tempo", arg) => match arg.parse::<f64>() {
+                Ok(bpm) if bpm.is_finite() && bpm > 0.0 => ControlCommand::Tempo(bpm),
+                _ => {
+                    return Err(format!(
+                        "Invalid tempo '{}', expected a positive number.",
+                        arg
+                    ));
+                }
+            },
+            ("load", "") => return Err("Missing the path of the scene to load.".to_string()),
+            ("load", path) => ControlCommand::Load(PathBuf::from(path)),
+            ("register", arg) => match arg.split_once(char::is_whitespace) {
+                Some((lang, command)) => ControlCommand::Register {
+                    lang: lang.to_string(),
+                    command: command.trim().to_string(),
+                },
+                None => {
+                    return Err("Usage: register <lang> <command>".to_string());
+                }
+            },
+            ("unregister", "") => return Err("Missing the language to unregister.".to_string()),
+            ("unregister", lang) => ControlCommand::Unregister(lang.to_string()),
+            ("play", "") => ControlCommand::Play,
+            ("stop", "") => ControlCommand::Stop,
+            ("panic", "") => ControlCommand::Panic,
+            ("help", "") => ControlCommand::Help,
+            ("play" | "stop" | "panic" | "help", _) => {
+                return Err(format!("'{}' takes no argument.", name));
+            }
+            _ => return Err(format!("Unknown command '{}'. {}", name, CONTROL_HELP)),
+        };
+        Ok(Some(command))
+    }
+
+    /// Messages sent to the scheduler to carry out the command.
+    pub fn scheduler_messages(&self) -> Result<Vec<SchedulerMessage>, String> {
+        let messages = match self {
+            ControlCommand::Tempo(bpm) => {
+                vec![SchedulerMessage::SetTempo(*bpm, ActionTiming::Immediate)]
+            }
+            ControlCommand::Play => vec![SchedulerMessage::TransportStart(ActionTiming::Immediate)],
+            ControlCommand::Stop | ControlCommand::Panic => {
+                vec![SchedulerMessage::TransportStop(ActionTiming::Immediate)]
+            }
+            ControlCommand::Load(path) => {
+                let scene = load_scene_file(path)?;
+                vec![SchedulerMessage::SetScene(scene, ActionTiming::Immediate)]
+            }
+            ControlCommand::Register { .. }
+            | ControlCommand::Unregister(_)
+            | ControlCommand::Help => Vec::new(),
+        };
+        Ok(messages)
+    }
+}
+
+/// Runs the commands read from `input` until it ends, reporting errors without stopping.
+pub fn run_control(input: impl BufRead, state: &ServerState) {
+    for line in input.lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let command = match ControlCommand::parse(&line) {
+            Ok(Some(command)) => command,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("[!] {}", e);
+                continue;
+            }
+        };
+        let messages = match command.scheduler_messages() {
+            Ok(messages) => messages,
+            Err(e) => {
+                eprintln!("[!] {}", e);
+                continue;
+            }
+        };
+        for message in messages {
+            if state.sched_iface.send(message).is_err() {
+                eprintln!("[!] Scheduler is gone, control interface stopped.");
+                return;
+            }
+        }
+        let languages = match &command {
+            ControlCommand::Register { lang, command } => {
+                let compiler = ExternalCompiler::new(lang.clone(), command.clone());
+                state.register_compiler(compiler, None)
+            }
+            ControlCommand::Unregister(lang) => state.unregister_language(lang),
+            _ => Ok(()),
+        };
+        if let Err(e) = languages {
+            eprintln!("[!] {}", e);
+            continue;
+        }
+        match command {
+            ControlCommand::Panic => state.devices.panic_all_midi_outputs(),
+            ControlCommand::Help => println!("{}", CONTROL_HELP),
+            _ => println!("[ control ] {}", line.trim()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::server_state;
+    use sova_core::scene::{Line, Scene};
+    use std::{fs, io::Cursor};
+
+    #[test]
+    fn invalid_commands_are_explained() {
+        assert_eq!(
+            ControlCommand::parse("  tempo 130 "),
+            Ok(Some(ControlCommand::Tempo(130.0)))
+        );
+        assert_eq!(ControlCommand::parse("# tempo 90"), Ok(None));
+        assert!(
+            ControlCommand::parse("tempo fast")
+                .unwrap_err()
+                .contains("Invalid tempo")
+        );
+        assert!(ControlCommand::parse("tempo -4").is_err());
+        assert!(ControlCommand::parse("load").is_err());
+        assert!(ControlCommand::parse("panic now").is_err());
+        assert_eq!(
+            ControlCommand::parse("register lisp  sova-lisp --asm"),
+            Ok(Some(ControlCommand::Register {
+                lang: "lisp".to_string(),
+                command: "sova-lisp --asm".to_string(),
+            }))
+        );
+        assert!(ControlCommand::parse("register lisp").is_err());
+        assert!(ControlCommand::parse("unregister").is_err());
+        assert!(
+            ControlCommand::parse("rewind")
+                .unwrap_err()
+                .contains("Unknown command")
+        );
+    }
+
+    #[test]
+    fn control_commands_reach_the_scheduler() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("set.json");
+        let scene = Scene::new(vec![Line::new(vec![1.0]), Line::new(vec![1.0])]);
+        fs::write(&path, serde_json::to_string(&scene).unwrap()).unwrap();
+        let commands = format!(
+            "tempo 130\n\n# comments are skipped\nrewind\nplay\nload {}\nload missing.json\npanic\n",
+            path.display()
+        );
+
+        let (state, sched_rx) = server_state();
+        run_control(Cursor::new(commands), &state);
+
+        let messages: Vec<SchedulerMessage> = sched_rx.try_iter().collect();
+        assert_eq!(messages.len(), 4);
+        assert!(matches!(
+            messages[0],
+            SchedulerMessage::SetTempo(bpm, ActionTiming::Immediate) if bpm == 130.0
+        ));
+        assert!(matches!(messages[1], SchedulerMessage::TransportStart(_)));
+        match &messages[2] {
+            SchedulerMessage::SetScene(loaded, _) => assert_eq!(loaded.n_lines(), 2),
+            other => panic!("expected the loaded scene, got {other:?}"),
+        }
+        assert!(matches!(messages[3], SchedulerMessage::TransportStop(_)));
+    }
+
+    #[test]
+    fn languages_are_registered_from_the_control_interface() {
+        let (state, sched_rx) = server_state();
+        let has_lisp = |state: &ServerState| state.languages().languages().any(|l| l == "lisp");
+
+        run_control(Cursor::new("register lisp sova-lisp --asm\n"), &state);
+        assert!(has_lisp(&state));
+        run_control(Cursor::new("unregister lisp\nunregister lisp\n"), &state);
+        assert!(!has_lisp(&state));
+
+        // The second removal fails, without reaching the scheduler
+        let updates = sched_rx
+            .try_iter()
+            .filter(|message| matches!(message, SchedulerMessage::SetLanguages(_)))
+            .count();
+        assert_eq!(updates, 2);
+    }
+}
@@ -1,12 +1,28 @@
 pub mod audio;
 pub mod client;
+mod codec;
+pub mod control;
 mod message;
+mod peer;
+pub mod recorder;
+mod scene_file;
 mod server;
+#[cfg(test)]
+mod test_support;
 
-pub use audio::AudioEngineState;
+pub use audio::{AUDIO_AVAILABLE, AudioEngineState};
 pub use client::{ClientMessage, CompressionStrategy, SovaClient};
-pub use message::ServerMessage;
+pub use codec::{CompressionCodec, SUPPORTED_CODECS, negotiate_codec};
+pub use control::{CONTROL_HELP, ControlCommand, run_control};
+pub use message::{
+    DEGRADED_ROUND_TRIP, MIN_PROTOCOL_VERSION, PEER_IDENTITY_PROTOCOL_VERSION,
+    PING_PROTOCOL_VERSION, PROTOCOL_VERSION, ServerMessage, negotiate_protocol_version,
+};
+pub use peer::PeerIdentity;
+pub use recorder::{SessionRecorder, load_recording, replay_session};
+pub use scene_file::{SnapshotFile, default_scene, initial_scene, load_scene_file};
 pub use server::{
-    AudioRestartConfig, AudioRestartRequest, DEFAULT_CLIENT_NAME, ServerState, Snapshot,
-    SovaCoreServer,
+    AudioRestartConfig, AudioRestartRequest, ConnectionSettings, DEFAULT_CLIENT_NAME,
+    DEFAULT_KEEPALIVE_INTERVAL_SECS, DEFAULT_KEEPALIVE_SECS, DEFAULT_WRITE_TIMEOUT_SECS,
+    IdleStopSettings, SceneStats, ServerState, Snapshot, SovaCoreServer,
 };
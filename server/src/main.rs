@@ -1,26 +1,36 @@
 use langs::{
     bali::BaliCompiler, bob::BobCompiler, boinx::BoinxInterpreterFactory,
-    forth::ForthInterpreterFactory,
+    forth::ForthInterpreterFactory, mini::MiniInterpreterFactory,
 };
 #[cfg(feature = "audio")]
 use sova_core::clock::Clock;
 use sova_core::clock::ClockServer;
+use sova_core::compiler::CompilationState;
 use sova_core::device_map::DeviceMap;
-use sova_core::scene::{Line, Scene};
+use sova_core::instruments::InstrumentMap;
+use sova_core::scene::script::Script;
 use sova_core::schedule::ActionTiming;
-use sova_core::schedule::{SchedulerMessage, SovaNotification};
+use sova_core::schedule::{EmptyFrameBehavior, SchedulerMessage, SovaNotification};
 use sova_core::vm::LanguageCenter;
-use sova_core::vm::Transcoder;
+use sova_core::vm::{DEFAULT_COMPILE_CACHE_CAPACITY, Transcoder};
 use sova_core::vm::interpreter::InterpreterDirectory;
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use std::io::ErrorKind;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex as StdMutex};
+use std::time::Duration;
 use thread_priority::{ThreadPriority, set_current_thread_priority};
 use tokio::sync::Mutex;
 
-use sova_server::{AudioEngineState, AudioRestartConfig, AudioRestartRequest, ServerState, SovaCoreServer};
+#[cfg(feature = "audio")]
+use sova_server::{AudioRestartConfig, audio::DEFAULT_TELEMETRY_SMOOTHING};
+use sova_server::{
+    AudioEngineState, AudioRestartRequest, CONTROL_HELP, ConnectionSettings,
+    DEFAULT_KEEPALIVE_INTERVAL_SECS, DEFAULT_KEEPALIVE_SECS, DEFAULT_WRITE_TIMEOUT_SECS,
+    IdleStopSettings, ServerState, SessionRecorder, SovaCoreServer, initial_scene, load_recording,
+    replay_session, run_control,
+};
 
 #[cfg(feature = "audio")]
 struct AudioRuntime {
@@ -28,7 +38,6 @@ struct AudioRuntime {
     running: Arc<AtomicBool>,
 }
 
-#[cfg(feature = "audio")]
 use std::path::PathBuf;
 
 pub const DEFAULT_MIDI_OUTPUT: &str = "Sova";
@@ -70,6 +79,78 @@ struct Cli {
     #[arg(short, long, value_name = "BEATS", default_value_t = DEFAULT_QUANTUM)]
     quantum: f64,
 
+    /// Scene, snapshot or project file (JSON) to start with instead of the default scene
+    #[arg(long, value_name = "PATH")]
+    initial_scene: Option<PathBuf>,
+
+    /// Record every client message to this file (line-delimited JSON), to replay the session later
+    #[arg(long, value_name = "PATH")]
+    record: Option<PathBuf>,
+
+    /// Replay a recorded session on startup, to reproduce the state it led to
+    #[arg(long, value_name = "PATH")]
+    replay: Option<PathBuf>,
+
+    /// Seed of the random values drawn by scripts, so that a set plays the same on every run
+    #[arg(long, value_name = "SEED")]
+    seed: Option<u64>,
+
+    /// Name of the client allowed to lock the scene against edits from the other clients
+    #[arg(long, value_name = "NAME")]
+    lead: Option<String>,
+
+    /// Read control commands (tempo 130, load scene.json, panic...) from the standard input
+    #[arg(long, default_value_t = false)]
+    control: bool,
+
+    /// Number of compiled scripts kept to skip recompiling identical ones (0 disables)
+    #[arg(long, value_name = "SCRIPTS", default_value_t = DEFAULT_COMPILE_CACHE_CAPACITY)]
+    compile_cache: usize,
+
+    /// Seconds of silence before a client connection gets probed by TCP keepalive (0 disables)
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_KEEPALIVE_SECS)]
+    keepalive: u64,
+
+    /// Seconds between two TCP keepalive probes
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_KEEPALIVE_INTERVAL_SECS)]
+    keepalive_interval: u64,
+
+    /// Seconds after which a client that stops reading is disconnected (0 disables)
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_WRITE_TIMEOUT_SECS)]
+    write_timeout: u64,
+
+    /// Seconds after which a client that sends nothing is disconnected (0 disables)
+    #[arg(long, value_name = "SECS", default_value_t = 0)]
+    idle_timeout: u64,
+
+    /// Minutes without any client edit after which the transport stops (0 disables)
+    #[arg(long, value_name = "MINUTES", default_value_t = 0)]
+    idle_stop: u64,
+
+    /// Start the transport again when a client becomes active after an idle stop
+    #[arg(long, default_value_t = false, requires = "idle_stop")]
+    idle_resume: bool,
+
+    /// What to do when a line reaches a frame without a script
+    #[arg(long, value_enum, value_name = "MODE", default_value_t = EmptyFrameMode::Ignore)]
+    empty_frames: EmptyFrameMode,
+
+    /// Script played on frames without a script, with `--empty-frames play`
+    #[arg(long, value_name = "SCRIPT", required_if_eq("empty_frames", "play"))]
+    empty_frame_script: Option<String>,
+
+    /// Language of the script played on frames without a script
+    #[arg(long, value_name = "LANG", default_value = "bob")]
+    empty_frame_lang: String,
+
+    /// Keep frames enabled after their script panicked, instead of disabling them
+    #[arg(long, default_value_t = false)]
+    keep_panicking_frames: bool,
+
+    /// JSON file mapping instrument names to sample folders or synth presets
+    #[arg(long, value_name = "PATH")]
+    instruments: Option<PathBuf>,
+
     #[cfg(feature = "audio")]
     /// Disable audio engine (no Doux)
     #[arg(long, default_value_t = false)]
@@ -99,6 +180,32 @@ struct Cli {
     /// Sample directory path (can be specified multiple times)
     #[arg(long = "sample-path", value_name = "PATH", action = clap::ArgAction::Append)]
     sample_paths: Vec<PathBuf>,
+
+    #[cfg(feature = "audio")]
+    /// Memory budget of the loaded samples in MB, clients are warned as it fills up
+    #[arg(long, value_name = "MB")]
+    sample_budget: Option<f32>,
+
+    #[cfg(feature = "audio")]
+    /// Weight of each new reading in the scope and CPU load sent to clients, 1 for raw values
+    #[arg(long, value_name = "FACTOR", default_value_t = DEFAULT_TELEMETRY_SMOOTHING)]
+    telemetry_smoothing: f32,
+
+    #[cfg(feature = "audio")]
+    /// Send the scope and CPU load without smoothing, for debugging
+    #[arg(long)]
+    raw_telemetry: bool,
+}
+
+/// What the scheduler does on frames without a script.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum EmptyFrameMode {
+    /// Play nothing, as if the frame was silent
+    Ignore,
+    /// Send a warning naming the line and the frame
+    Warn,
+    /// Play the script given by `--empty-frame-script`
+    Play,
 }
 
 #[tokio::main]
@@ -139,11 +246,26 @@ async fn main() {
         }
     }
 
+    if let Some(path) = cli.instruments.as_deref() {
+        let instruments = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                serde_json::from_str::<InstrumentMap>(&json).map_err(|e| e.to_string())
+            });
+        match instruments {
+            Ok(instruments) => devices.set_instruments(instruments),
+            Err(e) => {
+                eprintln!("Failed to load instruments from {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+
     let audio_engine_state = Arc::new(StdMutex::new(AudioEngineState::default()));
 
     #[cfg(feature = "audio")]
-    let (audio_restart_tx, audio_runtime) = if !cli.no_audio {
-        use sova_server::audio::{DouxConfig, DouxManager};
+    let (audio_restart_tx, audio_hush_tx, audio_runtime) = if !cli.no_audio {
+        use sova_server::audio::{DouxConfig, DouxManager, SampleBudget, TelemetrySmoothing};
 
         let initial_config = AudioRestartConfig {
             device: cli.audio_device.clone(),
@@ -154,12 +276,19 @@ async fn main() {
         };
 
         let (restart_tx, restart_rx) = crossbeam_channel::unbounded::<AudioRestartRequest>();
+        let (hush_tx, hush_rx) = crossbeam_channel::unbounded::<()>();
         let running = Arc::new(AtomicBool::new(true));
         let running_flag = Arc::clone(&running);
         let state_cache = Arc::clone(&audio_engine_state);
         let scope_sender = update_sender.clone();
         let devices_clone = Arc::clone(&devices);
         let clock_server_clone = Arc::clone(&clock_server);
+        let mut sample_budget = cli.sample_budget.map(SampleBudget::new);
+        let mut smoothing = if cli.raw_telemetry {
+            TelemetrySmoothing::raw()
+        } else {
+            TelemetrySmoothing::new(cli.telemetry_smoothing)
+        };
 
         let audio_thread_handle = std::thread::spawn(move || {
             use std::collections::HashMap;
@@ -271,6 +400,10 @@ async fn main() {
             let mut frame_counter = 0u32;
 
             while running_flag.load(Ordering::Relaxed) {
+                if let (Ok(()), Some(mgr)) = (hush_rx.try_recv(), manager.as_mut()) {
+                    mgr.hush();
+                }
+
                 if let Ok(request) = restart_rx.try_recv() {
                     println!("[ audio ] Received restart request");
 
@@ -335,18 +468,24 @@ async fn main() {
 
                 if let Some(ref mgr) = manager {
                     if let Some(scope) = mgr.scope_capture() {
-                        let peaks = scope.read_peaks(256);
+                        let peaks = smoothing.scope(scope.read_peaks(256));
                         let _ = scope_sender.send(SovaNotification::ScopeData(peaks));
                     }
 
                     if frame_counter % 6 == 0 {
                         if let Ok(engine) = mgr.engine_handle().lock() {
                             if let Ok(mut cache) = state_cache.lock() {
-                                cache.cpu_load = engine.metrics.load.get_load();
+                                cache.cpu_load = smoothing.cpu_load(engine.metrics.load.get_load());
                                 cache.active_voices = engine.active_voices;
                                 cache.peak_voices = engine.metrics.peak_voices.load(Ordering::Relaxed) as usize;
                                 cache.schedule_depth = engine.metrics.schedule_depth.load(Ordering::Relaxed) as usize;
                                 cache.sample_pool_mb = engine.metrics.sample_pool_mb();
+                                let warning = sample_budget
+                                    .as_mut()
+                                    .and_then(|budget| budget.update(cache.sample_pool_mb));
+                                if let Some(warning) = warning {
+                                    let _ = scope_sender.send(SovaNotification::Log(warning));
+                                }
                             }
                         }
                     }
@@ -362,6 +501,7 @@ async fn main() {
 
         (
             Some(restart_tx),
+            Some(hush_tx),
             Some(AudioRuntime {
                 audio_thread_handle,
                 running,
@@ -369,22 +509,26 @@ async fn main() {
         )
     } else {
         println!("Audio engine disabled (--no-audio flag).");
-        (None, None)
+        (None, None, None)
     };
 
     #[cfg(not(feature = "audio"))]
     let audio_restart_tx: Option<crossbeam_channel::Sender<AudioRestartRequest>> = None;
+    #[cfg(not(feature = "audio"))]
+    let audio_hush_tx: Option<crossbeam_channel::Sender<()>> = None;
 
     #[cfg(not(feature = "audio"))]
     println!("Audio engine not compiled (build without 'audio' feature).");
 
     let mut transcoder = Transcoder::default();
+    transcoder.cache.set_capacity(cli.compile_cache);
     transcoder.add_compiler(BaliCompiler);
     transcoder.add_compiler(BobCompiler);
 
     let mut interpreters = InterpreterDirectory::new();
     interpreters.add_factory(BoinxInterpreterFactory);
     interpreters.add_factory(ForthInterpreterFactory);
+    interpreters.add_factory(MiniInterpreterFactory);
 
     let languages = Arc::new(LanguageCenter {
         transcoder,
@@ -398,7 +542,7 @@ async fn main() {
             languages.clone(),
         );
 
-    let initial_scene = Scene::new(vec![Line::new(vec![1.0])]);
+    let initial_scene = initial_scene(cli.initial_scene.as_deref());
     let scene_image = Arc::new(Mutex::new(initial_scene.clone()));
 
     if let Err(e) = sched_iface.send(SchedulerMessage::SetScene(
@@ -409,7 +553,48 @@ async fn main() {
         std::process::exit(1);
     }
 
-    let server_state = ServerState::new(
+    if let Some(seed) = cli.seed {
+        let seeded = sched_iface.send(SchedulerMessage::SetRandomSeed(Some(seed)));
+        if let Err(e) = seeded {
+            eprintln!("Failed to send random seed to scheduler: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    let empty_frames = match cli.empty_frames {
+        EmptyFrameMode::Ignore => EmptyFrameBehavior::Ignore,
+        EmptyFrameMode::Warn => EmptyFrameBehavior::Warn,
+        EmptyFrameMode::Play => {
+            let content = cli.empty_frame_script.clone().unwrap_or_default();
+            let mut script = Script::new(content, cli.empty_frame_lang.clone());
+            languages.blocking_process(&mut script);
+            match script.compilation_state() {
+                CompilationState::Compiled(_) | CompilationState::Parsed(_) => (),
+                CompilationState::Error(e) => {
+                    eprintln!("Invalid script for empty frames: {}", e.info);
+                    std::process::exit(1);
+                }
+                _ => {
+                    eprintln!("Unknown language for empty frames: {}", script.lang());
+                    std::process::exit(1);
+                }
+            }
+            EmptyFrameBehavior::Play(script)
+        }
+    };
+    if let Err(e) = sched_iface.send(SchedulerMessage::SetEmptyFrameBehavior(empty_frames)) {
+        eprintln!("Failed to send empty frame behavior to scheduler: {}", e);
+        std::process::exit(1);
+    }
+    if cli.keep_panicking_frames {
+        let message = SchedulerMessage::SetDisablePanickingFrames(false);
+        if let Err(e) = sched_iface.send(message) {
+            eprintln!("Failed to send panicking frames setting: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    let mut server_state = ServerState::new(
         scene_image,
         clock_server,
         devices.clone(),
@@ -419,6 +604,61 @@ async fn main() {
         audio_engine_state,
         audio_restart_tx,
     );
+    let seconds = |secs: u64| (secs > 0).then(|| Duration::from_secs(secs));
+    server_state.connection = ConnectionSettings {
+        keepalive: seconds(cli.keepalive),
+        keepalive_interval: Duration::from_secs(cli.keepalive_interval.max(1)),
+        write_timeout: seconds(cli.write_timeout),
+        idle_timeout: seconds(cli.idle_timeout),
+    };
+    server_state.idle_stop =
+        seconds(cli.idle_stop.saturating_mul(60)).map(|after| IdleStopSettings {
+            after,
+            resume: cli.idle_resume,
+        });
+    server_state.scene_lead = cli.lead.clone();
+    server_state.audio_hush_tx = audio_hush_tx;
+
+    if let Some(path) = cli.replay.as_deref() {
+        match load_recording(path) {
+            Ok(messages) => {
+                println!(
+                    "Replaying {} recorded messages from '{}'.",
+                    messages.len(),
+                    path.display()
+                );
+                replay_session(&server_state, messages).await;
+            }
+            Err(e) => {
+                eprintln!("[!] {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(path) = cli.record.as_deref() {
+        match SessionRecorder::create(path) {
+            Ok(recorder) => {
+                println!("Recording client messages to '{}'.", path.display());
+                server_state.recorder = Some(Arc::new(recorder));
+            }
+            Err(e) => {
+                eprintln!("Failed to create recording '{}': {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if cli.control {
+        let control_state = server_state.clone();
+        std::thread::spawn(move || {
+            println!(
+                "Reading control commands from the standard input. {}",
+                CONTROL_HELP
+            );
+            run_control(std::io::stdin().lock(), &control_state);
+        });
+    }
 
     let server = SovaCoreServer::new(cli.ip, cli.port, server_state);
     println!("Starting Sova server on {}:{}...", server.ip, server.port);
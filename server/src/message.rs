@@ -1,17 +1,57 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    time::Duration,
+};
 
 use crate::audio::AudioEngineState;
 use serde::{Deserialize, Serialize};
 use sova_core::{
     clock::SyncTime,
-    compiler::CompilationState,
+    compiler::{CompilationError, CompilationState, CompilationWarning},
+    instruments::InstrumentMap,
     protocol::{DeviceInfo, log::LogMessage},
-    scene::{ExecutionMode, Frame, Line, Scene},
-    schedule::playback::PlaybackState,
+    scene::{ExecutionMode, Frame, Line, Scene, SceneMetadata, SceneTextError},
+    schedule::{DEFAULT_QUANTIZATION_GRID, playback::PlaybackState},
     vm::variable::VariableValue,
 };
 
-use crate::server::Snapshot;
+use crate::client::unix_micros;
+use crate::codec::CompressionCodec;
+use crate::peer::PeerIdentity;
+use crate::server::{SceneStats, Snapshot};
+
+/// Version of the protocol spoken by this build, bumped on incompatible changes.
+///
+/// - 1: clients that predate versioning, which only know the messages of that time.
+/// - 2: versions announced in the handshake, peers sent with their whole identity,
+///   pings, and the messages added since version 1.
+pub const PROTOCOL_VERSION: u32 = 2;
+/// Oldest protocol version this build still talks to.
+pub const MIN_PROTOCOL_VERSION: u32 = UNVERSIONED_PROTOCOL_VERSION;
+/// First protocol version in which servers answer pings.
+pub const PING_PROTOCOL_VERSION: u32 = 2;
+/// First protocol version in which peers are sent with their whole identity.
+/// Older clients know peers by their name only.
+pub const PEER_IDENTITY_PROTOCOL_VERSION: u32 = 2;
+/// Round trips longer than this are reported as a degraded connection.
+pub const DEGRADED_ROUND_TRIP: Duration = Duration::from_millis(250);
+/// Version of the peers that predate protocol versioning.
+const UNVERSIONED_PROTOCOL_VERSION: u32 = 1;
+
+/// Agrees on the newest protocol version both sides speak, given the version
+/// announced by the other side. Fails with a message for the user when the other
+/// side is too old to talk to.
+pub fn negotiate_protocol_version(version: Option<u32>) -> Result<u32, String> {
+    let version = version.unwrap_or(UNVERSIONED_PROTOCOL_VERSION);
+    if version < MIN_PROTOCOL_VERSION {
+        return Err(format!(
+            "Protocol version {} is not supported, this server speaks versions {} to {}. \
+             Please update your client.",
+            version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+        ));
+    }
+    Ok(version.min(PROTOCOL_VERSION))
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum ServerMessage {
@@ -19,55 +59,247 @@ pub enum ServerMessage {
         username: String,
         scene: Scene,
         devices: Vec<DeviceInfo>,
-        peers: Vec<String>,
+        peers: Vec<PeerIdentity>,
         link_state: (f64, f64, f64, u32, bool),
         is_playing: bool,
         available_languages: Vec<String>,
         audio_engine_state: AudioEngineState,
+        /// Whether the server was built with the audio engine.
+        #[serde(default = "default_audio_available")]
+        audio_available: bool,
+        #[serde(default)]
+        scene_lock: Option<String>,
+        #[serde(default = "default_quantization_grid")]
+        quantization_grid: f64,
+        /// Protocol version agreed on with the client.
+        #[serde(default = "default_protocol_version")]
+        protocol_version: u32,
+        /// Codec compressing the messages of the connection, agreed on with the client.
+        #[serde(default)]
+        codec: CompressionCodec,
     },
-    PeersUpdated(Vec<String>),
-    PeerStartedEditing(String, usize, usize),
-    PeerStoppedEditing(String, usize, usize),
+    PeersUpdated(Vec<PeerIdentity>),
+    PeerStartedEditing(PeerIdentity, usize, usize),
+    PeerStoppedEditing(PeerIdentity, usize, usize),
+    /// The followed peer opened a frame (peer, line_id, frame_id), for the view to move to it.
+    /// Sent instead of `PeerStartedEditing` to the clients following that peer.
+    FollowedPeerFocus(PeerIdentity, usize, usize),
     PlaybackStateChanged(PlaybackState),
     Log(LogMessage),
     Chat(String, String),
     Success,
     InternalError(String),
     ConnectionRefused(String),
+    /// A mutating request was refused because the scene is locked.
+    Rejected(String),
+    /// Name of the client holding the scene lock, `None` once released.
+    SceneLockChanged(Option<String>),
     Snapshot(Snapshot),
     DeviceList(Vec<DeviceInfo>),
+    /// Instrument names and the sample folders or synth presets they play.
+    Instruments(InstrumentMap),
+    /// Languages scripts can be written in, and the syntax definitions of those
+    /// registered at runtime with one.
+    Languages(Vec<String>, BTreeMap<String, String>),
     ClockState(f64, f64, SyncTime, f64),
+    /// Grid of quantized edits, in beats.
+    QuantizationGrid(f64),
     SceneValue(Scene),
     SceneMode(ExecutionMode),
+    SceneMetadata(SceneMetadata),
     LineValues(Vec<(usize, Line)>),
     LineConfigurations(Vec<(usize, Line)>),
     AddLine(usize, Line),
     RemoveLine(usize),
+    MoveLine(usize, usize),
     FrameValues(Vec<(usize, usize, Frame)>),
     AddFrame(usize, usize, Frame),
     RemoveFrame(usize, usize),
     FramePosition(Vec<Vec<(usize, usize)>>),
     GlobalVariablesUpdate(HashMap<String, VariableValue>),
     CompilationUpdate(usize, usize, u64, CompilationState),
+    /// Warnings of the latest compilation of a frame, empty when there are none.
+    CompilationWarnings(usize, usize, u64, Vec<CompilationWarning>),
+    /// An auditioned script did not compile.
+    AuditionFailed(CompilationError),
+    /// A scene written as text could not be read.
+    SceneTextInvalid(SceneTextError),
+    /// Compilation result (line_id, frame_id, state) of every script of the scene.
+    SceneValidation(Vec<(usize, usize, CompilationState)>),
+    /// Matches (line_id, frame_id, start, end) of a script search,
+    /// as byte offsets in the content of the scripts.
+    SearchResults(Vec<(usize, usize, usize, usize)>),
+    SceneStats(SceneStats),
+    /// Number of frames changed by a script replace, and the frames (line_id, frame_id)
+    /// left untouched because their new script does not compile.
+    ScriptsReplaced(usize, Vec<(usize, usize, CompilationError)>),
     DevicesRestored {
         missing_devices: Vec<String>,
     },
     AudioEngineState(AudioEngineState),
     ScopeData(Vec<(f32, f32)>),
+    /// Answer to a ping, carrying the stamp of the ping.
+    Pong(u64),
+}
+
+fn default_quantization_grid() -> f64 {
+    DEFAULT_QUANTIZATION_GRID
+}
+
+fn default_audio_available() -> bool {
+    true
+}
+
+fn default_protocol_version() -> u32 {
+    UNVERSIONED_PROTOCOL_VERSION
+}
+
+/// Messages naming peers, in the shape known to clients older than
+/// [`PEER_IDENTITY_PROTOCOL_VERSION`], where each peer is a bare name.
+#[derive(Serialize)]
+enum LegacyPeerMessage<'a> {
+    Hello {
+        username: &'a str,
+        scene: &'a Scene,
+        devices: &'a [DeviceInfo],
+        peers: Vec<&'a str>,
+        link_state: (f64, f64, f64, u32, bool),
+        is_playing: bool,
+        available_languages: &'a [String],
+        audio_engine_state: &'a AudioEngineState,
+        audio_available: bool,
+        scene_lock: &'a Option<String>,
+        quantization_grid: f64,
+        protocol_version: u32,
+        codec: CompressionCodec,
+    },
+    PeersUpdated(Vec<&'a str>),
+    PeerStartedEditing(&'a str, usize, usize),
+    PeerStoppedEditing(&'a str, usize, usize),
+}
+
+impl<'a> LegacyPeerMessage<'a> {
+    /// Legacy shape of a message naming peers, `None` for other messages.
+    fn from_message(msg: &'a ServerMessage) -> Option<Self> {
+        let names =
+            |peers: &'a [PeerIdentity]| peers.iter().map(|peer| peer.name.as_str()).collect();
+        let legacy = match msg {
+            ServerMessage::Hello {
+                username,
+                scene,
+                devices,
+                peers,
+                link_state,
+                is_playing,
+                available_languages,
+                audio_engine_state,
+                audio_available,
+                scene_lock,
+                quantization_grid,
+                protocol_version,
+                codec,
+            } => LegacyPeerMessage::Hello {
+                username,
+                scene,
+                devices,
+                peers: names(peers),
+                link_state: *link_state,
+                is_playing: *is_playing,
+                available_languages,
+                audio_engine_state,
+                audio_available: *audio_available,
+                scene_lock,
+                quantization_grid: *quantization_grid,
+                protocol_version: *protocol_version,
+                codec: *codec,
+            },
+            ServerMessage::PeersUpdated(peers) => LegacyPeerMessage::PeersUpdated(names(peers)),
+            // Older clients cannot follow a peer, and only see it editing
+            ServerMessage::PeerStartedEditing(peer, line_id, frame_id)
+            | ServerMessage::FollowedPeerFocus(peer, line_id, frame_id) => {
+                LegacyPeerMessage::PeerStartedEditing(&peer.name, *line_id, *frame_id)
+            }
+            ServerMessage::PeerStoppedEditing(peer, line_id, frame_id) => {
+                LegacyPeerMessage::PeerStoppedEditing(&peer.name, *line_id, *frame_id)
+            }
+            _ => return None,
+        };
+        Some(legacy)
+    }
 }
 
 impl ServerMessage {
+    /// Encodes the message as MessagePack for a client speaking the given protocol
+    /// version, naming peers the way that client expects.
+    pub fn to_msgpack(&self, protocol_version: u32) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+        if protocol_version >= PEER_IDENTITY_PROTOCOL_VERSION {
+            return rmp_serde::to_vec_named(self);
+        }
+        match LegacyPeerMessage::from_message(self) {
+            Some(legacy) => rmp_serde::to_vec_named(&legacy),
+            None => rmp_serde::to_vec_named(self),
+        }
+    }
+
+    /// The message in a shape a client speaking the given protocol version can decode,
+    /// `None` when that client has no use for it. Unversioned clients get refusals as
+    /// errors, and nothing for the features they do not know.
+    ///
+    /// Moved lines have no equivalent there: the caller sends the whole scene instead.
+    pub fn for_protocol(self, protocol_version: u32) -> Option<ServerMessage> {
+        if protocol_version > UNVERSIONED_PROTOCOL_VERSION {
+            return Some(self);
+        }
+        match self {
+            ServerMessage::Rejected(reason) => Some(ServerMessage::InternalError(reason)),
+            ServerMessage::AuditionFailed(error) => Some(ServerMessage::InternalError(format!(
+                "Audition failed: {}",
+                error
+            ))),
+            ServerMessage::SceneTextInvalid(error) => Some(ServerMessage::InternalError(
+                format!("Invalid scene text: {}", error),
+            )),
+            ServerMessage::SceneLockChanged(_)
+            | ServerMessage::Instruments(_)
+            | ServerMessage::Languages(_, _)
+            | ServerMessage::QuantizationGrid(_)
+            | ServerMessage::SceneMetadata(_)
+            | ServerMessage::MoveLine(_, _)
+            | ServerMessage::CompilationWarnings(_, _, _, _)
+            | ServerMessage::SceneValidation(_)
+            | ServerMessage::SearchResults(_)
+            | ServerMessage::SceneStats(_)
+            | ServerMessage::ScriptsReplaced(_, _)
+            | ServerMessage::Pong(_) => None,
+            msg => Some(msg),
+        }
+    }
+
+    /// Time since the ping answered by this message was sent, `None` for other messages.
+    pub fn round_trip_time(&self) -> Option<Duration> {
+        match self {
+            ServerMessage::Pong(stamp) => {
+                Some(Duration::from_micros(unix_micros().saturating_sub(*stamp)))
+            }
+            _ => None,
+        }
+    }
+
     pub fn compression_strategy(&self) -> crate::client::CompressionStrategy {
         use crate::client::CompressionStrategy;
         match self {
             ServerMessage::PeerStartedEditing(_, _, _)
             | ServerMessage::PeerStoppedEditing(_, _, _)
+            | ServerMessage::FollowedPeerFocus(_, _, _)
             | ServerMessage::ClockState(_, _, _, _)
+            | ServerMessage::QuantizationGrid(_)
             | ServerMessage::FramePosition(_)
             | ServerMessage::PlaybackStateChanged(_)
+            | ServerMessage::SceneLockChanged(_)
             | ServerMessage::GlobalVariablesUpdate(_)
             | ServerMessage::AudioEngineState(_)
-            | ServerMessage::ScopeData(_) => CompressionStrategy::Never,
+            | ServerMessage::ScopeData(_)
+            | ServerMessage::Pong(_) => CompressionStrategy::Never,
 
             ServerMessage::Hello { .. }
             | ServerMessage::SceneValue(_)
@@ -78,4 +310,42 @@ impl ServerMessage {
             _ => CompressionStrategy::Adaptive,
         }
     }
+
+    /// Keeps only the parts of a broadcast concerning the given lines,
+    /// `None` when nothing is left to send. Whole scenes, added, removed and moved
+    /// lines always go through, since they change the indices of the lines.
+    pub fn restricted_to_lines(self, lines: &BTreeSet<usize>) -> Option<Self> {
+        let keep = |line_id: &usize| lines.contains(line_id);
+        match self {
+            ServerMessage::LineValues(values) => {
+                let values: Vec<_> = values.into_iter().filter(|(l, _)| keep(l)).collect();
+                (!values.is_empty()).then_some(ServerMessage::LineValues(values))
+            }
+            ServerMessage::LineConfigurations(values) => {
+                let values: Vec<_> = values.into_iter().filter(|(l, _)| keep(l)).collect();
+                (!values.is_empty()).then_some(ServerMessage::LineConfigurations(values))
+            }
+            ServerMessage::FrameValues(frames) => {
+                let frames: Vec<_> = frames.into_iter().filter(|(l, _, _)| keep(l)).collect();
+                (!frames.is_empty()).then_some(ServerMessage::FrameValues(frames))
+            }
+            // Positions are indexed by line, so the other lines are only emptied
+            ServerMessage::FramePosition(positions) => Some(ServerMessage::FramePosition(
+                positions
+                    .into_iter()
+                    .enumerate()
+                    .map(|(l, pos)| if keep(&l) { pos } else { Vec::new() })
+                    .collect(),
+            )),
+            ServerMessage::AddFrame(line_id, _, _)
+            | ServerMessage::RemoveFrame(line_id, _)
+            | ServerMessage::PeerStartedEditing(_, line_id, _)
+            | ServerMessage::PeerStoppedEditing(_, line_id, _)
+            | ServerMessage::CompilationUpdate(line_id, _, _, _)
+            | ServerMessage::CompilationWarnings(line_id, _, _, _) => {
+                keep(&line_id).then_some(self)
+            }
+            other => Some(other),
+        }
+    }
 }
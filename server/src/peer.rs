@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use crate::codec::CompressionCodec;
+
+/// Identity a client presents to its peers.
+///
+/// Only the name is mandatory. Clients that do not pick a color get one
+/// derived from their name by the GUI, so older clients keep a stable color.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerIdentity {
+    pub name: String,
+    /// Preferred color, as a CSS color string (e.g. `#ff8800`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<String>,
+    /// Newest protocol version the client speaks, announced in the handshake.
+    /// Clients that predate protocol versioning send none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<u32>,
+    /// Compression codecs the client speaks, by order of preference.
+    /// Clients that predate codec negotiation send none, and only speak Zstd.
+    /// Codecs unknown to this build are read as `CompressionCodec::Unknown`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub codecs: Vec<CompressionCodec>,
+}
+
+impl PeerIdentity {
+    pub fn new(name: String) -> Self {
+        PeerIdentity {
+            name,
+            color: None,
+            emoji: None,
+            protocol_version: None,
+            codecs: Vec::new(),
+        }
+    }
+}
+
+impl From<String> for PeerIdentity {
+    fn from(name: String) -> Self {
+        PeerIdentity::new(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{PEER_IDENTITY_PROTOCOL_VERSION, ServerMessage};
+
+    #[test]
+    fn color_propagates_through_peer_messages() {
+        let peer = PeerIdentity {
+            name: "alice".to_string(),
+            color: Some("#ff8800".to_string()),
+            emoji: Some("🎹".to_string()),
+            protocol_version: None,
+            codecs: Vec::new(),
+        };
+
+        let bytes =
+            rmp_serde::to_vec_named(&ServerMessage::PeersUpdated(vec![peer.clone()])).unwrap();
+        match rmp_serde::from_slice(&bytes).unwrap() {
+            ServerMessage::PeersUpdated(peers) => assert_eq!(peers, vec![peer.clone()]),
+            other => panic!("unexpected message {:?}", other),
+        }
+
+        let bytes = rmp_serde::to_vec_named(&ServerMessage::PeerStartedEditing(peer.clone(), 1, 2))
+            .unwrap();
+        match rmp_serde::from_slice(&bytes).unwrap() {
+            ServerMessage::PeerStartedEditing(editor, 1, 2) => {
+                assert_eq!(editor.color.as_deref(), Some("#ff8800"))
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[test]
+    fn older_clients_receive_peers_by_name() {
+        #[derive(Deserialize)]
+        enum LegacyMessage {
+            PeersUpdated(Vec<String>),
+            PeerStartedEditing(String, usize, usize),
+        }
+
+        let peer = PeerIdentity::new("alice".to_string());
+        let legacy_version = PEER_IDENTITY_PROTOCOL_VERSION - 1;
+
+        let bytes = ServerMessage::PeersUpdated(vec![peer.clone()])
+            .to_msgpack(legacy_version)
+            .unwrap();
+        match rmp_serde::from_slice(&bytes).unwrap() {
+            LegacyMessage::PeersUpdated(names) => assert_eq!(names, vec!["alice".to_string()]),
+            _ => panic!("expected a peer list"),
+        }
+
+        let bytes = ServerMessage::PeerStartedEditing(peer.clone(), 1, 2)
+            .to_msgpack(legacy_version)
+            .unwrap();
+        match rmp_serde::from_slice(&bytes).unwrap() {
+            LegacyMessage::PeerStartedEditing(name, 1, 2) => assert_eq!(name, "alice"),
+            _ => panic!("expected an editing notice"),
+        }
+
+        let bytes = ServerMessage::PeersUpdated(vec![peer.clone()])
+            .to_msgpack(PEER_IDENTITY_PROTOCOL_VERSION)
+            .unwrap();
+        match rmp_serde::from_slice(&bytes).unwrap() {
+            ServerMessage::PeersUpdated(peers) => assert_eq!(peers, vec![peer]),
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Recording of the messages sent by clients, to replay a session and reproduce its state.
+//!
+//! A recording is line-delimited JSON: each line holds a [`RecordedMessage`].
+//! Nothing is redacted, chat messages included, so recording is opt-in.
+
+use crossbeam_channel::Sender;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+
+use crate::client::ClientMessage;
+use crate::server::{ServerState, on_message};
+
+/// A message received from a client, with its date in the session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedMessage {
+    /// Microseconds elapsed since the recording started.
+    pub micros: u64,
+    /// Name of the client that sent the message.
+    pub client: String,
+    pub message: ClientMessage,
+}
+
+/// Appends every message received by the server to a file.
+///
+/// The file is written by a thread of its own, so that recording never blocks the
+/// handling of client messages.
+pub struct SessionRecorder {
+    start: Instant,
+    entries: Option<Sender<RecordedMessage>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl SessionRecorder {
+    /// Starts a recording at `path`, replacing any previous file.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let (entries, received) = crossbeam_channel::unbounded::<RecordedMessage>();
+        let writer = thread::spawn(move || {
+            // Each line is flushed, so the recording survives a crash of the server
+            for entry in received {
+                let written = serde_json::to_writer(&mut file, &entry)
+                    .map_err(io::Error::from)
+                    .and_then(|_| file.write_all(b"\n"))
+                    .and_then(|_| file.flush());
+                if let Err(e) = written {
+                    eprintln!("[!] Failed to record client message: {}", e);
+                }
+            }
+        });
+        Ok(SessionRecorder {
+            start: Instant::now(),
+            entries: Some(entries),
+            writer: Some(writer),
+        })
+    }
+
+    /// Queues the message to be written as a new line of the recording.
+    pub fn record(&self, client: &str, message: &ClientMessage) {
+        let entry = RecordedMessage {
+            micros: self.start.elapsed().as_micros() as u64,
+            client: client.to_owned(),
+            message: message.clone(),
+        };
+        if let Some(entries) = &self.entries {
+            let _ = entries.send(entry);
+        }
+    }
+}
+
+impl Drop for SessionRecorder {
+    /// Waits for the messages still queued to be written.
+    fn drop(&mut self) {
+        drop(self.entries.take());
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Reads the messages of a recording, in the order they were received.
+pub fn load_recording(path: &Path) -> Result<Vec<RecordedMessage>, String> {
+    let file =
+        File::open(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let mut messages = Vec::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let message = serde_json::from_str(&line).map_err(|e| {
+            format!(
+                "'{}' line {}: invalid message: {}",
+                path.display(),
+                index + 1,
+                e
+            )
+        })?;
+        messages.push(message);
+    }
+    Ok(messages)
+}
+
+/// Processes recorded messages as if their clients had sent them again, in order.
+/// Answers are discarded, the recorded delays between messages are not waited for.
+///
+/// Only the messages changing the scene, transport or devices are replayed. Locks, edited
+/// frames, names and chat belonged to connections that are gone, and their releases on
+/// disconnection were never recorded.
+pub async fn replay_session(state: &ServerState, messages: Vec<RecordedMessage>) {
+    for recorded in messages {
+        if !recorded.message.is_mutating() {
+            continue;
+        }
+        let mut client = recorded.client;
+        on_message(recorded.message, state, &mut client).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::ServerMessage;
+    use crate::test_support::{scheduler, server_state};
+    use crossbeam_channel::Receiver;
+    use sova_core::{
+        Scene,
+        scene::{Line, script::Script},
+        schedule::{ActionTiming, SchedulerMessage},
+    };
+    use std::sync::Arc;
+
+    /// The scene a fresh scheduler ends up with once `messages` are applied.
+    fn scene_after(messages: Receiver<SchedulerMessage>) -> serde_json::Value {
+        let mut scheduler = scheduler();
+        for msg in messages.try_iter() {
+            scheduler.process_message(msg);
+        }
+        serde_json::to_value(&scheduler.scene).unwrap()
+    }
+
+    #[tokio::test]
+    async fn replaying_a_recording_rebuilds_the_same_scene() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+
+        let (mut original, original_rx) = server_state();
+        original.recorder = Some(Arc::new(SessionRecorder::create(&path).unwrap()));
+        let session = vec![
+            ClientMessage::SetScene(
+                Scene::new(vec![Line::new(vec![1.0, 2.0])]),
+                ActionTiming::Immediate,
+            ),
+            ClientMessage::AddLine(1, Line::new(vec![0.5]), ActionTiming::Immediate),
+            ClientMessage::SetLineGate(1, 0.25, ActionTiming::Immediate),
+            ClientMessage::RemoveFrame(0, 0, ActionTiming::Immediate),
+            ClientMessage::GetScene,
+        ];
+        let mut client = "alice".to_owned();
+        for message in session {
+            on_message(message, &original, &mut client).await;
+        }
+        // Ending the recording writes what is left of it
+        drop(original);
+
+        let recording = load_recording(&path).unwrap();
+        assert_eq!(recording.len(), 5);
+        assert!(recording.iter().all(|entry| entry.client == "alice"));
+        assert!(recording.windows(2).all(|w| w[0].micros <= w[1].micros));
+
+        let (replayed, replayed_rx) = server_state();
+        replay_session(&replayed, recording).await;
+
+        let expected = scene_after(original_rx);
+        assert_eq!(expected["lines"].as_array().unwrap().len(), 2);
+        assert_eq!(scene_after(replayed_rx), expected);
+    }
+
+    #[tokio::test]
+    async fn replayed_locks_do_not_outlive_their_session() {
+        let recorded = |message| RecordedMessage {
+            micros: 0,
+            client: "alice".to_owned(),
+            message,
+        };
+        let recording = vec![
+            recorded(ClientMessage::LockScene),
+            recorded(ClientMessage::StartedEditingFrame(0, 0)),
+            recorded(ClientMessage::SetLineGate(0, 0.5, ActionTiming::Immediate)),
+        ];
+        let (state, sched_rx) = server_state();
+        replay_session(&state, recording).await;
+        assert!(state.scene_lock.lock().await.is_none());
+        assert!(state.frame_locks.lock().await.is_empty());
+        assert_eq!(sched_rx.try_iter().count(), 1);
+
+        let mut bob = "bob".to_owned();
+        let set_script = ClientMessage::SchedulerControl(SchedulerMessage::SetScript(
+            0,
+            0,
+            Script::default(),
+            ActionTiming::Immediate,
+        ));
+        assert!(matches!(
+            on_message(set_script, &state, &mut bob).await,
+            ServerMessage::Success
+        ));
+    }
+}
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use sova_core::{
+    clock::SyncTime,
+    protocol::DeviceInfo,
+    scene::{Line, Scene},
+};
+use std::{fs, path::Path};
+
+use crate::server::Snapshot;
+
+/// A [`Snapshot`] as written to a file, where identical scripts are stored once.
+/// Files holding a plain snapshot are read as well.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    #[serde(with = "sova_core::scene::interned")]
+    pub scene: Scene,
+    pub tempo: f64,
+    pub beat: f64,
+    pub micros: SyncTime,
+    pub quantum: f64,
+    #[serde(default)]
+    pub devices: Option<Vec<DeviceInfo>>,
+}
+
+impl From<Snapshot> for SnapshotFile {
+    fn from(snapshot: Snapshot) -> Self {
+        SnapshotFile {
+            scene: snapshot.scene,
+            tempo: snapshot.tempo,
+            beat: snapshot.beat,
+            micros: snapshot.micros,
+            quantum: snapshot.quantum,
+            devices: snapshot.devices,
+        }
+    }
+}
+
+impl From<SnapshotFile> for Snapshot {
+    fn from(file: SnapshotFile) -> Self {
+        Snapshot {
+            scene: file.scene,
+            tempo: file.tempo,
+            beat: file.beat,
+            micros: file.micros,
+            quantum: file.quantum,
+            devices: file.devices,
+        }
+    }
+}
+
+/// The scene a fresh server starts with: one line holding a single frame.
+pub fn default_scene() -> Scene {
+    Scene::new(vec![Line::new(vec![1.0])])
+}
+
+/// Files a scene can be loaded from: a project saved by the GUI,
+/// a bare snapshot, or a bare scene.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SceneFile {
+    Project { snapshot: SnapshotFile },
+    Snapshot(SnapshotFile),
+    Scene(Scene),
+}
+
+pub fn load_scene_file(path: &Path) -> Result<Scene, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let file: SceneFile = serde_json::from_str(&content)
+        .map_err(|e| format!("'{}' is not a valid scene file: {}", path.display(), e))?;
+    Ok(match file {
+        SceneFile::Project { snapshot } | SceneFile::Snapshot(snapshot) => snapshot.scene,
+        SceneFile::Scene(scene) => scene,
+    })
+}
+
+/// The scene stored at `path`, or the default scene if there is none or it cannot be loaded.
+pub fn initial_scene(path: Option<&Path>) -> Scene {
+    let Some(path) = path else {
+        return default_scene();
+    };
+    match load_scene_file(path) {
+        Ok(scene) => {
+            println!("Loaded initial scene from '{}'.", path.display());
+            scene
+        }
+        Err(e) => {
+            eprintln!("[!] {}. Starting with the default scene.", e);
+            default_scene()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::scheduler;
+    use sova_core::{
+        scene::{Frame, script::Script},
+        schedule::{ActionTiming, SchedulerMessage},
+    };
+
+    #[test]
+    fn scheduler_starts_with_the_scene_file() {
+        let mut frame = Frame::from(Script::new("(note 60)".to_string(), "bali".to_string()));
+        frame.duration = 2.0;
+        let mut scene = Scene::new(vec![Line::new(vec![1.0]), Line::new(vec![1.0])]);
+        scene.line_mut(1).set_frame(0, frame);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("set.json");
+        fs::write(&path, serde_json::to_string(&scene).unwrap()).unwrap();
+
+        let mut scheduler = scheduler();
+        scheduler.process_message(SchedulerMessage::SetScene(
+            initial_scene(Some(&path)),
+            ActionTiming::Immediate,
+        ));
+
+        assert_eq!(scheduler.scene.n_lines(), 2);
+        let loaded = scheduler.scene.line(1).unwrap().frame(0).unwrap();
+        assert_eq!(loaded.script().content(), "(note 60)");
+        assert_eq!(loaded.duration, 2.0);
+    }
+
+    #[test]
+    fn missing_or_invalid_files_fall_back_to_the_default() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.json");
+        assert_eq!(initial_scene(Some(&missing)).n_lines(), 1);
+
+        let invalid = dir.path().join("invalid.json");
+        fs::write(&invalid, "not json").unwrap();
+        assert!(load_scene_file(&invalid).is_err());
+        assert_eq!(initial_scene(Some(&invalid)).n_lines(), 1);
+    }
+}
@@ -1,9 +1,18 @@
-use crate::audio::AudioEngineState;
+use crate::audio::{AUDIO_AVAILABLE, AudioEngineState};
 use crate::client::ClientMessage;
 use crossbeam_channel::{Receiver, Sender};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use sova_core::{Scene, schedule::playback::PlaybackState, vm::LanguageCenter};
+use socket2::{SockRef, TcpKeepalive};
+use sova_core::{
+    Scene,
+    compiler::{CompilationError, CompilationState, Compiler},
+    scene::{LineFill, parse_scene_text, script::Script},
+    schedule::playback::PlaybackState,
+    vm::{LanguageCenter, interpreter::InterpreterFactory},
+};
 use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
     io::ErrorKind,
     path::PathBuf,
     sync::{
@@ -12,7 +21,7 @@ use std::{
     },
     thread,
 };
-use tokio::time::Duration;
+use tokio::time::{Duration, Instant};
 use tokio::{
     io::{self, AsyncReadExt, AsyncWriteExt, BufReader, BufWriter},
     net::{TcpListener, TcpStream},
@@ -23,10 +32,15 @@ use tokio::{
 use sova_core::{
     clock::{Clock, ClockServer, SyncTime},
     device_map::DeviceMap,
-    schedule::{SchedulerMessage, SovaNotification},
+    schedule::{ActionTiming, DEFAULT_QUANTIZATION_GRID, SchedulerMessage, SovaNotification},
 };
 
-use crate::message::ServerMessage;
+use crate::codec::{CompressionCodec, decode_frame, encode_frame, frame_length, negotiate_codec};
+use crate::message::{
+    MIN_PROTOCOL_VERSION, PROTOCOL_VERSION, ServerMessage, negotiate_protocol_version,
+};
+use crate::peer::PeerIdentity;
+use crate::recorder::SessionRecorder;
 
 #[derive(Debug, Clone)]
 pub struct AudioRestartConfig {
@@ -44,12 +58,44 @@ pub struct AudioRestartRequest {
 
 pub const DEFAULT_CLIENT_NAME: &str = "Unknown musician";
 
-const COMPRESSION_MIN_SIZE: usize = 64;
-const COMPRESSION_ADAPTIVE_THRESHOLD: usize = 256;
-const HIGH_COMPRESSION_CUTOFF: usize = 1024;
-const COMPRESSION_FLAG: u32 = 0x80000000;
-const LENGTH_MASK: u32 = 0x7FFFFFFF;
 const POSITION_BROADCAST_INTERVAL_MS: u64 = 33;
+pub const DEFAULT_KEEPALIVE_SECS: u64 = 30;
+pub const DEFAULT_KEEPALIVE_INTERVAL_SECS: u64 = 5;
+pub const DEFAULT_WRITE_TIMEOUT_SECS: u64 = 10;
+
+/// How the server watches over client connections.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConnectionSettings {
+    /// Silence after which the OS starts probing a connection, `None` disables TCP keepalive.
+    pub keepalive: Option<Duration>,
+    /// Delay between two keepalive probes.
+    pub keepalive_interval: Duration,
+    /// Clients that stop reading for this long are disconnected, `None` waits forever.
+    pub write_timeout: Option<Duration>,
+    /// Clients that send nothing for this long are disconnected. Off by default, as
+    /// passive clients may listen for hours without a word.
+    pub idle_timeout: Option<Duration>,
+}
+
+impl Default for ConnectionSettings {
+    fn default() -> Self {
+        ConnectionSettings {
+            keepalive: Some(Duration::from_secs(DEFAULT_KEEPALIVE_SECS)),
+            keepalive_interval: Duration::from_secs(DEFAULT_KEEPALIVE_INTERVAL_SECS),
+            write_timeout: Some(Duration::from_secs(DEFAULT_WRITE_TIMEOUT_SECS)),
+            idle_timeout: None,
+        }
+    }
+}
+
+/// Stops the transport of an unattended server once clients have gone quiet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IdleStopSettings {
+    /// Time without any message from a client after which the transport stops.
+    pub after: Duration,
+    /// Whether the transport starts again when a client becomes active.
+    pub resume: bool,
+}
 
 #[derive(Clone)]
 pub struct ServerState {
@@ -57,12 +103,36 @@ pub struct ServerState {
     pub devices: Arc<DeviceMap>,
     pub sched_iface: Sender<SchedulerMessage>,
     pub update_sender: broadcast::Sender<SovaNotification>,
-    pub clients: Arc<Mutex<Vec<String>>>,
+    pub clients: Arc<Mutex<Vec<PeerIdentity>>>,
+    /// Name of the client currently holding the scene lock.
+    pub scene_lock: Arc<Mutex<Option<String>>>,
+    /// Name of the only client allowed to lock the scene, `None` letting no client lock it.
+    pub scene_lead: Option<String>,
+    /// Frames being edited, with the name of the client editing each of them.
+    pub frame_locks: Arc<Mutex<HashMap<(usize, usize), String>>>,
     pub scene_image: Arc<Mutex<Scene>>,
-    pub languages: Arc<LanguageCenter>,
+    /// Scene replaced by the latest scene load, until it is restored.
+    pub previous_scene: Arc<Mutex<Option<Scene>>>,
+    /// Languages scripts are compiled with, replaced when languages are registered at runtime.
+    pub languages: Arc<StdMutex<Arc<LanguageCenter>>>,
+    /// Syntax definitions of the languages registered at runtime, by language.
+    pub language_syntaxes: Arc<StdMutex<BTreeMap<String, String>>>,
     pub is_playing: Arc<AtomicBool>,
+    /// Grid of quantized edits in the scheduler, in beats.
+    pub quantization_grid: Arc<StdMutex<f64>>,
     pub audio_engine_state: Arc<StdMutex<AudioEngineState>>,
     pub audio_restart_tx: Option<Sender<AudioRestartRequest>>,
+    /// Silences the voices of the audio engine, `None` without an audio engine.
+    pub audio_hush_tx: Option<Sender<()>>,
+    /// Records every message received from clients, when enabled.
+    pub recorder: Option<Arc<SessionRecorder>>,
+    pub connection: ConnectionSettings,
+    /// Stops the transport when no client is active, `None` keeps it running.
+    pub idle_stop: Option<IdleStopSettings>,
+    /// Last time a client connected or sent a message.
+    pub last_activity: Arc<StdMutex<Instant>>,
+    /// Whether the transport is currently stopped for lack of activity.
+    pub idle_stopped: Arc<AtomicBool>,
 }
 
 impl ServerState {
@@ -82,20 +152,190 @@ impl ServerState {
             sched_iface,
             update_sender,
             clients: Arc::new(Mutex::new(Vec::new())),
+            scene_lock: Arc::new(Mutex::new(None)),
+            scene_lead: None,
+            frame_locks: Default::default(),
             scene_image,
-            languages,
+            previous_scene: Default::default(),
+            languages: Arc::new(StdMutex::new(languages)),
+            language_syntaxes: Default::default(),
             is_playing: Arc::new(AtomicBool::new(false)),
+            quantization_grid: Arc::new(StdMutex::new(DEFAULT_QUANTIZATION_GRID)),
             audio_engine_state,
             audio_restart_tx,
+            audio_hush_tx: None,
+            recorder: None,
+            connection: ConnectionSettings::default(),
+            idle_stop: None,
+            last_activity: Arc::new(StdMutex::new(Instant::now())),
+            idle_stopped: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Identity registered under `name`, or a bare one if the client is unknown.
+    pub async fn peer_identity(&self, name: &str) -> PeerIdentity {
+        self.clients
+            .lock()
+            .await
+            .iter()
+            .find(|peer| peer.name == name)
+            .cloned()
+            .unwrap_or_else(|| PeerIdentity::new(name.to_string()))
+    }
+
+    pub fn quantization_grid(&self) -> f64 {
+        self.quantization_grid
+            .lock()
+            .map(|guard| *guard)
+            .unwrap_or(DEFAULT_QUANTIZATION_GRID)
+    }
+
+    /// Notes that a client is active, restarting the transport if it was
+    /// stopped while idle and the settings ask for it.
+    pub fn record_activity(&self) {
+        if let Ok(mut last) = self.last_activity.lock() {
+            *last = Instant::now();
+        }
+        if self.idle_stopped.swap(false, Ordering::Relaxed)
+            && self.idle_stop.is_some_and(|idle| idle.resume)
+        {
+            println!("[ idle ] Client activity, restarting the transport.");
+            let _ = self
+                .sched_iface
+                .send(SchedulerMessage::TransportStart(ActionTiming::Immediate));
         }
     }
 
+    /// Stops the transport, releases hanging notes and silences the audio engine if
+    /// no client was active for the configured time. Returns whether the transport
+    /// was stopped.
+    pub fn stop_if_idle(&self, now: Instant) -> bool {
+        let Some(idle) = self.idle_stop else {
+            return false;
+        };
+        if !self.is_playing.load(Ordering::Relaxed) || self.idle_stopped.load(Ordering::Relaxed) {
+            return false;
+        }
+        let last = self.last_activity.lock().map(|last| *last).unwrap_or(now);
+        if now.saturating_duration_since(last) < idle.after {
+            return false;
+        }
+        println!(
+            "[ idle ] No client activity for {}s, stopping the transport.",
+            idle.after.as_secs()
+        );
+        self.idle_stopped.store(true, Ordering::Relaxed);
+        if self
+            .sched_iface
+            .send(SchedulerMessage::TransportStop(ActionTiming::Immediate))
+            .is_err()
+        {
+            eprintln!("Failed to send TransportStop to scheduler.");
+        }
+        self.devices.panic_all_midi_outputs();
+        if let Some(hush) = &self.audio_hush_tx {
+            let _ = hush.send(());
+        }
+        true
+    }
+
     pub fn get_audio_engine_state(&self) -> AudioEngineState {
         self.audio_engine_state
             .lock()
             .map(|guard| guard.clone())
             .unwrap_or_default()
     }
+
+    /// Languages scripts are currently compiled and run with.
+    pub fn languages(&self) -> Arc<LanguageCenter> {
+        match self.languages.lock() {
+            Ok(languages) => Arc::clone(&*languages),
+            Err(poisoned) => Arc::clone(&*poisoned.into_inner()),
+        }
+    }
+
+    pub fn language_syntaxes(&self) -> BTreeMap<String, String> {
+        self.language_syntaxes
+            .lock()
+            .map(|guard| guard.clone())
+            .unwrap_or_default()
+    }
+
+    /// Adds a compiler without restarting the server, replacing the language of the same name.
+    pub fn register_compiler(
+        &self,
+        compiler: impl Compiler + 'static,
+        syntax: Option<String>,
+    ) -> Result<(), String> {
+        let name = compiler.name().to_owned();
+        self.update_languages(&name, syntax, |languages| {
+            languages.interpreters.remove_factory(&name);
+            languages.transcoder.add_compiler(compiler);
+        })
+    }
+
+    /// Adds an interpreter without restarting the server, replacing the language of the
+    /// same name.
+    pub fn register_interpreter(
+        &self,
+        factory: impl InterpreterFactory + 'static,
+        syntax: Option<String>,
+    ) -> Result<(), String> {
+        let name = factory.name().to_owned();
+        self.update_languages(&name, syntax, |languages| {
+            languages.transcoder.remove_compiler(&name);
+            languages.interpreters.add_factory(factory);
+        })
+    }
+
+    /// Removes the compiler or interpreter of a language.
+    pub fn unregister_language(&self, lang: &str) -> Result<(), String> {
+        if !self.languages().languages().any(|name| name == lang) {
+            return Err(format!("No language named '{}'.", lang));
+        }
+        self.update_languages(lang, None, |languages| {
+            languages.remove_language(lang);
+        })
+    }
+
+    /// Changes a copy of the languages, which then replaces them in the server and in the
+    /// scheduler, and is broadcast to every client.
+    fn update_languages(
+        &self,
+        lang: &str,
+        syntax: Option<String>,
+        change: impl FnOnce(&mut LanguageCenter),
+    ) -> Result<(), String> {
+        {
+            let Ok(mut current) = self.languages.lock() else {
+                return Err("Languages are unavailable.".to_string());
+            };
+            let mut languages = LanguageCenter::clone(&current);
+            change(&mut languages);
+            let languages = Arc::new(languages);
+            if self
+                .sched_iface
+                .send(SchedulerMessage::SetLanguages(Arc::clone(&languages)))
+                .is_err()
+            {
+                eprintln!("Failed to send SetLanguages to scheduler.");
+                return Err("Scheduler communication error.".to_string());
+            }
+            *current = languages;
+        }
+        if let Ok(mut syntaxes) = self.language_syntaxes.lock() {
+            match syntax {
+                Some(syntax) => syntaxes.insert(lang.to_owned(), syntax),
+                None => syntaxes.remove(lang),
+            };
+        }
+        let available = self.languages().languages().map(str::to_owned).collect();
+        let syntaxes = self.language_syntaxes();
+        let _ = self
+            .update_sender
+            .send(SovaNotification::LanguagesChanged(available, syntaxes));
+        Ok(())
+    }
 }
 
 pub struct SovaCoreServer {
@@ -115,12 +355,48 @@ pub struct Snapshot {
     pub devices: Option<Vec<sova_core::protocol::DeviceInfo>>,
 }
 
-async fn on_message(
+/// Overview of a scene, for summary panels.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SceneStats {
+    pub lines: usize,
+    pub enabled_lines: usize,
+    pub frames: usize,
+    pub enabled_frames: usize,
+    /// Number of non-empty scripts written in each language.
+    pub scripts_per_language: BTreeMap<String, usize>,
+    /// Length in beats of the longest line.
+    pub duration: f64,
+}
+
+pub(crate) async fn on_message(
     msg: ClientMessage,
     state: &ServerState,
     client_name: &mut String,
 ) -> ServerMessage {
-    println!("[➡️ ] Client '{}' sent: {:?}", client_name, msg);
+    // Pings are neither logged nor recorded, and never count as activity
+    let is_ping = matches!(msg, ClientMessage::Ping(_));
+    if !is_ping {
+        println!("[➡️ ] Client '{}' sent: {:?}", client_name, msg);
+    }
+
+    if let Some(rejection) =
+        check_scene_lock(&msg, state.scene_lock.lock().await.as_deref(), client_name)
+    {
+        return rejection;
+    }
+    if let Some(rejection) = check_frame_locks(&msg, &*state.frame_locks.lock().await, client_name)
+    {
+        return rejection;
+    }
+
+    // Rejected edits are neither recorded nor counted as activity
+    if let Some(recorder) = state.recorder.as_ref().filter(|_| !is_ping) {
+        recorder.record(client_name, &msg);
+    }
+    // Reads, like the heartbeat of an idle GUI, do not keep the transport going
+    if msg.is_mutating() {
+        state.record_activity();
+    }
 
     match msg {
         ClientMessage::Chat(chat_msg) => {
@@ -131,35 +407,20 @@ async fn on_message(
             ServerMessage::Success
         }
         ClientMessage::SetName(new_name) => {
-            let mut clients_guard = state.clients.lock().await;
-            let old_name = client_name.clone();
-            let is_new_client = *client_name == DEFAULT_CLIENT_NAME;
-
-            if is_new_client {
-                println!("Client identified as: {}", new_name);
-                clients_guard.push(new_name.clone());
-            } else if let Some(i) = clients_guard.iter().position(|x| *x == old_name) {
-                println!("Client {} changed name to {}", clients_guard[i], new_name);
-                clients_guard[i] = new_name.clone();
-            } else {
-                eprintln!(
-                    "Error: Could not find old name '{}' to replace. Adding '{}'.",
-                    old_name, new_name
-                );
-                clients_guard.push(new_name.clone());
-            }
-            *client_name = new_name;
-
-            let updated_clients = clients_guard.clone();
-            drop(clients_guard);
-
-            let _ = state
-                .update_sender
-                .send(SovaNotification::ClientListChanged(updated_clients));
-
-            ServerMessage::Success
+            let mut identity = state.peer_identity(client_name).await;
+            identity.name = new_name;
+            update_identity(state, client_name, identity).await
+        }
+        ClientMessage::SetIdentity(identity) => {
+            update_identity(state, client_name, identity).await
         }
         ClientMessage::SchedulerControl(sched_msg) => {
+            if matches!(
+                sched_msg,
+                SchedulerMessage::SetScene(_, _) | SchedulerMessage::MorphToScene(_, _)
+            ) {
+                keep_previous_scene(state).await;
+            }
             if state.sched_iface.send(sched_msg).is_ok() {
                 ServerMessage::Success
             } else {
@@ -178,15 +439,55 @@ async fn on_message(
             }
             ServerMessage::Success
         }
+        ClientMessage::NudgeTempo(delta) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::NudgeTempo(delta, ActionTiming::Immediate))
+                .is_err()
+            {
+                eprintln!("Failed to send NudgeTempo to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::SetQuantizationGrid(grid) => {
+            if !grid.is_finite() || grid <= 0.0 {
+                return ServerMessage::InternalError(format!(
+                    "Invalid quantization grid: {grid}, it must be a positive number of beats."
+                ));
+            }
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetQuantizationGrid(grid))
+                .is_err()
+            {
+                eprintln!("Failed to send SetQuantizationGrid to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::SetRandomSeed(seed) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetRandomSeed(seed))
+                .is_err()
+            {
+                eprintln!("Failed to send SetRandomSeed to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
         ClientMessage::GetClock => {
             let clock = Clock::from(&state.clock_server);
             ServerMessage::ClockState(clock.tempo(), clock.beat(), clock.micros(), clock.quantum())
         }
+        ClientMessage::Ping(stamp) => ServerMessage::Pong(stamp),
         ClientMessage::GetScene => {
             ServerMessage::SceneValue(state.scene_image.lock().await.clone())
         }
         ClientMessage::GetPeers => ServerMessage::PeersUpdated(state.clients.lock().await.clone()),
         ClientMessage::SetScene(scene, timing) => {
+            keep_previous_scene(state).await;
             if state
                 .sched_iface
                 .send(SchedulerMessage::SetScene(scene, timing))
@@ -200,6 +501,53 @@ async fn on_message(
                 )
             }
         }
+        ClientMessage::MorphToScene(scene, duration) => {
+            if !duration.is_finite() || duration < 0.0 {
+                return ServerMessage::InternalError(format!(
+                    "Invalid morph duration: {duration}, it must be a positive number of beats."
+                ));
+            }
+            keep_previous_scene(state).await;
+            if state
+                .sched_iface
+                .send(SchedulerMessage::MorphToScene(scene, duration))
+                .is_err()
+            {
+                eprintln!("Failed to send MorphToScene to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::ImportSceneText(text) => {
+            let scene = match parse_scene_text(&text) {
+                Ok(scene) => scene,
+                Err(err) => return ServerMessage::SceneTextInvalid(err),
+            };
+            keep_previous_scene(state).await;
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetScene(scene, ActionTiming::Immediate))
+                .is_err()
+            {
+                eprintln!("Failed to send imported scene to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::UndoSceneLoad => {
+            let Some(scene) = state.previous_scene.lock().await.take() else {
+                return ServerMessage::InternalError("No scene load to undo.".to_string());
+            };
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetScene(scene, ActionTiming::Immediate))
+                .is_err()
+            {
+                eprintln!("Failed to send UndoSceneLoad to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
         ClientMessage::RemoveFrame(line_id, position, timing) => {
             if state
                 .sched_iface
@@ -214,6 +562,68 @@ async fn on_message(
                 )
             }
         }
+        ClientMessage::SetFrameNudge(line_id, frame_id, nudge, timing) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetFrameNudge(
+                    line_id, frame_id, nudge, timing,
+                ))
+                .is_err()
+            {
+                eprintln!("Failed to send SetFrameNudge to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::SetFrameOneShot(line_id, frame_id, one_shot) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetFrameOneShot(
+                    line_id,
+                    frame_id,
+                    one_shot,
+                    ActionTiming::Immediate,
+                ))
+                .is_err()
+            {
+                eprintln!("Failed to send SetFrameOneShot to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::EnableFramesBatch(frames, timing) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::EnableFramesBatch(frames, timing))
+                .is_err()
+            {
+                eprintln!("Failed to send EnableFramesBatch to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::DisableFramesBatch(frames, timing) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::DisableFramesBatch(frames, timing))
+                .is_err()
+            {
+                eprintln!("Failed to send DisableFramesBatch to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::RemoveFramesBatch(frames, timing) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::RemoveFramesBatch(frames, timing))
+                .is_err()
+            {
+                eprintln!("Failed to send RemoveFramesBatch to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
         ClientMessage::GetSnapshot => {
             let scene = state.scene_image.lock().await.clone();
             let clock = Clock::from(&state.clock_server);
@@ -229,6 +639,13 @@ async fn on_message(
             ServerMessage::Snapshot(snapshot)
         }
         ClientMessage::StartedEditingFrame(line_idx, frame_idx) => {
+            // The first client to open a frame keeps it until they close it
+            state
+                .frame_locks
+                .lock()
+                .await
+                .entry((line_idx, frame_idx))
+                .or_insert_with(|| client_name.clone());
             let _ = state
                 .update_sender
                 .send(SovaNotification::PeerStartedEditingFrame(
@@ -239,6 +656,11 @@ async fn on_message(
             ServerMessage::Success
         }
         ClientMessage::StoppedEditingFrame(line_idx, frame_idx) => {
+            let mut locks = state.frame_locks.lock().await;
+            if locks.get(&(line_idx, frame_idx)) == Some(&*client_name) {
+                locks.remove(&(line_idx, frame_idx));
+            }
+            drop(locks);
             let _ = state
                 .update_sender
                 .send(SovaNotification::PeerStoppedEditingFrame(
@@ -281,6 +703,130 @@ async fn on_message(
             }
             ServerMessage::Success
         }
+        ClientMessage::SetSceneMetadata(metadata, timing) => {
+            if let Err(reason) = metadata.validate() {
+                return ServerMessage::Rejected(reason);
+            }
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetSceneMetadata(metadata, timing))
+                .is_err()
+            {
+                eprintln!("Failed to send SetSceneMetadata to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::AuditionScript(content, lang) => {
+            // Compiling may run an external compiler, keep it off the connection tasks
+            let languages = state.languages();
+            let compiled =
+                tokio::task::spawn_blocking(move || compile_audition(&languages, content, lang))
+                    .await;
+            let script = match compiled {
+                Ok(Ok(script)) => script,
+                Ok(Err(err)) => return ServerMessage::AuditionFailed(err),
+                Err(e) => {
+                    return ServerMessage::InternalError(format!("Audition compile failed: {}", e));
+                }
+            };
+            if state
+                .sched_iface
+                .send(SchedulerMessage::AuditionScript(script))
+                .is_err()
+            {
+                eprintln!("Failed to send AuditionScript to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::ValidateScene => {
+            let scene = state.scene_image.lock().await.clone();
+            let languages = state.languages();
+            match tokio::task::spawn_blocking(move || validate_scene(&languages, &scene)).await {
+                Ok(report) => ServerMessage::SceneValidation(report),
+                Err(e) => ServerMessage::InternalError(format!("Scene validation failed: {}", e)),
+            }
+        }
+        ClientMessage::GetSceneStats => {
+            ServerMessage::SceneStats(scene_stats(&*state.scene_image.lock().await))
+        }
+        ClientMessage::SearchScripts(pattern) => match Regex::new(&pattern) {
+            Ok(regex) => {
+                let scene = state.scene_image.lock().await;
+                ServerMessage::SearchResults(search_scripts(&regex, &scene))
+            }
+            Err(e) => {
+                ServerMessage::InternalError(format!("Invalid search pattern '{}': {}", pattern, e))
+            }
+        },
+        ClientMessage::ReplaceInScripts(pattern, replacement) => {
+            let regex = match Regex::new(&pattern) {
+                Ok(regex) => regex,
+                Err(e) => {
+                    return ServerMessage::InternalError(format!(
+                        "Invalid search pattern '{}': {}",
+                        pattern, e
+                    ));
+                }
+            };
+            let scene = state.scene_image.lock().await.clone();
+            let locks = state.frame_locks.lock().await.clone();
+            let languages = state.languages();
+            let name = client_name.clone();
+            let replaced = tokio::task::spawn_blocking(move || {
+                let locked_by_others = |line_id, frame_id| {
+                    locks
+                        .get(&(line_id, frame_id))
+                        .is_some_and(|holder| *holder != name)
+                };
+                replace_in_scripts(&languages, &regex, &replacement, &scene, locked_by_others)
+            })
+            .await;
+            let (changed, failed) = match replaced {
+                Ok(replaced) => replaced,
+                Err(e) => {
+                    return ServerMessage::InternalError(format!("Script replace failed: {}", e));
+                }
+            };
+            // Scripts edited while the replacements compiled keep their edit, and only the
+            // script of the other frames is sent, leaving the rest of each frame as it is now
+            let scene = state.scene_image.lock().await;
+            let changed: Vec<_> = changed
+                .into_iter()
+                .filter(|(line_id, frame_id, searched, _)| {
+                    scene
+                        .get_frame(*line_id, *frame_id)
+                        .is_some_and(|frame| frame.script().content() == searched)
+                })
+                .collect();
+            drop(scene);
+            let n_changed = changed.len();
+            for (line_id, frame_id, _, script) in changed {
+                let set_script =
+                    SchedulerMessage::SetScript(line_id, frame_id, script, ActionTiming::Immediate);
+                if state.sched_iface.send(set_script).is_err() {
+                    eprintln!("Failed to send SetScript to scheduler.");
+                    return ServerMessage::InternalError(
+                        "Scheduler communication error.".to_string(),
+                    );
+                }
+            }
+            ServerMessage::ScriptsReplaced(n_changed, failed)
+        }
+        // The subscription belongs to the connection, which applies it in `process_client`
+        ClientMessage::SubscribeLines(_) => ServerMessage::Success,
+        // Likewise for the followed peer, once it is known to be another connected peer
+        ClientMessage::FollowPeer(Some(peer)) => {
+            if peer == *client_name {
+                return ServerMessage::InternalError("Cannot follow yourself.".to_string());
+            }
+            if !state.clients.lock().await.iter().any(|c| c.name == peer) {
+                return ServerMessage::InternalError(format!("No peer named '{}'.", peer));
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::FollowPeer(None) => ServerMessage::Success,
         ClientMessage::RequestDeviceList => {
             println!("[ info ] Client '{}' requested device list.", client_name);
             ServerMessage::DeviceList(state.devices.device_list())
@@ -388,6 +934,71 @@ async fn on_message(
                 name, e
             )),
         },
+        ClientMessage::SetDeviceLatency(name, latency) => {
+            if let Err(e) = state.devices.set_latency(name, latency) {
+                return ServerMessage::InternalError(format!("Failed to set latency: {}", e));
+            }
+            let updated_list = state.devices.device_list();
+            let _ = state
+                .update_sender
+                .send(SovaNotification::DeviceListChanged(updated_list.clone()));
+            ServerMessage::DeviceList(updated_list)
+        }
+        ClientMessage::SetMpeZone(name, members) => {
+            if let Err(e) = state.devices.set_mpe_zone(name, members) {
+                return ServerMessage::InternalError(format!("Failed to set MPE zone: {}", e));
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::GetInstruments => ServerMessage::Instruments(state.devices.instruments()),
+        ClientMessage::SetInstrument(name, sound) => {
+            state.devices.set_instrument(name, sound);
+            let instruments = state.devices.instruments();
+            let _ = state
+                .update_sender
+                .send(SovaNotification::InstrumentsChanged(instruments.clone()));
+            ServerMessage::Instruments(instruments)
+        }
+        ClientMessage::GetLanguages => ServerMessage::Languages(
+            state.languages().languages().map(str::to_owned).collect(),
+            state.language_syntaxes(),
+        ),
+        ClientMessage::SetMidiMetronome(slot, on) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetMidiMetronome(slot, on))
+                .is_err()
+            {
+                eprintln!("Failed to send SetMidiMetronome to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::SetOscClock(config) => {
+            if let Some(config) = &config {
+                if !config.rate.is_finite() || config.rate <= 0.0 {
+                    return ServerMessage::InternalError(format!(
+                        "Invalid OSC clock rate: {}, it must be a positive number of messages per beat.",
+                        config.rate
+                    ));
+                }
+                if !config.address.starts_with('/') {
+                    return ServerMessage::InternalError(format!(
+                        "Invalid OSC clock address: '{}', it must start with '/'.",
+                        config.address
+                    ));
+                }
+            }
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetOscClock(config))
+                .is_err()
+            {
+                eprintln!("Failed to send SetOscClock to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
         ClientMessage::GetLine(line_id) => {
             let scene = state.scene_image.lock().await;
             if let Some(line) = scene.line(line_id) {
@@ -418,6 +1029,111 @@ async fn on_message(
             }
             ServerMessage::Success
         }
+        ClientMessage::SetLinePlaybackMode(line_id, mode, timing) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetLinePlaybackMode(line_id, mode, timing))
+                .is_err()
+            {
+                eprintln!("Failed to send SetLinePlaybackMode to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::SetLineGate(line_id, gate, timing) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetLineGate(line_id, gate, timing))
+                .is_err()
+            {
+                eprintln!("Failed to send SetLineGate to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::SetLineMidiChannel(line_id, channel, timing) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetLineMidiChannel(line_id, channel, timing))
+                .is_err()
+            {
+                eprintln!("Failed to send SetLineMidiChannel to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::SetLineTranspose(line_id, semitones, timing) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetLineTranspose(
+                    line_id, semitones, timing,
+                ))
+                .is_err()
+            {
+                eprintln!("Failed to send SetLineTranspose to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::SetLineRetriggerOnEnable(line_id, retrigger, timing) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetLineRetriggerOnEnable(
+                    line_id, retrigger, timing,
+                ))
+                .is_err()
+            {
+                eprintln!("Failed to send SetLineRetriggerOnEnable to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::SetLineDurationGenerator(line_id, generator, timing) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetLineDurationGenerator(
+                    line_id, generator, timing,
+                ))
+                .is_err()
+            {
+                eprintln!("Failed to send SetLineDurationGenerator to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::SetLineFill(line_id, frames, every) => {
+            // The scheduler compiles the fill frames, and reports those that do not compile
+            let fill = Some(LineFill { frames, every }).filter(|fill| !fill.frames.is_empty());
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetLineFill(
+                    line_id,
+                    fill,
+                    ActionTiming::Immediate,
+                ))
+                .is_err()
+            {
+                eprintln!("Failed to send SetLineFill to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
+        ClientMessage::SetLineQuantization(line_id, grid, timing) => {
+            if let Some(grid) = grid.filter(|grid| !grid.is_finite() || *grid <= 0.0) {
+                return ServerMessage::InternalError(format!(
+                    "Invalid quantization grid: {grid}, it must be a positive number of beats."
+                ));
+            }
+            if state
+                .sched_iface
+                .send(SchedulerMessage::SetLineQuantization(line_id, grid, timing))
+                .is_err()
+            {
+                eprintln!("Failed to send SetLineQuantization to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
         ClientMessage::AddLine(line_id, line, timing) => {
             if state
                 .sched_iface
@@ -440,6 +1156,17 @@ async fn on_message(
             }
             ServerMessage::Success
         }
+        ClientMessage::MoveLine(from, to, timing) => {
+            if state
+                .sched_iface
+                .send(SchedulerMessage::MoveLine(from, to, timing))
+                .is_err()
+            {
+                eprintln!("Failed to send MoveLine to scheduler.");
+                return ServerMessage::InternalError("Scheduler communication error.".to_string());
+            }
+            ServerMessage::Success
+        }
         ClientMessage::GetFrame(line_id, frame_id) => {
             let scene = state.scene_image.lock().await;
             if let Some(frame) = scene.get_frame(line_id, frame_id) {
@@ -481,6 +1208,31 @@ async fn on_message(
                 .send(SovaNotification::DeviceListChanged(updated_list));
             ServerMessage::DevicesRestored { missing_devices }
         }
+        ClientMessage::LockScene | ClientMessage::UnlockScene
+            if state.scene_lead.as_deref() != Some(client_name.as_str()) =>
+        {
+            ServerMessage::Rejected("Only the lead client can lock the scene.".to_string())
+        }
+        ClientMessage::LockScene => {
+            let mut lock = state.scene_lock.lock().await;
+            *lock = Some(client_name.clone());
+            drop(lock);
+            println!("[ lock ] Scene locked by '{}'", client_name);
+            let _ = state
+                .update_sender
+                .send(SovaNotification::SceneLockChanged(Some(client_name.clone())));
+            ServerMessage::Success
+        }
+        ClientMessage::UnlockScene => {
+            let mut lock = state.scene_lock.lock().await;
+            if lock.take().is_some() {
+                println!("[ lock ] Scene unlocked by '{}'", client_name);
+                let _ = state
+                    .update_sender
+                    .send(SovaNotification::SceneLockChanged(None));
+            }
+            ServerMessage::Success
+        }
         ClientMessage::GetAudioEngineState => {
             ServerMessage::AudioEngineState(state.get_audio_engine_state())
         }
@@ -491,6 +1243,11 @@ async fn on_message(
             buffer_size,
             sample_paths,
         } => {
+            if !AUDIO_AVAILABLE {
+                return ServerMessage::InternalError(
+                    "Audio engine not compiled in this server".to_string(),
+                );
+            }
             let Some(ref restart_tx) = state.audio_restart_tx else {
                 return ServerMessage::InternalError("Audio engine not available".to_string());
             };
@@ -515,80 +1272,391 @@ async fn on_message(
                 );
             }
 
-            match response_rx.recv() {
-                Ok(Ok(new_state)) => ServerMessage::AudioEngineState(new_state),
-                Ok(Err(e)) => ServerMessage::InternalError(format!("Audio restart failed: {}", e)),
-                Err(_) => ServerMessage::InternalError("Audio restart channel closed".to_string()),
-            }
+            match response_rx.recv() {
+                Ok(Ok(new_state)) => ServerMessage::AudioEngineState(new_state),
+                Ok(Err(e)) => ServerMessage::InternalError(format!("Audio restart failed: {}", e)),
+                Err(_) => ServerMessage::InternalError("Audio restart channel closed".to_string()),
+            }
+        }
+    }
+}
+
+/// Refuses mutating messages, and lock changes, from anyone but the lock holder.
+fn check_scene_lock(
+    msg: &ClientMessage,
+    lock_holder: Option<&str>,
+    client_name: &str,
+) -> Option<ServerMessage> {
+    let holder = lock_holder?;
+    if holder == client_name {
+        return None;
+    }
+    let guarded = msg.is_mutating()
+        || matches!(msg, ClientMessage::LockScene | ClientMessage::UnlockScene);
+    guarded.then(|| ServerMessage::Rejected(format!("Scene is locked by '{}'.", holder)))
+}
+
+/// Refuses renames taking the name of another connected client or of the lead, and any
+/// rename while another client holds the scene lock.
+fn check_rename(
+    new_name: &str,
+    old_name: &str,
+    clients: &[PeerIdentity],
+    lock_holder: Option<&str>,
+    scene_lead: Option<&str>,
+) -> Option<ServerMessage> {
+    if let Some(holder) = lock_holder.filter(|holder| *holder != old_name) {
+        return Some(ServerMessage::Rejected(format!(
+            "Scene is locked by '{}'.",
+            holder
+        )));
+    }
+    if new_name.is_empty() || new_name == DEFAULT_CLIENT_NAME {
+        return Some(ServerMessage::Rejected(
+            "Invalid username (empty or reserved).".to_string(),
+        ));
+    }
+    if clients.iter().any(|peer| peer.name == new_name) {
+        return Some(ServerMessage::Rejected(format!(
+            "Username '{}' is already taken.",
+            new_name
+        )));
+    }
+    if scene_lead == Some(new_name) {
+        return Some(ServerMessage::Rejected(format!(
+            "'{}' leads the scene and can only be taken when connecting.",
+            new_name
+        )));
+    }
+    None
+}
+
+/// Refuses edits and removals of frames another client is editing. Lines and scenes
+/// replaced as a whole go through, the locks following or dropping with their frame.
+fn check_frame_locks(
+    msg: &ClientMessage,
+    locks: &HashMap<(usize, usize), String>,
+    client_name: &str,
+) -> Option<ServerMessage> {
+    // Frames overwritten or removed
+    let touched: Vec<(usize, usize)> = match msg {
+        ClientMessage::SchedulerControl(SchedulerMessage::SetScript(line, frame, _, _))
+        | ClientMessage::SchedulerControl(SchedulerMessage::RemoveFrame(line, frame, _))
+        | ClientMessage::RemoveFrame(line, frame, _) => vec![(*line, *frame)],
+        ClientMessage::SchedulerControl(SchedulerMessage::SetFrames(frames, _))
+        | ClientMessage::SetFrames(frames, _) => frames
+            .iter()
+            .map(|(line, frame, _)| (*line, *frame))
+            .collect(),
+        ClientMessage::SchedulerControl(SchedulerMessage::RemoveFramesBatch(frames, _))
+        | ClientMessage::RemoveFramesBatch(frames, _) => frames.clone(),
+        _ => return None,
+    };
+    touched.into_iter().find_map(|(line, frame)| {
+        let holder = locks
+            .get(&(line, frame))
+            .filter(|holder| *holder != client_name)?;
+        Some(ServerMessage::Rejected(format!(
+            "Frame {} of line {} is being edited by '{}'.",
+            frame, line, holder
+        )))
+    })
+}
+
+/// Index of a line after another one is added, removed or moved, `None` if it was removed.
+fn shift_line_index(index: usize, change: &SovaNotification) -> Option<usize> {
+    match *change {
+        SovaNotification::AddedLine(at, _) if index >= at => Some(index + 1),
+        SovaNotification::RemovedLine(at) if index == at => None,
+        SovaNotification::RemovedLine(at) if index > at => Some(index - 1),
+        SovaNotification::MovedLine(from, to) if index == from => Some(to),
+        SovaNotification::MovedLine(from, to) if from < index && index <= to => Some(index - 1),
+        SovaNotification::MovedLine(from, to) if to <= index && index < from => Some(index + 1),
+        _ => Some(index),
+    }
+}
+
+/// Follows the frames being edited through a change of the scene, which is not applied to
+/// `scene` yet. Returns the locks dropped along with their frame, as they no longer point
+/// at a frame the lock holder was editing.
+fn remap_frame_locks(
+    locks: &mut HashMap<(usize, usize), String>,
+    scene: &Scene,
+    change: &SovaNotification,
+) -> Vec<((usize, usize), String)> {
+    let reshapes_scene = matches!(
+        change,
+        SovaNotification::AddedLine(..)
+            | SovaNotification::RemovedLine(_)
+            | SovaNotification::MovedLine(..)
+            | SovaNotification::AddedFrame(..)
+            | SovaNotification::RemovedFrame(..)
+            | SovaNotification::UpdatedLines(_)
+            | SovaNotification::UpdatedScene(_)
+    );
+    if !reshapes_scene || locks.is_empty() {
+        return Vec::new();
+    }
+    let remap = |(line, frame): (usize, usize)| -> Option<(usize, usize)> {
+        match change {
+            SovaNotification::AddedLine(..)
+            | SovaNotification::RemovedLine(_)
+            | SovaNotification::MovedLine(..) => Some((shift_line_index(line, change)?, frame)),
+            SovaNotification::AddedFrame(l, at, _) if *l == line && frame >= *at => {
+                Some((line, frame + 1))
+            }
+            SovaNotification::RemovedFrame(l, at) if *l == line && frame == *at => None,
+            SovaNotification::RemovedFrame(l, at) if *l == line && frame > *at => {
+                Some((line, frame - 1))
+            }
+            // Frames of updated lines can no longer be told apart once their number changed
+            SovaNotification::UpdatedLines(lines) => {
+                let reshaped = lines.iter().any(|(l, updated)| {
+                    *l == line
+                        && scene.line(line).map(|old| old.n_frames()) != Some(updated.n_frames())
+                });
+                (!reshaped).then_some((line, frame))
+            }
+            SovaNotification::UpdatedScene(new_scene) => {
+                new_scene.has_frame(line, frame).then_some((line, frame))
+            }
+            _ => Some((line, frame)),
+        }
+    };
+    let mut dropped = Vec::new();
+    let remapped = locks
+        .drain()
+        .filter_map(|(frame, holder)| match remap(frame) {
+            Some(frame) => Some((frame, holder)),
+            None => {
+                dropped.push((frame, holder));
+                None
+            }
+        })
+        .collect();
+    *locks = remapped;
+    dropped
+}
+
+/// Compiles a script for audition, failing instead of playing a broken or unknown script.
+fn compile_audition(
+    languages: &LanguageCenter,
+    content: String,
+    lang: String,
+) -> Result<Script, CompilationError> {
+    let mut script = Script::new(content, lang);
+    languages.blocking_process(&mut script);
+    match script.compilation_state() {
+        CompilationState::Error(err) => Err(err.clone()),
+        CompilationState::NotCompiled | CompilationState::Compiling => Err(CompilationError {
+            lang: script.lang().to_owned(),
+            info: "nothing to audition in this language".to_string(),
+            from: 0,
+            to: 0,
+        }),
+        _ => Ok(script),
+    }
+}
+
+/// Keeps the current scene so that the scene load about to happen can be undone.
+async fn keep_previous_scene(state: &ServerState) {
+    let scene = state.scene_image.lock().await.clone();
+    *state.previous_scene.lock().await = Some(scene);
+}
+
+/// Matches of `regex` in every script of the scene, in scene order.
+fn search_scripts(regex: &Regex, scene: &Scene) -> Vec<(usize, usize, usize, usize)> {
+    let mut results = Vec::new();
+    for (line_id, line) in scene.lines.iter().enumerate() {
+        for (frame_id, frame) in line.frames.iter().enumerate() {
+            for found in regex.find_iter(frame.script().content()) {
+                results.push((line_id, frame_id, found.start(), found.end()));
+            }
+        }
+    }
+    results
+}
+
+fn scene_stats(scene: &Scene) -> SceneStats {
+    let mut stats = SceneStats {
+        lines: scene.n_lines(),
+        ..Default::default()
+    };
+    for line in scene.lines.iter() {
+        if line.is_enabled() {
+            stats.enabled_lines += 1;
+        }
+        stats.frames += line.n_frames();
+        stats.enabled_frames += line.frames.iter().filter(|frame| frame.enabled).count();
+        for script in line.scripts_iter().filter(|script| !script.is_empty()) {
+            *stats
+                .scripts_per_language
+                .entry(script.lang().to_owned())
+                .or_default() += 1;
+        }
+        stats.duration = stats.duration.max(line.length());
+    }
+    stats
+}
+
+/// New scripts by line and frame, along with the content they replaced.
+type ReplacedScripts = Vec<(usize, usize, String, Script)>;
+type FailedReplacements = Vec<(usize, usize, CompilationError)>;
+
+/// Replaces the matches of `regex` in every script of the scene, compiling each new script
+/// on its own. Returns the new scripts, and the frames left untouched because their new
+/// script does not compile. Frames for which `skip` holds are left alone.
+fn replace_in_scripts(
+    languages: &LanguageCenter,
+    regex: &Regex,
+    replacement: &str,
+    scene: &Scene,
+    skip: impl Fn(usize, usize) -> bool,
+) -> (ReplacedScripts, FailedReplacements) {
+    let mut changed = Vec::new();
+    let mut failed = Vec::new();
+    for (line_id, line) in scene.lines.iter().enumerate() {
+        for (frame_id, frame) in line.frames.iter().enumerate() {
+            let content = frame.script().content();
+            let replaced = regex.replace_all(content, replacement);
+            if replaced == content || skip(line_id, frame_id) {
+                continue;
+            }
+            let mut script = frame.script().clone();
+            script.set_content(replaced.into_owned());
+            languages.blocking_process(&mut script);
+            if let CompilationState::Error(err) = script.compilation_state() {
+                failed.push((line_id, frame_id, err.clone()));
+                continue;
+            }
+            changed.push((line_id, frame_id, content.to_owned(), script));
+        }
+    }
+    (changed, failed)
+}
+
+/// Compiles every non-empty script of a scene on its own copy, leaving the scene untouched.
+/// Scripts in a language nobody can run are reported as errors.
+fn validate_scene(
+    languages: &LanguageCenter,
+    scene: &Scene,
+) -> Vec<(usize, usize, CompilationState)> {
+    let mut report = Vec::new();
+    for (line_id, line) in scene.lines.iter().enumerate() {
+        for (frame_id, frame) in line.frames.iter().enumerate() {
+            let mut script = frame.script().clone();
+            if script.is_empty() {
+                continue;
+            }
+            languages.blocking_process(&mut script);
+            let state = match script.compilation_state() {
+                CompilationState::NotCompiled | CompilationState::Compiling => {
+                    CompilationState::Error(CompilationError {
+                        lang: script.lang().to_owned(),
+                        info: format!("unknown language '{}'", script.lang()),
+                        from: 0,
+                        to: 0,
+                    })
+                }
+                state => state.lightened(),
+            };
+            report.push((line_id, frame_id, state));
+        }
+    }
+    report
+}
+
+async fn update_identity(
+    state: &ServerState,
+    client_name: &mut String,
+    identity: PeerIdentity,
+) -> ServerMessage {
+    let old_name = client_name.clone();
+    let is_new_client = *client_name == DEFAULT_CLIENT_NAME;
+    let renamed = !is_new_client && identity.name != old_name;
+    let lock_holder = state.scene_lock.lock().await.clone();
+    let mut clients_guard = state.clients.lock().await;
+
+    // Locks and the lead are held by name, which must then stay with its connection
+    let rejection = renamed
+        .then(|| {
+            check_rename(
+                &identity.name,
+                &old_name,
+                &clients_guard,
+                lock_holder.as_deref(),
+                state.scene_lead.as_deref(),
+            )
+        })
+        .flatten();
+    if let Some(rejection) = rejection {
+        return rejection;
+    }
+
+    if is_new_client {
+        println!("Client identified as: {}", identity.name);
+        clients_guard.push(identity.clone());
+    } else if let Some(i) = clients_guard.iter().position(|x| x.name == old_name) {
+        println!(
+            "Client {} changed identity to {:?}",
+            clients_guard[i].name, identity
+        );
+        clients_guard[i] = identity.clone();
+    } else {
+        eprintln!(
+            "Error: Could not find old name '{}' to replace. Adding '{}'.",
+            old_name, identity.name
+        );
+        clients_guard.push(identity.clone());
+    }
+    let updated_clients = clients_guard.iter().map(|x| x.name.clone()).collect();
+    drop(clients_guard);
+
+    let mut lock = state.scene_lock.lock().await;
+    if lock.as_deref() == Some(old_name.as_str()) {
+        *lock = Some(identity.name.clone());
+        let _ = state
+            .update_sender
+            .send(SovaNotification::SceneLockChanged(lock.clone()));
+    }
+    drop(lock);
+    for holder in state.frame_locks.lock().await.values_mut() {
+        if *holder == old_name {
+            *holder = identity.name.clone();
         }
     }
+
+    *client_name = identity.name;
+
+    let _ = state
+        .update_sender
+        .send(SovaNotification::ClientListChanged(updated_clients));
+
+    ServerMessage::Success
 }
 
-async fn send_msg<W: AsyncWriteExt + Unpin>(writer: &mut W, msg: ServerMessage) -> io::Result<()> {
-    let msgpack_bytes = rmp_serde::to_vec_named(&msg).map_err(|e| {
+async fn send_msg<W: AsyncWriteExt + Unpin>(
+    writer: &mut W,
+    msg: ServerMessage,
+    codec: CompressionCodec,
+    protocol_version: u32,
+) -> io::Result<()> {
+    let msgpack_bytes = msg.to_msgpack(protocol_version).map_err(|e| {
         io::Error::new(
             io::ErrorKind::InvalidData,
             format!("Failed to serialize ServerMessage to MessagePack: {}", e),
         )
     })?;
 
-    let (final_bytes, is_compressed) = compress_message_intelligently(&msg, &msgpack_bytes)?;
-
-    let mut len = final_bytes.len() as u32;
-    if is_compressed {
-        len |= COMPRESSION_FLAG;
-    }
+    let (header, final_bytes) = encode_frame(msg.compression_strategy(), codec, &msgpack_bytes)?;
 
-    writer.write_all(&len.to_be_bytes()).await?;
+    writer.write_all(&header.to_be_bytes()).await?;
     writer.write_all(&final_bytes).await?;
     writer.flush().await?;
 
     Ok(())
 }
 
-fn compress_message_intelligently(
-    msg: &ServerMessage,
-    msgpack_bytes: &[u8],
-) -> io::Result<(Vec<u8>, bool)> {
-    use crate::client::CompressionStrategy;
-
-    match msg.compression_strategy() {
-        CompressionStrategy::Never => Ok((msgpack_bytes.to_vec(), false)),
-        CompressionStrategy::Always => {
-            if msgpack_bytes.len() > COMPRESSION_MIN_SIZE {
-                let compression_level = if msgpack_bytes.len() < HIGH_COMPRESSION_CUTOFF {
-                    1
-                } else {
-                    3
-                };
-                let compressed = zstd::encode_all(msgpack_bytes, compression_level)
-                    .map_err(|e| io::Error::other(format!("Compression failed: {}", e)))?;
-                if compressed.len() < msgpack_bytes.len() {
-                    Ok((compressed, true))
-                } else {
-                    Ok((msgpack_bytes.to_vec(), false))
-                }
-            } else {
-                Ok((msgpack_bytes.to_vec(), false))
-            }
-        }
-        CompressionStrategy::Adaptive => {
-            if msgpack_bytes.len() < COMPRESSION_ADAPTIVE_THRESHOLD {
-                Ok((msgpack_bytes.to_vec(), false))
-            } else {
-                let compression_level = if msgpack_bytes.len() < HIGH_COMPRESSION_CUTOFF {
-                    1
-                } else {
-                    3
-                };
-                let compressed = zstd::encode_all(msgpack_bytes, compression_level)
-                    .map_err(|e| io::Error::other(format!("Compression failed: {}", e)))?;
-                Ok((compressed, true))
-            }
-        }
-    }
-}
-
 impl SovaCoreServer {
     pub fn new(ip: String, port: u16, state: ServerState) -> Self {
         SovaCoreServer { ip, port, state }
@@ -624,6 +1692,7 @@ impl SovaCoreServer {
                 }
                 _ = tokio::time::sleep(Duration::from_millis(10)) => {
                     let _ = self.state.update_sender.send(SovaNotification::Tick);
+                    self.state.stop_if_idle(Instant::now());
                 }
             }
         }
@@ -633,8 +1702,10 @@ impl SovaCoreServer {
 
     pub fn start_image_maintainer(&self, scheduler_notifications: Receiver<SovaNotification>) {
         let scene_image = self.state.scene_image.clone();
+        let frame_locks = self.state.frame_locks.clone();
         let update_sender = self.state.update_sender.clone();
         let is_playing = self.state.is_playing.clone();
+        let quantization_grid = self.state.quantization_grid.clone();
         thread::spawn(move || {
             let position_broadcast_interval =
                 std::time::Duration::from_millis(POSITION_BROADCAST_INTERVAL_MS);
@@ -644,10 +1715,20 @@ impl SovaCoreServer {
                 match scheduler_notifications.recv() {
                     Ok(p) => {
                         let mut guard = scene_image.blocking_lock();
+                        let dropped =
+                            remap_frame_locks(&mut frame_locks.blocking_lock(), &guard, &p);
+                        for ((line_id, frame_id), holder) in dropped {
+                            let _ = update_sender.send(SovaNotification::PeerStoppedEditingFrame(
+                                holder, line_id, frame_id,
+                            ));
+                        }
                         match &p {
                             SovaNotification::UpdatedScene(scene) => {
                                 *guard = scene.clone();
                             }
+                            SovaNotification::UpdatedSceneMetadata(metadata) => {
+                                guard.metadata = *metadata;
+                            }
                             SovaNotification::UpdatedLines(lines) => {
                                 for (i, line) in lines {
                                     guard.set_line(*i, line.clone());
@@ -659,6 +1740,9 @@ impl SovaCoreServer {
                             SovaNotification::RemovedLine(index) => {
                                 guard.remove_line(*index);
                             }
+                            SovaNotification::MovedLine(from, to) => {
+                                guard.move_line(*from, *to);
+                            }
                             SovaNotification::UpdatedFrames(frames) => {
                                 for (line_id, frame_id, frame) in frames.iter() {
                                     guard.line_mut(*line_id).set_frame(*frame_id, frame.clone());
@@ -680,6 +1764,11 @@ impl SovaCoreServer {
                                 };
                                 is_playing.store(playing, Ordering::Relaxed);
                             }
+                            SovaNotification::QuantizationGridChanged(grid) => {
+                                if let Ok(mut current) = quantization_grid.lock() {
+                                    *current = *grid;
+                                }
+                            }
                             _ => (),
                         };
                         drop(guard);
@@ -710,8 +1799,54 @@ impl SovaCoreServer {
     }
 }
 
+/// Enables TCP keepalive on a client socket, so that the OS notices peers that vanished.
+fn apply_keepalive(socket: &TcpStream, settings: &ConnectionSettings) -> io::Result<()> {
+    let Some(time) = settings.keepalive else {
+        return Ok(());
+    };
+    let keepalive = TcpKeepalive::new()
+        .with_time(time)
+        .with_interval(settings.keepalive_interval);
+    SockRef::from(socket).set_tcp_keepalive(&keepalive)
+}
+
+/// Completes once `timeout` has elapsed since `since`, never without a timeout.
+async fn idle_deadline(since: Instant, timeout: Option<Duration>) {
+    match timeout {
+        Some(timeout) => tokio::time::sleep_until(since + timeout).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Sends a message, failing if the client does not take it before `timeout`.
+async fn send_msg_within<W: AsyncWriteExt + Unpin>(
+    writer: &mut W,
+    msg: ServerMessage,
+    codec: CompressionCodec,
+    protocol_version: u32,
+    timeout: Option<Duration>,
+) -> io::Result<()> {
+    let send = send_msg(writer, msg, codec, protocol_version);
+    let Some(timeout) = timeout else {
+        return send.await;
+    };
+    match tokio::time::timeout(timeout, send).await {
+        Ok(result) => result,
+        Err(_) => Err(io::Error::new(
+            ErrorKind::TimedOut,
+            "Client stopped reading",
+        )),
+    }
+}
+
 async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<String> {
     socket.set_nodelay(true)?;
+    apply_keepalive(&socket, &state.connection)?;
+    let ConnectionSettings {
+        write_timeout,
+        idle_timeout,
+        ..
+    } = state.connection;
     let client_addr = socket.peer_addr()?;
     let client_addr_str = client_addr.to_string();
     let (reader, writer) = socket.into_split();
@@ -722,9 +1857,27 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
     let mut clock = Clock::from(&state.clock_server);
 
     let hello_msg: ServerMessage;
+    let codec: CompressionCodec;
+    let protocol_version: u32;
 
-    match read_message_internal(&mut reader, &client_addr_str).await {
-        Ok(Some(ClientMessage::SetName(new_name))) => {
+    let handshake = select! {
+        msg = read_message_internal(&mut reader, &client_addr_str) => msg,
+        _ = idle_deadline(Instant::now(), idle_timeout) => Err(io::Error::new(
+            ErrorKind::TimedOut,
+            "No handshake received before the idle timeout",
+        )),
+    };
+    let handshake = handshake.map(|msg| {
+        msg.map(|msg| match msg {
+            ClientMessage::SetName(name) => Ok(PeerIdentity::new(name)),
+            ClientMessage::SetIdentity(identity) => Ok(identity),
+            other => Err(Box::new(other)),
+        })
+    });
+
+    match handshake {
+        Ok(Some(Ok(identity))) => {
+            let new_name = identity.name.clone();
             if new_name.is_empty() || new_name == DEFAULT_CLIENT_NAME {
                 eprintln!(
                     "Connection rejected: Invalid username '{}' from {}",
@@ -733,15 +1886,45 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
                 let refuse_msg = ServerMessage::ConnectionRefused(
                     "Invalid username (empty or reserved).".to_string(),
                 );
-                let _ = send_msg(&mut writer, refuse_msg).await;
+                let _ = send_msg(
+                    &mut writer,
+                    refuse_msg,
+                    CompressionCodec::Zstd,
+                    MIN_PROTOCOL_VERSION,
+                )
+                .await;
                 return Err(io::Error::new(
                     io::ErrorKind::InvalidInput,
                     "Invalid username",
                 ));
             }
 
+            protocol_version = match negotiate_protocol_version(identity.protocol_version) {
+                Ok(version) => version,
+                Err(reason) => {
+                    eprintln!(
+                        "Connection rejected: Incompatible protocol version {:?} from {}",
+                        identity.protocol_version, client_addr_str
+                    );
+                    let refuse_msg = ServerMessage::ConnectionRefused(reason);
+                    let _ = send_msg(
+                        &mut writer,
+                        refuse_msg,
+                        CompressionCodec::Zstd,
+                        MIN_PROTOCOL_VERSION,
+                    )
+                    .await;
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Incompatible protocol version",
+                    ));
+                }
+            };
+
+            codec = negotiate_codec(&identity.codecs);
+
             let mut clients_guard = state.clients.lock().await;
-            if clients_guard.iter().any(|name| name == &new_name) {
+            if clients_guard.iter().any(|peer| peer.name == new_name) {
                 eprintln!(
                     "Connection rejected: Username '{}' already taken by {}",
                     new_name, client_addr_str
@@ -750,7 +1933,13 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
                     "Username '{}' is already taken.",
                     new_name
                 ));
-                let _ = send_msg(&mut writer, refuse_msg).await;
+                let _ = send_msg(
+                    &mut writer,
+                    refuse_msg,
+                    CompressionCodec::Zstd,
+                    MIN_PROTOCOL_VERSION,
+                )
+                .await;
                 drop(clients_guard);
                 return Err(io::Error::new(
                     io::ErrorKind::AlreadyExists,
@@ -760,12 +1949,13 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
 
             client_name = new_name;
             println!("Client {} identified as: {}", client_addr_str, client_name);
-            clients_guard.push(client_name.clone());
+            clients_guard.push(identity);
 
             let initial_scene = state.scene_image.lock().await.clone();
             let initial_devices = state.devices.device_list();
             let initial_peers = clients_guard.clone();
-            let updated_peers_for_broadcast = initial_peers.clone();
+            let updated_peers_for_broadcast =
+                initial_peers.iter().map(|peer| peer.name.clone()).collect();
 
             drop(clients_guard);
 
@@ -785,7 +1975,7 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
             let initial_is_playing = state.is_playing.load(Ordering::Relaxed);
 
             let available_languages: Vec<String> =
-                state.languages.languages().map(str::to_owned).collect();
+                state.languages().languages().map(str::to_owned).collect();
 
             println!(
                 "[ handshake ] Sending Hello to {} ({}). Initial is_playing state: {}",
@@ -800,9 +1990,17 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
                 is_playing: initial_is_playing,
                 available_languages,
                 audio_engine_state: state.get_audio_engine_state(),
+                audio_available: AUDIO_AVAILABLE,
+                scene_lock: state.scene_lock.lock().await.clone(),
+                quantization_grid: state.quantization_grid(),
+                protocol_version,
+                codec,
             };
 
-            if send_msg(&mut writer, hello_msg).await.is_err() {
+            if send_msg(&mut writer, hello_msg, codec, protocol_version)
+                .await
+                .is_err()
+            {
                 eprintln!("Failed to send Hello to {}", client_name);
                 return Err(io::Error::new(
                     io::ErrorKind::WriteZero,
@@ -810,14 +2008,20 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
                 ));
             }
         }
-        Ok(Some(other_msg)) => {
+        Ok(Some(Err(other_msg))) => {
             eprintln!(
                 "Connection rejected: Expected SetName, received {:?} from {}",
                 other_msg, client_addr_str
             );
             let refuse_msg =
                 ServerMessage::ConnectionRefused("Invalid handshake sequence.".to_string());
-            let _ = send_msg(&mut writer, refuse_msg).await;
+            let _ = send_msg(
+                &mut writer,
+                refuse_msg,
+                CompressionCodec::Zstd,
+                MIN_PROTOCOL_VERSION,
+            )
+            .await;
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
                 "Invalid handshake sequence",
@@ -836,7 +2040,11 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
         }
     }
 
+    state.record_activity();
     let mut update_receiver = state.update_sender.subscribe();
+    let mut last_activity = Instant::now();
+    let mut subscription: Option<BTreeSet<usize>> = None;
+    let mut following: Option<String> = None;
 
     loop {
         select! {
@@ -845,9 +2053,27 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
             read_result = read_message_internal(&mut reader, &client_name) => {
                 match read_result {
                     Ok(Some(msg)) => {
+                        last_activity = Instant::now();
+                        if let ClientMessage::SubscribeLines(lines) = &msg {
+                            subscription = lines.clone().map(BTreeSet::from_iter);
+                        }
+                        let follow = match &msg {
+                            ClientMessage::FollowPeer(peer) => Some(peer.clone()),
+                            _ => None,
+                        };
                         let response = on_message(msg, &state, &mut client_name).await;
+                        if let (Some(peer), ServerMessage::Success) = (follow, &response) {
+                            following = peer;
+                        }
 
-                        if send_msg(&mut writer, response).await.is_err() {
+                        let Some(response) = response.for_protocol(protocol_version) else {
+                            continue;
+                        };
+                        let send_res = send_msg_within(
+                            &mut writer, response, codec, protocol_version, write_timeout,
+                        )
+                        .await;
+                        if send_res.is_err() {
                             eprintln!("Failed write direct response to {}", client_name);
                             break;
                         }
@@ -863,6 +2089,12 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
                 }
             }
 
+            // Checked before the updates, which are almost always ready
+            _ = idle_deadline(last_activity, idle_timeout) => {
+                println!("Client {} idle for too long. Closing connection.", client_name);
+                break;
+            }
+
             update_result = update_receiver.recv() => {
                 let notification = match update_result {
                     Ok(notif) => notif,
@@ -874,6 +2106,13 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
                         break;
                     }
                 };
+                // Subscribed lines are followed as other lines are added, removed or moved
+                if let Some(lines) = subscription.as_mut() {
+                    *lines = lines
+                        .iter()
+                        .filter_map(|line| shift_line_index(*line, &notification))
+                        .collect();
+                }
                 let broadcast_msg_opt: Option<ServerMessage> = match notification {
                     SovaNotification::UpdatedScene(p) => {
                         Some(ServerMessage::SceneValue(p))
@@ -881,6 +2120,9 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
                     SovaNotification::UpdatedSceneMode(m) => {
                         Some(ServerMessage::SceneMode(m))
                     }
+                    SovaNotification::UpdatedSceneMetadata(m) => {
+                        Some(ServerMessage::SceneMetadata(m))
+                    }
                     SovaNotification::UpdatedLines(lines) => {
                         Some(ServerMessage::LineValues(lines))
                     }
@@ -893,6 +2135,13 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
                     SovaNotification::RemovedLine(line_id) => {
                         Some(ServerMessage::RemoveLine(line_id))
                     }
+                    // Older clients cannot move lines, the scene image already has them moved
+                    SovaNotification::MovedLine(_, _) if protocol_version < PROTOCOL_VERSION => {
+                        Some(ServerMessage::SceneValue(state.scene_image.lock().await.clone()))
+                    }
+                    SovaNotification::MovedLine(from, to) => {
+                        Some(ServerMessage::MoveLine(from, to))
+                    }
                     SovaNotification::UpdatedFrames(frames) => {
                         Some(ServerMessage::FrameValues(frames))
                     }
@@ -919,8 +2168,11 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
                         let clock = Clock::from(&state.clock_server);
                         Some(ServerMessage::ClockState(clock.tempo(), clock.beat(), clock.micros(), clock.quantum()))
                     }
-                    SovaNotification::ClientListChanged(clients) => {
-                        Some(ServerMessage::PeersUpdated(clients))
+                    SovaNotification::QuantizationGridChanged(grid) => {
+                        Some(ServerMessage::QuantizationGrid(grid))
+                    }
+                    SovaNotification::ClientListChanged(_) => {
+                        Some(ServerMessage::PeersUpdated(state.clients.lock().await.clone()))
                     }
                     SovaNotification::ChatReceived(sender_name, chat_msg) => {
                         if sender_name != *client_name {
@@ -931,39 +2183,69 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
                     }
                     SovaNotification::PeerStartedEditingFrame(sender_name, line_idx, frame_idx) => {
                         if sender_name != *client_name {
-                            Some(ServerMessage::PeerStartedEditing(sender_name, line_idx, frame_idx))
+                            let sender = state.peer_identity(&sender_name).await;
+                            if following.as_deref() == Some(sender_name.as_str()) {
+                                Some(ServerMessage::FollowedPeerFocus(sender, line_idx, frame_idx))
+                            } else {
+                                Some(ServerMessage::PeerStartedEditing(sender, line_idx, frame_idx))
+                            }
                         } else {
                             None
                         }
                     }
                     SovaNotification::PeerStoppedEditingFrame(sender_name, line_idx, frame_idx) => {
                         if sender_name != *client_name {
-                            Some(ServerMessage::PeerStoppedEditing(sender_name, line_idx, frame_idx))
+                            let sender = state.peer_identity(&sender_name).await;
+                            Some(ServerMessage::PeerStoppedEditing(sender, line_idx, frame_idx))
                         } else {
                             None
                         }
                     }
+                    SovaNotification::SceneLockChanged(holder) => {
+                        Some(ServerMessage::SceneLockChanged(holder))
+                    }
                     SovaNotification::DeviceListChanged(devices) => {
                         println!("[ broadcast ] Sending updated device list ({} devices) to {}", devices.len(), client_name);
                         Some(ServerMessage::DeviceList(devices))
                     }
+                    SovaNotification::InstrumentsChanged(instruments) => {
+                        Some(ServerMessage::Instruments(instruments))
+                    }
                     SovaNotification::ScopeData(peaks) => {
                         Some(ServerMessage::ScopeData(peaks))
                     }
+                    SovaNotification::LanguagesChanged(languages, syntaxes) => {
+                        Some(ServerMessage::Languages(languages, syntaxes))
+                    }
                     SovaNotification::GlobalVariablesChanged(vars) => {
                         Some(ServerMessage::GlobalVariablesUpdate(vars))
                     }
                     SovaNotification::CompilationUpdated(line_id, frame_id, script_id, state) => {
                         Some(ServerMessage::CompilationUpdate(line_id, frame_id, script_id, state))
                     }
+                    SovaNotification::CompilationWarnings(line_id, frame_id, script_id, warnings) => {
+                        Some(ServerMessage::CompilationWarnings(line_id, frame_id, script_id, warnings))
+                    }
                     SovaNotification::Tick => {
                         clock.capture_app_state();
                         Some(ServerMessage::ClockState(clock.tempo(), clock.beat(), clock.micros(), clock.quantum()))
                     }
                 };
 
+                let broadcast_msg_opt = match &subscription {
+                    Some(lines) => {
+                        broadcast_msg_opt.and_then(|msg| msg.restricted_to_lines(lines))
+                    }
+                    None => broadcast_msg_opt,
+                };
+                let broadcast_msg_opt =
+                    broadcast_msg_opt.and_then(|msg| msg.for_protocol(protocol_version));
+
                 if let Some(broadcast_msg) = broadcast_msg_opt {
-                    let send_res = send_msg(&mut writer, broadcast_msg).await;
+                    let send_res = send_msg_within(
+                        &mut writer, broadcast_msg, codec, protocol_version, write_timeout,
+                    )
+                    .await;
                     if send_res.is_err() {
                         break;
                     }
@@ -973,12 +2255,36 @@ async fn process_client(socket: TcpStream, state: ServerState) -> io::Result<Str
     }
 
     println!("Cleaning up connection for client: {}", client_name);
+    let mut lock = state.scene_lock.lock().await;
+    if lock.as_deref() == Some(client_name.as_str()) {
+        *lock = None;
+        println!("Released scene lock held by {}.", client_name);
+        let _ = state
+            .update_sender
+            .send(SovaNotification::SceneLockChanged(None));
+    }
+    drop(lock);
+    let mut frame_locks = state.frame_locks.lock().await;
+    frame_locks.retain(|&(line_idx, frame_idx), holder| {
+        if *holder != client_name {
+            return true;
+        }
+        let _ = state
+            .update_sender
+            .send(SovaNotification::PeerStoppedEditingFrame(
+                client_name.clone(),
+                line_idx,
+                frame_idx,
+            ));
+        false
+    });
+    drop(frame_locks);
     if client_name != DEFAULT_CLIENT_NAME {
         let mut clients_guard = state.clients.lock().await;
-        if let Some(i) = clients_guard.iter().position(|x| *x == client_name) {
+        if let Some(i) = clients_guard.iter().position(|x| x.name == client_name) {
             clients_guard.remove(i);
             println!("Removed {} from client list.", client_name);
-            let updated_clients = clients_guard.clone();
+            let updated_clients = clients_guard.iter().map(|x| x.name.clone()).collect();
             drop(clients_guard);
             let _ = state
                 .update_sender
@@ -1006,9 +2312,8 @@ async fn read_message_internal<R: AsyncReadExt + Unpin>(
     let mut len_buf = [0u8; 4];
     match reader.read_exact(&mut len_buf).await {
         Ok(_) => {
-            let len_with_flag = u32::from_be_bytes(len_buf);
-            let is_compressed = (len_with_flag & COMPRESSION_FLAG) != 0;
-            let length = len_with_flag & LENGTH_MASK;
+            let header = u32::from_be_bytes(len_buf);
+            let length = frame_length(header);
 
             if length == 0 {
                 return Err(io::Error::new(
@@ -1017,14 +2322,15 @@ async fn read_message_internal<R: AsyncReadExt + Unpin>(
                 ));
             }
 
-            let mut message_buf = vec![0u8; length as usize];
+            let mut message_buf = vec![0u8; length];
             reader.read_exact(&mut message_buf).await?;
 
-            let final_bytes = if is_compressed {
-                decompress_message(&message_buf, client_id_for_logging)?
-            } else {
-                message_buf
-            };
+            let final_bytes = decode_frame(header, message_buf).inspect_err(|e| {
+                eprintln!(
+                    "Failed to decompress data from {}: {}",
+                    client_id_for_logging, e
+                );
+            })?;
 
             let msg = ClientMessage::deserialize(&final_bytes);
             if msg.is_err() {
@@ -1052,12 +2358,964 @@ async fn read_message_internal<R: AsyncReadExt + Unpin>(
     }
 }
 
-fn decompress_message(message_buf: &[u8], client_id: &str) -> io::Result<Vec<u8>> {
-    zstd::decode_all(message_buf).map_err(|e| {
-        eprintln!("Failed to decompress Zstd data from {}: {}", client_id, e);
-        io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!("Zstd decompression error: {}", e),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::server_state;
+    use langs::bob::BobCompiler;
+    use sova_core::scene::{Frame, Line, SceneMetadata};
+    use sova_core::schedule::ActionTiming;
+    use sova_core::vm::{Transcoder, interpreter::InterpreterDirectory};
+
+    #[test]
+    fn locked_scene_rejects_edits_but_not_reads() {
+        let set_script = ClientMessage::SchedulerControl(SchedulerMessage::SetScript(
+            0,
+            0,
+            Script::default(),
+            ActionTiming::Immediate,
+        ));
+
+        assert!(matches!(
+            check_scene_lock(&set_script, Some("lead"), "guest"),
+            Some(ServerMessage::Rejected(_))
+        ));
+        assert!(check_scene_lock(&ClientMessage::GetScene, Some("lead"), "guest").is_none());
+        let audition = ClientMessage::AuditionScript(String::new(), "bob".to_string());
+        assert!(matches!(
+            check_scene_lock(&audition, Some("lead"), "guest"),
+            Some(ServerMessage::Rejected(_))
+        ));
+        assert!(matches!(
+            check_scene_lock(&ClientMessage::UnlockScene, Some("lead"), "guest"),
+            Some(ServerMessage::Rejected(_))
+        ));
+
+        assert!(check_scene_lock(&set_script, Some("lead"), "lead").is_none());
+        assert!(check_scene_lock(&set_script, None, "guest").is_none());
+    }
+
+    #[tokio::test]
+    async fn only_the_lead_locks_the_scene() {
+        let (mut state, _) = server_state();
+        state.scene_lead = Some("lead".to_string());
+        let mut lead = "lead".to_string();
+        let mut guest = "guest".to_string();
+
+        assert!(matches!(
+            on_message(ClientMessage::LockScene, &state, &mut guest).await,
+            ServerMessage::Rejected(_)
+        ));
+        assert!(state.scene_lock.lock().await.is_none());
+        assert!(matches!(
+            on_message(ClientMessage::LockScene, &state, &mut lead).await,
+            ServerMessage::Success
+        ));
+        assert_eq!(state.scene_lock.lock().await.as_deref(), Some("lead"));
+        assert!(matches!(
+            on_message(ClientMessage::UnlockScene, &state, &mut guest).await,
+            ServerMessage::Rejected(_)
+        ));
+        assert!(matches!(
+            on_message(ClientMessage::UnlockScene, &state, &mut lead).await,
+            ServerMessage::Success
+        ));
+        assert!(state.scene_lock.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn guests_cannot_rename_themselves_into_the_lead() {
+        let (mut state, _) = server_state();
+        state.scene_lead = Some("lead".to_string());
+        state.clients.lock().await.extend([
+            PeerIdentity::new("lead".to_string()),
+            PeerIdentity::new("guest".to_string()),
+        ]);
+        let mut lead = "lead".to_string();
+        let mut guest = "guest".to_string();
+
+        assert!(matches!(
+            on_message(ClientMessage::SetName("lead".to_string()), &state, &mut guest).await,
+            ServerMessage::Rejected(_)
+        ));
+        assert_eq!(guest, "guest");
+        assert!(matches!(
+            on_message(ClientMessage::LockScene, &state, &mut lead).await,
+            ServerMessage::Success
+        ));
+        assert!(matches!(
+            on_message(ClientMessage::SetName("other".to_string()), &state, &mut guest).await,
+            ServerMessage::Rejected(_)
+        ));
+        assert!(matches!(
+            on_message(ClientMessage::UnlockScene, &state, &mut guest).await,
+            ServerMessage::Rejected(_)
+        ));
+
+        // Once the lead leaves, its name is still only taken by connecting
+        state.clients.lock().await.retain(|peer| peer.name != "lead");
+        state.scene_lock.lock().await.take();
+        assert!(matches!(
+            on_message(ClientMessage::SetName("lead".to_string()), &state, &mut guest).await,
+            ServerMessage::Rejected(_)
+        ));
+        assert!(matches!(
+            on_message(ClientMessage::SetName("other".to_string()), &state, &mut guest).await,
+            ServerMessage::Success
+        ));
+        assert_eq!(guest, "other");
+    }
+
+    #[tokio::test]
+    async fn frames_being_edited_refuse_scripts_from_other_peers() {
+        let (state, _sched_rx) = server_state();
+        let set_script = || {
+            ClientMessage::SchedulerControl(SchedulerMessage::SetScript(
+                0,
+                1,
+                Script::default(),
+                ActionTiming::Immediate,
+            ))
+        };
+        let mut alice = "alice".to_string();
+        let mut bob = "bob".to_string();
+
+        on_message(ClientMessage::StartedEditingFrame(0, 1), &state, &mut alice).await;
+        assert!(matches!(
+            on_message(set_script(), &state, &mut bob).await,
+            ServerMessage::Rejected(_)
+        ));
+        assert!(matches!(
+            on_message(set_script(), &state, &mut alice).await,
+            ServerMessage::Success
+        ));
+
+        // Only the editor releases the frame
+        on_message(ClientMessage::StoppedEditingFrame(0, 1), &state, &mut bob).await;
+        assert!(matches!(
+            on_message(set_script(), &state, &mut bob).await,
+            ServerMessage::Rejected(_)
+        ));
+        on_message(ClientMessage::StoppedEditingFrame(0, 1), &state, &mut alice).await;
+        assert!(matches!(
+            on_message(set_script(), &state, &mut bob).await,
+            ServerMessage::Success
+        ));
+    }
+
+    #[tokio::test]
+    async fn frames_being_edited_stay_with_their_editor_through_renames() {
+        let (state, _sched_rx) = server_state();
+        state.clients.lock().await.extend([
+            PeerIdentity::new("alice".to_string()),
+            PeerIdentity::new("bob".to_string()),
+        ]);
+        let set_script = || {
+            ClientMessage::SchedulerControl(SchedulerMessage::SetScript(
+                0,
+                1,
+                Script::default(),
+                ActionTiming::Immediate,
+            ))
+        };
+        let mut alice = "alice".to_string();
+        let mut bob = "bob".to_string();
+
+        on_message(ClientMessage::StartedEditingFrame(0, 1), &state, &mut alice).await;
+        assert!(matches!(
+            on_message(ClientMessage::SetName("alice".to_string()), &state, &mut bob).await,
+            ServerMessage::Rejected(_)
+        ));
+        assert_eq!(bob, "bob");
+        assert!(matches!(
+            on_message(set_script(), &state, &mut bob).await,
+            ServerMessage::Rejected(_)
+        ));
+
+        // The lock follows its holder to a new name
+        on_message(ClientMessage::SetName("carol".to_string()), &state, &mut alice).await;
+        assert_eq!(
+            state.frame_locks.lock().await.get(&(0, 1)).map(String::as_str),
+            Some("carol")
+        );
+        assert!(matches!(
+            on_message(ClientMessage::SetName("alice".to_string()), &state, &mut bob).await,
+            ServerMessage::Success
+        ));
+        assert!(matches!(
+            on_message(set_script(), &state, &mut bob).await,
+            ServerMessage::Rejected(_)
+        ));
+    }
+
+    #[test]
+    fn frames_being_edited_refuse_removals_from_other_peers() {
+        let locks = HashMap::from([((0, 1), "alice".to_string())]);
+        let rejected = |msg: ClientMessage| {
+            matches!(
+                check_frame_locks(&msg, &locks, "bob"),
+                Some(ServerMessage::Rejected(_))
+            )
+        };
+
+        let remove_frame = |frame| ClientMessage::RemoveFrame(0, frame, ActionTiming::Immediate);
+        assert!(rejected(remove_frame(1)));
+        assert!(!rejected(remove_frame(2)));
+        assert!(rejected(ClientMessage::RemoveFramesBatch(
+            vec![(1, 0), (0, 1)],
+            ActionTiming::Immediate
+        )));
+        assert!(check_frame_locks(&remove_frame(1), &locks, "alice").is_none());
+
+        // Whole lines and scenes are not held back by a single frame
+        assert!(!rejected(ClientMessage::SetLines(
+            vec![(0, Line::default())],
+            ActionTiming::Immediate
+        )));
+        assert!(!rejected(ClientMessage::RemoveLine(
+            0,
+            ActionTiming::Immediate
+        )));
+        assert!(!rejected(ClientMessage::SetScene(
+            Scene::default(),
+            ActionTiming::Immediate
+        )));
+        assert!(!rejected(ClientMessage::MorphToScene(
+            Scene::default(),
+            4.0
+        )));
+        assert!(!rejected(ClientMessage::ImportSceneText(String::new())));
+        assert!(!rejected(ClientMessage::UndoSceneLoad));
+    }
+
+    #[test]
+    fn frame_locks_follow_their_frame() {
+        let scene = Scene::new(vec![Line::new(vec![1.0; 3]), Line::new(vec![1.0; 2])]);
+        let mut locks = HashMap::from([((0, 1), "alice".to_string()), ((1, 0), "bob".to_string())]);
+        let holders = |locks: &HashMap<(usize, usize), String>| {
+            let mut holders: Vec<_> = locks.clone().into_iter().collect();
+            holders.sort();
+            holders
+        };
+
+        let added = SovaNotification::AddedFrame(0, 0, Frame::default());
+        assert!(remap_frame_locks(&mut locks, &scene, &added).is_empty());
+        let moved = SovaNotification::MovedLine(1, 0);
+        assert!(remap_frame_locks(&mut locks, &scene, &moved).is_empty());
+        assert_eq!(
+            holders(&locks),
+            vec![((0, 0), "bob".to_string()), ((1, 2), "alice".to_string())]
+        );
+
+        let removed = SovaNotification::RemovedLine(0);
+        let dropped = remap_frame_locks(&mut locks, &scene, &removed);
+        assert_eq!(dropped, vec![((0, 0), "bob".to_string())]);
+        let removed = SovaNotification::RemovedFrame(0, 1);
+        assert!(remap_frame_locks(&mut locks, &scene, &removed).is_empty());
+        assert_eq!(holders(&locks), vec![((0, 1), "alice".to_string())]);
+    }
+
+    /// Languages knowing only bob, which compiles without an interpreter.
+    fn bob_languages() -> LanguageCenter {
+        let mut transcoder = Transcoder::default();
+        transcoder.add_compiler(BobCompiler);
+        LanguageCenter {
+            transcoder,
+            interpreters: InterpreterDirectory::new(),
+        }
+    }
+
+    #[test]
+    fn auditions_only_accept_scripts_that_compile() {
+        let languages = bob_languages();
+        let audition = |content: &str, lang: &str| {
+            compile_audition(&languages, content.to_string(), lang.to_string())
+        };
+        assert!(audition(">> [note:", "bob").is_err());
+        assert!(audition(">> [note: 60]", "nope").is_err());
+        let script = audition(">> [note: 60]", "bob").unwrap();
+        assert!(script.is_compiled());
+    }
+
+    #[test]
+    fn validation_flags_only_the_broken_script() {
+        let languages = bob_languages();
+        let mut lines = vec![Line::new(vec![1.0, 1.0]), Line::new(vec![1.0, 1.0])];
+        lines[0].frames[0]
+            .set_script(Script::new(">> [note: 60]".to_string(), "bob".to_string()));
+        lines[1].frames[1].set_script(Script::new(">> [note:".to_string(), "bob".to_string()));
+        let scene = Scene::new(lines);
+
+        let report = validate_scene(&languages, &scene);
+        assert_eq!(report.len(), 2);
+        let failures: Vec<_> = report
+            .iter()
+            .filter_map(|(line, frame, state)| match state {
+                CompilationState::Error(err) => Some((*line, *frame, err)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(failures.len(), 1);
+        assert_eq!((failures[0].0, failures[0].1), (1, 1));
+        assert!(!failures[0].2.info.is_empty());
+        let broken = scene.line(1).unwrap().frames[1].script();
+        assert!(broken.compilation_state().has_not_been_compiled());
+    }
+
+    #[tokio::test]
+    async fn scene_loads_can_be_undone_once() {
+        let (state, sched_rx) = server_state();
+        let mut before = Scene::new(vec![Line::new(vec![1.0, 2.0]), Line::new(vec![0.5])]);
+        before.lines[1].frames[0].set_script(Script::new("hat".to_string(), "bob".to_string()));
+        *state.scene_image.lock().await = before.clone();
+        let mut name = "performer".to_string();
+
+        let loaded = Scene::new(vec![Line::new(vec![4.0])]);
+        let load = ClientMessage::SetScene(loaded, ActionTiming::Immediate);
+        assert!(matches!(
+            on_message(load, &state, &mut name).await,
+            ServerMessage::Success
+        ));
+        assert!(matches!(
+            sched_rx.try_recv(),
+            Ok(SchedulerMessage::SetScene(_, _))
+        ));
+
+        assert!(matches!(
+            on_message(ClientMessage::UndoSceneLoad, &state, &mut name).await,
+            ServerMessage::Success
+        ));
+        match sched_rx.try_recv() {
+            Ok(SchedulerMessage::SetScene(restored, ActionTiming::Immediate)) => assert_eq!(
+                serde_json::to_value(&restored).unwrap(),
+                serde_json::to_value(&before).unwrap()
+            ),
+            other => panic!("expected the previous scene, got {other:?}"),
+        }
+        assert!(matches!(
+            on_message(ClientMessage::UndoSceneLoad, &state, &mut name).await,
+            ServerMessage::InternalError(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn script_replace_changes_every_matching_frame() {
+        let (mut state, sched_rx) = server_state();
+        state.languages = Arc::new(StdMutex::new(Arc::new(bob_languages())));
+        let bob = |content: &str| Script::new(content.to_string(), "bob".to_string());
+        let mut lines = vec![Line::new(vec![1.0, 1.0]), Line::new(vec![1.0, 1.0])];
+        lines[0].frames[0].set_script(bob(">> [note: 60]"));
+        lines[0].frames[1].set_script(bob(">> [note: 60 vel: 100]"));
+        lines[1].frames[0].set_script(bob(">> [note: 48]"));
+        *state.scene_image.lock().await = Scene::new(lines);
+        let mut name = "replacer".to_string();
+        let replace = |pattern: &str, replacement: &str| {
+            ClientMessage::ReplaceInScripts(pattern.to_string(), replacement.to_string())
+        };
+
+        match on_message(replace("note: 60", "note: 62"), &state, &mut name).await {
+            ServerMessage::ScriptsReplaced(2, failed) => assert!(failed.is_empty()),
+            other => panic!("expected two replaced frames, got {other:?}"),
+        }
+        let contents: Vec<(usize, usize, String)> = sched_rx
+            .try_iter()
+            .map(|msg| match msg {
+                SchedulerMessage::SetScript(line_id, frame_id, script, ActionTiming::Immediate) => {
+                    (line_id, frame_id, script.content().to_owned())
+                }
+                other => panic!("expected a replaced script, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            contents,
+            vec![
+                (0, 0, ">> [note: 62]".to_string()),
+                (0, 1, ">> [note: 62 vel: 100]".to_string())
+            ]
+        );
+
+        // A replacement breaking a script leaves that frame alone
+        match on_message(replace(r"48\]", "48"), &state, &mut name).await {
+            ServerMessage::ScriptsReplaced(0, failed) => {
+                assert_eq!(failed.len(), 1);
+                assert_eq!((failed[0].0, failed[0].1), (1, 0));
+            }
+            other => panic!("expected a failed replace, got {other:?}"),
+        }
+        assert!(sched_rx.try_recv().is_err());
+        assert!(matches!(
+            on_message(replace("note(", ""), &state, &mut name).await,
+            ServerMessage::InternalError(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn script_search_reports_frames_and_spans() {
+        let (state, _) = server_state();
+        let mut lines = vec![Line::new(vec![1.0, 1.0]), Line::new(vec![1.0])];
+        lines[0].frames[1].set_script(Script::new(
+            "kick; snare; kick".to_string(),
+            "bob".to_string(),
+        ));
+        lines[1].frames[0].set_script(Script::new("hat".to_string(), "bob".to_string()));
+        *state.scene_image.lock().await = Scene::new(lines);
+        let mut name = "searcher".to_string();
+
+        let search = |pattern: &str| ClientMessage::SearchScripts(pattern.to_string());
+        match on_message(search(r"kick|hat"), &state, &mut name).await {
+            ServerMessage::SearchResults(results) => {
+                assert_eq!(results, vec![(0, 1, 0, 4), (0, 1, 13, 17), (1, 0, 0, 3)]);
+            }
+            other => panic!("expected search results, got {other:?}"),
+        }
+        match on_message(search("clap"), &state, &mut name).await {
+            ServerMessage::SearchResults(results) => assert!(results.is_empty()),
+            other => panic!("expected search results, got {other:?}"),
+        }
+        match on_message(search("kick("), &state, &mut name).await {
+            ServerMessage::InternalError(error) => {
+                assert!(error.contains("Invalid search pattern"))
+            }
+            other => panic!("expected an error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn scene_stats_count_lines_frames_and_languages() {
+        let (state, _) = server_state();
+        let mut lines = vec![
+            Line::new(vec![1.0, 1.0, 2.0]),
+            Line::new(vec![0.5]),
+            Line::new(vec![2.0, 3.0]),
+        ];
+        lines[0].frames[0].set_script(Script::new("kick".to_string(), "bob".to_string()));
+        lines[0].frames[1].set_script(Script::new("snare".to_string(), "bob".to_string()));
+        lines[0].frames[1].enabled = false;
+        lines[1].frames[0].enabled = false;
+        lines[2].frames[1].set_script(Script::new("60 note".to_string(), "forth".to_string()));
+        *state.scene_image.lock().await = Scene::new(lines);
+        let mut name = "performer".to_string();
+
+        match on_message(ClientMessage::GetSceneStats, &state, &mut name).await {
+            ServerMessage::SceneStats(stats) => assert_eq!(
+                stats,
+                SceneStats {
+                    lines: 3,
+                    enabled_lines: 2,
+                    frames: 6,
+                    enabled_frames: 4,
+                    scripts_per_language: BTreeMap::from([
+                        ("bob".to_string(), 2),
+                        ("forth".to_string(), 1),
+                    ]),
+                    duration: 5.0,
+                }
+            ),
+            other => panic!("expected the scene stats, got {other:?}"),
+        }
+    }
+
+    /// Identity of a client named `name` speaking the current protocol.
+    fn current_identity(name: &str) -> PeerIdentity {
+        PeerIdentity {
+            protocol_version: Some(PROTOCOL_VERSION),
+            ..PeerIdentity::new(name.to_string())
+        }
+    }
+
+    /// Connects a client named `name` to a server task running `process_client`.
+    async fn connect_test_client(
+        state: &ServerState,
+        name: &str,
+    ) -> (
+        crate::client::SovaClient,
+        tokio::task::JoinHandle<io::Result<String>>,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server_state = state.clone();
+        let connection = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            process_client(socket, server_state).await
+        });
+
+        let mut client = crate::client::SovaClient::new("127.0.0.1".to_string(), port);
+        client.connect().await.unwrap();
+        client
+            .send(ClientMessage::SetIdentity(current_identity(name)))
+            .await
+            .unwrap();
+        assert!(matches!(
+            client.read().await.unwrap(),
+            ServerMessage::Hello { .. }
+        ));
+        (client, connection)
+    }
+
+    #[tokio::test]
+    async fn clients_with_incompatible_protocols_are_refused() {
+        let (state, _) = server_state();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server_state = state.clone();
+        tokio::spawn(async move {
+            loop {
+                let (socket, _) = listener.accept().await.unwrap();
+                tokio::spawn(process_client(socket, server_state.clone()));
+            }
+        });
+
+        let handshake = |name: &str, protocol_version| {
+            let identity = PeerIdentity {
+                protocol_version,
+                ..PeerIdentity::new(name.to_string())
+            };
+            async move {
+                let mut client = crate::client::SovaClient::new("127.0.0.1".to_string(), port);
+                client.connect().await.unwrap();
+                client
+                    .send(ClientMessage::SetIdentity(identity))
+                    .await
+                    .unwrap();
+                client.read().await.unwrap()
+            }
+        };
+
+        match handshake("ancient", Some(MIN_PROTOCOL_VERSION - 1)).await {
+            ServerMessage::ConnectionRefused(reason) => {
+                assert!(reason.contains("Protocol version 0 is not supported"));
+                assert!(reason.contains(&format!("versions 1 to {}", PROTOCOL_VERSION)));
+            }
+            other => panic!("expected a refusal, got {other:?}"),
+        }
+        assert!(state.clients.lock().await.is_empty());
+
+        // Newer clients step down to our version
+        match handshake("future", Some(PROTOCOL_VERSION + 1)).await {
+            ServerMessage::Hello {
+                protocol_version, ..
+            } => assert_eq!(protocol_version, PROTOCOL_VERSION),
+            other => panic!("expected Hello, got {other:?}"),
+        }
+
+        // Older ones are spoken to in theirs, and can read their Hello
+        #[derive(Deserialize)]
+        enum LegacyServerMessage {
+            Hello {
+                username: String,
+                peers: Vec<String>,
+                protocol_version: u32,
+            },
+        }
+        let mut socket = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let set_name = ClientMessage::SetName("legacy".to_string());
+        let bytes = rmp_serde::to_vec_named(&set_name).unwrap();
+        let (header, payload) = encode_frame(
+            set_name.compression_strategy(),
+            CompressionCodec::Zstd,
+            &bytes,
         )
-    })
+        .unwrap();
+        socket.write_all(&header.to_be_bytes()).await.unwrap();
+        socket.write_all(&payload).await.unwrap();
+
+        let header = socket.read_u32().await.unwrap();
+        let mut payload = vec![0u8; frame_length(header)];
+        socket.read_exact(&mut payload).await.unwrap();
+        let hello = decode_frame(header, payload).unwrap();
+        let LegacyServerMessage::Hello {
+            username,
+            peers,
+            protocol_version,
+        } = rmp_serde::from_slice(&hello).unwrap();
+        assert_eq!(username, "legacy");
+        assert!(peers.contains(&"legacy".to_string()));
+        assert_eq!(protocol_version, MIN_PROTOCOL_VERSION);
+    }
+
+    #[test]
+    fn unversioned_clients_only_get_messages_they_know() {
+        let legacy = MIN_PROTOCOL_VERSION;
+        assert!(matches!(
+            ServerMessage::Rejected("locked".to_string()).for_protocol(legacy),
+            Some(ServerMessage::InternalError(reason)) if reason == "locked"
+        ));
+        assert!(ServerMessage::Pong(1).for_protocol(legacy).is_none());
+        assert!(ServerMessage::QuantizationGrid(1.0).for_protocol(legacy).is_none());
+        assert!(ServerMessage::SceneLockChanged(None).for_protocol(legacy).is_none());
+        assert!(matches!(
+            ServerMessage::Success.for_protocol(legacy),
+            Some(ServerMessage::Success)
+        ));
+        assert!(matches!(
+            ServerMessage::Pong(1).for_protocol(PROTOCOL_VERSION),
+            Some(ServerMessage::Pong(1))
+        ));
+    }
+
+    #[cfg(not(feature = "audio"))]
+    #[tokio::test]
+    async fn servers_without_audio_report_it() {
+        let (state, _) = server_state();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server_state = state.clone();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            process_client(socket, server_state).await
+        });
+
+        let mut client = crate::client::SovaClient::new("127.0.0.1".to_string(), port);
+        client.connect().await.unwrap();
+        client
+            .send(ClientMessage::SetIdentity(current_identity("quiet")))
+            .await
+            .unwrap();
+        match client.read().await.unwrap() {
+            ServerMessage::Hello {
+                audio_available,
+                audio_engine_state,
+                ..
+            } => {
+                assert!(!audio_available);
+                assert!(!audio_engine_state.running);
+                assert!(audio_engine_state.error.is_some());
+            }
+            other => panic!("expected Hello, got {other:?}"),
+        }
+
+        // Audio commands are answered instead of doing nothing
+        let mut name = "quiet".to_string();
+        let restart = ClientMessage::RestartAudioEngine {
+            device: None,
+            input_device: None,
+            channels: 2,
+            buffer_size: None,
+            sample_paths: Vec::new(),
+        };
+        assert!(matches!(
+            on_message(restart, &state, &mut name).await,
+            ServerMessage::InternalError(_)
+        ));
+        assert!(matches!(
+            on_message(ClientMessage::GetAudioEngineState, &state, &mut name).await,
+            ServerMessage::AudioEngineState(AudioEngineState { running: false, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn idle_clients_are_reaped_from_the_peer_list() {
+        let (mut state, _) = server_state();
+        state.connection.idle_timeout = Some(Duration::from_millis(200));
+        let (client, connection) = connect_test_client(&state, "sleepy").await;
+        assert_eq!(state.clients.lock().await.len(), 1);
+
+        // The client stays connected but never says anything again
+        let reaped = tokio::time::timeout(Duration::from_secs(5), connection)
+            .await
+            .expect("idle client was never disconnected")
+            .unwrap();
+        assert_eq!(reaped.unwrap(), "sleepy");
+        assert!(state.clients.lock().await.is_empty());
+        drop(client);
+    }
+
+    #[tokio::test]
+    async fn idle_servers_stop_the_transport() {
+        let (mut state, sched_rx) = server_state();
+        let (hush_tx, hush_rx) = crossbeam_channel::unbounded();
+        state.audio_hush_tx = Some(hush_tx);
+        state.idle_stop = Some(IdleStopSettings {
+            after: Duration::from_secs(60),
+            resume: true,
+        });
+        state.is_playing.store(true, Ordering::Relaxed);
+        let mut name = "installer".to_string();
+        let edit = || ClientMessage::SetInstrument("kick".to_string(), Some("bd".to_string()));
+        on_message(edit(), &state, &mut name).await;
+        assert!(!state.stop_if_idle(Instant::now() + Duration::from_secs(30)));
+        assert!(sched_rx.try_recv().is_err());
+        assert!(hush_rx.try_recv().is_err());
+
+        let later = Instant::now() + Duration::from_secs(61);
+        assert!(state.stop_if_idle(later));
+        assert!(matches!(
+            sched_rx.try_recv(),
+            Ok(SchedulerMessage::TransportStop(ActionTiming::Immediate))
+        ));
+        assert!(hush_rx.try_recv().is_ok());
+        // Stopped once, even before the scheduler reports it
+        assert!(!state.stop_if_idle(later));
+        assert!(sched_rx.try_recv().is_err());
+        assert!(hush_rx.try_recv().is_err());
+
+        // Heartbeats of a GUI left open are not activity
+        on_message(ClientMessage::GetClock, &state, &mut name).await;
+        assert!(sched_rx.try_recv().is_err());
+        assert!(state.idle_stopped.load(Ordering::Relaxed));
+
+        on_message(edit(), &state, &mut name).await;
+        assert!(matches!(
+            sched_rx.try_recv(),
+            Ok(SchedulerMessage::TransportStart(_))
+        ));
+        assert!(!state.idle_stopped.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn invalid_scene_metadata_is_rejected() {
+        let (state, sched_rx) = server_state();
+        let mut name = "alice".to_string();
+        let mut metadata = SceneMetadata::default();
+        metadata.time_signature.denominator = 3;
+        assert!(matches!(
+            on_message(
+                ClientMessage::SetSceneMetadata(metadata, ActionTiming::Immediate),
+                &state,
+                &mut name
+            )
+            .await,
+            ServerMessage::Rejected(_)
+        ));
+        assert!(sched_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn locked_out_edits_do_not_resume_idle_servers() {
+        let (mut state, sched_rx) = server_state();
+        state.idle_stop = Some(IdleStopSettings {
+            after: Duration::from_secs(60),
+            resume: true,
+        });
+        state.idle_stopped.store(true, Ordering::Relaxed);
+        *state.scene_lock.lock().await = Some("lead".to_string());
+        let mut guest = "guest".to_string();
+
+        let edit = ClientMessage::SetTempo(90.0, ActionTiming::Immediate);
+        assert!(matches!(
+            on_message(edit, &state, &mut guest).await,
+            ServerMessage::Rejected(_)
+        ));
+        assert!(sched_rx.try_recv().is_err());
+        assert!(state.idle_stopped.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn subscribed_clients_only_get_their_lines() {
+        let (state, _) = server_state();
+        let (mut client, _connection) = connect_test_client(&state, "installation").await;
+        client
+            .send(ClientMessage::SubscribeLines(Some(vec![1])))
+            .await
+            .unwrap();
+        assert!(matches!(
+            client.read().await.unwrap(),
+            ServerMessage::Success
+        ));
+
+        let updates = [
+            SovaNotification::UpdatedFrames(vec![(0, 0, Frame::default())]),
+            SovaNotification::PeerStartedEditingFrame("bob".to_string(), 0, 0),
+            SovaNotification::UpdatedFrames(vec![
+                (0, 1, Frame::default()),
+                (1, 0, Frame::default()),
+            ]),
+            SovaNotification::FramePositionChanged(vec![vec![(2, 0)], vec![(1, 0)]]),
+        ];
+        for update in updates {
+            state.update_sender.send(update).unwrap();
+        }
+
+        match client.read().await.unwrap() {
+            ServerMessage::FrameValues(frames) => {
+                let ids: Vec<_> = frames.iter().map(|(l, f, _)| (*l, *f)).collect();
+                assert_eq!(ids, vec![(1, 0)]);
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+        match client.read().await.unwrap() {
+            ServerMessage::FramePosition(positions) => {
+                assert_eq!(positions, vec![vec![], vec![(1, 0)]])
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn subscriptions_follow_their_lines() {
+        let (state, _) = server_state();
+        let (mut client, _connection) = connect_test_client(&state, "installation").await;
+        client
+            .send(ClientMessage::SubscribeLines(Some(vec![1])))
+            .await
+            .unwrap();
+        assert!(matches!(
+            client.read().await.unwrap(),
+            ServerMessage::Success
+        ));
+
+        // The subscribed line moves to the top, then a line is added above it
+        let updates = [
+            SovaNotification::MovedLine(1, 0),
+            SovaNotification::AddedLine(0, Line::default()),
+            SovaNotification::UpdatedFrames(vec![
+                (0, 0, Frame::default()),
+                (1, 0, Frame::default()),
+            ]),
+        ];
+        for update in updates {
+            state.update_sender.send(update).unwrap();
+        }
+
+        assert!(matches!(
+            client.read().await.unwrap(),
+            ServerMessage::MoveLine(1, 0)
+        ));
+        assert!(matches!(
+            client.read().await.unwrap(),
+            ServerMessage::AddLine(0, _)
+        ));
+        match client.read().await.unwrap() {
+            ServerMessage::FrameValues(frames) => {
+                let ids: Vec<_> = frames.iter().map(|(l, f, _)| (*l, *f)).collect();
+                assert_eq!(ids, vec![(1, 0)]);
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn followers_mirror_the_focus_of_the_followed_peer() {
+        let (state, _) = server_state();
+        let (mut alice, _alice_connection) = connect_test_client(&state, "alice").await;
+        let (mut bob, _bob_connection) = connect_test_client(&state, "bob").await;
+
+        // Skips the peer list updates broadcast as clients join
+        async fn next_message(client: &mut crate::client::SovaClient) -> ServerMessage {
+            loop {
+                match client.read().await.unwrap() {
+                    ServerMessage::PeersUpdated(_) => continue,
+                    msg => return msg,
+                }
+            }
+        }
+
+        bob.send(ClientMessage::FollowPeer(Some("alice".to_string())))
+            .await
+            .unwrap();
+        assert!(matches!(
+            next_message(&mut bob).await,
+            ServerMessage::Success
+        ));
+        alice
+            .send(ClientMessage::StartedEditingFrame(1, 2))
+            .await
+            .unwrap();
+        match next_message(&mut bob).await {
+            ServerMessage::FollowedPeerFocus(peer, 1, 2) => assert_eq!(peer.name, "alice"),
+            other => panic!("unexpected message {:?}", other),
+        }
+
+        bob.send(ClientMessage::FollowPeer(None)).await.unwrap();
+        assert!(matches!(
+            next_message(&mut bob).await,
+            ServerMessage::Success
+        ));
+        alice
+            .send(ClientMessage::StartedEditingFrame(0, 3))
+            .await
+            .unwrap();
+        assert!(matches!(
+            next_message(&mut bob).await,
+            ServerMessage::PeerStartedEditing(_, 0, 3)
+        ));
+
+        bob.send(ClientMessage::FollowPeer(Some("carol".to_string())))
+            .await
+            .unwrap();
+        assert!(matches!(
+            next_message(&mut bob).await,
+            ServerMessage::InternalError(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn instrument_changes_reach_every_client() {
+        let (state, _) = server_state();
+        let (mut alice, _alice_connection) = connect_test_client(&state, "alice").await;
+        let (mut bob, _bob_connection) = connect_test_client(&state, "bob").await;
+
+        alice
+            .send(ClientMessage::SetInstrument(
+                "kick".to_string(),
+                Some("bd".to_string()),
+            ))
+            .await
+            .unwrap();
+        loop {
+            match bob.read().await.unwrap() {
+                ServerMessage::PeersUpdated(_) => continue,
+                ServerMessage::Instruments(instruments) => {
+                    assert_eq!(instruments.resolve("kick"), Some("bd"));
+                    break;
+                }
+                other => panic!("unexpected message {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn compilers_registered_at_runtime_are_broadcast_and_compile() {
+        let (state, sched_rx) = server_state();
+        let (mut client, _connection) = connect_test_client(&state, "remote").await;
+        let audition = |languages: &LanguageCenter| {
+            compile_audition(languages, ">> [note: 60]".to_string(), "bob".to_string())
+        };
+        assert!(audition(&state.languages()).is_err());
+
+        state
+            .register_compiler(BobCompiler, Some("keywords: note vel".to_string()))
+            .unwrap();
+
+        loop {
+            match client.read().await.unwrap() {
+                ServerMessage::PeersUpdated(_) => continue,
+                ServerMessage::Languages(languages, syntaxes) => {
+                    assert!(languages.iter().any(|lang| lang == "bob"));
+                    assert_eq!(syntaxes["bob"], "keywords: note vel");
+                    break;
+                }
+                other => panic!("expected the available languages, got {other:?}"),
+            }
+        }
+        match sched_rx.try_recv() {
+            Ok(SchedulerMessage::SetLanguages(languages)) => assert!(audition(&languages).is_ok()),
+            other => panic!("expected the new languages, got {other:?}"),
+        }
+        assert!(audition(&state.languages()).is_ok());
+
+        state.unregister_language("bob").unwrap();
+        assert!(audition(&state.languages()).is_err());
+        assert!(state.unregister_language("bob").is_err());
+    }
+
+    #[tokio::test]
+    async fn pings_are_answered_with_their_stamp() {
+        let (state, _) = server_state();
+        let (mut client, _connection) = connect_test_client(&state, "remote").await;
+        let ping = ClientMessage::ping();
+        let ClientMessage::Ping(stamp) = ping else {
+            unreachable!()
+        };
+        client.send(ping).await.unwrap();
+
+        let pong = tokio::time::timeout(Duration::from_secs(1), client.read())
+            .await
+            .expect("no pong within a second")
+            .unwrap();
+        assert!(matches!(pong, ServerMessage::Pong(echoed) if echoed == stamp));
+        let round_trip = pong.round_trip_time().unwrap();
+        assert!(round_trip < Duration::from_secs(1));
+        assert!(ServerMessage::Success.round_trip_time().is_none());
+    }
 }
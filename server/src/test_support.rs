@@ -0,0 +1,46 @@
+//! Fixtures shared by the tests of the server crate.
+
+use crate::server::ServerState;
+use crossbeam_channel::{Receiver, unbounded};
+use sova_core::{
+    Scene,
+    clock::{Clock, ClockServer},
+    device_map::DeviceMap,
+    schedule::{Scheduler, SchedulerMessage},
+    vm::LanguageCenter,
+};
+use std::sync::{Arc, Mutex as StdMutex};
+use tokio::sync::{Mutex, broadcast};
+
+/// A server state over an empty scene, without audio engine, along with the
+/// receiving end of its scheduler channel.
+pub(crate) fn server_state() -> (ServerState, Receiver<SchedulerMessage>) {
+    let (sched_tx, sched_rx) = unbounded();
+    let state = ServerState::new(
+        Arc::new(Mutex::new(Scene::default())),
+        Arc::new(ClockServer::new(120.0, 4.0)),
+        Arc::new(DeviceMap::new()),
+        sched_tx,
+        broadcast::channel(16).0,
+        Arc::new(LanguageCenter::default()),
+        Arc::new(StdMutex::new(Default::default())),
+        None,
+    );
+    (state, sched_rx)
+}
+
+/// A scheduler over an empty scene, whose events and notifications are dropped.
+pub(crate) fn scheduler() -> Scheduler {
+    let (world_tx, _) = unbounded();
+    let (feedback_tx, feedback_rx) = unbounded();
+    let (notif_tx, _) = unbounded();
+    Scheduler::new(
+        Clock::from(Arc::new(ClockServer::new(120.0, 4.0))),
+        Arc::new(DeviceMap::new()),
+        Arc::new(LanguageCenter::default()),
+        world_tx,
+        feedback_tx,
+        feedback_rx,
+        notif_tx,
+    )
+}
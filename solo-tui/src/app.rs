@@ -19,19 +19,25 @@ use ratatui::{
 use sova_core::{
     LogMessage, Scene,
     clock::{Clock, ClockServer},
+    compiler::CompilationWarning,
     device_map::DeviceMap,
     protocol::DeviceInfo,
-    scene::{Frame, Line},
+    scene::{Frame, Line, script::Script},
     schedule::{ActionTiming, SchedulerMessage, SovaNotification, playback::PlaybackState},
     vm::{LanguageCenter, variable::VariableValue},
 };
 
+/// Number of recently left frames highlighted behind each playhead.
+const TRAIL_LENGTH: usize = 2;
+
 pub struct AppState {
     pub running: bool,
     pub scene_image: Scene,
     pub global_vars: HashMap<String, VariableValue>,
     pub playing: PlaybackState,
     pub positions: Vec<Vec<(usize, usize)>>,
+    /// Frames each line recently left, most recent first
+    pub trails: Vec<Vec<usize>>,
     pub clock: Clock,
     pub devices: Vec<DeviceInfo>,
     pub page: Page,
@@ -40,6 +46,14 @@ pub struct AppState {
     pub device_map: Arc<DeviceMap>,
     pub languages: Arc<LanguageCenter>,
     pub clipboard: Option<Clipboard>,
+    /// Script yanked from a frame, to be pasted into other frames
+    pub script_register: Option<Script>,
+    /// Latest compilation warnings per frame, with the id of the script they belong to
+    pub warnings: HashMap<(usize, usize), (u64, Vec<CompilationWarning>)>,
+    /// Frames other clients are editing, with the name of the first of them
+    pub peer_edits: HashMap<(usize, usize), String>,
+    /// Whether the scene changed since it was last saved or loaded
+    pub dirty: bool,
 }
 
 impl AppState {
@@ -51,11 +65,56 @@ impl AppState {
         self.scene_image.line(self.selected.0)
     }
 
+    pub fn selected_warnings(&self) -> &[CompilationWarning] {
+        let Some(frame) = self.selected_frame() else {
+            return &[];
+        };
+        match self.warnings.get(&self.selected) {
+            Some((id, warnings)) if *id == frame.script().id() => warnings,
+            _ => &[],
+        }
+    }
+
+    /// Moves the playheads, remembering the frames they just left.
+    pub fn update_positions(&mut self, positions: Vec<Vec<(usize, usize)>>) {
+        self.trails.resize(positions.len(), Vec::new());
+        for (line_index, trail) in self.trails.iter_mut().enumerate() {
+            let current = &positions[line_index];
+            let is_current = |frame: &usize| current.iter().any(|(f, _)| f == frame);
+            if let Some(previous) = self.positions.get(line_index) {
+                for (frame, _) in previous.iter().filter(|(f, _)| !is_current(f)) {
+                    trail.retain(|f| f != frame);
+                    trail.insert(0, *frame);
+                }
+            }
+            trail.retain(|f| !is_current(f));
+            trail.truncate(TRAIL_LENGTH);
+        }
+        self.positions = positions;
+    }
+
     pub fn refresh_devices(&mut self) {
         self.devices = self.device_map.device_list();
     }
 }
 
+/// Whether a notification changes the scene a save would write.
+fn edits_scene(notif: &SovaNotification) -> bool {
+    matches!(
+        notif,
+        SovaNotification::UpdatedSceneMode(_)
+            | SovaNotification::UpdatedSceneMetadata(_)
+            | SovaNotification::UpdatedLines(_)
+            | SovaNotification::UpdatedLineConfigurations(_)
+            | SovaNotification::AddedLine(_, _)
+            | SovaNotification::RemovedLine(_)
+            | SovaNotification::MovedLine(_, _)
+            | SovaNotification::UpdatedFrames(_)
+            | SovaNotification::AddedFrame(_, _, _)
+            | SovaNotification::RemovedFrame(_, _)
+    )
+}
+
 /// Application.
 pub struct App {
     pub sched_iface: Sender<SchedulerMessage>,
@@ -87,14 +146,19 @@ impl App {
                 global_vars: Default::default(),
                 playing: Default::default(),
                 positions: Default::default(),
+                trails: Default::default(),
                 clock: clock_server.into(),
                 devices: Default::default(),
                 page: Default::default(),
                 selected: Default::default(),
                 events: EventHandler::new(sched_update, log_rx),
                 clipboard: Clipboard::new().map(|x| Some(x)).unwrap_or_default(),
+                script_register: None,
                 device_map,
                 languages,
+                warnings: Default::default(),
+                peer_edits: Default::default(),
+                dirty: false,
             },
             scene_widget: SceneWidget::default(),
             edit_widget: EditWidget::default(),
@@ -150,18 +214,32 @@ impl App {
             AppEvent::Info(text) => self.notification.info(text),
             AppEvent::Positive(text) => self.notification.positive(text),
             AppEvent::Negative(text) => self.notification.negative(text),
+            AppEvent::Warning(text) => self.notification.warning(text),
+            AppEvent::Saved => {
+                self.state.dirty = false;
+                self.notification.positive("Saved scene !".to_owned());
+            }
             AppEvent::Quit => self.quit(),
         }
         Ok(())
     }
 
     pub fn handle_notification(&mut self, notif: SovaNotification) -> color_eyre::Result<()> {
+        if edits_scene(&notif) {
+            self.state.dirty = true;
+        }
         match notif {
             SovaNotification::Tick
             | SovaNotification::TempoChanged(_)
-            | SovaNotification::QuantumChanged(_) => (),
-            SovaNotification::UpdatedScene(scene) => self.state.scene_image = scene,
+            | SovaNotification::QuantumChanged(_)
+            | SovaNotification::QuantizationGridChanged(_) => (),
+            // Whole scenes only come from loading a file
+            SovaNotification::UpdatedScene(scene) => {
+                self.state.scene_image = scene;
+                self.state.dirty = false;
+            }
             SovaNotification::UpdatedSceneMode(m) => self.state.scene_image.mode = m,
+            SovaNotification::UpdatedSceneMetadata(m) => self.state.scene_image.metadata = m,
             SovaNotification::UpdatedLines(items) => {
                 for (index, line) in items {
                     self.state.scene_image.set_line(index, line);
@@ -176,6 +254,9 @@ impl App {
                 self.state.scene_image.insert_line(index, line)
             }
             SovaNotification::RemovedLine(index) => self.state.scene_image.remove_line(index),
+            SovaNotification::MovedLine(from, to) => {
+                self.state.scene_image.move_line(from, to);
+            }
             SovaNotification::UpdatedFrames(items) => {
                 for (line_index, frame_index, frame) in items {
                     self.state
@@ -207,18 +288,41 @@ impl App {
                     .frame_mut(frame_index);
                 *frame.compilation_state_mut() = state;
             }
+            SovaNotification::CompilationWarnings(line_index, frame_index, id, warnings) => {
+                if let Some(warning) = warnings.first() {
+                    self.state
+                        .events
+                        .send(AppEvent::Warning(warning.to_string()));
+                }
+                self.state
+                    .warnings
+                    .insert((line_index, frame_index), (id, warnings));
+            }
             SovaNotification::PlaybackStateChanged(state) => self.state.playing = state,
             SovaNotification::FramePositionChanged(positions) => {
-                self.state.positions = positions
+                self.state.update_positions(positions)
             }
             SovaNotification::GlobalVariablesChanged(values) => self.state.global_vars = values,
             SovaNotification::Log(msg) => self.log(msg),
             SovaNotification::DeviceListChanged(devices) => self.state.devices = devices,
+            SovaNotification::PeerStartedEditingFrame(peer, line_index, frame_index) => {
+                self.state
+                    .peer_edits
+                    .entry((line_index, frame_index))
+                    .or_insert(peer);
+            }
+            SovaNotification::PeerStoppedEditingFrame(peer, line_index, frame_index) => {
+                let key = (line_index, frame_index);
+                if self.state.peer_edits.get(&key) == Some(&peer) {
+                    self.state.peer_edits.remove(&key);
+                }
+            }
             SovaNotification::ClientListChanged(_)
             | SovaNotification::ChatReceived(_, _)
-            | SovaNotification::PeerStartedEditingFrame(_, _, _)
-            | SovaNotification::PeerStoppedEditingFrame(_, _, _)
-            | SovaNotification::ScopeData(_) => (),
+            | SovaNotification::SceneLockChanged(_)
+            | SovaNotification::ScopeData(_)
+            | SovaNotification::InstrumentsChanged(_)
+            | SovaNotification::LanguagesChanged(_, _) => (),
         }
         Ok(())
     }
@@ -235,6 +339,24 @@ impl App {
         }
 
         match key_event.code {
+            KeyCode::Esc if self.state.page == Page::Edit && self.edit_widget.is_showing_help() => {
+                self.edit_widget.hide_help();
+            }
+            KeyCode::Esc if self.state.dirty => {
+                self.state.events.send(AppEvent::Popup(
+                    "Unsaved changes".to_owned(),
+                    "Save the scene before quitting ?".to_owned(),
+                    PopupValue::Choice(
+                        0,
+                        vec!["Save".to_owned(), "Discard".to_owned(), "Cancel".to_owned()],
+                    ),
+                    Box::new(|state, x| match String::from(x).as_str() {
+                        "Save" => ConfigureWidget::ask_save(state, true),
+                        "Discard" => state.events.send(AppEvent::Quit),
+                        _ => (),
+                    }),
+                ));
+            }
             KeyCode::Esc => {
                 self.state.events.send(AppEvent::Popup(
                     "Exit Sova ?".to_owned(),
@@ -302,3 +424,37 @@ impl App {
         self.state.running = false;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_mark_the_scene_unsaved_until_it_is_saved() {
+        let (sched_tx, _sched_rx) = crossbeam_channel::unbounded();
+        let (_notif_tx, notif_rx) = crossbeam_channel::unbounded();
+        let (_log_tx, log_rx) = crossbeam_channel::unbounded();
+        let mut app = App::new(
+            sched_tx,
+            notif_rx,
+            log_rx,
+            Arc::new(ClockServer::new(120.0, 4.0)),
+            Arc::new(DeviceMap::new()),
+            Arc::new(LanguageCenter::default()),
+        );
+
+        let scene = Scene::new(vec![Line::new(vec![1.0])]);
+        app.handle_notification(SovaNotification::UpdatedScene(scene))
+            .unwrap();
+        assert!(!app.state.dirty);
+        app.handle_notification(SovaNotification::FramePositionChanged(vec![vec![(0, 0)]]))
+            .unwrap();
+        assert!(!app.state.dirty);
+
+        app.handle_notification(SovaNotification::AddedFrame(0, 1, Frame::from(1.0)))
+            .unwrap();
+        assert!(app.state.dirty);
+        app.handle_app_event(AppEvent::Saved).unwrap();
+        assert!(!app.state.dirty);
+    }
+}
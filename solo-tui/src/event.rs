@@ -46,6 +46,9 @@ pub enum AppEvent {
     Info(String),
     Positive(String),
     Negative(String),
+    Warning(String),
+    /// The scene has just been written to a file.
+    Saved,
     Quit,
 }
 
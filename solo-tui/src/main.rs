@@ -3,7 +3,7 @@ use std::sync::Arc;
 use crossbeam_channel::unbounded;
 use langs::{
     bali::BaliCompiler, bob::BobCompiler, boinx::BoinxInterpreterFactory,
-    forth::ForthInterpreterFactory,
+    forth::ForthInterpreterFactory, mini::MiniInterpreterFactory,
 };
 use sova_core::{
     Scene,
@@ -36,6 +36,7 @@ fn create_language_center() -> Arc<LanguageCenter> {
     let mut interpreters = InterpreterDirectory::new();
     interpreters.add_factory(BoinxInterpreterFactory);
     interpreters.add_factory(ForthInterpreterFactory);
+    interpreters.add_factory(MiniInterpreterFactory);
     Arc::new(LanguageCenter {
         transcoder,
         interpreters,
@@ -40,6 +40,10 @@ impl Notification {
         self.show(text, Color::LightRed);
     }
 
+    pub fn warning(&mut self, text: String) {
+        self.show(text, Color::Yellow);
+    }
+
     pub fn is_showing(&self) -> bool {
         self.triggered.elapsed().as_millis() < NOTIFICATION_TIME_MS as u128
     }
@@ -1,7 +1,7 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{buffer::Buffer, layout::{Constraint, Flex, Layout, Margin, Rect}, style::Stylize, text::{self, Span}, widgets::{Paragraph, StatefulWidget, Widget}};
 use sova_core::{scene::ExecutionMode, schedule::{ActionTiming, SchedulerMessage}};
-use sova_server::Snapshot;
+use sova_server::{Snapshot, SnapshotFile};
 
 use crate::{app::AppState, event::AppEvent, popup::PopupValue};
 
@@ -16,37 +16,45 @@ impl ConfigureWidget {
         "
     } 
 
+    /// Asks for a path and saves the scene there, then quits if `quit` is set.
+    pub fn ask_save(state: &mut AppState, quit: bool) {
+        state.events.send(AppEvent::Popup(
+            "Save scene".to_owned(),
+            "Input path to save scene".to_owned(),
+            PopupValue::Text("scene.sova".to_owned()),
+            Box::new(move |state, x| {
+                let micros = state.clock.micros();
+                let beat = state.clock.beat_at_date(micros);
+                let path = String::from(x);
+                let snapshot = Snapshot {
+                    scene: state.scene_image.clone(),
+                    tempo: state.clock.tempo(),
+                    beat,
+                    micros,
+                    quantum: state.clock.quantum(),
+                    devices: None
+                };
+                let Ok(snapshot) = serde_json::to_vec(&SnapshotFile::from(snapshot)) else {
+                    state.events.send(AppEvent::Negative("Failed to save scene !".to_owned()));
+                    return;
+                };
+                let res = std::fs::write(path, snapshot);
+                if res.is_ok() {
+                    state.events.send(AppEvent::Saved);
+                    if quit {
+                        state.events.send(AppEvent::Quit);
+                    }
+                } else {
+                    state.events.send(AppEvent::Negative("Failed to save scene !".to_owned()));
+                }
+            })
+        ));
+    }
+
     pub fn process_event(state: &mut AppState, event: KeyEvent) { 
         match event.code {
             KeyCode::Char('s') if event.modifiers == KeyModifiers::CONTROL => {
-                state.events.send(AppEvent::Popup(
-                    "Save scene".to_owned(), 
-                    "Input path to save scene".to_owned(), 
-                    PopupValue::Text("scene.sova".to_owned()), 
-                    Box::new(|state, x| {
-                        let micros = state.clock.micros();
-                        let beat = state.clock.beat_at_date(micros);
-                        let path = String::from(x);
-                        let snapshot = Snapshot {
-                            scene: state.scene_image.clone(),
-                            tempo: state.clock.tempo(),
-                            beat,
-                            micros,
-                            quantum: state.clock.quantum(),
-                            devices: None
-                        };
-                        let Ok(snapshot) = serde_json::to_vec(&snapshot) else {
-                            state.events.send(AppEvent::Negative("Failed to save scene !".to_owned()));
-                            return;
-                        };
-                        let res = std::fs::write(path, snapshot);
-                        if res.is_ok() {
-                            state.events.send(AppEvent::Positive("Saved scene !".to_owned()));
-                        } else {
-                            state.events.send(AppEvent::Negative("Failed to save scene !".to_owned()));
-                        }
-                    })
-                ));
+                Self::ask_save(state, false);
             } 
             KeyCode::Char('l') if event.modifiers == KeyModifiers::CONTROL => {
                 state.events.send(AppEvent::Popup(
@@ -59,7 +67,7 @@ impl ConfigureWidget {
                             state.events.send(AppEvent::Negative("Failed to read file !".to_owned()));
                             return;
                         };
-                        let Ok(snapshot) = serde_json::from_slice::<Snapshot>(&bytes) else {
+                        let Ok(snapshot) = serde_json::from_slice::<SnapshotFile>(&bytes) else {
                             state.events.send(AppEvent::Negative("Failed to load scene !".to_owned()));
                             return;
                         };
@@ -1,19 +1,42 @@
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use ratatui::{buffer::Buffer, layout::{Constraint, Layout, Rect}, style::{Style, Stylize}, widgets::{StatefulWidget, Widget}};
-use sova_core::{scene::script::Script, schedule::{ActionTiming, SchedulerMessage}};
+use ratatui::{buffer::Buffer, layout::{Constraint, Flex, Layout, Rect}, style::{Color, Style, Stylize}, text::{Line, Span, Text}, widgets::{Block, BorderType, Clear, Paragraph, StatefulWidget, Widget}};
+use sova_core::{compiler::CompilationState, scene::script::Script, schedule::{ActionTiming, SchedulerMessage}};
 use tui_textarea::{CursorMove, TextArea};
 
 use crate::{app::AppState, event::AppEvent, popup::PopupValue};
 
+/// Every key binding of the editor, listed by the help overlay.
+const KEY_BINDINGS: &[(&str, &str)] = &[
+    ("C-S", "Upload the script"),
+    ("C-L", "Choose the language"),
+    ("A-L", "Next language"),
+    ("A-S-L", "Previous language"),
+    ("C-A", "Select all"),
+    ("C-W", "Select the next word"),
+    ("C-Q", "Select the line"),
+    ("C-C", "Copy"),
+    ("C-X", "Cut"),
+    ("C-V", "Paste"),
+    ("C-Z", "Undo"),
+    ("C-Y", "Redo"),
+    ("C-Arrows", "Change page"),
+    ("C-Space", "Start or stop the transport"),
+    ("F1", "Show or hide this help"),
+];
+
 pub struct EditWidget {
-    text_area: TextArea<'static>
+    text_area: TextArea<'static>,
+    show_help: bool,
 }
 
 impl Default for EditWidget {
     fn default() -> Self {
         let mut text_area : TextArea = Default::default();
         text_area.set_line_number_style(Style::default().dark_gray());
-        Self { text_area }
+        Self {
+            text_area,
+            show_help: false,
+        }
     }
 }
 
@@ -50,6 +73,34 @@ fn upload_lang(state: &mut AppState, lang: String) {
     upload_script(state, script);
 }
 
+/// Language following (or preceding) `current` in `langs`, wrapping around the list.
+/// An unknown language cycles from the start (or the end) of the list.
+fn cycle_language<'a>(langs: &'a [String], current: &str, forward: bool) -> Option<&'a String> {
+    if langs.is_empty() {
+        return None;
+    }
+    let n = langs.len();
+    let index = match langs.iter().position(|l| l == current) {
+        Some(i) if forward => (i + 1) % n,
+        Some(i) => (i + n - 1) % n,
+        None if forward => 0,
+        None => n - 1,
+    };
+    langs.get(index)
+}
+
+fn cycle_lang(state: &mut AppState, forward: bool) {
+    let Some(frame) = state.selected_frame() else {
+        return;
+    };
+    let langs: Vec<String> = state.languages.languages().map(str::to_owned).collect();
+    let Some(lang) = cycle_language(&langs, frame.script().lang(), forward).cloned() else {
+        return;
+    };
+    upload_lang(state, lang.clone());
+    state.events.send(AppEvent::Info(format!("Language: {lang}")));
+}
+
 impl EditWidget {
 
     pub fn open(&mut self, state: &AppState) {
@@ -64,12 +115,28 @@ impl EditWidget {
     pub fn get_help() -> &'static str {
         "\
         C-S: Upload \n\
-        C-L: Change language \n\
-        C-A: Select all \n\
+        C-L: Change language  A-L/A-S-L: Next/previous language \n\
+        C-A: Select all  F1: All keys \n\
         "
     }
 
+    pub fn is_showing_help(&self) -> bool {
+        self.show_help
+    }
+
+    pub fn hide_help(&mut self) {
+        self.show_help = false;
+    }
+
     pub fn process_event(&mut self, state: &mut AppState, mut event: KeyEvent) { 
+        if event.code == KeyCode::F(1) {
+            self.show_help = !self.show_help;
+            return;
+        }
+        // The help overlay is modal, the script is left untouched while it shows
+        if self.show_help {
+            return;
+        }
         match event.code {
             KeyCode::Char('s') if event.modifiers == KeyModifiers::CONTROL => {
                 upload_content(state, self.get_content());
@@ -91,6 +158,12 @@ impl EditWidget {
                         upload_lang(state, x.into());
                     })));
             }
+            KeyCode::Char('l') if event.modifiers == KeyModifiers::ALT => {
+                cycle_lang(state, true);
+            }
+            KeyCode::Char('L') if event.modifiers.contains(KeyModifiers::ALT) => {
+                cycle_lang(state, false);
+            }
             KeyCode::Char('w') if event.modifiers == KeyModifiers::CONTROL => {
                 self.text_area.start_selection();
                 self.text_area.move_cursor(CursorMove::WordForward);
@@ -153,15 +226,105 @@ impl EditWidget {
         self.text_area.lines().join("\n")
     }
 
+    /// Draws the key bindings in a centered popup, when the help is showing.
+    fn render_help(&self, area: Rect, buf: &mut Buffer) {
+        if !self.show_help {
+            return;
+        }
+        let key_width = KEY_BINDINGS
+            .iter()
+            .map(|(key, _)| key.len())
+            .max()
+            .unwrap_or(0);
+        let lines: Vec<Line> = KEY_BINDINGS
+            .iter()
+            .map(|(key, action)| {
+                Line::from(vec![
+                    Span::styled(format!("{key:>key_width$}  "), Style::default().bold()),
+                    Span::from(*action),
+                ])
+            })
+            .collect();
+        let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+        let height = lines.len() as u16 + 2;
+        let [area] = Layout::horizontal([Constraint::Length(width)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(area);
+        Clear.render(area, buf);
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .title("Editor keys")
+            .on_black();
+        Paragraph::new(Text::from(lines))
+            .block(block)
+            .render(area, buf);
+    }
+
 }
 
 impl StatefulWidget for &EditWidget {
     type State = AppState;
 
-    fn render(self, area: Rect, buf: &mut Buffer, _state: &mut Self::State) {
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         use Constraint::*;
         let layout = Layout::vertical([Min(0), Length(2)]);
-        let [main_area, _tools_area] = layout.areas(area);
+        let [main_area, tools_area] = layout.areas(area);
         self.text_area.render(main_area, buf);
+
+        // Errors block execution, warnings do not: show them in distinct colors
+        let mut lines = Vec::new();
+        let compilation = state.selected_frame().map(|f| f.script().compilation_state());
+        if let Some(CompilationState::Error(err)) = compilation {
+            lines.push(Line::styled(err.to_string(), Color::LightRed));
+        }
+        for warning in state.selected_warnings() {
+            lines.push(Line::styled(warning.to_string(), Color::Yellow));
+        }
+        Paragraph::new(Text::from(lines)).render(tools_area, buf);
+        self.render_help(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language_cycle_wraps_both_ways() {
+        let langs: Vec<String> = ["bali", "bob", "forth"].map(str::to_owned).into();
+        let next = |current, forward| cycle_language(&langs, current, forward).map(String::as_str);
+
+        assert_eq!(next("bali", true), Some("bob"));
+        assert_eq!(next("forth", true), Some("bali"));
+        assert_eq!(next("bob", false), Some("bali"));
+        assert_eq!(next("bali", false), Some("forth"));
+        assert_eq!(next("unknown", true), Some("bali"));
+        assert_eq!(next("unknown", false), Some("forth"));
+        assert_eq!(cycle_language(&[], "bob", true), None);
+    }
+
+    #[test]
+    fn help_overlay_lists_the_editor_keys_when_toggled() {
+        let area = Rect::new(0, 0, 60, 24);
+        let rendered = |widget: &EditWidget| {
+            let mut buf = Buffer::empty(area);
+            widget.render_help(area, &mut buf);
+            buf.content.iter().map(|cell| cell.symbol()).collect::<String>()
+        };
+        let mut widget = EditWidget::default();
+        assert!(rendered(&widget).trim().is_empty());
+
+        widget.show_help = true;
+        let help = rendered(&widget);
+        assert!(help.contains("Editor keys"));
+        for (key, action) in KEY_BINDINGS {
+            assert!(help.contains(key) && help.contains(action));
+        }
+
+        widget.hide_help();
+        assert!(rendered(&widget).trim().is_empty());
     }
 }
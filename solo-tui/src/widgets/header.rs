@@ -6,9 +6,45 @@ use ratatui::{
     widgets::{Block, BorderType, Gauge, StatefulWidget, Widget},
 };
 use sova_core::schedule::playback::PlaybackState;
+use std::fmt;
 
 use crate::app::AppState;
 
+/// Subdivisions of a beat in the transport position, as in MIDI clock.
+pub const TICKS_PER_BEAT: u64 = 24;
+
+/// A position on the timeline as bars, beats and ticks.
+/// Bars and beats count from 1, ticks from 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransportPosition {
+    pub bar: u64,
+    pub beat: u64,
+    pub tick: u64,
+}
+
+impl TransportPosition {
+    /// The position of `beat` when bars last `quantum` beats.
+    /// Positions before the start of the timeline are shown as its start.
+    pub fn from_beat(beat: f64, quantum: f64) -> Self {
+        let beat = beat.max(0.0);
+        let bar = (beat / quantum).floor();
+        let in_bar = beat - bar * quantum;
+        let beat_in_bar = in_bar.floor();
+        let tick = ((in_bar - beat_in_bar) * TICKS_PER_BEAT as f64).floor();
+        TransportPosition {
+            bar: bar as u64 + 1,
+            beat: beat_in_bar as u64 + 1,
+            tick: (tick as u64).min(TICKS_PER_BEAT - 1),
+        }
+    }
+}
+
+impl fmt::Display for TransportPosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{:02}", self.bar, self.beat, self.tick)
+    }
+}
+
 #[derive(Default)]
 pub struct Header;
 
@@ -18,16 +54,11 @@ impl StatefulWidget for Header {
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         let beat = state.clock.beat();
         let quantum = state.clock.quantum();
-        let quantumized = beat % quantum;
-        let progress = quantumized / quantum;
+        let progress = (beat.max(0.0) % quantum / quantum).clamp(0.0, 1.0);
 
+        let position = TransportPosition::from_beat(beat, quantum);
         let label = Span::styled(
-            format!(
-                "{:.1} ({} / {})",
-                beat,
-                quantumized as i8 + 1,
-                quantum as i8
-            ),
+            format!("{position} ({beat:.1})"),
             Style::new().bold().fg(Color::White),
         );
 
@@ -37,7 +68,9 @@ impl StatefulWidget for Header {
             PlaybackState::Playing => "▶",
         };
 
-        let title = format!("| Sova - {:.0} BPM - {play} |", state.clock.tempo());
+        let tempo = state.clock.tempo();
+        let unsaved = if state.dirty { " *" } else { "" };
+        let title = format!("| Sova{unsaved} - {tempo:.0} BPM - {play} |");
 
         let block = Block::bordered()
             .border_type(BorderType::Rounded)
@@ -51,3 +84,19 @@ impl StatefulWidget for Header {
             .render(area, buf);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beats_are_counted_in_bars_of_the_quantum() {
+        let at = |beat, quantum| TransportPosition::from_beat(beat, quantum).to_string();
+        assert_eq!(at(0.0, 4.0), "1.1.00");
+        assert_eq!(at(2.5, 4.0), "1.3.12");
+        assert_eq!(at(3.99, 4.0), "1.4.23");
+        assert_eq!(at(4.0, 4.0), "2.1.00");
+        assert_eq!(at(7.5, 3.0), "3.2.12");
+        assert_eq!(at(-1.0, 4.0), "1.1.00");
+    }
+}
@@ -1,4 +1,8 @@
-use std::cmp::min;
+use std::{
+    cmp::min,
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
@@ -9,10 +13,13 @@ use ratatui::{
     text::Span,
     widgets::{
         StatefulWidget, Widget,
-        canvas::{Canvas, Context},
+        canvas::{Canvas, Context, Rectangle},
     },
 };
-use sova_core::schedule::{ActionTiming, SchedulerMessage};
+use sova_core::{
+    scene::{Line, script::Script},
+    schedule::{ActionTiming, SchedulerMessage},
+};
 
 use crate::{app::AppState, event::AppEvent, popup::PopupValue};
 
@@ -21,6 +28,18 @@ const LINE_RECT_HEIGHT: f64 = 3.0;
 
 const FRAME_RECT_HEIGHT: f64 = 4.0;
 
+/// Horizontal offset of the playhead marker inside a frame.
+const PLAYHEAD_OFFSET: f64 = 10.0;
+const PLAYHEAD: &str = "▶";
+const TRAIL: &str = "·";
+/// Marks the frames other clients are editing.
+const LOCKED: &str = "✎";
+
+/// Frames kept in view after the playhead when following it.
+const FOLLOW_MARGIN: usize = 1;
+/// How long moving the selection suspends following the playhead.
+const FOLLOW_PAUSE: Duration = Duration::from_secs(3);
+
 fn set_selected(state: &mut AppState, line_index: usize, frame_index: usize) {
     let before = state.selected;
     if state.scene_image.is_empty() {
@@ -40,33 +59,81 @@ fn set_selected(state: &mut AppState, line_index: usize, frame_index: usize) {
     }
 }
 
+/// Copy of a script, without its compilation state, to go into another frame.
+fn detached_script(script: &Script) -> Script {
+    let mut copy = Script::new(script.content().to_owned(), script.lang().to_owned());
+    copy.args = script.args.clone();
+    copy
+}
+
+/// Message pasting the script of the register into the frame at `target`.
+fn paste_script(register: &Script, target: (usize, usize)) -> SchedulerMessage {
+    SchedulerMessage::SetScript(
+        target.0,
+        target.1,
+        detached_script(register),
+        ActionTiming::Immediate,
+    )
+}
+
 #[derive(Default)]
-pub struct SceneWidget;
+pub struct SceneWidget {
+    /// Scroll the grid to keep the playhead of the selected line in view.
+    follow: bool,
+    /// Following resumes once this instant is reached.
+    follow_paused_until: Option<Instant>,
+}
 
 impl SceneWidget {
-    pub fn compute_start_coordinates(&self, state: &AppState, area: Rect) -> (f64, f64) {
-        let (width, height) = (f64::from(area.width), f64::from(area.height));
-        let x_selected = 1.0 + (state.selected.0 as f64) * LINE_RECT_WIDTH;
-        let y_selected = height - LINE_RECT_HEIGHT;
-        let y_selected = y_selected - (FRAME_RECT_HEIGHT * (state.selected.1 + 1) as f64);
-
-        let x = if x_selected + LINE_RECT_WIDTH > width {
-            x_selected + LINE_RECT_WIDTH - width
-        } else {
-            0.0
-        };
-        let y = if y_selected < 0.0 { y_selected } else { 0.0 };
+    pub fn is_following(&self) -> bool {
+        self.follow
+            && self
+                .follow_paused_until
+                .is_none_or(|until| Instant::now() >= until)
+    }
 
-        (x, y)
+    pub fn compute_start_coordinates(&self, state: &AppState, area: Rect) -> (f64, f64) {
+        let line_index = state.selected.0;
+        let playhead = state
+            .positions
+            .get(line_index)
+            .and_then(|pos| pos.first())
+            .map(|(frame, _)| *frame);
+        let n_frames = state.scene_image.line(line_index).map_or(0, Line::n_frames);
+        match playhead {
+            Some(frame) if self.is_following() && frame < n_frames => {
+                let margin = min(FOLLOW_MARGIN, n_frames - frame - 1);
+                start_coordinates((line_index, frame), margin, area)
+            }
+            _ => start_coordinates(state.selected, 0, area),
+        }
     }
 
     pub fn process_event(&mut self, state: &mut AppState, event: KeyEvent) {
         let selected = state.selected;
+        if self.follow
+            && matches!(
+                event.code,
+                KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right
+            )
+        {
+            self.follow_paused_until = Some(Instant::now() + FOLLOW_PAUSE);
+        }
         match event.code {
             KeyCode::Up => set_selected(state, selected.0, selected.1.saturating_sub(1)),
             KeyCode::Down => set_selected(state, selected.0, selected.1 + 1),
             KeyCode::Left => set_selected(state, selected.0.saturating_sub(1), selected.1),
             KeyCode::Right => set_selected(state, selected.0 + 1, selected.1),
+            KeyCode::Char('f') => {
+                self.follow = !self.follow;
+                self.follow_paused_until = None;
+                let msg = if self.follow {
+                    "Following the playhead"
+                } else {
+                    "Stopped following the playhead"
+                };
+                state.events.send(AppEvent::Info(msg.to_owned()));
+            }
             KeyCode::Char('i') => {
                 let (line_index, frame_index) = state.selected;
                 let msg = if state.scene_image.is_empty()
@@ -193,108 +260,211 @@ impl SceneWidget {
                 };
                 state.events.send(msg.into());
             }
+            KeyCode::Char('c') if state.selected_frame().is_some() => {
+                let script = detached_script(state.selected_frame().unwrap().script());
+                let msg = format!("Yanked {} script", script.lang());
+                state.script_register = Some(script);
+                state.events.send(AppEvent::Info(msg));
+            }
+            KeyCode::Char('p') if state.selected_frame().is_some() => {
+                let Some(register) = state.script_register.clone() else {
+                    state
+                        .events
+                        .send(AppEvent::Negative("No script to paste".to_owned()));
+                    return;
+                };
+                let target = state.selected;
+                let current = state.selected_frame().unwrap().script();
+                if current.content().is_empty() {
+                    state.events.send(paste_script(&register, target).into());
+                    state
+                        .events
+                        .send(AppEvent::Positive("Pasted script".to_owned()));
+                    return;
+                }
+                state.events.send(AppEvent::Popup(
+                    "Paste script".to_owned(),
+                    "Overwrite the script of this frame ?".to_owned(),
+                    PopupValue::Bool(false),
+                    Box::new(move |state, value| {
+                        if bool::from(value) {
+                            state.events.send(paste_script(&register, target).into());
+                            state
+                                .events
+                                .send(AppEvent::Positive("Pasted script".to_owned()));
+                        }
+                    }),
+                ));
+            }
             _ => (),
         }
     }
 
     pub fn get_help() -> &'static str {
         "\
-        I: insert frame after  R: remove frame     M: toggle frame\n\
-        L: insert line after   C-R: remove line    Y: copy frame after\n\
-        X: change repetitions  D: change duration  C-Y: copy line after\
+        I: insert frame after  R: remove frame     M: toggle frame       F: follow playhead\n\
+        L: insert line after   C-R: remove line    Y: copy frame after   C: yank script\n\
+        X: change repetitions  D: change duration  C-Y: copy line after  P: paste script\
         "
     }
 
     pub fn draw_scene(&self, state: &AppState, ctx: &mut Context, area: Rect) {
-        use ratatui::widgets::canvas::*;
+        draw_lines(
+            ctx,
+            area,
+            &state.scene_image.lines,
+            state.selected,
+            &state.positions,
+            &state.trails,
+            &state.peer_edits,
+        );
+    }
+}
+
+/// Bottom left corner of the visible part of the scene, scrolled so that the
+/// frame at `focus` and the `margin` frames after it stay in view.
+fn start_coordinates(focus: (usize, usize), margin: usize, area: Rect) -> (f64, f64) {
+    let (width, height) = (f64::from(area.width), f64::from(area.height));
+    let x_focus = 1.0 + (focus.0 as f64) * LINE_RECT_WIDTH;
+    let y_focus = height - LINE_RECT_HEIGHT;
+    let y_focus = y_focus - (FRAME_RECT_HEIGHT * (focus.1 + 1 + margin) as f64);
+
+    let x = if x_focus + LINE_RECT_WIDTH > width {
+        x_focus + LINE_RECT_WIDTH - width
+    } else {
+        0.0
+    };
+    let y = if y_focus < 0.0 { y_focus } else { 0.0 };
+
+    (x, y)
+}
+
+/// Draws the lines of a scene, with the playhead of each playing line
+/// and a fading mark on the frames it just left.
+fn draw_lines(
+    ctx: &mut Context,
+    area: Rect,
+    lines: &[Line],
+    selected: (usize, usize),
+    positions: &[Vec<(usize, usize)>],
+    trails: &[Vec<usize>],
+    peer_edits: &HashMap<(usize, usize), String>,
+) {
+    let top = f64::from(area.height);
+
+    let mut on_top = Vec::new();
+
+    for (line_index, line) in lines.iter().enumerate() {
+        let x_offset = 1.0 + line_index as f64 * LINE_RECT_WIDTH;
+        let y_top = top - LINE_RECT_HEIGHT;
+        let selected_line = selected.0 == line_index;
+        let rect = Rectangle {
+            x: x_offset,
+            y: y_top,
+            width: LINE_RECT_WIDTH,
+            height: LINE_RECT_HEIGHT,
+            color: if selected_line {
+                Color::LightMagenta
+            } else {
+                Color::White
+            },
+        };
+        if selected_line {
+            on_top.push(rect);
+        } else {
+            ctx.draw(&rect);
+        }
+        let text = if line.transpose != 0 {
+            format!("Line {} {:+}", line_index, line.transpose)
+        } else {
+            format!("Line {}", line_index)
+        };
+        let text_offset = 1.0 + (LINE_RECT_WIDTH / 2.0) - (text.len() as f64 / 2.0);
+        let text = if selected_line {
+            text.light_magenta().bold()
+        } else {
+            Span::from(text)
+        };
+        ctx.print(x_offset + text_offset, y_top + LINE_RECT_HEIGHT / 2.0, text);
 
-        let top = f64::from(area.height);
+        let line_pos = positions.get(line_index).map(Vec::as_slice).unwrap_or_default();
+        let trail = trails.get(line_index).map(Vec::as_slice).unwrap_or_default();
 
-        let mut on_top = Vec::new();
-        let pos = &state.positions;
+        for (frame_index, frame) in line.frames.iter().enumerate() {
+            let selected_frame = selected == (line_index, frame_index);
+            let color = if selected_frame {
+                Color::LightMagenta
+            } else {
+                Color::White
+            };
 
-        for (line_index, line) in state.scene_image.lines.iter().enumerate() {
-            let x_offset = 1.0 + line_index as f64 * LINE_RECT_WIDTH;
-            let y_top = top - LINE_RECT_HEIGHT;
-            let selected_line = state.selected.0 == line_index;
+            let y_frame = y_top - (FRAME_RECT_HEIGHT * (frame_index + 1) as f64);
             let rect = Rectangle {
                 x: x_offset,
-                y: y_top,
+                y: y_frame,
                 width: LINE_RECT_WIDTH,
-                height: LINE_RECT_HEIGHT,
-                color: if selected_line {
-                    Color::LightMagenta
-                } else {
-                    Color::White
-                },
+                height: FRAME_RECT_HEIGHT,
+                color,
             };
-            if selected_line {
+            if selected_frame {
                 on_top.push(rect);
             } else {
                 ctx.draw(&rect);
             }
-            let text = format!("Line {}", line_index);
-            let text_offset = 1.0 + (LINE_RECT_WIDTH / 2.0) - (text.len() as f64 / 2.0);
-            let text = if selected_line {
-                text.light_magenta().bold()
+
+            let frame_name = format!("Frame {}", frame_index);
+            let nudged = frame.nudge != 0.0;
+            let frame_infos = if nudged {
+                format!(
+                    "{:.2} x {} {:+.2}",
+                    frame.duration, frame.repetitions, frame.nudge
+                )
             } else {
-                Span::from(text)
+                format!("{:.2} x {}", frame.duration, frame.repetitions)
             };
-            ctx.print(x_offset + text_offset, y_top + LINE_RECT_HEIGHT / 2.0, text);
 
-            let line_pos = pos.get(line_index).cloned().unwrap_or_default();
-
-            for (frame_index, frame) in line.frames.iter().enumerate() {
-                let selected_frame = state.selected == (line_index, frame_index);
-                let color = if selected_frame {
-                    Color::LightMagenta
-                } else {
-                    Color::White
-                };
+            let (mut frame_name, frame_infos) = if selected_frame {
+                (
+                    frame_name.light_magenta().bold(),
+                    frame_infos.light_magenta().bold(),
+                )
+            } else if nudged {
+                (Span::from(frame_name), frame_infos.yellow())
+            } else {
+                (Span::from(frame_name), Span::from(frame_infos))
+            };
 
-                let y_frame = y_top - (FRAME_RECT_HEIGHT * (frame_index + 1) as f64);
-                let rect = Rectangle {
-                    x: x_offset,
-                    y: y_frame,
-                    width: LINE_RECT_WIDTH,
-                    height: FRAME_RECT_HEIGHT,
-                    color,
-                };
-                if selected_frame {
-                    on_top.push(rect);
-                } else {
-                    ctx.draw(&rect);
-                }
+            let playhead = line_pos.iter().find(|(f, _)| *f == frame_index);
+            if playhead.is_some() {
+                frame_name = frame_name.bg(Color::White).fg(Color::Black);
+            }
+            if !frame.enabled {
+                frame_name = frame_name.crossed_out().gray();
+            }
 
-                let frame_name = format!("Frame {}", frame_index);
-                let frame_infos = format!("{:.2} x {}", frame.duration, frame.repetitions);
+            let x = 2.0 + x_offset;
+            if peer_edits.contains_key(&(line_index, frame_index)) {
+                ctx.print(x_offset + 1.0, y_frame + 2.0, LOCKED.light_red());
+            }
+            ctx.print(x, y_frame + 2.0, frame_name);
+            ctx.print(x, y_frame + 1.0, frame_infos);
 
-                let (mut frame_name, frame_infos) = if selected_frame {
-                    (
-                        frame_name.light_magenta().bold(),
-                        frame_infos.light_magenta().bold(),
-                    )
+            let x_marker = x_offset + PLAYHEAD_OFFSET;
+            if let Some((_, repetition)) = playhead {
+                let marker = if frame.repetitions > 1 {
+                    format!("{PLAYHEAD} {}/{}", repetition + 1, frame.repetitions)
                 } else {
-                    (Span::from(frame_name), Span::from(frame_infos))
+                    PLAYHEAD.to_owned()
                 };
-
-                let active = line_pos.iter().any(|(f,_)| *f == frame_index);
-                if active {
-                    frame_name = frame_name.bg(Color::White).fg(Color::Black);
-                }
-                if !frame.enabled {
-                    frame_name = frame_name.crossed_out().gray();
-                }
-
-                let x = 2.0 + x_offset;
-                ctx.print(x, y_frame + 2.0, frame_name);
-                ctx.print(x, y_frame + 1.0, frame_infos);
+                ctx.print(x_marker, y_frame + 2.0, marker.light_green().bold());
+            } else if trail.contains(&frame_index) {
+                ctx.print(x_marker, y_frame + 2.0, TRAIL.dark_gray());
             }
         }
+    }
 
-        for rect in on_top {
-            ctx.draw(&rect);
-        }
+    for rect in on_top {
+        ctx.draw(&rect);
     }
 }
 
@@ -314,3 +484,97 @@ impl StatefulWidget for &SceneWidget {
             .render(area, buf);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Row of the first cell sequence containing `text` between the given columns.
+    fn find_row(buf: &Buffer, columns: std::ops::Range<u16>, text: &str) -> Option<u16> {
+        (0..buf.area.height).find(|&y| {
+            let row: String = columns.clone().map(|x| buf[(x, y)].symbol()).collect();
+            row.contains(text)
+        })
+    }
+
+    #[test]
+    fn playheads_are_drawn_on_the_playing_frames() {
+        let mut short_line = Line::new(vec![1.0, 1.0]);
+        short_line.frames[0].repetitions = 2;
+        short_line.set_transpose(12);
+        let lines = vec![Line::new(vec![1.0, 1.0, 1.0]), short_line];
+        let positions = vec![vec![(2, 0)], vec![(0, 1)]];
+        let trails = vec![vec![1], vec![]];
+        let peer_edits = HashMap::from([((1, 1), "bob".to_string())]);
+
+        let area = Rect::new(0, 0, 40, 24);
+        let mut buf = Buffer::empty(area);
+        Canvas::default()
+            .marker(Marker::Braille)
+            .paint(|ctx| draw_lines(ctx, area, &lines, (0, 0), &positions, &trails, &peer_edits))
+            .x_bounds([0.0, 40.0])
+            .y_bounds([0.0, 24.0])
+            .render(area, &mut buf);
+
+        let first = 1..17;
+        let second = 17..33;
+        assert_eq!(
+            find_row(&buf, first.clone(), PLAYHEAD),
+            find_row(&buf, first.clone(), "Frame 2")
+        );
+        assert_eq!(
+            find_row(&buf, first.clone(), TRAIL),
+            find_row(&buf, first.clone(), "Frame 1")
+        );
+        assert_eq!(
+            find_row(&buf, second.clone(), "▶ 2/2"),
+            find_row(&buf, second.clone(), "Frame 0")
+        );
+        assert!(find_row(&buf, second.clone(), "Frame 0").is_some());
+        assert!(find_row(&buf, second.clone(), TRAIL).is_none());
+        assert!(find_row(&buf, first, "Line 0").is_some());
+        assert!(find_row(&buf, second.clone(), "Line 1 +12").is_some());
+
+        let markers = buf.content.iter().filter(|cell| cell.symbol() == PLAYHEAD).count();
+        assert_eq!(markers, 2);
+        assert_eq!(
+            find_row(&buf, second.clone(), LOCKED),
+            find_row(&buf, second, "Frame 1")
+        );
+    }
+
+    #[test]
+    fn following_keeps_the_playhead_in_view_with_a_margin() {
+        let area = Rect::new(0, 0, 40, 23);
+        // 20 rows below the line header fit 5 frames
+        for frame in 0..4 {
+            assert_eq!(start_coordinates((0, frame), 1, area), (0.0, 0.0));
+        }
+        assert_eq!(start_coordinates((0, 4), 1, area), (0.0, -4.0));
+        assert_eq!(start_coordinates((0, 9), 1, area), (0.0, -24.0));
+        assert_eq!(start_coordinates((0, 9), 0, area), (0.0, -20.0));
+
+        assert_eq!(start_coordinates((2, 0), 1, area), (9.0, 0.0));
+    }
+
+    #[test]
+    fn pasting_the_register_copies_content_and_language() {
+        let mut source = Line::new(vec![1.0]);
+        let mut script = Script::new(">> [note: 60]".to_owned(), "bob".to_owned());
+        script.args.insert("root".to_owned(), "60".to_owned());
+        source.frame_mut(0).set_script(script);
+
+        let register = detached_script(source.frame(0).unwrap().script());
+        assert_eq!(register.content(), ">> [note: 60]");
+        assert_eq!(register.lang(), "bob");
+
+        let SchedulerMessage::SetScript(line_id, frame_id, pasted, _) =
+            paste_script(&register, (2, 3))
+        else {
+            panic!("pasting should set the script of the target frame");
+        };
+        assert_eq!((line_id, frame_id), (2, 3));
+        assert!(pasted.is_like(source.frame(0).unwrap().script()));
+        assert!(pasted.has_not_been_compiled());
+    }
+}
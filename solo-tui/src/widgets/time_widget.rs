@@ -1,8 +1,15 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{buffer::Buffer, layout::{Constraint, Flex, Layout, Margin, Rect}, style::Stylize, text::{Line, Span}, widgets::{Paragraph, StatefulWidget, Widget}};
 
+use sova_core::schedule::{ActionTiming, SchedulerMessage};
+
 use crate::{app::AppState, event::AppEvent, popup::PopupValue};
 
+/// Tempo nudge in BPM, for syncing to an external source by ear.
+const TEMPO_NUDGE: f64 = 0.1;
+/// Tempo nudge in BPM when holding Shift.
+const TEMPO_NUDGE_LARGE: f64 = 1.0;
+
 #[derive(Default)]
 pub struct TimeWidget;
 
@@ -10,9 +17,9 @@ impl TimeWidget {
 
     pub fn get_help() -> &'static str {
         "\
-        T: Configure tempo     Up: Increase tempo     Space: Play/Pause \n\
-        Q: Configure quantum   Down: decrease tempo                     \n\
-        R: Reset beat          S: Start/Stop sync                       \n\
+        T: Configure tempo     Up: Increase tempo     Space: Play/Pause      \n\
+        Q: Configure quantum   Down: decrease tempo   Left/Right: Nudge tempo\n\
+        R: Reset beat          S: Start/Stop sync     Shift: Larger nudge    \n\
         "
     }
 
@@ -46,6 +53,19 @@ impl TimeWidget {
             KeyCode::Down => {
                 state.clock.set_tempo(state.clock.tempo() - 1.0);
             }
+            KeyCode::Left | KeyCode::Right => {
+                let mut delta = if event.modifiers.contains(KeyModifiers::SHIFT) {
+                    TEMPO_NUDGE_LARGE
+                } else {
+                    TEMPO_NUDGE
+                };
+                if event.code == KeyCode::Left {
+                    delta = -delta;
+                }
+                state.events.send(
+                    SchedulerMessage::NudgeTempo(delta, ActionTiming::Immediate).into()
+                );
+            }
             KeyCode::Char('s') => {
                 state.clock.set_start_stop_sync();
                 state.events.send(AppEvent::Positive("Start/Stop sync".to_owned()));